@@ -2,6 +2,8 @@ pub mod assistant_panel;
 pub mod assistant_settings;
 mod codegen;
 mod completion_provider;
+mod prompt_library;
+mod prompt_picker;
 mod prompts;
 mod saved_conversation;
 mod streaming_diff;
@@ -32,6 +34,8 @@ actions!(
         ToggleFocus,
         ResetKey,
         InlineAssist,
+        InlineAssistWithPrompt,
+        NewConversationWithPrompt,
         ToggleIncludeConversation,
     ]
 );
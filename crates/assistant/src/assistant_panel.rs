@@ -1,11 +1,14 @@
 use crate::{
     assistant_settings::{AssistantDockPosition, AssistantSettings, ZedDotDevModel},
     codegen::{self, Codegen, CodegenKind},
+    prompt_library::{PromptContext, PromptLibrary, PromptTemplate},
+    prompt_picker::{PromptPicker, PromptPickerTarget},
     prompts::generate_content_prompt,
-    Assist, CompletionProvider, CycleMessageRole, InlineAssist, LanguageModel,
-    LanguageModelRequest, LanguageModelRequestMessage, MessageId, MessageMetadata, MessageStatus,
-    NewConversation, QuoteSelection, ResetKey, Role, SavedConversation, SavedConversationMetadata,
-    SavedMessage, Split, ToggleFocus, ToggleIncludeConversation,
+    Assist, CompletionProvider, CycleMessageRole, InlineAssist, InlineAssistWithPrompt,
+    LanguageModel, LanguageModelRequest, LanguageModelRequestMessage, MessageId, MessageMetadata,
+    MessageStatus, NewConversation, NewConversationWithPrompt, QuoteSelection, ResetKey, Role,
+    SavedConversation, SavedConversationMetadata, SavedMessage, Split, ToggleFocus,
+    ToggleIncludeConversation,
 };
 use anyhow::Result;
 use chrono::{DateTime, Local};
@@ -29,12 +32,22 @@ use gpui::{
     StatefulInteractiveElement, Styled, Subscription, Task, TextStyle, UniformListScrollHandle,
     View, ViewContext, VisualContext, WeakModel, WeakView, WhiteSpace, WindowContext,
 };
-use language::{language_settings::SoftWrap, Buffer, BufferId, LanguageRegistry, ToOffset as _};
+use language::{
+    language_settings::SoftWrap, Buffer, BufferId, BufferSnapshot, LanguageRegistry, ToOffset as _,
+};
 use parking_lot::Mutex;
 use project::Project;
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
-use settings::Settings;
-use std::{cmp, fmt::Write, iter, ops::Range, path::PathBuf, sync::Arc, time::Duration};
+use settings::{Settings, SettingsLocation};
+use std::{
+    cmp,
+    fmt::Write,
+    iter,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use telemetry_events::AssistantKind;
 use theme::ThemeSettings;
 use ui::{
@@ -42,10 +55,14 @@ use ui::{
     utils::{DateTimeType, FormatDistance},
     ButtonLike, Tab, TabBar, Tooltip,
 };
-use util::{paths::CONVERSATIONS_DIR, post_inc, ResultExt, TryFutureExt};
+use util::{
+    paths::{CONVERSATIONS_DIR, PROMPTS_DIR},
+    post_inc, ResultExt, TryFutureExt,
+};
 use uuid::Uuid;
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
+    register_detachable_panel,
     searchable::Direction,
     Save, Toast, ToggleZoom, Toolbar, Workspace,
 };
@@ -63,11 +80,14 @@ pub fn init(cx: &mut AppContext) {
                     workspace.toggle_panel_focus::<AssistantPanel>(cx);
                 })
                 .register_action(AssistantPanel::inline_assist)
+                .register_action(AssistantPanel::inline_assist_with_prompt)
+                .register_action(AssistantPanel::new_conversation_with_prompt)
                 .register_action(AssistantPanel::cancel_last_inline_assist)
                 .register_action(ConversationEditor::quote_selection);
         },
     )
     .detach();
+    register_detachable_panel(cx, AssistantPanel::load);
 }
 
 pub struct AssistantPanel {
@@ -90,6 +110,8 @@ pub struct AssistantPanel {
     include_conversation_in_next_inline_assist: bool,
     inline_prompt_history: VecDeque<String>,
     _watch_saved_conversations: Task<Result<()>>,
+    prompt_library: Arc<PromptLibrary>,
+    _watch_prompt_library: Task<Result<()>>,
     model: LanguageModel,
     authentication_prompt: Option<AnyView>,
 }
@@ -112,6 +134,10 @@ impl AssistantPanel {
                 .await
                 .log_err()
                 .unwrap_or_default();
+            let prompt_library = PromptLibrary::load(fs.clone(), &PROMPTS_DIR)
+                .await
+                .log_err()
+                .unwrap_or_default();
 
             // TODO: deserialize state.
             let workspace_handle = workspace.clone();
@@ -137,6 +163,28 @@ impl AssistantPanel {
                         anyhow::Ok(())
                     });
 
+                    const PROMPT_LIBRARY_WATCH_DURATION: Duration = Duration::from_millis(100);
+                    let _watch_prompt_library = cx.spawn({
+                        let fs = workspace.app_state().fs.clone();
+                        move |this, mut cx| async move {
+                            let mut events =
+                                fs.watch(&PROMPTS_DIR, PROMPT_LIBRARY_WATCH_DURATION).await;
+                            while events.next().await.is_some() {
+                                let prompt_library = PromptLibrary::load(fs.clone(), &PROMPTS_DIR)
+                                    .await
+                                    .log_err()
+                                    .unwrap_or_default();
+                                this.update(&mut cx, |this, cx| {
+                                    this.prompt_library = Arc::new(prompt_library);
+                                    cx.notify();
+                                })
+                                .ok();
+                            }
+
+                            anyhow::Ok(())
+                        }
+                    });
+
                     let toolbar = cx.new_view(|cx| {
                         let mut toolbar = Toolbar::new();
                         toolbar.set_can_navigate(false, cx);
@@ -180,6 +228,8 @@ impl AssistantPanel {
                         include_conversation_in_next_inline_assist: false,
                         inline_prompt_history: Default::default(),
                         _watch_saved_conversations,
+                        prompt_library: Arc::new(prompt_library),
+                        _watch_prompt_library,
                         model,
                         authentication_prompt: None,
                     }
@@ -233,6 +283,50 @@ impl AssistantPanel {
         workspace: &mut Workspace,
         _: &InlineAssist,
         cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::start_inline_assist(workspace, None, cx);
+    }
+
+    fn inline_assist_with_prompt(
+        workspace: &mut Workspace,
+        _: &InlineAssistWithPrompt,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::toggle_prompt_picker(workspace, PromptPickerTarget::InlineAssist, cx);
+    }
+
+    fn new_conversation_with_prompt(
+        workspace: &mut Workspace,
+        _: &NewConversationWithPrompt,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::toggle_prompt_picker(workspace, PromptPickerTarget::NewConversation, cx);
+    }
+
+    fn toggle_prompt_picker(
+        workspace: &mut Workspace,
+        target: PromptPickerTarget,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let settings = AssistantSettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+
+        let Some(assistant) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+        let prompt_library = assistant.read(cx).prompt_library.clone();
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            PromptPicker::new(prompt_library, target, workspace_handle, cx)
+        });
+    }
+
+    pub(crate) fn start_inline_assist(
+        workspace: &mut Workspace,
+        prompt_template: Option<Arc<PromptTemplate>>,
+        cx: &mut ViewContext<Workspace>,
     ) {
         let settings = AssistantSettings::get_global(cx);
         if !settings.enabled {
@@ -254,7 +348,7 @@ impl AssistantPanel {
 
         if assistant.update(cx, |assistant, cx| assistant.is_authenticated(cx)) {
             assistant.update(cx, |assistant, cx| {
-                assistant.new_inline_assist(&active_editor, cx, &project)
+                assistant.new_inline_assist(&active_editor, prompt_template, cx, &project)
             });
         } else {
             let assistant = assistant.downgrade();
@@ -264,7 +358,7 @@ impl AssistantPanel {
                     .await?;
                 if assistant.update(&mut cx, |assistant, cx| assistant.is_authenticated(cx))? {
                     assistant.update(&mut cx, |assistant, cx| {
-                        assistant.new_inline_assist(&active_editor, cx, &project)
+                        assistant.new_inline_assist(&active_editor, prompt_template, cx, &project)
                     })?;
                 } else {
                     workspace.update(&mut cx, |workspace, cx| {
@@ -281,6 +375,7 @@ impl AssistantPanel {
     fn new_inline_assist(
         &mut self,
         editor: &View<Editor>,
+        prompt_template: Option<Arc<PromptTemplate>>,
         cx: &mut ViewContext<Self>,
         project: &Model<Project>,
     ) {
@@ -324,6 +419,7 @@ impl AssistantPanel {
                 measurements.clone(),
                 self.include_conversation_in_next_inline_assist,
                 self.inline_prompt_history.clone(),
+                prompt_template.clone(),
                 codegen.clone(),
                 self.workspace.clone(),
                 cx,
@@ -366,6 +462,7 @@ impl AssistantPanel {
                 inline_assistant: Some((block_id, inline_assistant.clone())),
                 codegen: codegen.clone(),
                 project: project.downgrade(),
+                prompt_template,
                 _subscriptions: vec![
                     cx.subscribe(&inline_assistant, Self::handle_inline_assistant_event),
                     cx.subscribe(editor, {
@@ -580,6 +677,7 @@ impl AssistantPanel {
         }
 
         let codegen = pending_assist.codegen.clone();
+        let prompt_template = pending_assist.prompt_template.clone();
         let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
         let range = codegen.read(cx).range();
         let start = snapshot.point_to_buffer_offset(range.start);
@@ -622,7 +720,28 @@ impl AssistantPanel {
             1.0
         };
 
-        let user_prompt = user_prompt.to_string();
+        let context = PromptContext {
+            user_prompt: Some(user_prompt.to_string()),
+            ..buffer_prompt_context(&buffer, range.clone(), project_name.clone())
+        };
+        let system_prompt = buffer.file().and_then(|file| {
+            let location = SettingsLocation {
+                worktree_id: file.worktree_id(),
+                path: file.path().as_ref(),
+            };
+            self.default_system_prompt(Some(location), &context, cx)
+        });
+
+        let user_prompt = if let Some(template) = prompt_template {
+            let mut rendered = template.render(&context);
+            if !template.uses_user_prompt() && !user_prompt.is_empty() {
+                rendered.push_str("\n\n");
+                rendered.push_str(user_prompt);
+            }
+            rendered
+        } else {
+            user_prompt.to_string()
+        };
 
         let prompt = cx.background_executor().spawn(async move {
             let language_name = language_name.as_deref();
@@ -630,6 +749,12 @@ impl AssistantPanel {
         });
 
         let mut messages = Vec::new();
+        if let Some(system_prompt) = system_prompt {
+            messages.push(LanguageModelRequestMessage {
+                role: Role::System,
+                content: system_prompt,
+            });
+        }
         if let Some(conversation) = conversation {
             let conversation = conversation.read(cx);
             let buffer = conversation.buffer.read(cx);
@@ -709,7 +834,56 @@ impl AssistantPanel {
         });
     }
 
+    /// Starts a new conversation, reading the workspace to seed it. Callers already updating
+    /// the workspace must use [`Self::new_conversation_in_workspace`] instead.
     fn new_conversation(&mut self, cx: &mut ViewContext<Self>) -> View<ConversationEditor> {
+        let system_prompt = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| self.conversation_system_prompt(None, workspace.read(cx), cx));
+        self.new_conversation_with_system_prompt(system_prompt, cx)
+    }
+
+    /// Starts a new conversation, seeded with the given template or, when none is
+    /// provided, with the project's default system prompt.
+    pub(crate) fn new_conversation_in_workspace(
+        &mut self,
+        workspace: &Workspace,
+        template: Option<&PromptTemplate>,
+        cx: &mut ViewContext<Self>,
+    ) -> View<ConversationEditor> {
+        let system_prompt = self.conversation_system_prompt(template, workspace, cx);
+        self.new_conversation_with_system_prompt(system_prompt, cx)
+    }
+
+    fn conversation_system_prompt(
+        &self,
+        template: Option<&PromptTemplate>,
+        workspace: &Workspace,
+        cx: &AppContext,
+    ) -> Option<String> {
+        let context = active_editor_prompt_context(workspace, cx);
+        if let Some(template) = template {
+            return Some(template.render(&context));
+        }
+
+        let project = workspace.project().read(cx);
+        let worktree_id = project.visible_worktrees(cx).next()?.read(cx).id();
+        self.default_system_prompt(
+            Some(SettingsLocation {
+                worktree_id: worktree_id.to_usize(),
+                path: Path::new(""),
+            }),
+            &context,
+            cx,
+        )
+    }
+
+    fn new_conversation_with_system_prompt(
+        &mut self,
+        system_prompt: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) -> View<ConversationEditor> {
         let editor = cx.new_view(|cx| {
             ConversationEditor::new(
                 self.model.clone(),
@@ -719,10 +893,39 @@ impl AssistantPanel {
                 cx,
             )
         });
+        if let Some(system_prompt) = system_prompt {
+            editor.update(cx, |editor, cx| {
+                editor.conversation.update(cx, |conversation, cx| {
+                    conversation.insert_system_prompt(&system_prompt, cx)
+                });
+                editor.editor.update(cx, |editor, cx| {
+                    editor.move_to_end(&editor::actions::MoveToEnd, cx)
+                });
+            });
+        }
         self.show_conversation(editor.clone(), cx);
         editor
     }
 
+    /// Resolves the `default_system_prompt` setting for the given location, looking
+    /// it up in the prompt library first and otherwise treating it as literal text.
+    fn default_system_prompt(
+        &self,
+        location: Option<SettingsLocation>,
+        context: &PromptContext,
+        cx: &AppContext,
+    ) -> Option<String> {
+        let default_system_prompt = AssistantSettings::get(location, cx)
+            .default_system_prompt
+            .as_ref()
+            .filter(|prompt| !prompt.is_empty())?;
+        if let Some(template) = self.prompt_library.template(default_system_prompt) {
+            Some(template.render(context))
+        } else {
+            Some(default_system_prompt.clone())
+        }
+    }
+
     fn show_conversation(
         &mut self,
         conversation_editor: View<ConversationEditor>,
@@ -1630,6 +1833,21 @@ impl Conversation {
         }
     }
 
+    /// Turns the first message of the conversation into a system message containing
+    /// the given prompt, followed by an empty user message.
+    fn insert_system_prompt(&mut self, prompt: &str, cx: &mut ModelContext<Self>) {
+        let Some(first_message) = self.message_anchors.first().cloned() else {
+            return;
+        };
+
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit([(0..0, prompt)], None, cx));
+        if let Some(metadata) = self.messages_metadata.get_mut(&first_message.id) {
+            metadata.role = Role::System;
+        }
+        self.insert_message_after(first_message.id, Role::User, MessageStatus::Done, cx);
+    }
+
     fn insert_message_after(
         &mut self,
         message_id: MessageId,
@@ -2335,7 +2553,7 @@ impl ConversationEditor {
                 let conversation = panel
                     .active_conversation_editor()
                     .cloned()
-                    .unwrap_or_else(|| panel.new_conversation(cx));
+                    .unwrap_or_else(|| panel.new_conversation_in_workspace(workspace, None, cx));
                 conversation.update(cx, |conversation, cx| {
                     conversation
                         .editor
@@ -2558,15 +2776,17 @@ impl InlineAssistant {
         measurements: Arc<Mutex<BlockMeasurements>>,
         include_conversation: bool,
         prompt_history: VecDeque<String>,
+        prompt_template: Option<Arc<PromptTemplate>>,
         codegen: Model<Codegen>,
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let prompt_editor = cx.new_view(|cx| {
             let mut editor = Editor::single_line(cx);
-            let placeholder = match codegen.read(cx).kind() {
-                CodegenKind::Transform { .. } => "Enter transformation prompt…",
-                CodegenKind::Generate { .. } => "Enter generation prompt…",
+            let placeholder = match (codegen.read(cx).kind(), prompt_template) {
+                (_, Some(template)) => format!("Enter prompt for '{}'…", template.name),
+                (CodegenKind::Transform { .. }, None) => "Enter transformation prompt…".into(),
+                (CodegenKind::Generate { .. }, None) => "Enter generation prompt…".into(),
             };
             editor.set_placeholder_text(placeholder, cx);
             editor
@@ -2733,11 +2953,74 @@ struct BlockMeasurements {
 struct PendingInlineAssist {
     editor: WeakView<Editor>,
     inline_assistant: Option<(BlockId, View<InlineAssistant>)>,
+    prompt_template: Option<Arc<PromptTemplate>>,
     codegen: Model<Codegen>,
     _subscriptions: Vec<Subscription>,
     project: WeakModel<Project>,
 }
 
+/// Builds the context of templates used outside of inline assists, describing the
+/// newest selection of the workspace's active editor.
+fn active_editor_prompt_context(workspace: &Workspace, cx: &AppContext) -> PromptContext {
+    let project_name = workspace
+        .project()
+        .read(cx)
+        .worktree_root_names(cx)
+        .collect::<Vec<&str>>()
+        .join("/");
+    let context = PromptContext {
+        project_name: Some(project_name),
+        ..Default::default()
+    };
+
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return context;
+    };
+    let editor = editor.read(cx);
+    let range = editor.selections.newest::<usize>(cx).range();
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let start = snapshot.point_to_buffer_offset(range.start);
+    let end = snapshot.point_to_buffer_offset(range.end);
+    match start.zip(end) {
+        Some(((buffer, start), (end_buffer, end)))
+            if buffer.remote_id() == end_buffer.remote_id() =>
+        {
+            buffer_prompt_context(buffer, start..end, context.project_name)
+        }
+        _ => context,
+    }
+}
+
+/// Describes the given range of a buffer to prompt templates.
+fn buffer_prompt_context(
+    buffer: &BufferSnapshot,
+    range: Range<usize>,
+    project_name: Option<String>,
+) -> PromptContext {
+    let language = buffer
+        .language_at(range.start)
+        .filter(|language| !Arc::ptr_eq(language, &language::PLAIN_TEXT))
+        .map(|language| language.name().to_string());
+    PromptContext {
+        user_prompt: None,
+        selection: Some(buffer.text_for_range(range.clone()).collect()),
+        language,
+        file_path: buffer
+            .file()
+            .map(|file| file.path().to_string_lossy().into_owned()),
+        outline: buffer
+            .symbols_containing(range.start, None)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| item.text)
+            .collect(),
+        project_name,
+    }
+}
+
 fn merge_ranges(ranges: &mut Vec<Range<Anchor>>, buffer: &MultiBufferSnapshot) {
     ranges.sort_unstable_by(|a, b| {
         a.start
@@ -2788,7 +3071,9 @@ fn report_assistant_event(
 mod tests {
     use super::*;
     use crate::{FakeCompletionProvider, MessageId};
+    use fs::FakeFs;
     use gpui::{AppContext, TestAppContext};
+    use serde_json::json;
     use settings::SettingsStore;
 
     #[gpui::test]
@@ -3175,6 +3460,60 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_quote_selection_without_conversation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            workspace::AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+            init(cx);
+        });
+        cx.set_global(CompletionProvider::Fake(FakeCompletionProvider::default()));
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "main.rs": "fn main() {}\n" }))
+            .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let panel = cx
+            .update(|cx| AssistantPanel::load(workspace.downgrade(), cx.to_async()))
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| workspace.add_panel(panel.clone(), cx));
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/root/main.rs"), true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, cx)
+        });
+        panel.update(cx, |panel, _| {
+            assert!(panel.active_conversation_editor().is_none())
+        });
+
+        // Quoting runs while the workspace is being updated, so the new conversation
+        // has to be seeded from it rather than by reading it again.
+        workspace.update(cx, |workspace, cx| {
+            ConversationEditor::quote_selection(workspace, &QuoteSelection, cx)
+        });
+        let text = panel.update(cx, |panel, cx| {
+            let conversation_editor = panel.active_conversation_editor().unwrap().read(cx);
+            conversation_editor
+                .conversation
+                .read(cx)
+                .buffer
+                .read(cx)
+                .text()
+        });
+        assert!(text.contains("fn main() {}"), "{text:?}");
+    }
+
     fn messages(
         conversation: &Model<Conversation>,
         cx: &AppContext,
@@ -166,6 +166,7 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub provider: AssistantProvider,
+    pub default_system_prompt: Option<String>,
 }
 
 /// Assistant panel settings
@@ -221,6 +222,7 @@ impl AssistantSettingsContent {
                         }
                     })
                 },
+                default_system_prompt: None,
             },
         }
     }
@@ -255,6 +257,7 @@ impl Default for VersionedAssistantSettingsContent {
             default_width: None,
             default_height: None,
             provider: None,
+            default_system_prompt: None,
         })
     }
 }
@@ -286,6 +289,13 @@ pub struct AssistantSettingsContentV1 {
     /// This can either be the internal `zed.dev` service or an external `openai` service,
     /// each with their respective default models and configurations.
     provider: Option<AssistantProvider>,
+    /// The system prompt used when starting new conversations and inline assists.
+    ///
+    /// This can either be the name of a template in the prompt library, or the
+    /// literal text of the prompt. It is most useful in a project's `.zed/settings.json`.
+    ///
+    /// Default: null
+    default_system_prompt: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -341,6 +351,9 @@ impl Settings for AssistantSettings {
                 &mut settings.default_height,
                 value.default_height.map(Into::into),
             );
+            if let Some(default_system_prompt) = value.default_system_prompt.clone() {
+                settings.default_system_prompt = Some(default_system_prompt);
+            }
             if let Some(provider) = value.provider.clone() {
                 match (&mut settings.provider, provider) {
                     (
@@ -466,5 +479,29 @@ mod tests {
                 default_model: ZedDotDevModel::Custom("custom".into())
             }
         );
+        assert_eq!(
+            AssistantSettings::get_global(cx).default_system_prompt,
+            None
+        );
+
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "default_system_prompt": "docstrings"
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            AssistantSettings::get_global(cx)
+                .default_system_prompt
+                .as_deref(),
+            Some("docstrings")
+        );
     }
 }
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use fs::Fs;
+use futures::StreamExt;
+use std::{
+    ffi::OsStr,
+    fmt::Write,
+    iter,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// A named prompt template loaded from the user's prompt library.
+///
+/// Templates are plain Markdown files stored in the prompts directory. The
+/// file stem is used as the template's name, and the file contents may refer
+/// to variables using `{{variable}}` syntax. See [`PromptContext`] for the
+/// list of supported variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub path: PathBuf,
+    pub body: String,
+}
+
+/// The values that can be substituted into a [`PromptTemplate`].
+///
+/// Inline assists describe the buffer being transformed. Conversation templates and
+/// the `default_system_prompt` of a new conversation describe the selection of the
+/// workspace's active editor instead, and have no `user_prompt`.
+#[derive(Clone, Debug, Default)]
+pub struct PromptContext {
+    /// The prompt the user typed into the inline assistant, if any.
+    pub user_prompt: Option<String>,
+    /// The text currently selected in the editor.
+    pub selection: Option<String>,
+    /// The name of the language of the active buffer.
+    pub language: Option<String>,
+    /// The path of the active buffer, relative to its worktree.
+    pub file_path: Option<String>,
+    /// The outline items enclosing the selection, from outermost to innermost.
+    pub outline: Vec<String>,
+    /// The names of the project's worktree roots, joined by `/`.
+    pub project_name: Option<String>,
+}
+
+impl PromptContext {
+    fn variable(&self, name: &str) -> Option<String> {
+        match name {
+            "user_prompt" => Some(self.user_prompt.clone().unwrap_or_default()),
+            "selection" => Some(self.selection.clone().unwrap_or_default()),
+            "language" => Some(self.language.clone().unwrap_or_default()),
+            "file_path" => Some(self.file_path.clone().unwrap_or_default()),
+            "project_name" => Some(self.project_name.clone().unwrap_or_default()),
+            "outline" => {
+                let mut outline = String::new();
+                for (depth, item) in self.outline.iter().enumerate() {
+                    writeln!(outline, "{}{}", "  ".repeat(depth), item).unwrap();
+                }
+                Some(outline.trim_end().to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl PromptTemplate {
+    /// Substitutes every known `{{variable}}` in the template with its value
+    /// from the given context. Unknown variables are left untouched.
+    pub fn render(&self, context: &PromptContext) -> String {
+        let mut rendered = String::with_capacity(self.body.len());
+        let mut end_of_last_placeholder = 0;
+        for (range, name) in self.placeholders() {
+            rendered.push_str(&self.body[end_of_last_placeholder..range.start]);
+            if let Some(value) = context.variable(name) {
+                rendered.push_str(&value);
+            } else {
+                rendered.push_str(&self.body[range.clone()]);
+            }
+            end_of_last_placeholder = range.end;
+        }
+        rendered.push_str(&self.body[end_of_last_placeholder..]);
+        rendered
+    }
+
+    /// Whether the template embeds the user's typed prompt. Templates that don't
+    /// are treated as standalone instructions, and the user's prompt is appended.
+    pub fn uses_user_prompt(&self) -> bool {
+        self.placeholders().any(|(_, name)| name == "user_prompt")
+    }
+
+    /// Returns the range of every `{{variable}}` in the body, along with the
+    /// variable's name stripped of surrounding whitespace.
+    fn placeholders(&self) -> impl Iterator<Item = (Range<usize>, &str)> {
+        let mut offset = 0;
+        iter::from_fn(move || {
+            let start = offset + self.body[offset..].find("{{")?;
+            let end = start + 2 + self.body[start + 2..].find("}}")?;
+            offset = end + 2;
+            Some((start..offset, self.body[start + 2..end].trim()))
+        })
+    }
+}
+
+/// The set of prompt templates available to the assistant.
+#[derive(Clone, Debug, Default)]
+pub struct PromptLibrary {
+    templates: Vec<Arc<PromptTemplate>>,
+}
+
+impl PromptLibrary {
+    /// Loads every `.md` file in the given directory as a prompt template.
+    pub async fn load(fs: Arc<dyn Fs>, prompts_dir: &Path) -> Result<Self> {
+        fs.create_dir(prompts_dir).await?;
+
+        let mut templates = Vec::new();
+        let mut paths = fs.read_dir(prompts_dir).await?;
+        while let Some(path) = paths.next().await {
+            let path = path?;
+            if path.extension() != Some(OsStr::new("md")) {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let body = fs.load(&path).await?;
+            templates.push(Arc::new(PromptTemplate {
+                name: name.to_string(),
+                path: path.clone(),
+                body,
+            }));
+        }
+        templates.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { templates })
+    }
+
+    pub fn templates(&self) -> &[Arc<PromptTemplate>] {
+        &self.templates
+    }
+
+    pub fn template(&self, name: &str) -> Option<Arc<PromptTemplate>> {
+        self.templates
+            .iter()
+            .find(|template| template.name == name)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_prompt_template() {
+        let template = PromptTemplate {
+            name: "tests".into(),
+            path: PathBuf::from("/prompts/tests.md"),
+            body: "Write {{language}} tests for `{{ file_path }}` in {{outline}}.\n{{user_prompt}} {{unknown}} {{".into(),
+        };
+        assert!(template.uses_user_prompt());
+        let template_with_spaces = PromptTemplate {
+            body: "Document {{selection}} as {{ user_prompt }}".into(),
+            ..template.clone()
+        };
+        assert!(template_with_spaces.uses_user_prompt());
+        let template_without_user_prompt = PromptTemplate {
+            body: "Document {{selection}}, not {{user_prompt".into(),
+            ..template.clone()
+        };
+        assert!(!template_without_user_prompt.uses_user_prompt());
+
+        let context = PromptContext {
+            user_prompt: Some("Cover the error cases.".into()),
+            language: Some("Rust".into()),
+            file_path: Some("src/lib.rs".into()),
+            outline: vec!["mod parser".into(), "fn parse".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            template.render(&context),
+            "Write Rust tests for `src/lib.rs` in mod parser\n  fn parse.\nCover the error cases. {{unknown}} {{"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_load_prompt_library(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/prompts",
+            json!({
+                "tests.md": "Write tests for {{selection}}",
+                "docstrings.md": "Document {{selection}}",
+                "notes.txt": "not a template",
+            }),
+        )
+        .await;
+
+        let library = PromptLibrary::load(fs, Path::new("/prompts"))
+            .await
+            .unwrap();
+        let names = library
+            .templates()
+            .iter()
+            .map(|template| template.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["docstrings", "tests"]);
+        assert_eq!(
+            library.template("tests").unwrap().body,
+            "Write tests for {{selection}}"
+        );
+        assert!(library.template("notes").is_none());
+    }
+}
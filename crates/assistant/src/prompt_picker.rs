@@ -0,0 +1,205 @@
+use crate::{
+    prompt_library::{PromptLibrary, PromptTemplate},
+    AssistantPanel,
+};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+/// What to do with the template picked in a [`PromptPicker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptPickerTarget {
+    InlineAssist,
+    NewConversation,
+}
+
+pub struct PromptPicker {
+    picker: View<Picker<PromptPickerDelegate>>,
+}
+
+impl PromptPicker {
+    pub fn new(
+        library: Arc<PromptLibrary>,
+        target: PromptPickerTarget,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = PromptPickerDelegate::new(cx.view().downgrade(), library, target, workspace);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for PromptPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for PromptPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for PromptPicker {}
+impl ModalView for PromptPicker {}
+
+pub struct PromptPickerDelegate {
+    prompt_picker: WeakView<PromptPicker>,
+    workspace: WeakView<Workspace>,
+    target: PromptPickerTarget,
+    templates: Vec<Arc<PromptTemplate>>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PromptPickerDelegate {
+    fn new(
+        prompt_picker: WeakView<PromptPicker>,
+        library: Arc<PromptLibrary>,
+        target: PromptPickerTarget,
+        workspace: WeakView<Workspace>,
+    ) -> Self {
+        let templates = library.templates().to_vec();
+        let candidates = templates
+            .iter()
+            .enumerate()
+            .map(|(id, template)| StringMatchCandidate::new(id, template.name.clone()))
+            .collect();
+        Self {
+            prompt_picker,
+            workspace,
+            target,
+            templates,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for PromptPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.target {
+            PromptPickerTarget::InlineAssist => "Select a prompt for inline assist...".into(),
+            PromptPickerTarget::NewConversation => {
+                "Select a system prompt for the new conversation...".into()
+            }
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(template) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.templates.get(mat.candidate_id))
+            .cloned()
+        else {
+            return;
+        };
+
+        self.dismissed(cx);
+        let target = self.target;
+        self.workspace
+            .update(cx, |workspace, cx| match target {
+                PromptPickerTarget::InlineAssist => {
+                    AssistantPanel::start_inline_assist(workspace, Some(template), cx)
+                }
+                PromptPickerTarget::NewConversation => {
+                    if let Some(panel) = workspace.focus_panel::<AssistantPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.new_conversation_in_workspace(workspace, Some(&template), cx);
+                        });
+                    }
+                }
+            })
+            .log_err();
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.prompt_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
@@ -78,6 +78,7 @@ CREATE TABLE "worktree_entries" (
     "inode" INTEGER NOT NULL,
     "mtime_seconds" INTEGER NOT NULL,
     "mtime_nanos" INTEGER NOT NULL,
+    "size" INTEGER NOT NULL DEFAULT 0,
     "is_symlink" BOOL NOT NULL,
     "is_external" BOOL NOT NULL,
     "is_ignored" BOOL NOT NULL,
@@ -108,7 +109,8 @@ CREATE TABLE "worktree_settings_files" (
     "worktree_id" INTEGER NOT NULL,
     "path" VARCHAR NOT NULL,
     "content" TEXT,
-    PRIMARY KEY(project_id, worktree_id, path),
+    "kind" INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(project_id, worktree_id, path, kind),
     FOREIGN KEY(project_id, worktree_id) REFERENCES worktrees (project_id, id) ON DELETE CASCADE
 );
 CREATE INDEX "index_worktree_settings_files_on_project_id" ON "worktree_settings_files" ("project_id");
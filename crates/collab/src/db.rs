@@ -717,6 +717,7 @@ pub struct Worktree {
 pub struct WorktreeSettingsFile {
     pub path: String,
     pub content: String,
+    pub kind: proto::LocalSettingsKind,
 }
 
 pub struct NewExtensionVersion {
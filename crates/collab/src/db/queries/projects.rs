@@ -251,6 +251,7 @@ impl Database {
                         inode: ActiveValue::set(entry.inode as i64),
                         mtime_seconds: ActiveValue::set(mtime.seconds as i64),
                         mtime_nanos: ActiveValue::set(mtime.nanos as i32),
+                        size: ActiveValue::set(entry.size as i64),
                         is_symlink: ActiveValue::set(entry.is_symlink),
                         is_ignored: ActiveValue::set(entry.is_ignored),
                         is_external: ActiveValue::set(entry.is_external),
@@ -271,6 +272,7 @@ impl Database {
                         worktree_entry::Column::Inode,
                         worktree_entry::Column::MtimeSeconds,
                         worktree_entry::Column::MtimeNanos,
+                        worktree_entry::Column::Size,
                         worktree_entry::Column::IsSymlink,
                         worktree_entry::Column::IsIgnored,
                         worktree_entry::Column::GitStatus,
@@ -479,6 +481,7 @@ impl Database {
                     project_id: ActiveValue::Set(project_id),
                     worktree_id: ActiveValue::Set(update.worktree_id as i64),
                     path: ActiveValue::Set(update.path.clone()),
+                    kind: ActiveValue::Set(update.kind),
                     content: ActiveValue::Set(content.clone()),
                 })
                 .on_conflict(
@@ -486,6 +489,7 @@ impl Database {
                         worktree_settings_file::Column::ProjectId,
                         worktree_settings_file::Column::WorktreeId,
                         worktree_settings_file::Column::Path,
+                        worktree_settings_file::Column::Kind,
                     ])
                     .update_column(worktree_settings_file::Column::Content)
                     .to_owned(),
@@ -497,6 +501,7 @@ impl Database {
                     project_id: ActiveValue::Set(project_id),
                     worktree_id: ActiveValue::Set(update.worktree_id as i64),
                     path: ActiveValue::Set(update.path.clone()),
+                    kind: ActiveValue::Set(update.kind),
                     ..Default::default()
                 })
                 .exec(&*tx)
@@ -663,6 +668,7 @@ impl Database {
                             seconds: db_entry.mtime_seconds as u64,
                             nanos: db_entry.mtime_nanos as u32,
                         }),
+                        size: db_entry.size as u64,
                         is_symlink: db_entry.is_symlink,
                         is_ignored: db_entry.is_ignored,
                         is_external: db_entry.is_external,
@@ -730,6 +736,8 @@ impl Database {
                     worktree.settings_files.push(WorktreeSettingsFile {
                         path: db_settings_file.path,
                         content: db_settings_file.content,
+                        kind: proto::LocalSettingsKind::from_i32(db_settings_file.kind)
+                            .unwrap_or(proto::LocalSettingsKind::Settings),
                     });
                 }
             }
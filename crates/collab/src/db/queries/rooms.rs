@@ -610,6 +610,7 @@ impl Database {
                                         seconds: db_entry.mtime_seconds as u64,
                                         nanos: db_entry.mtime_nanos as u32,
                                     }),
+                                    size: db_entry.size as u64,
                                     is_symlink: db_entry.is_symlink,
                                     is_ignored: db_entry.is_ignored,
                                     is_external: db_entry.is_external,
@@ -681,6 +682,8 @@ impl Database {
                             worktree.settings_files.push(WorktreeSettingsFile {
                                 path: db_settings_file.path,
                                 content: db_settings_file.content,
+                                kind: proto::LocalSettingsKind::from_i32(db_settings_file.kind)
+                                    .unwrap_or(proto::LocalSettingsKind::Settings),
                             });
                         }
                     }
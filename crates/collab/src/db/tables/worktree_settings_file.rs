@@ -10,6 +10,8 @@ pub struct Model {
     pub worktree_id: i64,
     #[sea_orm(primary_key)]
     pub path: String,
+    #[sea_orm(primary_key)]
+    pub kind: i32,
     pub content: String,
 }
 
@@ -346,6 +346,8 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::SynchronizeBuffers>)
             .add_request_handler(forward_read_only_project_request::<proto::InlayHints>)
             .add_request_handler(forward_read_only_project_request::<proto::OpenBufferByPath>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSharedTerminals>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetProjectTasks>)
             .add_request_handler(forward_mutating_project_request::<proto::GetCompletions>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::ApplyCompletionAdditionalEdits>,
@@ -366,6 +368,8 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::ExpandProjectEntry>)
             .add_request_handler(forward_mutating_project_request::<proto::OnTypeFormatting>)
             .add_request_handler(forward_mutating_project_request::<proto::SaveBuffer>)
+            .add_request_handler(forward_mutating_project_request::<proto::TerminalInput>)
+            .add_request_handler(forward_mutating_project_request::<proto::RunProjectTask>)
             .add_message_handler(create_buffer_for_peer)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
@@ -373,6 +377,9 @@ impl Server {
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateDiffBase>)
+            .add_message_handler(broadcast_terminal_message::<proto::ShareTerminal>)
+            .add_message_handler(broadcast_terminal_message::<proto::UpdateTerminal>)
+            .add_message_handler(broadcast_terminal_message::<proto::UnshareTerminal>)
             .add_request_handler(get_users)
             .add_request_handler(user_handler(fuzzy_search_users))
             .add_request_handler(user_handler(request_contact))
@@ -1479,6 +1486,7 @@ async fn rejoin_room(
                             worktree_id: worktree.id,
                             path: settings_file.path,
                             content: Some(settings_file.content),
+                            kind: settings_file.kind.into(),
                         },
                     )?;
                 }
@@ -1910,6 +1918,7 @@ fn join_project_internal(
                     worktree_id: worktree.id,
                     path: settings_file.path,
                     content: Some(settings_file.content),
+                    kind: settings_file.kind.into(),
                 },
             )?;
         }
@@ -2275,6 +2284,33 @@ async fn broadcast_project_message_from_host<T: EntityMessage<Entity = ShareProj
     Ok(())
 }
 
+/// Notify guests that a terminal shared by the host has changed. Only the host can share
+/// terminals, as they run on its machine.
+async fn broadcast_terminal_message<T: EntityMessage<Entity = ShareProject>>(
+    request: T,
+    session: Session,
+) -> Result<()> {
+    let project_id = ProjectId::from_proto(request.remote_entity_id());
+    let project_connection_ids = {
+        let db = session.db().await;
+        db.check_user_is_project_host(project_id, session.connection_id)
+            .await?;
+        db.project_connection_ids(project_id, session.connection_id)
+            .await?
+    };
+
+    broadcast(
+        Some(session.connection_id),
+        project_connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    Ok(())
+}
+
 /// Start following another user in a call.
 async fn follow(
     request: proto::Follow,
@@ -19,8 +19,8 @@ use language::{
 use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use project::{
-    search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
-    SearchResult,
+    project_settings::ProjectSettings, search::SearchQuery, DiagnosticSummary, FormatTrigger,
+    HoverBlockKind, Location, Project, ProjectPath, SearchResult,
 };
 use rand::prelude::*;
 use serde_json::json;
@@ -36,6 +36,7 @@ use std::{
     },
     time::Duration,
 };
+use task::Task as _;
 use unindent::Unindent as _;
 
 #[ctor::ctor]
@@ -5930,3 +5931,116 @@ async fn test_cmd_k_left(cx: &mut TestAppContext) {
         assert!(workspace.items(cx).collect::<Vec<_>>().len() == 2);
     });
 }
+
+#[gpui::test]
+async fn test_guest_tasks(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    // The project's own settings try to let guests run tasks.
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".zed": {
+                    "settings.json": r#"{ "guest_tasks": { "enabled": true } }"#,
+                    "tasks.json": r#"[{ "label": "print file", "command": "echo $ZED_FILE" }]"#,
+                },
+                "a.txt": "hello",
+            }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    executor.run_until_parked();
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let spawned_tasks = Rc::new(RefCell::new(Vec::new()));
+    cx_a.update(|cx| {
+        let spawned_tasks = spawned_tasks.clone();
+        cx.subscribe(&project_a, move |_, event, _| {
+            if let project::Event::SpawnTask(spawn_in_terminal) = event {
+                spawned_tasks
+                    .borrow_mut()
+                    .push(spawn_in_terminal.env.clone());
+            }
+        })
+        .detach();
+    });
+
+    // Only the host's user settings can let guests list and run tasks.
+    assert!(project_b
+        .update(cx_b, |project, cx| project.host_tasks(cx))
+        .await
+        .is_err());
+    assert!(project_b
+        .update(cx_b, |project, cx| {
+            project.run_host_task(task::TaskId("print file".into()), None, cx)
+        })
+        .await
+        .is_err());
+    executor.run_until_parked();
+    assert!(spawned_tasks.borrow().is_empty());
+
+    cx_a.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.guest_tasks.enabled = Some(true);
+            });
+        });
+    });
+    let tasks = project_b
+        .update(cx_b, |project, cx| project.host_tasks(cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        tasks
+            .iter()
+            .map(|(_, task)| task.name().to_string())
+            .collect::<Vec<_>>(),
+        vec!["print file".to_string()]
+    );
+
+    // The host derives the task variables from the guest's selection.
+    let buffer_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_buffer((worktree_id, "a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let location = buffer_b.read_with(cx_b, |buffer, _| Location {
+        buffer: buffer_b.clone(),
+        range: buffer.anchor_before(1)..buffer.anchor_after(3),
+    });
+    let task_id = tasks[0].1.id().clone();
+    project_b
+        .update(cx_b, |project, cx| {
+            project.run_host_task(task_id, Some(location), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let spawned_tasks = spawned_tasks.borrow();
+    assert_eq!(spawned_tasks.len(), 1);
+    let env = &spawned_tasks[0];
+    assert_eq!(env["ZED_FILE"], "/dir/a.txt");
+    assert_eq!(env["ZED_WORKTREE_ROOT"], "/dir");
+    assert_eq!(env["ZED_ROW"], "1");
+    assert_eq!(env["ZED_COLUMN"], "2");
+    assert_eq!(env["ZED_SELECTED_TEXT"], "el");
+}
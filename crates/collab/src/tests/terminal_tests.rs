@@ -0,0 +1,178 @@
+use crate::tests::TestServer;
+use call::ActiveCall;
+use futures::channel::mpsc;
+use gpui::{BackgroundExecutor, Context as _, Model, TestAppContext};
+use rpc::proto;
+use serde_json::json;
+use terminal::{SharedTerminalState, Terminal, TerminalBuilder};
+
+#[gpui::test]
+async fn test_shared_terminal_input_requires_write_access(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    cx_b.update(terminal::init);
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree("/a", json!({ "a.txt": "a-contents" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project("/a", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let (terminal_a, mut input_a) = build_host_terminal(cx_a);
+    let terminal_id = terminal_a.entity_id().as_u64();
+    project_a
+        .update(cx_a, |project, cx| project.share_terminal(&terminal_a, cx))
+        .unwrap();
+    executor.run_until_parked();
+    let terminal_b = project_b.read_with(cx_b, |project, _| {
+        project.remote_terminals().next().unwrap().clone()
+    });
+    assert!(!is_writable(&terminal_b, cx_b));
+
+    // The host refuses the input of collaborators it didn't allow to type.
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    assert!(client_b
+        .client()
+        .request(proto::TerminalInput {
+            project_id,
+            terminal_id,
+            input: b"rm -rf /\r".to_vec(),
+        })
+        .await
+        .is_err());
+    executor.run_until_parked();
+    assert!(input_a.try_next().is_err());
+
+    // Once allowed to, the guest's input reaches the host's shell.
+    project_a.update(cx_a, |project, cx| {
+        project.set_terminal_writer(&terminal_a, client_b.user_id().unwrap(), true, cx)
+    });
+    executor.run_until_parked();
+    assert!(is_writable(&terminal_b, cx_b));
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    executor.run_until_parked();
+    assert_eq!(input_a.try_next().unwrap(), Some(b"ls\r".to_vec()));
+
+    // Until the host revokes the permission.
+    project_a.update(cx_a, |project, cx| {
+        project.set_terminal_writer(&terminal_a, client_b.user_id().unwrap(), false, cx)
+    });
+    executor.run_until_parked();
+    assert!(!is_writable(&terminal_b, cx_b));
+    assert!(client_b
+        .client()
+        .request(proto::TerminalInput {
+            project_id,
+            terminal_id,
+            input: b"ls\r".to_vec(),
+        })
+        .await
+        .is_err());
+    executor.run_until_parked();
+    assert!(input_a.try_next().is_err());
+}
+
+#[gpui::test]
+async fn test_shared_terminal_refuses_input_of_read_only_guests(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    cx_b.update(terminal::init);
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    // Client A shares a project in the channel, which client B joins as a guest.
+    let project_a = client_a.build_test_project(cx_a).await;
+    active_call_a
+        .update(cx_a, |call, cx| call.join_channel(channel_id, cx))
+        .await
+        .unwrap();
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+    assert!(project_b.read_with(cx_b, |project, _| project.is_read_only()));
+
+    let (terminal_a, mut input_a) = build_host_terminal(cx_a);
+    let terminal_id = terminal_a.entity_id().as_u64();
+    project_a
+        .update(cx_a, |project, cx| {
+            project.share_terminal(&terminal_a, cx)?;
+            project.set_terminal_writer(&terminal_a, client_b.user_id().unwrap(), true, cx);
+            anyhow::Ok(())
+        })
+        .unwrap();
+    executor.run_until_parked();
+
+    // Even when the host allows them to type, guests that can't edit the project can't.
+    let terminal_b = project_b.read_with(cx_b, |project, _| {
+        project.remote_terminals().next().unwrap().clone()
+    });
+    assert!(!is_writable(&terminal_b, cx_b));
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    assert!(client_b
+        .client()
+        .request(proto::TerminalInput {
+            project_id,
+            terminal_id,
+            input: b"ls\r".to_vec(),
+        })
+        .await
+        .is_err());
+    executor.run_until_parked();
+    assert!(input_a.try_next().is_err());
+}
+
+/// Builds a terminal without a shell, whose input is sent to the returned channel.
+fn build_host_terminal(
+    cx: &mut TestAppContext,
+) -> (Model<Terminal>, mpsc::UnboundedReceiver<Vec<u8>>) {
+    let (input_tx, input_rx) = mpsc::unbounded();
+    let state = SharedTerminalState {
+        title: "zsh".into(),
+        columns: 80,
+        lines: 24,
+        writable: true,
+    };
+    let terminal =
+        cx.new_model(|cx| TerminalBuilder::new_remote(state, None, input_tx).subscribe(cx));
+    (terminal, input_rx)
+}
+
+fn is_writable(terminal: &Model<Terminal>, cx: &mut TestAppContext) -> bool {
+    terminal.read_with(cx, |terminal, _| {
+        terminal
+            .shared_state()
+            .map_or(false, |state| state.writable)
+    })
+}
@@ -1254,7 +1254,7 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &AppContext) -> Task<Result<String>> {
+        fn load_bytes(&self, _: &AppContext) -> Task<Result<Vec<u8>>> {
             unimplemented!()
         }
 
@@ -1264,6 +1264,7 @@ mod tests {
             _: &clock::Global,
             _: language::RopeFingerprint,
             _: language::LineEnding,
+            _: language::Encoding,
             _: Option<std::time::SystemTime>,
             _: &mut AppContext,
         ) {
@@ -40,10 +40,11 @@ use ui::{h_flex, prelude::*, Icon, IconName, Label};
 use util::TryFutureExt;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
+    location_list::{LocationEntry, LocationList},
     ItemNavHistory, Pane, ToolbarItemLocation, Workspace,
 };
 
-actions!(diagnostics, [Deploy, ToggleWarnings]);
+actions!(diagnostics, [Deploy, ToggleWarnings, PopulateLocationList]);
 
 const CONTEXT_LINE_COUNT: u32 = 1;
 
@@ -114,7 +115,9 @@ impl Render for ProjectDiagnosticsEditor {
 
 impl ProjectDiagnosticsEditor {
     fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-        workspace.register_action(Self::deploy);
+        workspace
+            .register_action(Self::deploy)
+            .register_action(Self::populate_location_list);
     }
 
     fn new(
@@ -206,6 +209,61 @@ impl ProjectDiagnosticsEditor {
         }
     }
 
+    /// Makes the primary diagnostics of the project the active location list of the workspace.
+    fn populate_location_list(
+        workspace: &mut Workspace,
+        _: &PopulateLocationList,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let include_warnings = workspace
+            .item_of_type::<ProjectDiagnosticsEditor>(cx)
+            .map_or_else(
+                || ProjectDiagnosticsSettings::get_global(cx).include_warnings,
+                |editor| editor.read(cx).include_warnings,
+            );
+        let max_severity = if include_warnings {
+            DiagnosticSeverity::WARNING
+        } else {
+            DiagnosticSeverity::ERROR
+        };
+        let open_buffers = workspace.project().update(cx, |project, cx| {
+            let mut paths = project
+                .diagnostic_summaries(false, cx)
+                .map(|(path, _, _)| path)
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths.dedup();
+            paths
+                .into_iter()
+                .map(|path| project.open_buffer(path, cx))
+                .collect::<Vec<_>>()
+        });
+        cx.spawn(|workspace, mut cx| async move {
+            let buffers = try_join_all(open_buffers).await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let mut entries = Vec::new();
+                for buffer in buffers {
+                    let snapshot = buffer.read(cx).snapshot();
+                    for (_, group) in snapshot.diagnostic_groups(None) {
+                        let entry = &group.entries[group.primary_ix];
+                        if entry.diagnostic.severity <= max_severity {
+                            let message = entry.diagnostic.message.lines().next().unwrap_or("");
+                            entries.push(
+                                LocationEntry::new(buffer.clone(), entry.range.clone(), cx)
+                                    .with_message(message.to_string()),
+                            );
+                        }
+                    }
+                }
+                let list = LocationList::new("Diagnostics", entries);
+                workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.push(list, cx));
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn toggle_warnings(&mut self, _: &ToggleWarnings, cx: &mut ViewContext<Self>) {
         self.include_warnings = !self.include_warnings;
         self.paths_to_update = self.current_diagnostics.clone();
@@ -1,5 +1,5 @@
-use crate::ProjectDiagnosticsEditor;
-use gpui::{div, EventEmitter, ParentElement, Render, ViewContext, WeakView};
+use crate::{PopulateLocationList, ProjectDiagnosticsEditor};
+use gpui::{Action, EventEmitter, ParentElement, Render, ViewContext, WeakView};
 use ui::prelude::*;
 use ui::{IconButton, IconName, Tooltip};
 use workspace::{item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
@@ -23,17 +23,27 @@ impl Render for ToolbarControls {
             "Include Warnings"
         };
 
-        div().child(
-            IconButton::new("toggle-warnings", IconName::ExclamationTriangle)
-                .tooltip(move |cx| Tooltip::text(tooltip, cx))
-                .on_click(cx.listener(|this, _, cx| {
-                    if let Some(editor) = this.editor.as_ref().and_then(|editor| editor.upgrade()) {
-                        editor.update(cx, |editor, cx| {
-                            editor.toggle_warnings(&Default::default(), cx);
-                        });
-                    }
-                })),
-        )
+        h_flex()
+            .child(
+                IconButton::new("toggle-warnings", IconName::ExclamationTriangle)
+                    .tooltip(move |cx| Tooltip::text(tooltip, cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(editor) =
+                            this.editor.as_ref().and_then(|editor| editor.upgrade())
+                        {
+                            editor.update(cx, |editor, cx| {
+                                editor.toggle_warnings(&Default::default(), cx);
+                            });
+                        }
+                    })),
+            )
+            .child(
+                IconButton::new("populate-location-list", IconName::Menu)
+                    .tooltip(|cx| {
+                        Tooltip::for_action("Send to Location List", &PopulateLocationList, cx)
+                    })
+                    .on_click(|_, cx| cx.dispatch_action(PopulateLocationList.boxed_clone())),
+            )
     }
 }
 
@@ -129,6 +129,7 @@ gpui::actions!(
         AcceptPartialCopilotSuggestion,
         AddSelectionAbove,
         AddSelectionBelow,
+        AddSelectionToWorkingSet,
         Backspace,
         Cancel,
         ConfirmRename,
@@ -158,8 +159,13 @@ gpui::actions!(
         DeleteToNextWordEnd,
         DeleteToPreviousSubwordStart,
         DeleteToPreviousWordStart,
+        DismissExcerpt,
         DisplayCursorNames,
+        ExpandExcerpts,
+        ExpandExcerptsDown,
+        ExpandExcerptsUp,
         ExpandMacroRecursively,
+        ExpandSnippet,
         FindAllReferences,
         Fold,
         FoldSelectedRanges,
@@ -0,0 +1,321 @@
+use crate::{
+    display_map::{HighlightedChunk, ToDisplayPoint},
+    DisplayPoint, EditorSnapshot,
+};
+use gpui::{AppContext, Hsla};
+use language::Point;
+use std::{collections::VecDeque, ops::Range};
+
+impl EditorSnapshot {
+    /// Returns the display ranges of the brackets in the given display rows, each colored
+    /// with the accent matching its pair's nesting depth. Only the buffers whose language
+    /// settings enable bracket colorization are considered.
+    pub(crate) fn bracket_colors_in_range(
+        &self,
+        display_rows: Range<u32>,
+        accents: &[Hsla],
+        cx: &AppContext,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let mut brackets = Vec::new();
+        if accents.is_empty() || display_rows.is_empty() {
+            return brackets;
+        }
+
+        let buffer = &self.buffer_snapshot;
+        let start = DisplayPoint::new(display_rows.start, 0).to_point(&self.display_snapshot);
+        let end = if display_rows.end > self.max_point().row() {
+            buffer.max_point()
+        } else {
+            DisplayPoint::new(display_rows.end, 0).to_point(&self.display_snapshot)
+        };
+
+        // Bracket pairs are looked up one excerpt at a time.
+        let start_offset = buffer.point_to_offset(start);
+        let mut segment_starts = vec![start_offset];
+        segment_starts.extend(
+            buffer
+                .excerpt_boundaries_in_range(start..end)
+                .map(|boundary| buffer.point_to_offset(Point::new(boundary.row, 0)))
+                .filter(|offset| *offset > start_offset),
+        );
+        let end_offset = buffer.point_to_offset(end);
+        for (ix, segment_start) in segment_starts.iter().copied().enumerate() {
+            let segment_end = segment_starts
+                .get(ix + 1)
+                // Leave out the newline separating this excerpt from the next one.
+                .map(|next_start| next_start.saturating_sub(1).max(segment_start))
+                .unwrap_or(end_offset);
+            if !buffer.settings_at(segment_start, cx).colorize_brackets {
+                continue;
+            }
+            let Some(pairs) = buffer.bracket_ranges(segment_start..segment_end) else {
+                continue;
+            };
+
+            let mut pairs = pairs.collect::<Vec<_>>();
+            pairs.sort_by_key(|(open, close)| (open.start, close.end));
+            pairs.dedup();
+
+            let mut enclosing_pair_ends = Vec::<usize>::new();
+            for (open, close) in pairs {
+                while enclosing_pair_ends
+                    .last()
+                    .map_or(false, |end| *end <= open.start)
+                {
+                    enclosing_pair_ends.pop();
+                }
+                let color = accents[enclosing_pair_ends.len() % accents.len()];
+                enclosing_pair_ends.push(close.end);
+
+                for range in [open, close] {
+                    if range.end > segment_start && range.start < segment_end {
+                        let start = range.start.to_display_point(&self.display_snapshot);
+                        let end = range.end.to_display_point(&self.display_snapshot);
+                        if start < end {
+                            brackets.push((start..end, color));
+                        }
+                    }
+                }
+            }
+        }
+
+        brackets.sort_by_key(|(range, _)| range.start);
+        brackets
+    }
+}
+
+/// Wraps the highlighted chunks of the display rows starting at a given row, splitting
+/// them around the given brackets to color them.
+pub(crate) struct BracketColorizedChunks<'a, I> {
+    chunks: I,
+    brackets: VecDeque<(Range<DisplayPoint>, Hsla)>,
+    position: DisplayPoint,
+    pending_chunk: Option<HighlightedChunk<'a>>,
+}
+
+impl<'a, I: Iterator<Item = HighlightedChunk<'a>>> BracketColorizedChunks<'a, I> {
+    pub fn new(chunks: I, start_row: u32, brackets: Vec<(Range<DisplayPoint>, Hsla)>) -> Self {
+        Self {
+            chunks,
+            brackets: brackets.into(),
+            position: DisplayPoint::new(start_row, 0),
+            pending_chunk: None,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = HighlightedChunk<'a>>> Iterator for BracketColorizedChunks<'a, I> {
+    type Item = HighlightedChunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.pending_chunk.take().or_else(|| self.chunks.next())?;
+        while self
+            .brackets
+            .front()
+            .map_or(false, |(range, _)| range.end <= self.position)
+        {
+            self.brackets.pop_front();
+        }
+        let Some((range, color)) = self.brackets.front().cloned() else {
+            self.position = position_after(chunk.chunk, self.position);
+            return Some(chunk);
+        };
+
+        // Split the chunk at the end of the bracket when we're in it, and at its start otherwise.
+        let (split_offset, color) = if range.start <= self.position {
+            (
+                offset_of(chunk.chunk, self.position, range.end),
+                Some(color),
+            )
+        } else {
+            (offset_of(chunk.chunk, self.position, range.start), None)
+        };
+        let (head, tail) = chunk
+            .chunk
+            .split_at(split_offset.unwrap_or(chunk.chunk.len()));
+        if !tail.is_empty() {
+            self.pending_chunk = Some(HighlightedChunk {
+                chunk: tail,
+                style: chunk.style,
+                is_tab: chunk.is_tab,
+            });
+        }
+        self.position = position_after(head, self.position);
+
+        let mut style = chunk.style;
+        if let Some(color) = color {
+            style.get_or_insert_with(Default::default).color = Some(color);
+        }
+        Some(HighlightedChunk {
+            chunk: head,
+            style,
+            is_tab: chunk.is_tab,
+        })
+    }
+}
+
+/// Returns the offset in `text`, which starts at `position`, of the given display point.
+fn offset_of(text: &str, mut position: DisplayPoint, target: DisplayPoint) -> Option<usize> {
+    for (offset, ch) in text.char_indices() {
+        if position >= target {
+            return Some(offset);
+        }
+        advance(&mut position, ch);
+    }
+    None
+}
+
+fn position_after(text: &str, mut position: DisplayPoint) -> DisplayPoint {
+    for ch in text.chars() {
+        advance(&mut position, ch);
+    }
+    position
+}
+
+fn advance(position: &mut DisplayPoint, ch: char) {
+    if ch == '\n' {
+        *position.row_mut() += 1;
+        *position.column_mut() = 0;
+    } else {
+        *position.column_mut() += ch.len_utf8() as u32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::{init_test, update_test_language_settings},
+        test::build_editor,
+        MultiBuffer,
+    };
+    use gpui::{Context, TestAppContext};
+    use language::{Buffer, BufferId, Language, LanguageConfig, LanguageMatcher};
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_bracket_colors(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.colorize_brackets = Some(true)
+        });
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_brackets_query(
+                r#"
+                ("(" @open ")" @close)
+                ("{" @open "}" @close)
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let text = "fn a() {\n    b(c());\n}";
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+                .with_language(language, cx)
+        });
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+        editor
+            .condition::<crate::EditorEvent>(cx, |editor, cx| {
+                !editor.buffer.read(cx).is_parsing(cx)
+            })
+            .await;
+
+        let accents = [gpui::red(), gpui::green(), gpui::blue()];
+        let colored_brackets = |rows: Range<u32>, cx: &mut gpui::VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                editor
+                    .snapshot(cx)
+                    .bracket_colors_in_range(rows, &accents, cx)
+                    .into_iter()
+                    .map(|(range, color)| {
+                        (
+                            (range.start.row(), range.start.column()),
+                            accents.iter().position(|accent| *accent == color).unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            colored_brackets(0..3, cx),
+            vec![
+                ((0, 4), 0),
+                ((0, 5), 0),
+                ((0, 7), 0),
+                ((1, 5), 1),
+                ((1, 7), 2),
+                ((1, 8), 2),
+                ((1, 9), 1),
+                ((2, 0), 0),
+            ]
+        );
+        // Brackets keep the depth of their enclosing pairs, even when those aren't visible.
+        assert_eq!(
+            colored_brackets(1..2, cx),
+            vec![((1, 5), 1), ((1, 7), 2), ((1, 8), 2), ((1, 9), 1)]
+        );
+
+        update_test_language_settings(cx, |settings| {
+            settings.defaults.colorize_brackets = Some(false);
+        });
+        assert_eq!(colored_brackets(0..3, cx), Vec::new());
+    }
+
+    #[test]
+    fn test_bracket_colorized_chunks() {
+        let chunks = ["fn a(", ") {\n", "}"]
+            .into_iter()
+            .map(|chunk| HighlightedChunk {
+                chunk,
+                style: None,
+                is_tab: false,
+            });
+        let brackets = vec![
+            (
+                DisplayPoint::new(0, 4)..DisplayPoint::new(0, 5),
+                gpui::red(),
+            ),
+            (
+                DisplayPoint::new(0, 5)..DisplayPoint::new(0, 6),
+                gpui::red(),
+            ),
+            (
+                DisplayPoint::new(0, 7)..DisplayPoint::new(0, 8),
+                gpui::blue(),
+            ),
+            (
+                DisplayPoint::new(1, 0)..DisplayPoint::new(1, 1),
+                gpui::blue(),
+            ),
+        ];
+        let chunks = BracketColorizedChunks::new(chunks, 0, brackets)
+            .map(|chunk| (chunk.chunk, chunk.style.and_then(|style| style.color)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunks,
+            vec![
+                ("fn a", None),
+                ("(", Some(gpui::red())),
+                (")", Some(gpui::red())),
+                (" ", None),
+                ("{", Some(gpui::blue())),
+                ("\n", None),
+                ("}", Some(gpui::blue())),
+            ]
+        );
+    }
+}
@@ -14,6 +14,7 @@
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behaviour.
 pub mod actions;
 mod blink_manager;
+mod bracket_colorization;
 pub mod display_map;
 mod editor_settings;
 mod element;
@@ -24,6 +25,7 @@ mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+mod indent_guides;
 pub mod items;
 mod mouse_context_menu;
 pub mod movement;
@@ -31,6 +33,8 @@ mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod sticky_scroll;
+mod working_set;
 
 #[cfg(test)]
 mod editor_tests;
@@ -83,11 +87,11 @@ use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use mouse_context_menu::MouseContextMenu;
 use movement::TextLayoutDetails;
-use multi_buffer::ToOffsetUtf16;
 pub use multi_buffer::{
     Anchor, AnchorRangeExt, ExcerptId, ExcerptRange, MultiBuffer, MultiBufferSnapshot, ToOffset,
     ToPoint,
 };
+use multi_buffer::{ExpandExcerptDirection, ToOffsetUtf16};
 use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
@@ -126,7 +130,10 @@ use ui::{
 use util::{maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::Toast;
 use workspace::{
-    searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
+    item::PreviewTabsSettings,
+    location_list::{LocationEntry, LocationList},
+    searchable::SearchEvent,
+    ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
 };
 
 use crate::hover_links::find_url;
@@ -225,6 +232,7 @@ impl InlayId {
 enum DocumentHighlightRead {}
 enum DocumentHighlightWrite {}
 enum InputComposition {}
+enum InlinePreview {}
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Direction {
@@ -406,6 +414,7 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
+    working_set: bool,
     cursor_shape: CursorShape,
     collapse_matches: bool,
     autoindent_mode: Option<AutoindentMode>,
@@ -419,9 +428,11 @@ pub struct Editor {
     hover_state: HoverState,
     gutter_hovered: bool,
     hovered_link_state: Option<HoveredLinkState>,
+    sticky_header_cache: sticky_scroll::StickyHeaderCache,
     copilot_state: CopilotState,
     inlay_hint_cache: InlayHintCache,
     next_inlay_id: usize,
+    inline_previews: Vec<InlayId>,
     _subscriptions: Vec<Subscription>,
     pixel_position_of_newest_cursor: Option<gpui::Point<Pixels>>,
     gutter_width: Pixels,
@@ -612,6 +623,8 @@ struct AutocloseRegion {
 #[derive(Debug)]
 struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
+    /// The values to choose from for each tabstop, parallel to `ranges`.
+    choices: Vec<Option<Vec<String>>>,
     active_index: usize,
 }
 
@@ -1457,6 +1470,7 @@ impl Editor {
         clone.selections.clone_state(&self.selections);
         clone.scroll_manager.clone_state(&self.scroll_manager);
         clone.searchable = self.searchable;
+        clone.working_set = self.working_set;
         clone
     }
 
@@ -1543,11 +1557,13 @@ impl Editor {
             next_completion_id: 0,
             completion_documentation_pre_resolve_debounce: DebouncedDelay::new(),
             next_inlay_id: 0,
+            inline_previews: Vec::new(),
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             pending_rename: Default::default(),
             searchable: true,
+            working_set: false,
             cursor_shape: Default::default(),
             autoindent_mode: Some(AutoindentMode::EachLine),
             collapse_matches: false,
@@ -1562,6 +1578,7 @@ impl Editor {
             remote_id: None,
             hover_state: Default::default(),
             hovered_link_state: Default::default(),
+            sticky_header_cache: Default::default(),
             copilot_state: Default::default(),
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
             gutter_hovered: false,
@@ -3548,7 +3565,9 @@ impl Editor {
         let snippet;
         let text;
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let variables = self.snippet_variables(cx);
+            snippet =
+                Some(Snippet::parse_with_variables(&completion.new_text, &variables).log_err()?);
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -4409,10 +4428,15 @@ impl Editor {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.select_ranges(tabstop.iter().cloned());
             });
+            let choices = snippet.choices.first().cloned().flatten();
             self.snippet_stack.push(SnippetState {
                 active_index: 0,
                 ranges: tabstops,
+                choices: snippet.choices,
             });
+            if let Some(choices) = choices {
+                self.show_snippet_choices(&choices, cx);
+            }
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
             if self.autoclose_regions.is_empty() {
@@ -4453,6 +4477,174 @@ impl Editor {
         Ok(())
     }
 
+    /// Shows the values of a snippet's choice tabstop in the completions menu.
+    fn show_snippet_choices(&mut self, choices: &[String], cx: &mut ViewContext<Self>) {
+        let selection = self.selections.newest_anchor().clone();
+        let multibuffer = self.buffer.read(cx);
+        let Some((buffer, start)) = multibuffer.text_anchor_for_position(selection.start, cx)
+        else {
+            return;
+        };
+        let Some((_, end)) = multibuffer.text_anchor_for_position(selection.end, cx) else {
+            return;
+        };
+
+        let completions = choices
+            .iter()
+            .map(|choice| Completion {
+                old_range: start..end,
+                new_text: choice.clone(),
+                label: CodeLabel::plain(choice.clone(), None),
+                server_id: project::NON_LSP_COMPLETION_SERVER_ID,
+                documentation: Some(Documentation::Undocumented),
+                lsp_completion: lsp::CompletionItem {
+                    label: choice.clone(),
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+        let matches = choices
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, choice)| StringMatch {
+                candidate_id,
+                score: 0.,
+                positions: Vec::new(),
+                string: choice.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        self.completion_tasks.clear();
+        self.discard_copilot_suggestion(cx);
+        *self.context_menu.write() = Some(ContextMenu::Completions(CompletionsMenu {
+            id: post_inc(&mut self.next_completion_id),
+            initial_position: selection.start,
+            buffer,
+            match_candidates: choices
+                .iter()
+                .enumerate()
+                .map(|(id, choice)| StringMatchCandidate::new(id, choice.clone()))
+                .collect(),
+            completions: Arc::new(RwLock::new(completions.into())),
+            matches: matches.into(),
+            selected_item: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+            selected_completion_documentation_resolve_debounce: Arc::new(Mutex::new(
+                DebouncedDelay::new(),
+            )),
+        }));
+        cx.notify();
+    }
+
+    /// The values of the variables that can be referenced in snippets, such as `$TM_FILENAME`.
+    fn snippet_variables(
+        &self,
+        cx: &mut ViewContext<Self>,
+    ) -> std::collections::HashMap<String, String> {
+        let mut variables = std::collections::HashMap::default();
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selection = self.selections.newest::<Point>(cx);
+        let head = selection.head();
+
+        variables.insert(
+            "TM_SELECTED_TEXT".into(),
+            snapshot.text_for_range(selection.range()).collect(),
+        );
+        variables.insert(
+            "TM_CURRENT_LINE".into(),
+            snapshot
+                .text_for_range(
+                    Point::new(head.row, 0)..Point::new(head.row, snapshot.line_len(head.row)),
+                )
+                .collect(),
+        );
+        let (word_range, kind) = snapshot.surrounding_word(head);
+        if kind == Some(CharKind::Word) {
+            variables.insert(
+                "TM_CURRENT_WORD".into(),
+                snapshot.text_for_range(word_range).collect(),
+            );
+        }
+        variables.insert("TM_LINE_INDEX".into(), head.row.to_string());
+        variables.insert("TM_LINE_NUMBER".into(), (head.row + 1).to_string());
+
+        if let Some(file) = snapshot.file_at(head) {
+            let file_name = Path::new(file.file_name(cx));
+            variables.insert("TM_FILENAME".into(), file_name.to_string_lossy().into());
+            if let Some(stem) = file_name.file_stem() {
+                variables.insert("TM_FILENAME_BASE".into(), stem.to_string_lossy().into());
+            }
+            variables.insert(
+                "RELATIVE_FILEPATH".into(),
+                file.path().to_string_lossy().into(),
+            );
+            if let Some(file) = file.as_local() {
+                let abs_path = file.abs_path(cx);
+                if let Some(directory) = abs_path.parent() {
+                    variables.insert("TM_DIRECTORY".into(), directory.to_string_lossy().into());
+                }
+                variables.insert("TM_FILEPATH".into(), abs_path.to_string_lossy().into());
+            }
+        }
+        if let Some(project) = &self.project {
+            let root_names = project.read(cx).worktree_root_names(cx).join(", ");
+            variables.insert("WORKSPACE_NAME".into(), root_names);
+        }
+        if let Some(clipboard) = cx.read_from_clipboard() {
+            variables.insert("CLIPBOARD".into(), clipboard.text().clone());
+        }
+
+        variables
+    }
+
+    /// Replaces the word before the cursor with the user snippet it's the prefix of.
+    pub fn expand_snippet(&mut self, _: &ExpandSnippet, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            cx.propagate();
+            return;
+        };
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<usize>(cx).head();
+        let (word_range, kind) = snapshot.surrounding_word(head);
+        if kind != Some(CharKind::Word) || word_range.start == head {
+            cx.propagate();
+            return;
+        }
+        let word_range = word_range.start..head;
+        let prefix = snapshot
+            .text_for_range(word_range.clone())
+            .collect::<String>();
+
+        let language_name = snapshot
+            .language_at(head)
+            .map(|language| language.name().to_string());
+        let worktree = snapshot
+            .file_at(head)
+            .map(|file| project::WorktreeId::from_usize(file.worktree_id()));
+        let snippets = project
+            .read(cx)
+            .snippet_inventory()
+            .read(cx)
+            .snippets_for(language_name.as_deref(), worktree);
+        let Some(user_snippet) = snippets
+            .iter()
+            .find(|snippet| snippet.prefixes.iter().any(|p| *p == prefix))
+        else {
+            cx.propagate();
+            return;
+        };
+
+        let variables = self.snippet_variables(cx);
+        let Some(snippet) = Snippet::parse_with_variables(&user_snippet.body, &variables).log_err()
+        else {
+            return;
+        };
+        self.transact(cx, |this, cx| {
+            this.insert_snippet(&[word_range], snippet, cx).log_err();
+        });
+    }
+
     pub fn move_to_next_snippet_tabstop(&mut self, cx: &mut ViewContext<Self>) -> bool {
         self.move_to_snippet_tabstop(Bias::Right, cx)
     }
@@ -4485,6 +4677,9 @@ impl Editor {
                 self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.select_anchor_ranges(current_ranges.iter().cloned())
                 });
+                if let Some(Some(choices)) = snippet.choices.get(snippet.active_index) {
+                    self.show_snippet_choices(choices, cx);
+                }
                 // If snippet state is not at the last tabstop, push it back on the stack
                 if snippet.active_index + 1 < snippet.ranges.len() {
                     self.snippet_stack.push(snippet);
@@ -7829,7 +8024,14 @@ impl Editor {
                                             workspace.active_pane().clone()
                                         };
 
-                                        workspace.open_project_item(pane, target.buffer.clone(), cx)
+                                        let allow_preview = PreviewTabsSettings::get_global(cx)
+                                            .enable_preview_from_code_navigation;
+                                        workspace.open_project_item_preview(
+                                            pane,
+                                            target.buffer.clone(),
+                                            allow_preview,
+                                            cx,
+                                        )
                                     });
                                 target_editor.update(cx, |target_editor, cx| {
                                     // When selecting a definition in a different buffer, disable the nav history
@@ -8052,9 +8254,12 @@ impl Editor {
                         cx.window_context().defer(move |cx| {
                             let target_editor: View<Self> =
                                 workspace.update(cx, |workspace, cx| {
-                                    workspace.open_project_item(
+                                    let allow_preview = PreviewTabsSettings::get_global(cx)
+                                        .enable_preview_from_code_navigation;
+                                    workspace.open_project_item_preview(
                                         workspace.active_pane().clone(),
                                         target.buffer.clone(),
+                                        allow_preview,
                                         cx,
                                     )
                                 });
@@ -8082,6 +8287,16 @@ impl Editor {
                         )
                     })
                     .unwrap();
+                let entries = locations
+                    .iter()
+                    .map(|location| {
+                        LocationEntry::new(location.buffer.clone(), location.range.clone(), cx)
+                    })
+                    .collect();
+                let list = LocationList::new(title.clone(), entries);
+                workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.push(list, cx));
                 Self::open_locations_in_multibuffer(
                     workspace, locations, replica_id, title, false, cx,
                 );
@@ -9407,6 +9622,40 @@ impl Editor {
         cx.notify();
     }
 
+    /// Shows each text inline at its position without editing the buffer, replacing the previews
+    /// shown before.
+    pub fn set_inline_previews(
+        &mut self,
+        previews: Vec<(Anchor, String)>,
+        style: HighlightStyle,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let inlays = previews
+            .into_iter()
+            .map(|(position, text)| {
+                Inlay::suggestion(post_inc(&mut self.next_inlay_id), position, text)
+            })
+            .collect::<Vec<_>>();
+        let highlights = inlays
+            .iter()
+            .map(|inlay| InlayHighlight {
+                inlay: inlay.id,
+                inlay_position: inlay.position,
+                range: 0..inlay.text.len(),
+            })
+            .collect();
+        let to_remove = mem::replace(
+            &mut self.inline_previews,
+            inlays.iter().map(|inlay| inlay.id).collect(),
+        );
+        self.splice_inlays(to_remove, inlays, cx);
+        self.highlight_inlays::<InlinePreview>(highlights, style, cx);
+    }
+
+    pub fn clear_inline_previews(&mut self, cx: &mut ViewContext<Self>) {
+        self.set_inline_previews(Vec::new(), HighlightStyle::default(), cx);
+    }
+
     pub fn text_highlights<'a, T: 'static>(
         &'a self,
         cx: &'a AppContext,
@@ -9499,6 +9748,10 @@ impl Editor {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
+            multi_buffer::Event::ExcerptsExpanded { ids } => {
+                self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                cx.emit(EditorEvent::ExcerptsExpanded { ids: ids.clone() })
+            }
             multi_buffer::Event::Reparsed => cx.emit(EditorEvent::Reparsed),
             multi_buffer::Event::LanguageChanged => {
                 cx.emit(EditorEvent::Reparsed);
@@ -9604,6 +9857,88 @@ impl Editor {
         });
     }
 
+    fn expand_excerpts(&mut self, _: &ExpandExcerpts, cx: &mut ViewContext<Self>) {
+        self.expand_selected_excerpts(ExpandExcerptDirection::UpAndDown, cx)
+    }
+
+    fn expand_excerpts_up(&mut self, _: &ExpandExcerptsUp, cx: &mut ViewContext<Self>) {
+        self.expand_selected_excerpts(ExpandExcerptDirection::Up, cx)
+    }
+
+    fn expand_excerpts_down(&mut self, _: &ExpandExcerptsDown, cx: &mut ViewContext<Self>) {
+        self.expand_selected_excerpts(ExpandExcerptDirection::Down, cx)
+    }
+
+    fn expand_selected_excerpts(
+        &mut self,
+        direction: ExpandExcerptDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.buffer.read(cx).is_singleton() {
+            cx.propagate();
+            return;
+        }
+
+        let excerpt_ids = self.selected_excerpt_ids(cx);
+        self.expand_excerpts_in_direction(excerpt_ids, direction, cx);
+    }
+
+    pub fn expand_excerpt(
+        &mut self,
+        excerpt_id: ExcerptId,
+        direction: ExpandExcerptDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.expand_excerpts_in_direction([excerpt_id], direction, cx);
+    }
+
+    fn expand_excerpts_in_direction(
+        &mut self,
+        excerpt_ids: impl IntoIterator<Item = ExcerptId>,
+        direction: ExpandExcerptDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let line_count = EditorSettings::get_global(cx).expand_excerpt_lines;
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.expand_excerpts(excerpt_ids, line_count, direction, cx)
+        });
+    }
+
+    fn dismiss_excerpt(&mut self, _: &DismissExcerpt, cx: &mut ViewContext<Self>) {
+        if self.buffer.read(cx).is_singleton() {
+            cx.propagate();
+            return;
+        }
+
+        let excerpt_ids = self.selected_excerpt_ids(cx);
+        self.dismiss_excerpts(excerpt_ids, cx);
+    }
+
+    pub fn dismiss_excerpts(
+        &mut self,
+        excerpt_ids: impl IntoIterator<Item = ExcerptId>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.buffer
+            .update(cx, |buffer, cx| buffer.remove_excerpts(excerpt_ids, cx));
+    }
+
+    /// Returns the ids of the excerpts intersecting any selection, in the order they appear.
+    fn selected_excerpt_ids(&self, cx: &AppContext) -> Vec<ExcerptId> {
+        let buffer = self.buffer.read(cx);
+        let mut excerpt_ids = Vec::new();
+        for selection in self.selections.all::<usize>(cx) {
+            for (_, _, excerpt_id) in
+                buffer.range_to_buffer_ranges(selection.start..selection.end, cx)
+            {
+                if !excerpt_ids.contains(&excerpt_id) {
+                    excerpt_ids.push(excerpt_id);
+                }
+            }
+        }
+        excerpt_ids
+    }
+
     fn jump(
         &mut self,
         path: ProjectPath,
@@ -10214,6 +10549,9 @@ pub enum EditorEvent {
     ExcerptsRemoved {
         ids: Vec<ExcerptId>,
     },
+    ExcerptsExpanded {
+        ids: Vec<ExcerptId>,
+    },
     BufferEdited,
     Edited,
     Reparsed,
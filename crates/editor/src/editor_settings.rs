@@ -13,6 +13,7 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub sticky_scroll: StickyScroll,
     pub vertical_scroll_margin: f32,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
@@ -20,6 +21,8 @@ pub struct EditorSettings {
     pub redact_private_values: bool,
     #[serde(default)]
     pub double_click_in_multibuffer: DoubleClickInMultibuffer,
+    pub persist_undo_history: bool,
+    pub expand_excerpt_lines: u32,
 }
 
 /// When to populate a new search's query based on the text under the cursor.
@@ -68,6 +71,12 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -132,6 +141,8 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
     /// The number of lines to keep above/below the cursor when auto-scrolling.
     ///
     /// Default: 3.
@@ -160,6 +171,18 @@ pub struct EditorSettingsContent {
     ///
     /// Default: select
     pub double_click_in_multibuffer: Option<DoubleClickInMultibuffer>,
+
+    /// Whether to also keep the undo history of buffers whose unsaved changes
+    /// are restored after quitting (see `restore_unsaved_buffers`).
+    ///
+    /// Default: false
+    pub persist_undo_history: Option<bool>,
+
+    /// How many lines to add to an excerpt's context when expanding it
+    /// in a multibuffer.
+    ///
+    /// Default: 3
+    pub expand_excerpt_lines: Option<u32>,
 }
 
 // Toolbar related settings
@@ -217,6 +240,21 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the header lines of the enclosing outline items
+    /// (modules, types, functions...) at the top of the editor while
+    /// scrolling through their bodies.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of header lines pinned at the top of the editor.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 impl Settings for EditorSettings {
     const KEY: Option<&'static str> = None;
 
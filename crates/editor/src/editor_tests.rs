@@ -5542,6 +5542,30 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_choices(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple("let x = ;", cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("${1|first,second|}.${2|len,is_empty|}()").unwrap();
+        editor.insert_snippet(&[8..8], snippet, cx).unwrap();
+        assert_eq!(editor.text(cx), "let x = first.len();");
+        assert_eq!(editor.selections.ranges::<usize>(cx), [8..13]);
+        assert!(editor.context_menu_visible());
+
+        _ = editor.confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx);
+        assert_eq!(editor.text(cx), "let x = second.len();");
+        assert!(!editor.context_menu_visible());
+
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.selections.ranges::<usize>(cx), [15..18]);
+        assert!(editor.context_menu_visible());
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -9806,6 +9830,108 @@ async fn test_mutlibuffer_in_navigation_history(cx: &mut gpui::TestAppContext) {
         .unwrap();
 }
 
+#[gpui::test]
+async fn test_working_set_excerpts(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/a",
+        json!({
+            "main.rs": sample_text(10, 4, 'a'),
+            "other.rs": sample_text(10, 4, 'l'),
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+    let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+    let mut editors = Vec::new();
+    for path in ["/a/main.rs", "/a/other.rs"] {
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer(path, cx))
+            .await
+            .unwrap();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project.clone()), cx));
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor.clone()), cx)
+            })
+            .unwrap();
+        editors.push(editor);
+    }
+
+    editors[0].update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(2, 1)..Point::new(2, 1)])
+        });
+        editor.add_selection_to_working_set(&AddSelectionToWorkingSet, cx);
+    });
+    cx.executor().run_until_parked();
+    let working_set = workspace
+        .update(cx, |workspace, cx| {
+            let working_sets = workspace
+                .items_of_type::<Editor>(cx)
+                .filter(|editor| editor.read(cx).is_working_set())
+                .collect::<Vec<_>>();
+            assert_eq!(working_sets.len(), 1);
+            working_sets[0].clone()
+        })
+        .unwrap();
+    assert_eq!(
+        working_set.update(cx, |editor, cx| editor.text(cx)),
+        "bbbb\ncccc\ndddd\n"
+    );
+
+    // Ranges already in the working set aren't added twice, and later selections
+    // are added to the existing working set.
+    editors[0].update(cx, |editor, cx| {
+        editor.add_selection_to_working_set(&AddSelectionToWorkingSet, cx)
+    });
+    editors[1].update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(8, 0)..Point::new(9, 2)])
+        });
+        editor.add_selection_to_working_set(&AddSelectionToWorkingSet, cx);
+    });
+    cx.executor().run_until_parked();
+    workspace
+        .update(cx, |workspace, cx| {
+            assert_eq!(
+                workspace
+                    .items_of_type::<Editor>(cx)
+                    .filter(|editor| editor.read(cx).is_working_set())
+                    .count(),
+                1
+            );
+        })
+        .unwrap();
+    assert_eq!(
+        working_set.update(cx, |editor, cx| editor.text(cx)),
+        "bbbb\ncccc\ndddd\n\nssss\ntttt\nuuuu"
+    );
+
+    working_set.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 0)..Point::new(1, 0)])
+        });
+        editor.expand_excerpts_up(&ExpandExcerptsUp, cx);
+        assert_eq!(
+            editor.text(cx),
+            "aaaa\nbbbb\ncccc\ndddd\n\nssss\ntttt\nuuuu"
+        );
+        editor.expand_excerpts_down(&ExpandExcerptsDown, cx);
+        assert_eq!(
+            editor.text(cx),
+            "aaaa\nbbbb\ncccc\ndddd\neeee\nffff\ngggg\n\nssss\ntttt\nuuuu"
+        );
+
+        editor.dismiss_excerpt(&DismissExcerpt, cx);
+        assert_eq!(editor.text(cx), "ssss\ntttt\nuuuu");
+    });
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(row as u32, column as u32);
     point..point
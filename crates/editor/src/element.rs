@@ -1,4 +1,5 @@
 use crate::{
+    bracket_colorization::BracketColorizedChunks,
     display_map::{
         BlockContext, BlockStyle, DisplaySnapshot, FoldStatus, HighlightedChunk, ToDisplayPoint,
         TransformBlock,
@@ -29,9 +30,9 @@ use gpui::{
     TextStyleRefinement, View, ViewContext, WindowContext,
 };
 use itertools::Itertools;
-use language::language_settings::ShowWhitespaceSetting;
+use language::language_settings::{IndentGuideColoring, ShowWhitespaceSetting};
 use lsp::DiagnosticSeverity;
-use multi_buffer::Anchor;
+use multi_buffer::{Anchor, ExpandExcerptDirection};
 use project::{
     project_settings::{GitGutterSetting, ProjectSettings},
     ProjectPath,
@@ -154,6 +155,7 @@ impl EditorElement {
         register_action(view, cx, Editor::backspace);
         register_action(view, cx, Editor::delete);
         register_action(view, cx, Editor::tab);
+        register_action(view, cx, Editor::expand_snippet);
         register_action(view, cx, Editor::tab_prev);
         register_action(view, cx, Editor::indent);
         register_action(view, cx, Editor::outdent);
@@ -290,6 +292,11 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
+        register_action(view, cx, Editor::expand_excerpts);
+        register_action(view, cx, Editor::expand_excerpts_up);
+        register_action(view, cx, Editor::expand_excerpts_down);
+        register_action(view, cx, Editor::dismiss_excerpt);
+        register_action(view, cx, Editor::add_selection_to_working_set);
         register_action(view, cx, Editor::toggle_soft_wrap);
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_inlay_hints);
@@ -1308,8 +1315,9 @@ impl EditorElement {
                 .collect()
         } else {
             let chunks = snapshot.highlighted_chunks(rows.clone(), true, &self.style);
+            let brackets = snapshot.bracket_colors_in_range(rows.clone(), cx.theme().accents(), cx);
             LineWithInvisibles::from_chunks(
-                chunks,
+                BracketColorizedChunks::new(chunks, rows.start, brackets),
                 &self.style.text,
                 MAX_LINE_LEN,
                 rows.len(),
@@ -1320,6 +1328,123 @@ impl EditorElement {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_indent_guides(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<u32>,
+        newest_selection_head: Option<DisplayPoint>,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        cx: &ElementContext,
+    ) -> Vec<IndentGuideLayout> {
+        if snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+
+        let active_row = newest_selection_head.map(|head| head.to_point(snapshot).row);
+        let colors = cx.theme().colors();
+        let accents = cx.theme().accents();
+        snapshot
+            .indent_guides_in_range(rows, active_row, cx)
+            .into_iter()
+            .filter_map(|guide| {
+                let x = content_origin.x + self.column_pixels(guide.column() as usize, cx)
+                    - scroll_pixel_position.x;
+                if x < content_origin.x {
+                    return None;
+                }
+
+                let display_rows = snapshot.indent_guide_display_rows(&guide);
+                let y = content_origin.y + line_height * display_rows.start as f32
+                    - scroll_pixel_position.y;
+                let color = match guide.coloring {
+                    IndentGuideColoring::Rainbow if !accents.is_empty() => {
+                        let mut color = accents[guide.depth as usize % accents.len()];
+                        if !guide.active {
+                            color.fade_out(0.6);
+                        }
+                        color
+                    }
+                    _ if guide.active => colors.editor_indent_guide_active,
+                    _ => colors.editor_indent_guide,
+                };
+                Some(IndentGuideLayout {
+                    bounds: Bounds {
+                        origin: point(x, y),
+                        size: size(px(1.), line_height * display_rows.len() as f32),
+                    },
+                    color,
+                })
+            })
+            .collect()
+    }
+
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        start_row: u32,
+        gutter_hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        line_height: Pixels,
+        cx: &mut ElementContext,
+    ) -> Vec<StickyHeaderLayout> {
+        let settings = EditorSettings::get_global(cx);
+        let sticky_scroll = settings.sticky_scroll;
+        if !sticky_scroll.enabled || snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+
+        let include_line_numbers = settings.gutter.line_numbers;
+        let font_size = self.style.text.font_size.to_pixels(cx.rem_size());
+        let width = gutter_hitbox.size.width + text_hitbox.size.width;
+        let rows = self.editor.update(cx, |editor, _| {
+            editor.sticky_header_rows(snapshot, start_row, sticky_scroll.max_depth)
+        });
+        rows.into_iter()
+            .enumerate()
+            .filter_map(|(ix, row)| {
+                let line = self.layout_lines(row..row + 1, &[], snapshot, cx).pop()?;
+                let buffer_row = snapshot.buffer_rows(row).next().flatten();
+                let line_number =
+                    if let Some(buffer_row) = buffer_row.filter(|_| include_line_numbers) {
+                        let line_number = (buffer_row + 1).to_string();
+                        let run = TextRun {
+                            len: line_number.len(),
+                            font: self.style.text.font(),
+                            color: cx.theme().colors().editor_line_number,
+                            background_color: None,
+                            underline: None,
+                            strikethrough: None,
+                        };
+                        cx.text_system()
+                            .shape_line(line_number.into(), font_size, &[run])
+                            .log_err()
+                    } else {
+                        None
+                    };
+                let origin = point(
+                    gutter_hitbox.origin.x,
+                    text_hitbox.origin.y + line_height * ix as f32,
+                );
+                let hitbox = cx.insert_hitbox(
+                    Bounds {
+                        origin,
+                        size: size(width, line_height),
+                    },
+                    false,
+                );
+                Some(StickyHeaderLayout {
+                    row,
+                    line,
+                    line_number,
+                    hitbox,
+                })
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn build_blocks(
         &self,
@@ -1377,11 +1502,13 @@ impl EditorElement {
                 }
 
                 TransformBlock::ExcerptHeader {
+                    id,
                     buffer,
                     range,
                     starts_new_buffer,
                     ..
                 } => {
+                    let excerpt_id = *id;
                     let include_root = self
                         .editor
                         .read(cx)
@@ -1463,6 +1590,67 @@ impl EditorElement {
                                                 }),
                                         ),
                                     )
+                                    .child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(
+                                                IconButton::new(
+                                                    ("expand excerpt up", block_id),
+                                                    IconName::ArrowUp,
+                                                )
+                                                .icon_size(IconSize::XSmall)
+                                                .tooltip(|cx| {
+                                                    Tooltip::text("Expand Excerpt Up", cx)
+                                                })
+                                                .on_click(cx.listener_for(
+                                                    &self.editor,
+                                                    move |editor, _, cx| {
+                                                        cx.stop_propagation();
+                                                        editor.expand_excerpt(
+                                                            excerpt_id,
+                                                            ExpandExcerptDirection::Up,
+                                                            cx,
+                                                        );
+                                                    },
+                                                )),
+                                            )
+                                            .child(
+                                                IconButton::new(
+                                                    ("expand excerpt down", block_id),
+                                                    IconName::ArrowDown,
+                                                )
+                                                .icon_size(IconSize::XSmall)
+                                                .tooltip(|cx| {
+                                                    Tooltip::text("Expand Excerpt Down", cx)
+                                                })
+                                                .on_click(cx.listener_for(
+                                                    &self.editor,
+                                                    move |editor, _, cx| {
+                                                        cx.stop_propagation();
+                                                        editor.expand_excerpt(
+                                                            excerpt_id,
+                                                            ExpandExcerptDirection::Down,
+                                                            cx,
+                                                        );
+                                                    },
+                                                )),
+                                            )
+                                            .child(
+                                                IconButton::new(
+                                                    ("dismiss excerpt", block_id),
+                                                    IconName::Close,
+                                                )
+                                                .icon_size(IconSize::XSmall)
+                                                .tooltip(|cx| Tooltip::text("Dismiss Excerpt", cx))
+                                                .on_click(cx.listener_for(
+                                                    &self.editor,
+                                                    move |editor, _, cx| {
+                                                        cx.stop_propagation();
+                                                        editor.dismiss_excerpts([excerpt_id], cx);
+                                                    },
+                                                )),
+                                            ),
+                                    )
                                     .when_some(jump_data.clone(), |this, jump_data| {
                                         this.cursor_pointer()
                                             .tooltip(|cx| {
@@ -1964,6 +2152,10 @@ impl EditorElement {
                         color,
                     ));
                 }
+
+                for indent_guide in &layout.indent_guides {
+                    cx.paint_quad(fill(indent_guide.bounds, indent_guide.color));
+                }
             }
         })
     }
@@ -2607,6 +2799,72 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_headers(&mut self, layout: &EditorLayout, cx: &mut ElementContext) {
+        let Some(last_header) = layout.sticky_headers.last() else {
+            return;
+        };
+
+        let line_height = layout.position_map.line_height;
+        let text_x = layout.content_origin.x - layout.position_map.scroll_pixel_position.x;
+        for header in &layout.sticky_headers {
+            let bounds = header.hitbox.bounds;
+            cx.paint_quad(fill(bounds, self.style.background));
+            if let Some(line_number) = &header.line_number {
+                let origin = point(
+                    layout.gutter_hitbox.upper_right().x
+                        - line_number.width
+                        - layout.gutter_dimensions.right_padding,
+                    bounds.origin.y,
+                );
+                line_number.paint(origin, line_height, cx).log_err();
+            }
+            cx.with_content_mask(
+                Some(ContentMask {
+                    bounds: layout.text_hitbox.bounds,
+                }),
+                |cx| {
+                    header
+                        .line
+                        .line
+                        .paint(point(text_x, bounds.origin.y), line_height, cx)
+                        .log_err();
+                },
+            );
+            cx.set_cursor_style(CursorStyle::PointingHand, &header.hitbox);
+        }
+        cx.paint_quad(fill(
+            Bounds {
+                origin: last_header.hitbox.bounds.lower_left(),
+                size: size(last_header.hitbox.size.width, px(1.)),
+            },
+            cx.theme().colors().border_variant,
+        ));
+
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let headers = layout
+                .sticky_headers
+                .iter()
+                .map(|header| (header.hitbox.clone(), header.row))
+                .collect::<Vec<_>>();
+            move |event: &MouseDownEvent, phase, cx| {
+                if phase != DispatchPhase::Bubble || event.button != MouseButton::Left {
+                    return;
+                }
+                if let Some((depth, (_, row))) = headers
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (hitbox, _))| hitbox.is_hovered(cx))
+                {
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_to_sticky_header(*row, depth, cx)
+                    });
+                    cx.stop_propagation();
+                }
+            }
+        });
+    }
+
     fn paint_mouse_context_menu(&mut self, layout: &mut EditorLayout, cx: &mut ElementContext) {
         if let Some(mouse_context_menu) = layout.mouse_context_menu.as_mut() {
             mouse_context_menu.paint(cx);
@@ -3242,6 +3500,25 @@ impl Element for EditorElement {
                     );
                 });
 
+                let indent_guides = self.layout_indent_guides(
+                    &snapshot,
+                    start_row..end_row,
+                    newest_selection_head,
+                    content_origin,
+                    scroll_pixel_position,
+                    line_height,
+                    cx,
+                );
+
+                let sticky_headers = self.layout_sticky_headers(
+                    &snapshot,
+                    start_row,
+                    &gutter_hitbox,
+                    &text_hitbox,
+                    line_height,
+                    cx,
+                );
+
                 let cursors = self.layout_cursors(
                     &snapshot,
                     &selections,
@@ -3386,6 +3663,7 @@ impl Element for EditorElement {
                     }),
                     visible_display_row_range: start_row..end_row,
                     wrap_guides,
+                    indent_guides,
                     hitbox,
                     text_hitbox,
                     gutter_hitbox,
@@ -3401,6 +3679,7 @@ impl Element for EditorElement {
                     display_hunks,
                     folds,
                     blocks,
+                    sticky_headers,
                     cursors,
                     selections,
                     mouse_context_menu,
@@ -3453,6 +3732,10 @@ impl Element for EditorElement {
                     });
                 }
 
+                cx.with_element_id(Some("sticky_headers"), |cx| {
+                    self.paint_sticky_headers(layout, cx);
+                });
+
                 self.paint_scrollbar(layout, cx);
                 self.paint_mouse_context_menu(layout, cx);
             });
@@ -3480,6 +3763,7 @@ pub struct EditorLayout {
     scrollbar_layout: Option<ScrollbarLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
+    indent_guides: Vec<IndentGuideLayout>,
     visible_display_row_range: Range<u32>,
     active_rows: BTreeMap<u32, bool>,
     highlighted_rows: BTreeMap<u32, Hsla>,
@@ -3487,6 +3771,7 @@ pub struct EditorLayout {
     display_hunks: Vec<DisplayDiffHunk>,
     folds: Vec<FoldLayout>,
     blocks: Vec<BlockLayout>,
+    sticky_headers: Vec<StickyHeaderLayout>,
     highlighted_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     redacted_ranges: Vec<Range<DisplayPoint>>,
     cursors: Vec<CursorLayout>,
@@ -3505,6 +3790,18 @@ impl EditorLayout {
     }
 }
 
+struct IndentGuideLayout {
+    bounds: Bounds<Pixels>,
+    color: Hsla,
+}
+
+struct StickyHeaderLayout {
+    row: u32,
+    line: LineWithInvisibles,
+    line_number: Option<ShapedLine>,
+    hitbox: Hitbox,
+}
+
 struct ScrollbarLayout {
     hitbox: Hitbox,
     visible_row_range: Range<f32>,
@@ -0,0 +1,270 @@
+use crate::{display_map::ToDisplayPoint, DisplayPoint, EditorSnapshot};
+use gpui::AppContext;
+use language::{language_settings::IndentGuideColoring, IndentKind, IndentSize, Point};
+use std::ops::Range;
+
+/// How many rows outside of the visible ones are searched for non-blank lines, which
+/// determine the indentation of the blank lines at the edges of the visible range.
+const MAX_BLANK_LINE_LOOKAROUND: u32 = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndentGuide {
+    /// The buffer rows spanned by the guide.
+    pub rows: Range<u32>,
+    /// The indent level of the guide, the outermost guide being at depth 0.
+    pub depth: u32,
+    /// The tab size of the buffer, with which `depth` maps to a column.
+    pub tab_size: u32,
+    /// Whether this is the guide of the scope containing the cursor.
+    pub active: bool,
+    pub coloring: IndentGuideColoring,
+}
+
+impl IndentGuide {
+    pub fn column(&self) -> u32 {
+        self.depth * self.tab_size
+    }
+}
+
+impl EditorSnapshot {
+    /// Returns the indent guides of the given display rows, computed from the indentation
+    /// of each line. Blank lines take the indentation of the least indented of their
+    /// surrounding lines, so that guides run through the blank lines of a block.
+    pub(crate) fn indent_guides_in_range(
+        &self,
+        display_rows: Range<u32>,
+        active_row: Option<u32>,
+        cx: &AppContext,
+    ) -> Vec<IndentGuide> {
+        let max_point = self.buffer_snapshot.max_point();
+        let start_row = DisplayPoint::new(display_rows.start, 0)
+            .to_point(&self.display_snapshot)
+            .row;
+        let end_row = DisplayPoint::new(display_rows.end.min(self.max_point().row()), 0)
+            .to_point(&self.display_snapshot)
+            .row
+            + 1;
+
+        // Guides don't cross the boundaries between excerpts.
+        let mut segment_starts = vec![start_row];
+        segment_starts.extend(
+            self.buffer_snapshot
+                .excerpt_boundaries_in_range(Point::new(start_row, 0)..Point::new(end_row, 0))
+                .map(|boundary| boundary.row)
+                .filter(|row| *row > start_row),
+        );
+
+        let mut guides = Vec::new();
+        for (ix, segment_start) in segment_starts.iter().copied().enumerate() {
+            let segment_end = segment_starts.get(ix + 1).copied().unwrap_or(end_row);
+            let settings = self
+                .buffer_snapshot
+                .settings_at(Point::new(segment_start, 0), cx);
+            if !settings.indent_guides.enabled {
+                continue;
+            }
+
+            let tab_size = settings.tab_size.get();
+            let lookaround_start = if ix == 0 {
+                segment_start.saturating_sub(MAX_BLANK_LINE_LOOKAROUND)
+            } else {
+                segment_start
+            };
+            let lookaround_end = if ix + 1 == segment_starts.len() {
+                (segment_end + MAX_BLANK_LINE_LOOKAROUND).min(max_point.row + 1)
+            } else {
+                segment_end
+            };
+            let line_indents = (lookaround_start..lookaround_end).map(|row| {
+                if self.buffer_snapshot.is_line_blank(row) {
+                    None
+                } else {
+                    Some(self.buffer_snapshot.indent_size_for_line(row))
+                }
+            });
+            let levels = indent_levels(line_indents, tab_size);
+            let levels = &levels[(segment_start - lookaround_start) as usize
+                ..(segment_end - lookaround_start) as usize];
+
+            guides.extend(guides_for_levels(segment_start, levels).into_iter().map(
+                |(rows, depth)| IndentGuide {
+                    rows,
+                    depth,
+                    tab_size,
+                    active: false,
+                    coloring: settings.indent_guides.coloring,
+                },
+            ));
+        }
+
+        if let Some(active_row) = active_row {
+            if let Some(ix) = active_guide_ix(&guides, active_row) {
+                guides[ix].active = true;
+            }
+        }
+        guides
+    }
+
+    /// Returns the display rows spanned by the given indent guide.
+    pub(crate) fn indent_guide_display_rows(&self, guide: &IndentGuide) -> Range<u32> {
+        let start = Point::new(guide.rows.start, 0)
+            .to_display_point(&self.display_snapshot)
+            .row();
+        let last_row = guide.rows.end - 1;
+        let end = Point::new(last_row, self.buffer_snapshot.line_len(last_row))
+            .to_display_point(&self.display_snapshot)
+            .row()
+            + 1;
+        start..end
+    }
+}
+
+/// Computes the number of indent guides of each line, blank lines (given as `None`)
+/// having as many as the least indented of their surrounding lines.
+fn indent_levels(
+    line_indents: impl Iterator<Item = Option<IndentSize>>,
+    tab_size: u32,
+) -> Vec<u32> {
+    let mut levels = line_indents
+        .map(|indent| {
+            indent.map(|indent| {
+                let columns = match indent.kind {
+                    IndentKind::Space => indent.len,
+                    IndentKind::Tab => indent.len * tab_size,
+                };
+                columns.div_ceil(tab_size)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut next_levels = vec![None; levels.len()];
+    let mut next_level = None;
+    for (ix, level) in levels.iter().enumerate().rev() {
+        next_levels[ix] = next_level;
+        if level.is_some() {
+            next_level = *level;
+        }
+    }
+
+    let mut prev_level = None;
+    for (level, next_level) in levels.iter_mut().zip(next_levels) {
+        match level {
+            Some(level) => prev_level = Some(*level),
+            None => *level = Some(prev_level.unwrap_or(0).min(next_level.unwrap_or(0))),
+        }
+    }
+
+    levels.into_iter().map(|level| level.unwrap_or(0)).collect()
+}
+
+/// Turns the indent levels of consecutive rows, starting at `start_row`, into the row
+/// ranges and depths of the guides running through them.
+fn guides_for_levels(start_row: u32, levels: &[u32]) -> Vec<(Range<u32>, u32)> {
+    let mut guides = Vec::new();
+    let mut open_guide_starts = Vec::<u32>::new();
+    for (row, level) in (start_row..).zip(levels.iter().copied()) {
+        while open_guide_starts.len() as u32 > level {
+            let depth = open_guide_starts.len() as u32 - 1;
+            let start = open_guide_starts.pop().unwrap();
+            guides.push((start..row, depth));
+        }
+        while (open_guide_starts.len() as u32) < level {
+            open_guide_starts.push(row);
+        }
+    }
+
+    let end_row = start_row + levels.len() as u32;
+    while let Some(start) = open_guide_starts.pop() {
+        guides.push((start..end_row, open_guide_starts.len() as u32));
+    }
+    guides.sort_by_key(|(rows, depth)| (rows.start, *depth));
+    guides
+}
+
+/// Finds the guide of the scope containing `row`: the innermost guide running through it,
+/// or the guide of the block starting right below it, when the row is that block's header.
+fn active_guide_ix(guides: &[IndentGuide], row: u32) -> Option<usize> {
+    let containing_ix = guides
+        .iter()
+        .enumerate()
+        .filter(|(_, guide)| guide.rows.contains(&row))
+        .max_by_key(|(_, guide)| guide.depth)
+        .map(|(ix, _)| ix);
+    let block_depth = containing_ix.map_or(0, |ix| guides[ix].depth + 1);
+    guides
+        .iter()
+        .position(|guide| guide.rows.start == row + 1 && guide.depth == block_depth)
+        .or(containing_ix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(text: &str, tab_size: u32) -> Vec<u32> {
+        let line_indents = text.lines().map(|line| {
+            if line.trim().is_empty() {
+                None
+            } else if line.starts_with('\t') {
+                let len = line.chars().take_while(|c| *c == '\t').count();
+                Some(IndentSize {
+                    len: len as u32,
+                    kind: IndentKind::Tab,
+                })
+            } else {
+                let len = line.chars().take_while(|c| *c == ' ').count();
+                Some(IndentSize::spaces(len as u32))
+            }
+        });
+        indent_levels(line_indents, tab_size)
+    }
+
+    #[test]
+    fn test_indent_guides() {
+        let text = [
+            "fn main() {",
+            "    if true {",
+            "        a();",
+            "",
+            "        b();",
+            "    }",
+            "",
+            "    c();",
+            "}",
+        ]
+        .join("\n");
+        let levels = levels(&text, 4);
+        assert_eq!(levels, vec![0, 1, 2, 2, 2, 1, 1, 1, 0]);
+        assert_eq!(
+            guides_for_levels(10, &levels),
+            vec![(11..18, 0), (12..15, 1)]
+        );
+
+        let guides = guides_for_levels(10, &levels)
+            .into_iter()
+            .map(|(rows, depth)| IndentGuide {
+                rows,
+                depth,
+                tab_size: 4,
+                active: false,
+                coloring: IndentGuideColoring::Fixed,
+            })
+            .collect::<Vec<_>>();
+        // Inside of a block, its own guide is active.
+        assert_eq!(active_guide_ix(&guides, 13), Some(1));
+        assert_eq!(active_guide_ix(&guides, 16), Some(0));
+        // On a block's header, the guide of its body is active.
+        assert_eq!(active_guide_ix(&guides, 11), Some(1));
+        assert_eq!(active_guide_ix(&guides, 10), Some(0));
+        assert_eq!(active_guide_ix(&guides, 18), None);
+    }
+
+    #[test]
+    fn test_indent_levels_with_tabs_and_partial_indents() {
+        assert_eq!(levels("a\n\tb\n\t\tc\n\td", 2), vec![0, 1, 2, 1]);
+        // A partially indented line still gets a guide at each tab stop of its indent.
+        assert_eq!(levels("a\n   b\n  c", 2), vec![0, 2, 1]);
+        // Leading and trailing blank lines have no guides.
+        assert_eq!(levels("\n  a\n", 2), vec![0, 1]);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
-    editor_settings::SeedQuerySetting, persistence::DB, scroll::ScrollAnchor, Anchor, Autoscroll,
-    Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer, MultiBufferSnapshot,
-    NavigationData, ToPoint as _,
+    editor_settings::SeedQuerySetting,
+    persistence::{SerializedUnsavedState, DB},
+    scroll::ScrollAnchor,
+    Anchor, AnchorRangeExt as _, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId,
+    ExcerptRange, MultiBuffer, MultiBufferSnapshot, NavigationData, ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -874,6 +876,25 @@ impl Item for Editor {
                 }
             })
             .detach();
+        } else if self.working_set {
+            serialize_working_set(&self.buffer, workspace_id, item_id, cx);
+
+            cx.subscribe(&self.buffer, |this, buffer, event, cx| {
+                if let Some((_, workspace_id)) = this.workspace.as_ref() {
+                    if let multi_buffer::Event::ExcerptsAdded { .. }
+                    | multi_buffer::Event::ExcerptsRemoved { .. }
+                    | multi_buffer::Event::ExcerptsExpanded { .. } = event
+                    {
+                        serialize_working_set(
+                            &buffer,
+                            *workspace_id,
+                            cx.view().item_id().as_u64() as ItemId,
+                            cx,
+                        );
+                    }
+                }
+            })
+            .detach();
         }
     }
 
@@ -907,7 +928,9 @@ impl Item for Editor {
                 f(ItemEvent::UpdateBreadcrumbs);
             }
 
-            EditorEvent::ExcerptsAdded { .. } | EditorEvent::ExcerptsRemoved { .. } => {
+            EditorEvent::ExcerptsAdded { .. }
+            | EditorEvent::ExcerptsRemoved { .. }
+            | EditorEvent::ExcerptsExpanded { .. } => {
                 f(ItemEvent::Edit);
             }
 
@@ -922,42 +945,267 @@ impl Item for Editor {
         item_id: ItemId,
         cx: &mut ViewContext<Pane>,
     ) -> Task<Result<View<Self>>> {
-        let project_item: Result<_> = project.update(cx, |project, cx| {
-            // Look up the path with this key associated, create a self with that path
-            let path = DB
-                .get_path(item_id, workspace_id)?
-                .context("No path stored for this editor")?;
-
-            let (worktree, path) = project
-                .find_local_worktree(&path, cx)
-                .with_context(|| format!("No worktree for path: {path:?}"))?;
-            let project_path = ProjectPath {
-                worktree_id: worktree.read(cx).id(),
-                path: path.into(),
-            };
+        let unsaved_state = DB
+            .get_unsaved_state(item_id, workspace_id)
+            .log_err()
+            .flatten();
+        let path = DB.get_path(item_id, workspace_id).log_err().flatten();
+        let has_contents = unsaved_state
+            .as_ref()
+            .map_or(false, |state| state.contents.is_some());
+        if path.is_none() && !has_contents {
+            let excerpts = DB
+                .get_excerpts(item_id, workspace_id)
+                .log_err()
+                .unwrap_or_default();
+            if !excerpts.is_empty() {
+                return deserialize_working_set(project, excerpts, cx);
+            }
+            return Task::ready(Err(anyhow!("No path stored for this editor")));
+        }
 
-            Ok(project.open_path(project_path, cx))
-        });
+        let open_buffer = path.map(|path| {
+            project.update(cx, |project, cx| {
+                let Some((worktree, path)) = project.find_local_worktree(&path, cx) else {
+                    return Task::ready(Err(anyhow!("No worktree for path: {path:?}")));
+                };
+                let project_path = ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: path.into(),
+                };
 
-        project_item
-            .map(|project_item| {
-                cx.spawn(|pane, mut cx| async move {
+                let project_item = project.open_path(project_path, cx);
+                cx.spawn(|_, _| async move {
                     let (_, project_item) = project_item.await?;
-                    let buffer = project_item
+                    project_item
                         .downcast::<Buffer>()
-                        .map_err(|_| anyhow!("Project item at stored path was not a buffer"))?;
-                    pane.update(&mut cx, |_, cx| {
-                        cx.new_view(|cx| {
-                            let mut editor = Editor::for_buffer(buffer, Some(project), cx);
+                        .map_err(|_| anyhow!("Project item at stored path was not a buffer"))
+                })
+            })
+        });
 
-                            editor.read_scroll_position_from_db(item_id, workspace_id, cx);
-                            editor
-                        })
-                    })
+        cx.spawn(|pane, mut cx| async move {
+            // The unsaved contents may be the only copy of the changes, so they're restored
+            // into an untitled buffer if their file can't be opened anymore.
+            let buffer = match open_buffer {
+                Some(open_buffer) if has_contents => open_buffer.await.log_err(),
+                Some(open_buffer) => Some(open_buffer.await?),
+                None => None,
+            };
+            let buffer = match buffer {
+                Some(buffer) => buffer,
+                None => {
+                    project.update(&mut cx, |project, cx| project.create_buffer("", None, cx))??
+                }
+            };
+            if let Some(state) = unsaved_state.as_ref() {
+                restore_unsaved_changes(&buffer, state, &mut cx).await?;
+            }
+
+            let has_unsaved_state = unsaved_state.is_some();
+            let editor = pane.update(&mut cx, |_, cx| {
+                cx.new_view(|cx| {
+                    let mut editor = Editor::for_buffer(buffer, Some(project), cx);
+
+                    editor.read_scroll_position_from_db(item_id, workspace_id, cx);
+                    if let Some(state) = unsaved_state {
+                        let len = editor.buffer.read(cx).len(cx);
+                        editor.fold_ranges(
+                            state
+                                .folds
+                                .into_iter()
+                                .map(|fold| fold.start.min(len)..fold.end.min(len)),
+                            false,
+                            cx,
+                        );
+                        if !state.selections.is_empty() {
+                            editor.change_selections(None, cx, |selections| {
+                                selections.select_ranges(
+                                    state
+                                        .selections
+                                        .into_iter()
+                                        .map(|(tail, head)| tail.min(len)..head.min(len)),
+                                )
+                            });
+                        }
+                    }
+                    editor
                 })
+            })?;
+
+            // Unsaved changes are only restored once, so that they can't be applied
+            // to a later editor that happens to be assigned the same item id.
+            if has_unsaved_state {
+                DB.delete_unsaved_state(item_id, workspace_id)
+                    .await
+                    .log_err();
+            }
+            Ok(editor)
+        })
+    }
+
+    fn persist_unsaved_changes(
+        &mut self,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let buffer = self.buffer().read(cx).as_singleton()?;
+        if self.project.as_ref()?.read(cx).is_remote() {
+            return None;
+        }
+
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        let buffer = buffer.read(cx);
+        if buffer
+            .file()
+            .map_or(false, |file| file.as_local().is_none())
+        {
+            return None;
+        }
+
+        let contents = buffer.is_dirty().then(|| buffer.text());
+        let history = (contents.is_some() && EditorSettings::get_global(cx).persist_undo_history)
+            .then(|| prost::Message::encode_to_vec(&buffer.serialize_history()));
+        let saved_mtime = buffer.saved_mtime();
+        let selections = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .map(|selection| (selection.tail(), selection.head()))
+            .collect();
+        let display_snapshot = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let folds = display_snapshot
+            .folds_in_range(0..display_snapshot.buffer_snapshot.len())
+            .map(|fold| fold.range.to_offset(&display_snapshot.buffer_snapshot))
+            .collect();
+
+        let state = SerializedUnsavedState {
+            contents,
+            saved_mtime,
+            selections,
+            folds,
+            history,
+        };
+        Some(
+            cx.background_executor()
+                .spawn(async move { DB.save_unsaved_state(item_id, workspace_id, state).await }),
+        )
+    }
+}
+
+/// Saves the file and rows of each excerpt of a working set. Excerpts of buffers
+/// without a local file can't be reopened, so they aren't saved.
+fn serialize_working_set(
+    buffer: &Model<MultiBuffer>,
+    workspace_id: WorkspaceId,
+    item_id: ItemId,
+    cx: &mut AppContext,
+) {
+    let snapshot = buffer.read(cx).snapshot(cx);
+    let excerpts = snapshot
+        .excerpts()
+        .filter_map(|(_, buffer, range)| {
+            let path = buffer.file()?.as_local()?.abs_path(cx);
+            let start = language::ToPoint::to_point(&range.context.start, buffer);
+            let end = language::ToPoint::to_point(&range.context.end, buffer);
+            // Excerpts usually end at the start of the line following their last row.
+            let end_row = if end.column == 0 && end.row > start.row {
+                end.row - 1
+            } else {
+                end.row
+            };
+            Some((path, start.row, end_row))
+        })
+        .collect();
+
+    cx.background_executor()
+        .spawn(async move {
+            DB.save_excerpts(item_id, workspace_id, excerpts)
+                .await
+                .log_err()
+        })
+        .detach();
+}
+
+fn deserialize_working_set(
+    project: Model<Project>,
+    excerpts: Vec<(PathBuf, u32, u32)>,
+    cx: &mut ViewContext<Pane>,
+) -> Task<Result<View<Editor>>> {
+    let buffers = project.update(cx, |project, cx| {
+        excerpts
+            .into_iter()
+            .filter_map(|(path, start_row, end_row)| {
+                let (worktree, path) = project.find_local_worktree(&path, cx)?;
+                let project_path = ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: path.into(),
+                };
+                Some((project.open_buffer(project_path, cx), start_row, end_row))
             })
-            .unwrap_or_else(|error| Task::ready(Err(error)))
+            .collect::<Vec<_>>()
+    });
+
+    cx.spawn(|pane, mut cx| async move {
+        let mut excerpts = Vec::new();
+        for (buffer, start_row, end_row) in buffers {
+            if let Some(buffer) = buffer.await.log_err() {
+                excerpts.push((buffer, start_row, end_row));
+            }
+        }
+        if excerpts.is_empty() {
+            return Err(anyhow!("None of the working set's files could be opened"));
+        }
+
+        pane.update(&mut cx, |_, cx| {
+            let replica_id = project.read(cx).replica_id();
+            let buffer = Editor::build_working_set(excerpts, replica_id, cx);
+            cx.new_view(|cx| Editor::for_working_set(buffer, project, cx))
+        })
+    })
+}
+
+/// Applies the persisted unsaved contents to a freshly opened buffer, replaying its undo
+/// history when the file on disk still matches the text the history was recorded against.
+async fn restore_unsaved_changes(
+    buffer: &Model<Buffer>,
+    state: &SerializedUnsavedState,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let Some(contents) = state.contents.clone() else {
+        return Ok(());
+    };
+
+    let restored_history = state
+        .history
+        .as_deref()
+        .and_then(|history| <proto::BufferHistory as prost::Message>::decode(history).log_err())
+        .and_then(|history| {
+            buffer
+                .update(cx, |buffer, cx| {
+                    buffer.restore_history(history, cx)?;
+                    anyhow::Ok(buffer.text() == contents)
+                })
+                .log_err()?
+                .log_err()
+        })
+        .unwrap_or(false);
+    if !restored_history {
+        let diff = buffer
+            .update(cx, |buffer, cx| buffer.diff(contents, cx))?
+            .await;
+        buffer.update(cx, |buffer, cx| {
+            buffer.apply_diff(diff, cx);
+        })?;
     }
+
+    // Restoring the modification time the changes were based on surfaces a conflict
+    // if the file was modified on disk since the workspace was closed.
+    buffer.update(cx, |buffer, cx| {
+        if buffer.saved_mtime() != state.saved_mtime {
+            buffer.set_saved_mtime(state.saved_mtime, cx);
+        }
+    })
 }
 
 impl ProjectItem for Editor {
@@ -1045,6 +1293,20 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            // Structural replacements reuse the metavariables captured in the underlying buffer.
+            let range = identifier.to_offset(&text);
+            let start = range.start;
+            let replacement = text.excerpt_containing(start..start).and_then(|excerpt| {
+                query.structural_replacement(excerpt.buffer(), excerpt.map_range_to_buffer(range))
+            });
+            if let Some(replacement) = replacement {
+                self.transact(cx, |this, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1262,9 +1524,169 @@ fn path_for_file<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::AppContext;
+    use crate::editor_tests::init_test;
+    use gpui::{AppContext, TestAppContext, VisualTestContext};
     use language::TestFile;
+    use project::FakeFs;
+    use serde_json::json;
     use std::path::Path;
+    use workspace::WORKSPACE_DB;
+
+    #[gpui::test]
+    async fn test_restore_unsaved_changes(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "file.txt": "one\ntwo\nthree\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project.clone()), cx));
+        let item_id = editor.item_id().as_u64() as ItemId;
+        DB.save_path(item_id, workspace_id, PathBuf::from("/root/file.txt"))
+            .await
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.edit([(0..0, "zero\n")], cx);
+            editor.fold_ranges([9..12], false, cx);
+            editor.change_selections(None, cx, |s| s.select_ranges([5..8]));
+        });
+        editor
+            .update(cx, |editor, cx| {
+                editor.persist_unsaved_changes(workspace_id, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        // The file changes on disk while the workspace is closed.
+        fs.insert_file("/root/file.txt", "ONE\ntwo\nthree\n".into())
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let restored = deserialize_editor(&workspace, project, workspace_id, item_id, cx).await;
+        restored.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "zero\none\ntwo\nthree\n");
+            assert_eq!(editor.selections.ranges::<usize>(cx), [5..8]);
+            assert_eq!(folds(editor, cx), [9..12]);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let buffer = buffer.read(cx);
+            assert!(buffer.file().is_some());
+            assert!(buffer.is_dirty());
+            assert!(buffer.has_conflict());
+        });
+
+        // Unsaved changes are only restored once.
+        assert_eq!(DB.get_unsaved_state(item_id, workspace_id).unwrap(), None);
+    }
+
+    #[gpui::test]
+    async fn test_restore_unsaved_changes_without_file(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "file.txt": "one\ntwo\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+
+        // Untitled buffers are restored from their contents alone.
+        let buffer = project
+            .update(cx, |project, cx| project.create_buffer("", None, cx))
+            .unwrap();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project.clone()), cx));
+        let item_id = editor.item_id().as_u64() as ItemId;
+        editor.update(cx, |editor, cx| {
+            editor.edit([(0..0, "untitled\n")], cx);
+            editor.change_selections(None, cx, |s| s.select_ranges([2..4]));
+        });
+        editor
+            .update(cx, |editor, cx| {
+                editor.persist_unsaved_changes(workspace_id, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        let restored =
+            deserialize_editor(&workspace, project.clone(), workspace_id, item_id, cx).await;
+        restored.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "untitled\n");
+            assert_eq!(editor.selections.ranges::<usize>(cx), [2..4]);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let buffer = buffer.read(cx);
+            assert!(buffer.file().is_none());
+        });
+
+        // Changes to a file that can't be opened anymore are kept in an untitled buffer.
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project.clone()), cx));
+        let item_id = editor.item_id().as_u64() as ItemId;
+        DB.save_path(item_id, workspace_id, PathBuf::from("/root/file.txt"))
+            .await
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.edit([(4..7, "TWO")], cx);
+            editor.fold_ranges([0..3], false, cx);
+        });
+        editor
+            .update(cx, |editor, cx| {
+                editor.persist_unsaved_changes(workspace_id, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        let project = Project::test(fs.clone(), [], cx).await;
+        let restored = deserialize_editor(&workspace, project, workspace_id, item_id, cx).await;
+        restored.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "one\nTWO\n");
+            assert_eq!(folds(editor, cx), [0..3]);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let buffer = buffer.read(cx);
+            assert!(buffer.file().is_none());
+            assert!(buffer.is_dirty());
+        });
+        assert_eq!(DB.get_unsaved_state(item_id, workspace_id).unwrap(), None);
+    }
+
+    async fn deserialize_editor(
+        workspace: &View<Workspace>,
+        project: Model<Project>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+        let editor = pane
+            .update(cx, |_, cx| {
+                Editor::deserialize(project, workspace.downgrade(), workspace_id, item_id, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        editor
+    }
+
+    fn folds(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Vec<Range<usize>> {
+        let snapshot = editor.display_map.update(cx, |map, cx| map.snapshot(cx));
+        snapshot
+            .folds_in_range(0..snapshot.buffer_snapshot.len())
+            .map(|fold| fold.range.to_offset(&snapshot.buffer_snapshot))
+            .collect()
+    }
 
     #[gpui::test]
     fn test_path_for_file(cx: &mut AppContext) {
@@ -1,5 +1,14 @@
-use std::path::PathBuf;
+use std::{
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
+use anyhow::Result;
+use db::sqlez::{
+    bindable::{Bind, Column, StaticColumnCount},
+    statement::Statement,
+};
 use db::sqlez_macros::sql;
 use db::{define_connection, query};
 
@@ -15,6 +24,26 @@ define_connection!(
     //   scroll_vertical_offset: f32,
     //   scroll_horizontal_offset: f32,
     // )
+    //
+    // editor_unsaved_states(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   contents: Option<String>,
+    //   saved_mtime_seconds: Option<i64>,
+    //   saved_mtime_nanos: Option<u32>,
+    //   selections: String,
+    //   folds: String,
+    //   history: Option<Vec<u8>>,
+    // )
+    //
+    // editor_excerpts(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   excerpt_ix: usize,
+    //   path: PathBuf,
+    //   start_row: u32,
+    //   end_row: u32,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
             CREATE TABLE editors(
@@ -31,9 +60,98 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN scroll_top_row INTEGER NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_horizontal_offset REAL NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_vertical_offset REAL NOT NULL DEFAULT 0;
+        ),
+        sql! (
+            CREATE TABLE editor_unsaved_states(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                contents TEXT,
+                saved_mtime_seconds INTEGER,
+                saved_mtime_nanos INTEGER,
+                selections TEXT NOT NULL,
+                folds TEXT NOT NULL,
+                history BLOB,
+                PRIMARY KEY(item_id, workspace_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        sql! (
+            CREATE TABLE editor_excerpts(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                excerpt_ix INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                start_row INTEGER NOT NULL,
+                end_row INTEGER NOT NULL,
+                PRIMARY KEY(item_id, workspace_id, excerpt_ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
         )];
 );
 
+/// The state of an editor persisted when quitting, including its buffer's unsaved changes.
+#[derive(Debug, Default, PartialEq)]
+pub struct SerializedUnsavedState {
+    /// The buffer's text, if it had unsaved changes.
+    pub contents: Option<String>,
+    /// When the file was last saved or reloaded before the unsaved changes were made.
+    pub saved_mtime: Option<SystemTime>,
+    /// The selections' tail and head offsets.
+    pub selections: Vec<(usize, usize)>,
+    pub folds: Vec<Range<usize>>,
+    /// A serialized [`rpc::proto::BufferHistory`].
+    pub history: Option<Vec<u8>>,
+}
+
+impl StaticColumnCount for SerializedUnsavedState {
+    fn column_count() -> usize {
+        6
+    }
+}
+
+impl Bind for &SerializedUnsavedState {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let saved_mtime = self
+            .saved_mtime
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| (duration.as_secs() as i64, duration.subsec_nanos()));
+        let next_index = statement.bind(&self.contents, start_index)?;
+        let next_index = statement.bind(&saved_mtime.map(|(seconds, _)| seconds), next_index)?;
+        let next_index = statement.bind(&saved_mtime.map(|(_, nanos)| nanos), next_index)?;
+        let next_index = statement.bind(&serde_json::to_string(&self.selections)?, next_index)?;
+        let next_index = statement.bind(&serde_json::to_string(&self.folds)?, next_index)?;
+        statement.bind(&self.history, next_index)
+    }
+}
+
+impl Column for SerializedUnsavedState {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (contents, next_index) = Option::<String>::column(statement, start_index)?;
+        let (seconds, next_index) = Option::<i64>::column(statement, next_index)?;
+        let (nanos, next_index) = Option::<u32>::column(statement, next_index)?;
+        let (selections, next_index) = String::column(statement, next_index)?;
+        let (folds, next_index) = String::column(statement, next_index)?;
+        let (history, next_index) = Option::<Vec<u8>>::column(statement, next_index)?;
+        let saved_mtime = seconds
+            .zip(nanos)
+            .map(|(seconds, nanos)| UNIX_EPOCH + Duration::new(seconds as u64, nanos));
+        Ok((
+            Self {
+                contents,
+                saved_mtime,
+                selections: serde_json::from_str(&selections)?,
+                folds: serde_json::from_str(&folds)?,
+                history,
+            },
+            next_index,
+        ))
+    }
+}
+
 impl EditorDb {
     query! {
         pub fn get_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
@@ -80,4 +198,86 @@ impl EditorDb {
             WHERE item_id = ?1 AND workspace_id = ?2
         }
     }
+
+    query! {
+        pub fn get_unsaved_state(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<SerializedUnsavedState>> {
+            SELECT contents, saved_mtime_seconds, saved_mtime_nanos, selections, folds, history
+            FROM editor_unsaved_states
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    pub async fn save_unsaved_state(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        state: SerializedUnsavedState,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT OR REPLACE INTO editor_unsaved_states
+                    (item_id, workspace_id, contents, saved_mtime_seconds, saved_mtime_nanos, selections, folds, history)
+                VALUES
+                    (?, ?, ?, ?, ?, ?, ?, ?)
+            ))?((item_id, workspace_id, &state))
+        })
+        .await
+    }
+
+    query! {
+        pub async fn delete_unsaved_state(item_id: ItemId, workspace_id: WorkspaceId) -> Result<()> {
+            DELETE FROM editor_unsaved_states
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    // Returns the excerpts of a working set, as the path of their file along with
+    // the first and last rows they show
+    query! {
+        pub fn get_excerpts(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, start_row, end_row
+            FROM editor_excerpts
+            WHERE item_id = ? AND workspace_id = ?
+            ORDER BY excerpt_ix
+        }
+    }
+
+    pub async fn save_excerpts(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        excerpts: Vec<(PathBuf, u32, u32)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_excerpts", || {
+                // A working set doesn't have a path, so it must not be restored as a
+                // single buffer that was saved with the same item id.
+                conn.exec_bound(sql!(
+                    DELETE FROM editors
+                    WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))?;
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_excerpts
+                    WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))?;
+                for (excerpt_ix, (path, start_row, end_row)) in excerpts.iter().enumerate() {
+                    conn.exec_bound(sql!(
+                        INSERT INTO editor_excerpts
+                            (item_id, workspace_id, excerpt_ix, path, start_row, end_row)
+                        VALUES
+                            (?, ?, ?, ?, ?, ?)
+                    ))?((
+                        item_id,
+                        workspace_id,
+                        excerpt_ix,
+                        path.as_path(),
+                        *start_row,
+                        *end_row,
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
 }
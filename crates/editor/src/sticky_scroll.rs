@@ -0,0 +1,202 @@
+use crate::{display_map::ToDisplayPoint, Anchor, DisplayPoint, Editor, EditorSnapshot};
+use gpui::{point, ViewContext};
+use language::Bias;
+use std::ops::Range;
+
+/// The ranges of the outline items enclosing the top of the viewport, kept between frames
+/// so that the buffer's outline is only queried again once the editor is scrolled to
+/// another line, edited or reparsed.
+#[derive(Default)]
+pub(crate) struct StickyHeaderCache {
+    key: Option<StickyHeaderCacheKey>,
+    enclosing_items: Vec<Range<Anchor>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct StickyHeaderCacheKey {
+    scroll_top_offset: usize,
+    edit_count: usize,
+    parse_count: usize,
+}
+
+impl Editor {
+    /// Returns the display rows of the header lines to pin at the top of the editor, see
+    /// [`EditorSnapshot::sticky_header_rows`].
+    pub(crate) fn sticky_header_rows(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        scroll_top_row: u32,
+        max_depth: usize,
+    ) -> Vec<u32> {
+        if max_depth == 0 || snapshot.buffer_snapshot.as_singleton().is_none() {
+            return Vec::new();
+        }
+
+        let scroll_top_offset =
+            DisplayPoint::new(scroll_top_row, 0).to_offset(&snapshot.display_snapshot, Bias::Left);
+        let key = StickyHeaderCacheKey {
+            scroll_top_offset,
+            edit_count: snapshot.buffer_snapshot.edit_count(),
+            parse_count: snapshot.buffer_snapshot.parse_count(),
+        };
+        let cache = &mut self.sticky_header_cache;
+        if cache.key != Some(key) {
+            cache.key = Some(key);
+            cache.enclosing_items = snapshot
+                .buffer_snapshot
+                .symbols_containing(scroll_top_offset, None)
+                .map(|(_, items)| items.into_iter().map(|item| item.range).collect())
+                .unwrap_or_default();
+        }
+        snapshot.sticky_header_rows(&cache.enclosing_items, scroll_top_row, max_depth)
+    }
+
+    /// Scrolls to the header pinned at `depth`, placing it right below the headers of
+    /// its enclosing items, which stay pinned.
+    pub(crate) fn scroll_to_sticky_header(
+        &mut self,
+        row: u32,
+        depth: usize,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let scroll_position = self.scroll_position(cx);
+        let scroll_top = row.saturating_sub(depth as u32);
+        self.set_scroll_position(point(scroll_position.x, scroll_top as f32), cx);
+    }
+}
+
+impl EditorSnapshot {
+    /// Returns the display rows of the header lines to pin at the top of the editor when
+    /// it's scrolled to `scroll_top_row`, from the outermost of the outline items enclosing
+    /// that row to the innermost one.
+    ///
+    /// An item's header is pinned while it is scrolled out of view, as long as some of
+    /// the item's body is still visible below the pinned headers.
+    fn sticky_header_rows(
+        &self,
+        enclosing_items: &[Range<Anchor>],
+        scroll_top_row: u32,
+        max_depth: usize,
+    ) -> Vec<u32> {
+        let mut rows = Vec::new();
+        for item in enclosing_items {
+            if rows.len() == max_depth {
+                break;
+            }
+
+            // The row hidden behind the header, if it gets pinned.
+            let covered_row = scroll_top_row + rows.len() as u32;
+            let header_row = item.start.to_display_point(&self.display_snapshot).row();
+            let end_row = item.end.to_display_point(&self.display_snapshot).row();
+            if header_row >= covered_row || end_row <= covered_row {
+                break;
+            }
+            if rows.last() != Some(&header_row) {
+                rows.push(header_row);
+            }
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::build_editor, MultiBuffer};
+    use gpui::{Context, TestAppContext, VisualTestContext};
+    use language::{Buffer, BufferId, Language, LanguageConfig, LanguageMatcher, Point};
+    use std::sync::Arc;
+    use unindent::Unindent;
+
+    #[gpui::test]
+    async fn test_sticky_header_rows(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (impl_item
+                    "impl" @context
+                    type: (_) @name) @item
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                (mod_item
+                    "mod" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let text = r#"
+            mod outer {
+                impl Point {
+                    fn first(&self) {
+                        let a = 1;
+                        let b = 2;
+                        let c = 3;
+                    }
+
+                    fn second(&self) {
+                        let d = 4;
+                    }
+                }
+            }
+        "#
+        .unindent();
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+                .with_language(language, cx)
+        });
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+        editor
+            .condition::<crate::EditorEvent>(cx, |editor, cx| {
+                !editor.buffer.read(cx).is_parsing(cx)
+            })
+            .await;
+
+        let sticky_header_rows = |scroll_top_row, max_depth, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                editor.sticky_header_rows(&snapshot, scroll_top_row, max_depth)
+            })
+        };
+        // Nothing is pinned while the headers are visible.
+        assert_eq!(sticky_header_rows(0, 5, cx), Vec::<u32>::new());
+        // Scrolled into the middle of the first function, all of its enclosing items are pinned.
+        assert_eq!(sticky_header_rows(3, 5, cx), vec![0, 1, 2]);
+        assert_eq!(sticky_header_rows(3, 2, cx), vec![0, 1]);
+        // The function's header is unpinned when its body would be hidden behind the headers.
+        assert_eq!(sticky_header_rows(4, 5, cx), vec![0, 1]);
+        assert_eq!(sticky_header_rows(9, 5, cx), vec![0, 1]);
+        assert_eq!(sticky_header_rows(3, 0, cx), Vec::<u32>::new());
+
+        // The cached outline items are queried again once the buffer is edited.
+        assert_eq!(sticky_header_rows(3, 5, cx), vec![0, 1, 2]);
+        editor.update(cx, |editor, cx| {
+            editor.buffer.update(cx, |buffer, cx| {
+                buffer.edit([(Point::new(0, 0)..Point::new(0, 0), "\n\n")], None, cx)
+            })
+        });
+        editor
+            .condition::<crate::EditorEvent>(cx, |editor, cx| {
+                !editor.buffer.read(cx).is_parsing(cx)
+            })
+            .await;
+        assert_eq!(sticky_header_rows(3, 5, cx), vec![2]);
+        assert_eq!(sticky_header_rows(5, 5, cx), vec![2, 3, 4]);
+    }
+}
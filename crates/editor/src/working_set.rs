@@ -0,0 +1,148 @@
+//! A working set is a multibuffer built by hand, collecting the ranges of code a user
+//! is working on from any file of the project. It is persisted along with the workspace.
+
+use crate::{AddSelectionToWorkingSet, Editor};
+use clock::ReplicaId;
+use gpui::{AppContext, Context, Model, ViewContext};
+use language::{Buffer, Capability, Point, ToPoint as _};
+use multi_buffer::{ExcerptRange, MultiBuffer};
+use project::Project;
+use std::ops::Range;
+
+pub(crate) const WORKING_SET_TITLE: &str = "Working Set";
+/// The number of lines shown around the ranges added to a working set.
+const WORKING_SET_CONTEXT_LINES: u32 = 1;
+
+impl Editor {
+    pub(crate) fn for_working_set(
+        buffer: Model<MultiBuffer>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut editor = Self::for_multibuffer(buffer, Some(project), cx);
+        editor.working_set = true;
+        editor
+    }
+
+    /// Builds the multibuffer of a working set out of the given excerpts, each
+    /// given as a buffer along with the first and last rows it shows.
+    pub(crate) fn build_working_set(
+        excerpts: Vec<(Model<Buffer>, u32, u32)>,
+        replica_id: ReplicaId,
+        cx: &mut AppContext,
+    ) -> Model<MultiBuffer> {
+        cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(replica_id, Capability::ReadWrite)
+                .with_title(WORKING_SET_TITLE.into());
+            for (buffer, start_row, end_row) in excerpts {
+                let max_point = buffer.read(cx).max_point();
+                let start = Point::new(start_row, 0).min(max_point);
+                let end = Point::new(end_row + 1, 0).min(max_point);
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: start..end,
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        })
+    }
+
+    /// Whether this editor shows the working set of its workspace.
+    pub fn is_working_set(&self) -> bool {
+        self.working_set
+    }
+
+    pub(crate) fn add_selection_to_working_set(
+        &mut self,
+        _: &AddSelectionToWorkingSet,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.working_set {
+            return;
+        }
+        let (Some(workspace), Some(project)) = (self.workspace(), self.project.clone()) else {
+            cx.propagate();
+            return;
+        };
+
+        let multibuffer = self.buffer.read(cx);
+        let mut ranges_by_buffer = Vec::<(Model<Buffer>, Vec<Range<Point>>)>::new();
+        for selection in self.selections.all::<usize>(cx) {
+            for (buffer, range, _) in
+                multibuffer.range_to_buffer_ranges(selection.start..selection.end, cx)
+            {
+                let snapshot = buffer.read(cx);
+                let range = range.start.to_point(snapshot)..range.end.to_point(snapshot);
+                match ranges_by_buffer
+                    .iter_mut()
+                    .find(|(existing_buffer, _)| *existing_buffer == buffer)
+                {
+                    Some((_, ranges)) => ranges.push(range),
+                    None => ranges_by_buffer.push((buffer, vec![range])),
+                }
+            }
+        }
+
+        // We defer the pane interaction because we ourselves are a workspace item
+        // and adding a new item causes the pane to call a method on us reentrantly,
+        // which panics if we're on the stack.
+        cx.window_context().defer(move |cx| {
+            workspace.update(cx, |workspace, cx| {
+                let existing_working_set = workspace
+                    .items_of_type::<Editor>(cx)
+                    .find(|editor| editor.read(cx).working_set);
+                let working_set = existing_working_set.unwrap_or_else(|| {
+                    let replica_id = project.read(cx).replica_id();
+                    let buffer = Editor::build_working_set(Vec::new(), replica_id, cx);
+                    let editor = cx.new_view(|cx| Editor::for_working_set(buffer, project, cx));
+                    workspace.add_item_to_active_pane(Box::new(editor.clone()), cx);
+                    editor
+                });
+                working_set.update(cx, |editor, cx| {
+                    editor.add_ranges_to_working_set(ranges_by_buffer, cx)
+                });
+            })
+        });
+    }
+
+    /// Adds excerpts for the given ranges, skipping those already shown in the working set.
+    fn add_ranges_to_working_set(
+        &mut self,
+        ranges_by_buffer: Vec<(Model<Buffer>, Vec<Range<Point>>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.buffer.update(cx, |multibuffer, cx| {
+            for (buffer, ranges) in ranges_by_buffer {
+                let snapshot = buffer.read(cx).snapshot();
+                let existing_ranges = multibuffer
+                    .excerpts_for_buffer(&buffer, cx)
+                    .into_iter()
+                    .map(|(_, range)| {
+                        range.context.start.to_point(&snapshot)
+                            ..range.context.end.to_point(&snapshot)
+                    })
+                    .collect::<Vec<_>>();
+                let ranges = ranges
+                    .into_iter()
+                    .filter(|range| {
+                        !existing_ranges.iter().any(|existing_range| {
+                            existing_range.start <= range.start && range.end <= existing_range.end
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if !ranges.is_empty() {
+                    multibuffer.push_excerpts_with_context_lines(
+                        buffer,
+                        ranges,
+                        WORKING_SET_CONTEXT_LINES,
+                        cx,
+                    );
+                }
+            }
+        });
+    }
+}
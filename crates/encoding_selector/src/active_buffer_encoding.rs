@@ -0,0 +1,74 @@
+use editor::Editor;
+use gpui::{
+    div, Action, IntoElement, ParentElement, Render, Subscription, View, ViewContext, WeakView,
+};
+use language::Encoding;
+use ui::{popover_menu, Button, ButtonCommon, ContextMenu, LabelSize, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+use crate::{ReopenWithEncoding, SaveWithEncoding};
+
+pub struct ActiveBufferEncoding {
+    active_encoding: Option<Encoding>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl ActiveBufferEncoding {
+    pub fn new(_workspace: &Workspace) -> Self {
+        Self {
+            active_encoding: None,
+            _observe_active_editor: None,
+        }
+    }
+
+    fn update_encoding(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        self.active_encoding = editor
+            .read(cx)
+            .active_excerpt(cx)
+            .filter(|(_, buffer, _)| buffer.read(cx).file().is_some())
+            .map(|(_, buffer, _)| buffer.read(cx).encoding());
+        cx.notify();
+    }
+}
+
+impl Render for ActiveBufferEncoding {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(encoding) = self.active_encoding else {
+            return div();
+        };
+
+        div().child(
+            popover_menu("encoding-menu")
+                .menu(|cx| {
+                    Some(ContextMenu::build(cx, |menu, _| {
+                        menu.action("Reopen with Encoding...", ReopenWithEncoding.boxed_clone())
+                            .action("Save with Encoding...", SaveWithEncoding.boxed_clone())
+                    }))
+                })
+                .anchor(gpui::AnchorCorner::BottomRight)
+                .trigger(
+                    Button::new("change-encoding", encoding.display_name())
+                        .label_size(LabelSize::Small)
+                        .tooltip(|cx| Tooltip::text("Change Encoding", cx)),
+                ),
+        )
+    }
+}
+
+impl StatusItemView for ActiveBufferEncoding {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_encoding));
+            self.update_encoding(editor, cx);
+        } else {
+            self.active_encoding = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
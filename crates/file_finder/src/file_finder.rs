@@ -1,29 +1,37 @@
 #[cfg(test)]
 mod file_finder_tests;
+mod persistence;
 
 use collections::{HashMap, HashSet};
-use editor::{scroll::Autoscroll, Bias, Editor};
-use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
+use command_palette::CommandPalette;
+use editor::{
+    items::entry_git_aware_label_color, scroll::Autoscroll, Anchor, Bias, Editor, ToPoint as _,
+};
+use fuzzy::{CharBag, PathMatch, PathMatchCandidate, StringMatchCandidate};
 use gpui::{
     actions, rems, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
     Model, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task, View,
     ViewContext, VisualContext, WeakView,
 };
 use itertools::Itertools;
+use persistence::DB;
 use picker::{Picker, PickerDelegate};
-use project::{PathMatchCandidateSet, Project, ProjectPath, WorktreeId};
+use project::{DiagnosticSummary, PathMatchCandidateSet, Project, ProjectPath, Symbol, WorktreeId};
+use settings::Settings;
 use std::{
     cmp,
+    ops::Range,
     path::{Path, PathBuf},
     sync::{
         atomic::{self, AtomicBool},
         Arc,
     },
+    time::{SystemTime, UNIX_EPOCH},
 };
 use text::Point;
 use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
 use util::{paths::PathLikeWithPosition, post_inc, ResultExt};
-use workspace::{ModalView, Workspace};
+use workspace::{item::PreviewTabsSettings, ModalView, Workspace, WorkspaceId};
 
 actions!(file_finder, [Toggle, SelectPrev]);
 
@@ -78,10 +86,22 @@ impl FileFinder {
             .map(|(history_path, abs_path)| FoundPath::new(history_path, abs_path))
             .collect::<Vec<_>>();
 
+        let workspace_id = workspace.database_id();
+        let frecencies = load_frecencies(workspace_id, project, cx);
+        let mut diagnostic_summaries = HashMap::<ProjectPath, DiagnosticSummary>::default();
+        for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+            let path_summary = diagnostic_summaries.entry(project_path).or_default();
+            path_summary.error_count += summary.error_count;
+            path_summary.warning_count += summary.warning_count;
+        }
+        let active_editor = workspace
+            .active_item_as::<Editor>(cx)
+            .map(|editor| editor.downgrade());
+
         let project = workspace.project().clone();
         let weak_workspace = cx.view().downgrade();
         workspace.toggle_modal(cx, |cx| {
-            let delegate = FileFinderDelegate::new(
+            let mut delegate = FileFinderDelegate::new(
                 cx.view().downgrade(),
                 weak_workspace,
                 project,
@@ -89,6 +109,10 @@ impl FileFinder {
                 history_items,
                 cx,
             );
+            delegate.workspace_id = workspace_id;
+            delegate.frecencies = Arc::new(frecencies);
+            delegate.diagnostic_summaries = diagnostic_summaries;
+            delegate.active_editor = active_editor;
 
             FileFinder::new(delegate, cx)
         });
@@ -156,6 +180,12 @@ pub struct FileFinderDelegate {
     has_changed_selected_index: bool,
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
+    workspace_id: WorkspaceId,
+    frecencies: Arc<HashMap<ProjectPath, f64>>,
+    diagnostic_summaries: HashMap<ProjectPath, DiagnosticSummary>,
+    active_editor: Option<WeakView<Editor>>,
+    query_mode: QueryMode,
+    jump_matches: Vec<JumpMatch>,
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -387,6 +417,93 @@ impl FileSearchQuery {
     }
 }
 
+/// What the file finder searches for, picked by the first character of the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryMode {
+    Files,
+    /// `@`: symbols in the outline of the active editor.
+    Outline,
+    /// `#`: symbols of the whole project, from the language servers.
+    Symbols,
+    /// `:`: a line (and column) of the active editor.
+    Line,
+    /// `>`: hands the query over to the command palette.
+    Commands,
+}
+
+impl QueryMode {
+    fn parse(query: &str) -> (Self, &str) {
+        let mode = match query.chars().next() {
+            Some('@') => Self::Outline,
+            Some('#') => Self::Symbols,
+            Some(':') => Self::Line,
+            Some('>') => Self::Commands,
+            _ => return (Self::Files, query),
+        };
+        (mode, query[1..].trim())
+    }
+}
+
+/// A match of the outline, symbol or line modes, jumping to a position when confirmed.
+#[derive(Debug, Clone)]
+struct JumpMatch {
+    label: String,
+    positions: Vec<usize>,
+    detail: String,
+    target: JumpTarget,
+}
+
+#[derive(Debug, Clone)]
+enum JumpTarget {
+    Outline(Range<Anchor>),
+    Symbol(Symbol),
+    Line(Point),
+}
+
+/// How much the frecency of a file raises its fuzzy match score.
+const FRECENCY_BOOST: f64 = 0.1;
+
+/// Scores how likely a file is to be opened again, from how many times it was opened from the
+/// file finder and how long ago it was last opened.
+fn frecency(open_count: u32, seconds_since_last_open: i64) -> f64 {
+    const DAY: i64 = 24 * 60 * 60;
+    let recency_weight = match seconds_since_last_open.max(0) / DAY {
+        0..=3 => 1.0,
+        4..=14 => 0.7,
+        15..=31 => 0.5,
+        32..=90 => 0.3,
+        _ => 0.1,
+    };
+    open_count as f64 * recency_weight
+}
+
+fn load_frecencies(
+    workspace_id: WorkspaceId,
+    project: &Project,
+    cx: &AppContext,
+) -> HashMap<ProjectPath, f64> {
+    let now = unix_timestamp();
+    DB.opened_paths(workspace_id)
+        .log_err()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(abs_path, open_count, last_opened)| {
+            let (worktree, path) = project.find_local_worktree(&abs_path, cx)?;
+            let project_path = ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: Arc::from(path),
+            };
+            Some((project_path, frecency(open_count, now - last_opened)))
+        })
+        .collect()
+}
+
+fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
 impl FileFinderDelegate {
     fn new(
         file_finder: WeakView<FileFinder>,
@@ -411,6 +528,12 @@ impl FileFinderDelegate {
             selected_index: 0,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
+            workspace_id: WorkspaceId::default(),
+            frecencies: Arc::default(),
+            diagnostic_summaries: HashMap::default(),
+            active_editor: None,
+            query_mode: QueryMode::Files,
+            jump_matches: Vec::new(),
         }
     }
 
@@ -461,8 +584,9 @@ impl FileFinderDelegate {
         self.cancel_flag.store(true, atomic::Ordering::Relaxed);
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
+        let frecencies = self.frecencies.clone();
         cx.spawn(|picker, mut cx| async move {
-            let matches = fuzzy::match_path_sets(
+            let mut matches = fuzzy::match_path_sets(
                 candidate_sets.as_slice(),
                 query.path_like.path_query(),
                 relative_to,
@@ -473,7 +597,18 @@ impl FileFinderDelegate {
             )
             .await
             .into_iter()
-            .map(ProjectPanelOrdMatch);
+            .map(|mut path_match| {
+                let project_path = ProjectPath {
+                    worktree_id: WorktreeId::from_usize(path_match.worktree_id),
+                    path: path_match.path.clone(),
+                };
+                if let Some(frecency) = frecencies.get(&project_path) {
+                    path_match.score *= 1. + FRECENCY_BOOST * frecency.ln_1p();
+                }
+                ProjectPanelOrdMatch(path_match)
+            })
+            .collect::<Vec<_>>();
+            matches.sort_by(|a, b| b.cmp(a));
             let did_cancel = cancel_flag.load(atomic::Ordering::Relaxed);
             picker
                 .update(&mut cx, |picker, cx| {
@@ -685,6 +820,267 @@ impl FileFinderDelegate {
         })
     }
 
+    fn set_jump_matches(
+        &mut self,
+        search_id: usize,
+        matches: Vec<JumpMatch>,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        if search_id >= self.latest_search_id {
+            self.latest_search_id = search_id;
+            self.jump_matches = matches;
+            self.selected_index = 0;
+            cx.notify();
+        }
+    }
+
+    fn active_editor(&self) -> Option<View<Editor>> {
+        self.active_editor.as_ref()?.upgrade()
+    }
+
+    /// Parses `row` or `row:column` into a match for that position of the active editor.
+    fn line_matches(&self, query: &str, cx: &AppContext) -> Vec<JumpMatch> {
+        let Some(editor) = self.active_editor() else {
+            return Vec::new();
+        };
+        let (row, column) = match query.split_once(':') {
+            Some((row, column)) => (row, Some(column)),
+            None => (query, None),
+        };
+        let Ok(row) = row.trim().parse::<u32>() else {
+            return Vec::new();
+        };
+        let column = column.and_then(|column| column.trim().parse::<u32>().ok());
+        let line_count = editor.read(cx).buffer().read(cx).read(cx).max_point().row + 1;
+
+        let label = match column {
+            Some(column) => format!("Go to line {row}, column {column}"),
+            None => format!("Go to line {row}"),
+        };
+        vec![JumpMatch {
+            label,
+            positions: Vec::new(),
+            detail: format!("{line_count} lines"),
+            target: JumpTarget::Line(Point::new(
+                row.saturating_sub(1),
+                column.unwrap_or(1).saturating_sub(1),
+            )),
+        }]
+    }
+
+    fn spawn_outline_search(
+        &mut self,
+        query: &str,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Task<()> {
+        let search_id = post_inc(&mut self.search_count);
+        let Some(editor) = self.active_editor() else {
+            self.set_jump_matches(search_id, Vec::new(), cx);
+            return Task::ready(());
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let Some(outline) = snapshot.outline(None) else {
+            self.set_jump_matches(search_id, Vec::new(), cx);
+            return Task::ready(());
+        };
+
+        let query = query.to_string();
+        let executor = cx.background_executor().clone();
+        cx.spawn(|picker, mut cx| async move {
+            let mut string_matches = outline.search(&query, executor).await;
+            if !query.is_empty() {
+                // Drop the ancestors of the matching items that the outline adds to show them in
+                // a tree, and put the best matches first instead.
+                string_matches.retain(|string_match| !string_match.positions.is_empty());
+                string_matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+            }
+            let matches = string_matches
+                .into_iter()
+                .map(|string_match| {
+                    let item = &outline.items[string_match.candidate_id];
+                    let row = item.range.start.to_point(&snapshot).row;
+                    JumpMatch {
+                        label: item.text.clone(),
+                        positions: string_match.positions,
+                        detail: format!("line {}", row + 1),
+                        target: JumpTarget::Outline(item.range.clone()),
+                    }
+                })
+                .collect();
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.set_jump_matches(search_id, matches, cx)
+                })
+                .log_err();
+        })
+    }
+
+    fn spawn_symbol_search(&mut self, query: &str, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let search_id = post_inc(&mut self.search_count);
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(query, cx));
+        let query = query.to_string();
+        cx.spawn(|picker, mut cx| async move {
+            let symbols = symbols.await.log_err().unwrap_or_default();
+            let candidates = symbols
+                .iter()
+                .enumerate()
+                .map(|(id, symbol)| {
+                    StringMatchCandidate::new(
+                        id,
+                        symbol.label.text[symbol.label.filter_range.clone()].to_string(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let executor = cx.background_executor().clone();
+            let string_matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                100,
+                &Default::default(),
+                executor,
+            )
+            .await;
+            let matches = string_matches
+                .into_iter()
+                .map(|string_match| {
+                    let symbol = &symbols[string_match.candidate_id];
+                    JumpMatch {
+                        label: string_match.string,
+                        positions: string_match.positions,
+                        detail: symbol.path.path.to_string_lossy().to_string(),
+                        target: JumpTarget::Symbol(symbol.clone()),
+                    }
+                })
+                .collect();
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.set_jump_matches(search_id, matches, cx)
+                })
+                .log_err();
+        })
+    }
+
+    /// Replaces the file finder with the command palette, searching for the rest of the query.
+    fn open_command_palette(&mut self, query: &str, cx: &mut ViewContext<Picker<Self>>) {
+        self.file_finder
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+        let workspace = self.workspace.clone();
+        let query = query.to_string();
+        // Wait for the file finder to be dismissed, so that the command palette returns the
+        // focus to the editor rather than to the file finder.
+        cx.window_context().defer(move |cx| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    CommandPalette::toggle(workspace, &query, cx)
+                })
+                .log_err();
+        });
+    }
+
+    fn confirm_jump(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(jump_match) = self.jump_matches.get(self.selected_index) else {
+            return;
+        };
+        match &jump_match.target {
+            JumpTarget::Symbol(symbol) => {
+                let buffer = self
+                    .project
+                    .update(cx, |project, cx| project.open_buffer_for_symbol(symbol, cx));
+                let symbol = symbol.clone();
+                let workspace = self.workspace.clone();
+                cx.spawn(|_, mut cx| async move {
+                    let buffer = buffer.await?;
+                    workspace.update(&mut cx, |workspace, cx| {
+                        let position = buffer
+                            .read(cx)
+                            .clip_point_utf16(symbol.range.start, Bias::Left);
+                        let pane = if secondary {
+                            workspace.adjacent_pane(cx)
+                        } else {
+                            workspace.active_pane().clone()
+                        };
+                        let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                s.select_ranges([position..position])
+                            });
+                        });
+                    })?;
+                    Ok::<_, anyhow::Error>(())
+                })
+                .detach_and_log_err(cx);
+            }
+            JumpTarget::Outline(range) => {
+                let position = range.start;
+                if let Some(editor) = self.active_editor() {
+                    editor.update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_ranges([position..position])
+                        });
+                    });
+                }
+            }
+            JumpTarget::Line(point) => {
+                let point = *point;
+                if let Some(editor) = self.active_editor() {
+                    editor.update(cx, |editor, cx| {
+                        let point = editor
+                            .buffer()
+                            .read(cx)
+                            .read(cx)
+                            .clip_point(point, Bias::Left);
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_ranges([point..point])
+                        });
+                    });
+                }
+            }
+        }
+        self.file_finder
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_jump_match(&self, ix: usize, selected: bool) -> Option<ListItem> {
+        let jump_match = self.jump_matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .inset(true)
+                .selected(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .py_px()
+                        .child(HighlightedLabel::new(
+                            jump_match.label.clone(),
+                            jump_match.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(jump_match.detail.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+
+    /// Remembers that a file was opened from the file finder, to rank it higher next time.
+    fn record_open(&self, abs_path: PathBuf, cx: &AppContext) {
+        let workspace_id = self.workspace_id;
+        cx.background_executor()
+            .spawn(async move {
+                DB.record_open(workspace_id, abs_path, unix_timestamp())
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+
     /// Skips first history match (that is displayed topmost) if it's currently opened.
     fn calculate_selected_index(&self) -> usize {
         if let Some(Match::History(path, _)) = self.matches.get(0) {
@@ -703,11 +1099,14 @@ impl PickerDelegate for FileFinderDelegate {
     type ListItem = ListItem;
 
     fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
-        "Search project files...".into()
+        "Search files, or type @ for outline, # for symbols, : for line, > for commands...".into()
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        match self.query_mode {
+            QueryMode::Files => self.matches.len(),
+            _ => self.jump_matches.len(),
+        }
     }
 
     fn selected_index(&self) -> usize {
@@ -721,6 +1120,9 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn separators_after_indices(&self) -> Vec<usize> {
+        if self.query_mode != QueryMode::Files {
+            return Vec::new();
+        }
         let history_items = self.matches.history.len();
         if history_items == 0 || self.matches.search.is_empty() {
             Vec::new()
@@ -734,6 +1136,24 @@ impl PickerDelegate for FileFinderDelegate {
         raw_query: String,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Task<()> {
+        let (query_mode, query) = QueryMode::parse(raw_query.trim_start());
+        self.query_mode = query_mode;
+        match query_mode {
+            QueryMode::Files => {}
+            QueryMode::Outline => return self.spawn_outline_search(query, cx),
+            QueryMode::Symbols => return self.spawn_symbol_search(query, cx),
+            QueryMode::Line => {
+                let search_id = post_inc(&mut self.search_count);
+                let matches = self.line_matches(query, cx);
+                self.set_jump_matches(search_id, matches, cx);
+                return Task::ready(());
+            }
+            QueryMode::Commands => {
+                self.open_command_palette(query, cx);
+                return Task::ready(());
+            }
+        }
+
         let raw_query = raw_query.replace(' ', "");
         let raw_query = raw_query.trim();
         if raw_query.is_empty() {
@@ -779,16 +1199,42 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<FileFinderDelegate>>) {
+        if self.query_mode != QueryMode::Files {
+            self.confirm_jump(secondary, cx);
+            return;
+        }
         if let Some(m) = self.matches.get(self.selected_index()) {
+            let abs_path = match &m {
+                Match::History(found_path, _) => found_path.absolute.clone(),
+                Match::Search(m) => self
+                    .project
+                    .read(cx)
+                    .worktree_for_id(WorktreeId::from_usize(m.0.worktree_id), cx)
+                    .map(|worktree| worktree.read(cx).abs_path().join(&m.0.path)),
+            };
+            if let Some(abs_path) = abs_path {
+                self.record_open(abs_path, cx);
+            }
             if let Some(workspace) = self.workspace.upgrade() {
                 let open_task = workspace.update(cx, move |workspace, cx| {
-                    let split_or_open = |workspace: &mut Workspace, project_path, cx| {
-                        if secondary {
-                            workspace.split_path(project_path, cx)
-                        } else {
-                            workspace.open_path(project_path, None, true, cx)
-                        }
-                    };
+                    let split_or_open =
+                        |workspace: &mut Workspace,
+                         project_path,
+                         cx: &mut ViewContext<Workspace>| {
+                            let allow_preview =
+                                PreviewTabsSettings::get_global(cx).enable_preview_from_file_finder;
+                            if secondary {
+                                workspace.split_path(project_path, cx)
+                            } else {
+                                workspace.open_path_preview(
+                                    project_path,
+                                    None,
+                                    true,
+                                    allow_preview,
+                                    cx,
+                                )
+                            }
+                        };
                     match m {
                         Match::History(history_match, _) => {
                             let worktree_id = history_match.project.worktree_id;
@@ -897,10 +1343,29 @@ impl PickerDelegate for FileFinderDelegate {
         selected: bool,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
+        if self.query_mode != QueryMode::Files {
+            return self.render_jump_match(ix, selected);
+        }
+
         let path_match = self
             .matches
             .get(ix)
             .expect("Invalid matches state: no element for index {ix}");
+        let project_path = match &path_match {
+            Match::History(found_path, _) => found_path.project.clone(),
+            Match::Search(m) => ProjectPath {
+                worktree_id: WorktreeId::from_usize(m.0.worktree_id),
+                path: m.0.path.clone(),
+            },
+        };
+        let file_name_color = self
+            .project
+            .read(cx)
+            .entry_for_path(&project_path, cx)
+            .map_or(Color::Default, |entry| {
+                entry_git_aware_label_color(entry.git_status, entry.is_ignored, selected)
+            });
+        let diagnostic_summary = self.diagnostic_summaries.get(&project_path).copied();
 
         let (file_name, file_name_positions, full_path, full_path_positions) =
             self.labels_for_match(path_match, cx, ix);
@@ -914,21 +1379,68 @@ impl PickerDelegate for FileFinderDelegate {
                     h_flex()
                         .gap_2()
                         .py_px()
-                        .child(HighlightedLabel::new(file_name, file_name_positions))
+                        .child(
+                            HighlightedLabel::new(file_name, file_name_positions)
+                                .color(file_name_color),
+                        )
                         .child(
                             HighlightedLabel::new(full_path, full_path_positions)
                                 .size(LabelSize::Small)
                                 .color(Color::Muted),
                         ),
-                ),
+                )
+                .end_slot::<AnyElement>(diagnostic_summary.map(render_diagnostic_summary)),
         )
     }
 }
 
+fn render_diagnostic_summary(summary: DiagnosticSummary) -> AnyElement {
+    h_flex()
+        .gap_1()
+        .when(summary.error_count > 0, |this| {
+            this.child(
+                Icon::new(IconName::XCircle)
+                    .size(IconSize::Small)
+                    .color(Color::Error),
+            )
+            .child(Label::new(summary.error_count.to_string()).size(LabelSize::Small))
+        })
+        .when(summary.warning_count > 0, |this| {
+            this.child(
+                Icon::new(IconName::ExclamationTriangle)
+                    .size(IconSize::Small)
+                    .color(Color::Warning),
+            )
+            .child(Label::new(summary.warning_count.to_string()).size(LabelSize::Small))
+        })
+        .into_any_element()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_query_mode_prefixes() {
+        assert_eq!(QueryMode::parse("main.rs"), (QueryMode::Files, "main.rs"));
+        assert_eq!(QueryMode::parse("@ render"), (QueryMode::Outline, "render"));
+        assert_eq!(
+            QueryMode::parse("#Workspace"),
+            (QueryMode::Symbols, "Workspace")
+        );
+        assert_eq!(QueryMode::parse(":12:4"), (QueryMode::Line, "12:4"));
+        assert_eq!(QueryMode::parse(">"), (QueryMode::Commands, ""));
+    }
+
+    #[test]
+    fn test_frecency_favors_recent_and_frequent_opens() {
+        const DAY: i64 = 24 * 60 * 60;
+        assert!(frecency(1, DAY) > frecency(1, 10 * DAY));
+        assert!(frecency(1, 10 * DAY) > frecency(1, 365 * DAY));
+        assert!(frecency(5, 10 * DAY) > frecency(1, DAY));
+        assert_eq!(frecency(0, DAY), 0.);
+    }
+
     #[test]
     fn test_custom_project_search_ordering_in_file_finder() {
         let mut file_finder_sorted_output = vec![
@@ -258,6 +258,74 @@ async fn test_row_column_numbers_query_inside_file(cx: &mut TestAppContext) {
         });
 }
 
+#[gpui::test]
+async fn test_line_query_moves_caret_in_active_editor(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "first.rs": "// First line\n// Second line\n// Third line\n",
+                }
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/src".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+    open_queried_buffer("fir", 1, "first.rs", &workspace, cx).await;
+
+    let picker = open_file_picker(&workspace, cx);
+    cx.simulate_input(":3:4");
+    picker.update(cx, |finder, _| {
+        assert_eq!(finder.delegate.query_mode, QueryMode::Line);
+        assert_eq!(finder.delegate.match_count(), 1);
+    });
+    cx.dispatch_action(Confirm);
+
+    let editor = cx.update(|cx| {
+        let workspace = workspace.read(cx);
+        assert!(
+            workspace.active_modal::<FileFinder>(cx).is_none(),
+            "File finder should be dismissed after jumping to a line"
+        );
+        workspace.active_item_as::<Editor>(cx).unwrap()
+    });
+    editor.update(cx, |editor, cx| {
+        let all_selections = editor.selections.all_adjusted(cx);
+        assert_eq!(all_selections.len(), 1);
+        let caret_selection = all_selections.into_iter().next().unwrap();
+        assert_eq!(caret_selection.start, caret_selection.end);
+        assert_eq!(caret_selection.start, Point::new(2, 3));
+    });
+}
+
+#[gpui::test]
+async fn test_command_query_opens_command_palette(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    cx.update(command_palette::init);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree("/src", json!({ "test": { "first.rs": "// First line\n" } }))
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/src".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+    open_queried_buffer("fir", 1, "first.rs", &workspace, cx).await;
+
+    open_file_picker(&workspace, cx);
+    cx.simulate_input(">");
+    cx.run_until_parked();
+    workspace.update(cx, |workspace, cx| {
+        assert!(workspace.active_modal::<FileFinder>(cx).is_none());
+        assert!(workspace.active_modal::<CommandPalette>(cx).is_some());
+    });
+}
+
 #[gpui::test]
 async fn test_row_column_numbers_query_outside_file(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+use std::path::PathBuf;
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // file_finder_opened_paths(
+    //     workspace_id: usize, // References workspaces table
+    //     abs_path: PathBuf,
+    //     open_count: usize, // How many times the path was opened from the file finder
+    //     last_opened: i64, // Unix timestamp of the latest open, in seconds
+    // )
+    pub static ref DB: FileFinderDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE file_finder_opened_paths (
+                workspace_id INTEGER NOT NULL,
+                abs_path TEXT NOT NULL,
+                open_count INTEGER NOT NULL,
+                last_opened INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, abs_path),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl FileFinderDb {
+    query! {
+        pub(crate) fn opened_paths(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, i64)>> {
+            SELECT abs_path, open_count, last_opened
+            FROM file_finder_opened_paths
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub(crate) async fn record_open(workspace_id: WorkspaceId, abs_path: PathBuf, timestamp: i64) -> Result<()> {
+            INSERT INTO file_finder_opened_paths(workspace_id, abs_path, open_count, last_opened)
+            VALUES (?1, ?2, 1, ?3)
+            ON CONFLICT(workspace_id, abs_path) DO UPDATE SET
+                open_count = open_count + 1,
+                last_opened = ?3
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::open_test_db;
+
+    #[gpui::test]
+    async fn test_record_open() {
+        let db = FileFinderDb(open_test_db("test_record_open").await);
+        let workspace_id = db
+            .select_row::<WorkspaceId>(sql!(
+                INSERT INTO workspaces DEFAULT VALUES RETURNING workspace_id
+            ))
+            .unwrap()()
+        .unwrap()
+        .unwrap();
+
+        db.record_open(workspace_id, PathBuf::from("/root/a.rs"), 10)
+            .await
+            .unwrap();
+        db.record_open(workspace_id, PathBuf::from("/root/b.rs"), 20)
+            .await
+            .unwrap();
+        db.record_open(workspace_id, PathBuf::from("/root/a.rs"), 30)
+            .await
+            .unwrap();
+
+        let mut opened_paths = db.opened_paths(workspace_id).unwrap();
+        opened_paths.sort();
+        assert_eq!(
+            opened_paths,
+            vec![
+                (PathBuf::from("/root/a.rs"), 2, 30),
+                (PathBuf::from("/root/b.rs"), 1, 20),
+            ]
+        );
+    }
+}
@@ -45,9 +45,9 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, RopeFingerprint, Selection,
-    SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint,
-    ToPointUtf16, Transaction, TransactionId, Unclipped,
+    Encoding, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, RopeFingerprint,
+    Selection, SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16,
+    ToPoint, ToPointUtf16, Transaction, TransactionId, Unclipped,
 };
 use theme::SyntaxTheme;
 #[cfg(any(test, feature = "test-support"))]
@@ -89,6 +89,8 @@ pub struct Buffer {
     saved_version: clock::Global,
     /// A hash of the current contents of the buffer's file.
     file_fingerprint: RopeFingerprint,
+    /// The encoding the buffer's file was loaded with, and will be saved with.
+    encoding: Encoding,
     transaction_depth: usize,
     was_dirty_before_starting_transaction: Option<bool>,
     reload_task: Option<Task<Result<()>>>,
@@ -399,16 +401,18 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's raw contents from disk.
+    fn load_bytes(&self, cx: &AppContext) -> Task<Result<Vec<u8>>>;
 
     /// Called when the buffer is reloaded from disk.
+    #[allow(clippy::too_many_arguments)]
     fn buffer_reloaded(
         &self,
         buffer_id: BufferId,
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: Option<SystemTime>,
         cx: &mut AppContext,
     );
@@ -576,6 +580,9 @@ impl Buffer {
             rpc::proto::LineEnding::from_i32(message.line_ending)
                 .ok_or_else(|| anyhow!("missing line_ending"))?,
         ));
+        if let Some(encoding) = message.encoding {
+            this.encoding = proto::deserialize_encoding(&encoding)?;
+        }
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.file_fingerprint = proto::deserialize_fingerprint(&message.saved_version_fingerprint)?;
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
@@ -590,6 +597,7 @@ impl Buffer {
             base_text: self.base_text().to_string(),
             diff_base: self.diff_base.as_ref().map(|h| h.to_string()),
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
+            encoding: Some(proto::serialize_encoding(self.encoding)),
             saved_version: proto::serialize_version(&self.saved_version),
             saved_version_fingerprint: proto::serialize_fingerprint(self.file_fingerprint),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
@@ -649,6 +657,12 @@ impl Buffer {
         self
     }
 
+    /// Assign the encoding of the buffer's file, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Returns the [Capability] of this buffer.
     pub fn capability(&self) -> Capability {
         self.capability
@@ -672,6 +686,7 @@ impl Buffer {
             saved_mtime,
             saved_version: buffer.version(),
             file_fingerprint: buffer.as_rope().fingerprint(),
+            encoding: Encoding::default(),
             reload_task: None,
             transaction_depth: 0,
             was_dirty_before_starting_transaction: None,
@@ -756,6 +771,19 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The encoding the buffer's file was loaded with, and will be saved with.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets the encoding the buffer's file will be saved with.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        if self.encoding != encoding {
+            self.encoding = encoding;
+            cx.notify();
+        }
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.parse_count += 1;
@@ -795,27 +823,63 @@ impl Buffer {
         cx.notify();
     }
 
+    /// Overrides the modification time of the file contents the buffer was last saved
+    /// or reloaded with, as when restoring unsaved changes made to an earlier version
+    /// of the file. The buffer has a conflict if the file was modified since.
+    pub fn set_saved_mtime(&mut self, mtime: Option<SystemTime>, cx: &mut ModelContext<Self>) {
+        self.saved_mtime = mtime;
+        cx.notify();
+    }
+
     /// Reloads the contents of the buffer from disk.
     pub fn reload(
         &mut self,
         cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Option<Transaction>> {
+        self.reload_with_encoding(None, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given
+    /// encoding. When no encoding is given, the current one is kept unless the
+    /// file's contents are not valid in it.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Option<Encoding>,
+        cx: &mut ModelContext<Self>,
     ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
+        let prev_encoding = self.encoding;
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
-            let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
+            let Some((new_mtime, new_bytes)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), file.load_bytes(cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let new_bytes = new_bytes.await?;
+            let (new_text, new_encoding) = cx
+                .background_executor()
+                .spawn(async move {
+                    let detected = Encoding::detect(&new_bytes, Some(prev_encoding));
+                    let encoding = match (encoding, detected) {
+                        // Keep the file's byte order mark when reopening it with its own encoding.
+                        (Some(encoding), Ok(detected)) if encoding.name() == detected.name() => {
+                            detected
+                        }
+                        (Some(encoding), _) => encoding,
+                        (None, detected) => detected?,
+                    };
+                    anyhow::Ok((encoding.decode(&new_bytes), encoding))
+                })
+                .await?;
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
             this.update(&mut cx, |this, cx| {
+                this.encoding = new_encoding;
                 if this.version() == diff.base_version {
                     this.finalize_last_transaction();
                     this.apply_diff(diff, cx);
@@ -872,6 +936,7 @@ impl Buffer {
                 &self.saved_version,
                 self.file_fingerprint,
                 self.line_ending(),
+                self.encoding,
                 self.saved_mtime,
                 cx,
             );
@@ -1857,6 +1922,66 @@ impl Buffer {
         cx.notify();
     }
 
+    /// Serializes the buffer's operations and undo history, so that they can be
+    /// restored into a buffer created from the same base text.
+    pub fn serialize_history(&self) -> rpc::proto::BufferHistory {
+        let (undo_stack, redo_stack) = self.text.undo_history();
+        rpc::proto::BufferHistory {
+            base_text_fingerprint: proto::serialize_fingerprint(
+                self.text.base_text().fingerprint(),
+            ),
+            operations: self
+                .text
+                .operations()
+                .values()
+                .map(|operation| proto::serialize_operation(&Operation::Buffer(operation.clone())))
+                .collect(),
+            undo_stack: undo_stack
+                .iter()
+                .map(proto::serialize_transaction)
+                .collect(),
+            redo_stack: redo_stack
+                .iter()
+                .map(proto::serialize_transaction)
+                .collect(),
+        }
+    }
+
+    /// Replays a history serialized with [`Buffer::serialize_history`]. Fails if the
+    /// buffer wasn't created from the same base text, or was already edited.
+    pub fn restore_history(
+        &mut self,
+        history: rpc::proto::BufferHistory,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let base_text_fingerprint = proto::deserialize_fingerprint(&history.base_text_fingerprint)?;
+        if base_text_fingerprint != self.text.base_text().fingerprint() {
+            return Err(anyhow!("the buffer's base text has changed"));
+        }
+        if !self.text.operations().is_empty() {
+            return Err(anyhow!("the buffer has already been edited"));
+        }
+
+        let operations = history
+            .operations
+            .into_iter()
+            .map(proto::deserialize_operation)
+            .collect::<Result<Vec<_>>>()?;
+        let undo_stack = history
+            .undo_stack
+            .into_iter()
+            .map(proto::deserialize_transaction)
+            .collect::<Result<Vec<_>>>()?;
+        let redo_stack = history
+            .redo_stack
+            .into_iter()
+            .map(proto::deserialize_transaction)
+            .collect::<Result<Vec<_>>>()?;
+        self.apply_ops(operations, cx)?;
+        self.text.restore_undo_history(undo_stack, redo_stack);
+        Ok(())
+    }
+
     /// Applies the given remote operations to the buffer.
     pub fn apply_ops<I: IntoIterator<Item = Operation>>(
         &mut self,
@@ -2078,6 +2078,43 @@ fn test_serialization(cx: &mut gpui::AppContext) {
     assert_eq!(buffer2.read(cx).text(), "abcDF");
 }
 
+#[gpui::test]
+fn test_restore_history(cx: &mut gpui::AppContext) {
+    let buffer1 = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "abc");
+        buffer.edit([(3..3, "D")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.edit([(4..4, "E")], None, cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "abcD");
+        buffer
+    });
+    let history = buffer1.read(cx).serialize_history();
+
+    let buffer2 = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "abc");
+        buffer.restore_history(history.clone(), cx).unwrap();
+        assert_eq!(buffer.text(), "abcD");
+        assert!(buffer.is_dirty());
+
+        buffer.redo(cx);
+        assert_eq!(buffer.text(), "abcDE");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "abc");
+        buffer
+    });
+    assert_eq!(buffer2.read(cx).text(), "abc");
+
+    // Histories can't be restored into buffers with a different base text.
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "xyz");
+        assert!(buffer.restore_history(history, cx).is_err());
+        assert_eq!(buffer.text(), "xyz");
+        buffer
+    });
+}
+
 #[gpui::test(iterations = 100)]
 fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -13,6 +13,7 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+mod structural_query;
 mod syntax_map;
 mod task_context;
 
@@ -71,6 +72,7 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::{Outline, OutlineItem};
+pub use structural_query::{StructuralMatch, StructuralQuery};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer};
 pub use text::LineEnding;
 pub use tree_sitter::{Parser, Tree};
@@ -1364,6 +1366,11 @@ impl Grammar {
         self.id
     }
 
+    /// Parses text that isn't part of a buffer, e.g. the contents of a file that isn't open.
+    pub fn parse_str(&self, text: &str) -> Tree {
+        self.parse_text(&Rope::from(text), None)
+    }
+
     fn parse_text(&self, text: &Rope, old_tree: Option<Tree>) -> Tree {
         PARSER.with(|parser| {
             let mut parser = parser.borrow_mut();
@@ -10,7 +10,7 @@ use schemars::{
     JsonSchema,
 };
 use serde::{Deserialize, Serialize};
-use settings::{Settings, SettingsLocation};
+use settings::{EditorconfigProperties, IndentStyle, Settings, SettingsLocation, SettingsStore};
 use std::{num::NonZeroU32, path::Path, sync::Arc};
 
 impl<'a> Into<SettingsLocation<'a>> for &'a dyn File {
@@ -28,13 +28,29 @@ pub fn init(cx: &mut AppContext) {
 }
 
 /// Returns the settings for the specified language from the provided file.
+///
+/// The properties of the `.editorconfig` files applying to the file take
+/// precedence over both the user's and the folder-specific settings.
 pub fn language_settings<'a>(
     language: Option<&Arc<Language>>,
     file: Option<&Arc<dyn File>>,
     cx: &'a AppContext,
 ) -> &'a LanguageSettings {
     let language_name = language.map(|l| l.name());
-    all_language_settings(file, cx).language(language_name.as_deref())
+    let settings = all_language_settings(file, cx).language(language_name.as_deref());
+    file.and_then(|file| {
+        cx.global::<SettingsStore>().editorconfig_value(
+            file.worktree_id(),
+            file.path(),
+            language_name,
+            |properties| {
+                let mut settings = settings.clone();
+                settings.apply_editorconfig(properties);
+                settings
+            },
+        )
+    })
+    .unwrap_or(settings)
 }
 
 /// Returns the settings for all languages from the provided file.
@@ -76,6 +92,11 @@ pub struct LanguageSettings {
     pub show_wrap_guides: bool,
     /// Character counts at which to show wrap guides in the editor.
     pub wrap_guides: Vec<usize>,
+    /// Indent guide related settings.
+    pub indent_guides: IndentGuideSettings,
+    /// Whether to color bracket pairs by their nesting depth, cycling through
+    /// the theme's accent colors.
+    pub colorize_brackets: bool,
     /// Whether or not to perform a buffer format before saving.
     pub format_on_save: FormatOnSave,
     /// Whether or not to remove any trailing whitespace from lines of a buffer
@@ -109,6 +130,26 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
 }
 
+impl LanguageSettings {
+    fn apply_editorconfig(&mut self, properties: &EditorconfigProperties) {
+        if let Some(indent_style) = properties.indent_style {
+            self.hard_tabs = indent_style == IndentStyle::Tab;
+        }
+        if let Some(tab_size) = properties.tab_size() {
+            self.tab_size = tab_size;
+        }
+        if let Some(max_line_length) = properties.max_line_length {
+            self.preferred_line_length = max_line_length;
+        }
+        if let Some(trim_trailing_whitespace) = properties.trim_trailing_whitespace {
+            self.remove_trailing_whitespace_on_save = trim_trailing_whitespace;
+        }
+        if let Some(insert_final_newline) = properties.insert_final_newline {
+            self.ensure_final_newline_on_save = insert_final_newline;
+        }
+    }
+}
+
 /// The settings for [GitHub Copilot](https://github.com/features/copilot).
 #[derive(Clone, Debug, Default)]
 pub struct CopilotSettings {
@@ -177,6 +218,15 @@ pub struct LanguageSettingsContent {
     /// Default: []
     #[serde(default)]
     pub wrap_guides: Option<Vec<usize>>,
+    /// Indent guide related settings.
+    #[serde(default)]
+    pub indent_guides: Option<IndentGuideSettings>,
+    /// Whether to color bracket pairs by their nesting depth, cycling through
+    /// the theme's accent colors.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub colorize_brackets: Option<bool>,
     /// Whether or not to perform a buffer format before saving.
     ///
     /// Default: on
@@ -327,6 +377,32 @@ pub enum Formatter {
     },
 }
 
+/// The settings for indent guides.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct IndentGuideSettings {
+    /// Whether to show indent guides in the editor.
+    ///
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How to color the indent guides.
+    ///
+    /// Default: fixed
+    #[serde(default)]
+    pub coloring: IndentGuideColoring,
+}
+
+/// Controls how indent guides are colored.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IndentGuideColoring {
+    /// Use the theme's indent guide colors for every indent level.
+    #[default]
+    Fixed,
+    /// Cycle through the theme's accent colors by indent level.
+    Rainbow,
+}
+
 /// The settings for inlay hints.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct InlayHintSettings {
@@ -617,6 +693,8 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     );
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
+    merge(&mut settings.indent_guides, src.indent_guides);
+    merge(&mut settings.colorize_brackets, src.colorize_brackets);
     merge(
         &mut settings.code_actions_on_format,
         src.code_actions_on_format.clone(),
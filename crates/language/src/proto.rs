@@ -40,6 +40,21 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Serializes a [`text::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: text::Encoding) -> proto::Encoding {
+    proto::Encoding {
+        name: encoding.name().to_string(),
+        has_bom: encoding.has_bom(),
+    }
+}
+
+/// Deserializes a [`text::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(message: &proto::Encoding) -> Result<text::Encoding> {
+    text::Encoding::for_label(&message.name)
+        .map(|encoding| encoding.with_bom(message.has_bom))
+        .ok_or_else(|| anyhow!("unknown encoding {:?}", message.name))
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
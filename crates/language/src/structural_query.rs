@@ -0,0 +1,610 @@
+//! Structural search: matching code patterns against the syntax trees of a buffer.
+//!
+//! A [`StructuralQuery`] is either a code pattern or a raw Tree-sitter query. Patterns are
+//! written in the language being searched, with metavariables standing in for parts of the code:
+//! `$NAME` matches a single syntax node, `$$$NAME` matches any number of sibling nodes, and `$_`
+//! and `$$$` do the same without capturing anything. A metavariable used twice must match the
+//! same text both times. Queries that start with `(` and contain a capture are compiled as raw
+//! Tree-sitter queries, and match the `@match` capture, or all of their captures otherwise.
+//!
+//! Replacement templates refer to metavariables (or query captures) by the same `$NAME` syntax.
+
+use crate::{
+    syntax_map::{QueryCursorHandle, TextProvider},
+    BufferSnapshot, Grammar, GrammarId, PARSER,
+};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use std::{cmp::Reverse, fmt, ops::Range, sync::Arc};
+use text::Rope;
+use tree_sitter::{Node, Query, Tree};
+
+/// Prefix of the identifiers that stand in for `$NAME` metavariables when parsing a pattern.
+const SINGLE_PLACEHOLDER_PREFIX: &str = "zed_meta_";
+/// Prefix of the identifiers that stand in for `$$$NAME` metavariables when parsing a pattern.
+const MULTIPLE_PLACEHOLDER_PREFIX: &str = "zed_metas_";
+
+lazy_static! {
+    static ref METAVARIABLE_REGEX: Regex =
+        Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").unwrap();
+    static ref TEMPLATE_VARIABLE_REGEX: Regex =
+        Regex::new(r"\$\$\$([A-Za-z_][A-Za-z0-9_]*)|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    static ref WORD_REGEX: Regex = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+}
+
+/// A code pattern or raw Tree-sitter query, compiled lazily for each grammar it is matched
+/// against.
+pub struct StructuralQuery {
+    source: String,
+    pattern: String,
+    is_raw: bool,
+    literals: Vec<String>,
+    compiled: Mutex<HashMap<GrammarId, Option<Arc<CompiledQuery>>>>,
+}
+
+/// A range of a buffer matched by a [`StructuralQuery`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    /// The matched range.
+    pub range: Range<usize>,
+    /// The ranges matched by each named metavariable or capture, in the order they were bound.
+    pub captures: Vec<(String, Range<usize>)>,
+}
+
+enum CompiledQuery {
+    Pattern {
+        tree: Tree,
+        /// The child indices leading from the root of the tree to the node the pattern is made of.
+        root_path: Vec<usize>,
+    },
+    Raw(Query),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Metavariable<'a> {
+    Single(Option<&'a str>),
+    Multiple(Option<&'a str>),
+}
+
+impl StructuralQuery {
+    pub fn new(source: impl ToString) -> Result<Self> {
+        let source = source.to_string();
+        let trimmed = source.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow!("structural query is empty"));
+        }
+
+        let is_raw = trimmed.starts_with('(') && trimmed.contains('@');
+        let (pattern, literals) = if is_raw {
+            (trimmed.to_string(), Vec::new())
+        } else {
+            let code = METAVARIABLE_REGEX.replace_all(trimmed, " ");
+            if code.trim().is_empty() {
+                return Err(anyhow!(
+                    "structural pattern must contain code besides metavariables"
+                ));
+            }
+            let mut literals = WORD_REGEX
+                .find_iter(&code)
+                .map(|word| word.as_str().to_string())
+                .collect::<Vec<_>>();
+            literals.sort();
+            literals.dedup();
+
+            let pattern = METAVARIABLE_REGEX.replace_all(trimmed, |captures: &Captures| {
+                match (captures.get(1), captures.get(2)) {
+                    (_, Some(name)) => format!("{SINGLE_PLACEHOLDER_PREFIX}{}", name.as_str()),
+                    (name, None) => format!(
+                        "{MULTIPLE_PLACEHOLDER_PREFIX}{}",
+                        name.map_or("", |name| name.as_str())
+                    ),
+                }
+            });
+            (pattern.into_owned(), literals)
+        };
+
+        Ok(Self {
+            source,
+            pattern,
+            is_raw,
+            literals,
+            compiled: Default::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether this query is a raw Tree-sitter query rather than a code pattern.
+    pub fn is_raw(&self) -> bool {
+        self.is_raw
+    }
+
+    /// The words that occur verbatim in every match of this query, for cheaply ruling out
+    /// files that can't contain one.
+    pub fn literals(&self) -> &[String] {
+        &self.literals
+    }
+
+    /// Returns the matches of this query that lie within the given range of the buffer,
+    /// ordered by their start.
+    ///
+    /// Nested matches are not reported: once a node matches, its descendants are skipped.
+    pub fn matches(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        let mut layers = buffer.syntax_layers().peekable();
+        if layers.peek().is_none() {
+            // The buffer hasn't been parsed yet, for example because its language was just
+            // assigned, so parse it here.
+            if let Some(grammar) = buffer
+                .language_at(0)
+                .and_then(|language| language.grammar())
+            {
+                let tree = parse(grammar, buffer.as_rope());
+                self.push_matches(grammar, tree.root_node(), buffer, &range, &mut matches);
+            }
+        }
+        for layer in layers {
+            if let Some(grammar) = layer.language.grammar() {
+                self.push_matches(grammar, layer.node(), buffer, &range, &mut matches);
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, Reverse(mat.range.end)));
+        matches.dedup_by(|a, b| a.range == b.range);
+        matches
+    }
+
+    /// Expands the replacement template for the match spanning exactly the given range, if any.
+    pub fn replacement_for(
+        &self,
+        template: &str,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        self.matches(buffer, range.clone())
+            .into_iter()
+            .find(|mat| mat.range == range)
+            .map(|mat| mat.expand(template, buffer))
+    }
+
+    fn compiled(&self, grammar: &Grammar) -> Option<Arc<CompiledQuery>> {
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| self.compile(grammar).map(Arc::new))
+            .clone()
+    }
+
+    /// Compiles the query for the given grammar, returning `None` if it isn't valid there.
+    fn compile(&self, grammar: &Grammar) -> Option<CompiledQuery> {
+        if self.is_raw {
+            return Query::new(&grammar.ts_language, &self.pattern)
+                .ok()
+                .map(CompiledQuery::Raw);
+        }
+
+        let tree = parse(grammar, &Rope::from(self.pattern.as_str()));
+        if contains_error(tree.root_node()) {
+            return None;
+        }
+
+        // Descend to the innermost node spanning the whole pattern, so that a pattern like
+        // `$X.unwrap()` matches call expressions rather than whole statements or files.
+        let full_range = 0..self.pattern.len();
+        let mut root_path = Vec::new();
+        let mut node = tree.root_node();
+        loop {
+            let mut cursor = node.walk();
+            let child = node
+                .children(&mut cursor)
+                .enumerate()
+                .find(|(_, child)| !child.is_extra() && child.byte_range() == full_range);
+            match child {
+                Some((ix, child)) => {
+                    root_path.push(ix);
+                    node = child;
+                }
+                None => break,
+            }
+        }
+        if metavariable(&self.pattern[node.byte_range()]).is_some() {
+            return None;
+        }
+
+        Some(CompiledQuery::Pattern { tree, root_path })
+    }
+
+    fn push_matches(
+        &self,
+        grammar: &Grammar,
+        root: Node,
+        buffer: &BufferSnapshot,
+        range: &Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        let Some(compiled) = self.compiled(grammar) else {
+            return;
+        };
+
+        match compiled.as_ref() {
+            CompiledQuery::Raw(query) => {
+                let mut query_cursor = QueryCursorHandle::new();
+                query_cursor.set_byte_range(range.clone());
+                for mat in query_cursor.matches(query, root, TextProvider(buffer.as_rope())) {
+                    let mut match_range = None;
+                    let mut union: Option<Range<usize>> = None;
+                    let mut captures = Vec::new();
+                    for capture in mat.captures {
+                        let name = query.capture_names()[capture.index as usize].to_string();
+                        let capture_range = capture.node.byte_range();
+                        union = Some(match union {
+                            Some(union) => {
+                                union.start.min(capture_range.start)
+                                    ..union.end.max(capture_range.end)
+                            }
+                            None => capture_range.clone(),
+                        });
+                        if name == "match" {
+                            match_range = Some(capture_range.clone());
+                        }
+                        captures.push((name, capture_range));
+                    }
+
+                    if let Some(match_range) = match_range.or(union) {
+                        if match_range.start >= range.start && match_range.end <= range.end {
+                            matches.push(StructuralMatch {
+                                range: match_range,
+                                captures,
+                            });
+                        }
+                    }
+                }
+            }
+            CompiledQuery::Pattern { tree, root_path } => {
+                let pattern_root = root_path
+                    .iter()
+                    .fold(tree.root_node(), |node, ix| node.child(*ix).unwrap());
+                let mut cursor = root.walk();
+                loop {
+                    let node = cursor.node();
+                    let node_range = node.byte_range();
+                    let mut descend =
+                        node_range.start <= range.end && node_range.end >= range.start;
+                    if descend
+                        && node_range.start >= range.start
+                        && node_range.end <= range.end
+                        && node.kind_id() == pattern_root.kind_id()
+                    {
+                        let mut matcher = Matcher {
+                            pattern: &self.pattern,
+                            buffer,
+                            captures: Vec::new(),
+                        };
+                        if matcher.match_node(pattern_root, node) {
+                            matches.push(StructuralMatch {
+                                range: node_range,
+                                captures: matcher.captures,
+                            });
+                            descend = false;
+                        }
+                    }
+
+                    if descend && cursor.goto_first_child() {
+                        continue;
+                    }
+                    while !cursor.goto_next_sibling() {
+                        if !cursor.goto_parent() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for StructuralQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralQuery")
+            .field("source", &self.source)
+            .field("is_raw", &self.is_raw)
+            .finish()
+    }
+}
+
+impl StructuralMatch {
+    /// Expands a replacement template, substituting the text captured by each `$NAME` or
+    /// `$$$NAME` it refers to. Names that weren't captured are left as they are.
+    pub fn expand(&self, template: &str, buffer: &BufferSnapshot) -> String {
+        TEMPLATE_VARIABLE_REGEX
+            .replace_all(template, |captures: &Captures| {
+                let name = captures
+                    .get(1)
+                    .or_else(|| captures.get(2))
+                    .map_or("", |name| name.as_str());
+                match self.captures.iter().find(|(captured, _)| captured == name) {
+                    Some((_, range)) => buffer.text_for_range(range.clone()).collect::<String>(),
+                    None => captures[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+}
+
+struct Matcher<'a> {
+    pattern: &'a str,
+    buffer: &'a BufferSnapshot,
+    captures: Vec<(String, Range<usize>)>,
+}
+
+impl<'a> Matcher<'a> {
+    fn match_node(&mut self, pattern: Node, candidate: Node) -> bool {
+        match metavariable(self.pattern_text(pattern)) {
+            Some(Metavariable::Single(name)) => {
+                return candidate.is_named() && self.bind(name, candidate.byte_range());
+            }
+            Some(Metavariable::Multiple(name)) => {
+                return self.bind(name, candidate.byte_range());
+            }
+            None => {}
+        }
+
+        if pattern.kind_id() != candidate.kind_id() {
+            return false;
+        }
+        if pattern.child_count() == 0 {
+            return self.text_eq(candidate.byte_range(), self.pattern_text(pattern));
+        }
+
+        let patterns = significant_children(pattern);
+        let candidates = significant_children(candidate);
+        self.match_sequence(&patterns, &candidates, candidate.end_byte())
+    }
+
+    /// Matches a sequence of pattern siblings against a sequence of candidate siblings,
+    /// backtracking over the number of nodes each `$$$` metavariable consumes.
+    fn match_sequence(&mut self, patterns: &[Node], candidates: &[Node], end: usize) -> bool {
+        let Some((pattern, patterns)) = patterns.split_first() else {
+            return candidates.is_empty();
+        };
+
+        let captures_len = self.captures.len();
+        if let Some(Metavariable::Multiple(name)) = metavariable(self.pattern_text(*pattern)) {
+            let start = candidates
+                .first()
+                .map_or(end, |candidate| candidate.start_byte());
+            for count in 0..=candidates.len() {
+                let range = match count.checked_sub(1) {
+                    Some(last) => start..candidates[last].end_byte(),
+                    None => start..start,
+                };
+                if self.bind(name, range)
+                    && self.match_sequence(patterns, &candidates[count..], end)
+                {
+                    return true;
+                }
+                self.captures.truncate(captures_len);
+            }
+            return false;
+        }
+
+        let Some((candidate, candidates)) = candidates.split_first() else {
+            return false;
+        };
+        if self.match_node(*pattern, *candidate) && self.match_sequence(patterns, candidates, end) {
+            return true;
+        }
+        self.captures.truncate(captures_len);
+        false
+    }
+
+    fn bind(&mut self, name: Option<&str>, range: Range<usize>) -> bool {
+        let Some(name) = name else {
+            return true;
+        };
+        if let Some((_, bound)) = self.captures.iter().find(|(captured, _)| captured == name) {
+            let bound = self.buffer_text(bound.clone());
+            return self.text_eq(range, &bound);
+        }
+        self.captures.push((name.to_string(), range));
+        true
+    }
+
+    fn pattern_text(&self, node: Node) -> &'a str {
+        &self.pattern[node.byte_range()]
+    }
+
+    fn buffer_text(&self, range: Range<usize>) -> String {
+        self.buffer.text_for_range(range).collect()
+    }
+
+    fn text_eq(&self, range: Range<usize>, text: &str) -> bool {
+        range.len() == text.len() && self.buffer_text(range) == text
+    }
+}
+
+/// Returns the metavariable that a pattern node consists of, if any.
+fn metavariable(text: &str) -> Option<Metavariable> {
+    fn is_name(name: &str) -> bool {
+        name.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    }
+
+    if let Some(name) = text.strip_prefix(MULTIPLE_PLACEHOLDER_PREFIX) {
+        is_name(name).then_some(Metavariable::Multiple((!name.is_empty()).then_some(name)))
+    } else if let Some(name) = text.strip_prefix(SINGLE_PLACEHOLDER_PREFIX) {
+        (!name.is_empty() && is_name(name))
+            .then_some(Metavariable::Single((name != "_").then_some(name)))
+    } else {
+        None
+    }
+}
+
+/// The children of a node that take part in matching, leaving out comments and the tokens
+/// Tree-sitter inserted to recover from errors.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn contains_error(node: Node) -> bool {
+    if !node.has_error() {
+        return false;
+    }
+    if node.is_error() {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<_>>();
+    children.into_iter().any(contains_error)
+}
+
+fn parse(grammar: &Grammar, text: &Rope) -> Tree {
+    PARSER.with(|parser| {
+        let mut parser = parser.borrow_mut();
+        parser.set_included_ranges(&[]).expect("overlapping ranges");
+        parser
+            .set_language(&grammar.ts_language)
+            .expect("incompatible grammar");
+        let mut chunks = text.chunks_in_range(0..text.len());
+        parser
+            .parse_with(
+                &mut move |offset, _| {
+                    chunks.seek(offset);
+                    chunks.next().unwrap_or("").as_bytes()
+                },
+                None,
+            )
+            .expect("invalid language")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, Language, LanguageConfig, LanguageMatcher};
+    use gpui::{AppContext, Context};
+    use text::BufferId;
+    use unindent::Unindent as _;
+
+    #[gpui::test]
+    fn test_structural_patterns(cx: &mut AppContext) {
+        let text = r#"
+            fn main() {
+                let a = foo().unwrap();
+                let b = self.bar(1, 2).unwrap();
+                let c = baz.unwrap_or(3);
+                // qux.unwrap()
+                same(a, a);
+                same(a, b);
+            }
+        "#
+        .unindent();
+        let snapshot = rust_buffer(&text, cx);
+
+        let query = StructuralQuery::new("$X.unwrap()").unwrap();
+        assert_eq!(query.literals(), &["unwrap".to_string()]);
+        let matches = query.matches(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            matched_texts(&matches, &snapshot),
+            &["foo().unwrap()", "self.bar(1, 2).unwrap()"]
+        );
+        assert_eq!(
+            matches
+                .iter()
+                .map(|mat| mat.expand("$X?", &snapshot))
+                .collect::<Vec<_>>(),
+            &["foo()?", "self.bar(1, 2)?"]
+        );
+        let second_range = matches[1].range.clone();
+        assert_eq!(
+            query.replacement_for("$X?", &snapshot, second_range.clone()),
+            Some("self.bar(1, 2)?".to_string())
+        );
+        assert_eq!(
+            query.replacement_for("$X?", &snapshot, second_range.start + 1..second_range.end),
+            None
+        );
+
+        // Multiple metavariables match any number of siblings.
+        let query = StructuralQuery::new("$RECEIVER.bar($$$ARGS)").unwrap();
+        let matches = query.matches(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            matches[0].expand("bar($RECEIVER, $$$ARGS)", &snapshot),
+            "bar(self, 1, 2)"
+        );
+
+        // A metavariable used twice has to match the same text.
+        let query = StructuralQuery::new("same($A, $A)").unwrap();
+        let matches = query.matches(&snapshot, 0..snapshot.len());
+        assert_eq!(matched_texts(&matches, &snapshot), &["same(a, a)"]);
+
+        // Matches are limited to the given range.
+        let query = StructuralQuery::new("$X.unwrap()").unwrap();
+        let start = text.find("let b").unwrap();
+        let matches = query.matches(&snapshot, start..snapshot.len());
+        assert_eq!(
+            matched_texts(&matches, &snapshot),
+            &["self.bar(1, 2).unwrap()"]
+        );
+
+        assert!(StructuralQuery::new("$X").is_err());
+        assert!(StructuralQuery::new("  ").is_err());
+    }
+
+    #[gpui::test]
+    fn test_raw_structural_queries(cx: &mut AppContext) {
+        let text = r#"
+            fn one() {}
+            fn two(x: u32) {}
+        "#
+        .unindent();
+        let snapshot = rust_buffer(&text, cx);
+
+        let query = StructuralQuery::new(
+            "(function_item name: (identifier) @name parameters: (parameters (_))) @match",
+        )
+        .unwrap();
+        assert!(query.is_raw());
+        let matches = query.matches(&snapshot, 0..snapshot.len());
+        assert_eq!(matched_texts(&matches, &snapshot), &["fn two(x: u32) {}"]);
+        assert_eq!(matches[0].expand("fn $name() {}", &snapshot), "fn two() {}");
+
+        // Queries that aren't valid for a grammar don't match anything.
+        let query = StructuralQuery::new("(no_such_node) @match").unwrap();
+        assert!(query.matches(&snapshot, 0..snapshot.len()).is_empty());
+    }
+
+    fn rust_buffer(text: &str, cx: &mut AppContext) -> BufferSnapshot {
+        let language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::language()),
+        ));
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+                .with_language(language, cx)
+        });
+        buffer.read(cx).snapshot()
+    }
+
+    fn matched_texts(matches: &[StructuralMatch], buffer: &BufferSnapshot) -> Vec<String> {
+        matches
+            .iter()
+            .map(|mat| buffer.text_for_range(mat.range.clone()).collect())
+            .collect()
+    }
+}
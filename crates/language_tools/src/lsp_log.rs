@@ -696,6 +696,7 @@ impl SearchableItem for LspLogView {
             regex: true,
             // LSP log is read-only.
             replacement: false,
+            structural: false,
         }
     }
     fn active_match_index(
@@ -0,0 +1,241 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, View,
+    ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::NotifyTaskExt, ModalView, SavedLayout, Workspace};
+
+actions!(layout_selector, [Toggle]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(LayoutSelector::register).detach();
+}
+
+pub struct LayoutSelector {
+    picker: View<Picker<LayoutSelectorDelegate>>,
+}
+
+impl LayoutSelector {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &Toggle, cx| {
+            let Some(layouts) = workspace.layouts(cx).log_err() else {
+                return;
+            };
+            let handle = cx.view().downgrade();
+            workspace.toggle_modal(cx, move |cx| LayoutSelector::new(handle, layouts, cx));
+        });
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        layouts: Vec<SavedLayout>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = LayoutSelectorDelegate::new(cx.view().downgrade(), workspace, layouts);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for LayoutSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for LayoutSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LayoutSelector {}
+impl ModalView for LayoutSelector {}
+
+pub struct LayoutSelectorDelegate {
+    layout_selector: WeakView<LayoutSelector>,
+    workspace: WeakView<Workspace>,
+    layouts: Vec<SavedLayout>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+}
+
+impl LayoutSelectorDelegate {
+    fn new(
+        layout_selector: WeakView<LayoutSelector>,
+        workspace: WeakView<Workspace>,
+        layouts: Vec<SavedLayout>,
+    ) -> Self {
+        Self {
+            layout_selector,
+            workspace,
+            layouts,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+        }
+    }
+
+    /// Saves the current layout of the workspace under the query, for the workspace only.
+    fn save_layout(&mut self, include_items: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let name = self.query.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .save_layout(name, false, include_items, cx)
+                    .detach_and_notify_err(cx);
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+}
+
+impl PickerDelegate for LayoutSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Switch to a layout, or name the current one...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        // Confirming a name that matches no layout saves the current one under it, with the open
+        // items when secondary.
+        let Some(mat) = self.matches.get(self.selected_index).filter(|_| !secondary) else {
+            self.save_layout(secondary, cx);
+            return;
+        };
+        let name = self.layouts[mat.candidate_id].name.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.apply_layout(&name, cx).detach_and_notify_err(cx);
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.layout_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .layouts
+            .iter()
+            .enumerate()
+            .map(|(id, layout)| StringMatchCandidate::new(id, layout.name.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.query = query;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let layout = &self.layouts[mat.candidate_id];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    layout.name.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot::<Label>(
+                    layout
+                        .global
+                        .then(|| Label::new("global").color(Color::Muted)),
+                ),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.query.trim().is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .child(
+                    Button::new("save-layout", "Save Layout").on_click(
+                        cx.listener(|picker, _, cx| picker.delegate.save_layout(false, cx)),
+                    ),
+                )
+                .child(
+                    Button::new("save-layout-with-items", "Save with Open Items")
+                        .style(ButtonStyle::Filled)
+                        .on_click(
+                            cx.listener(|picker, _, cx| picker.delegate.save_layout(true, cx)),
+                        ),
+                )
+                .into_any_element(),
+        )
+    }
+}
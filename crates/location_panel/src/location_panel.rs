@@ -0,0 +1,407 @@
+mod location_panel_settings;
+
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use fs::Fs;
+use gpui::{
+    actions, uniform_list, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, Model, Pixels, Render, Subscription, Task, UniformListScrollHandle, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+pub use location_panel_settings::LocationPanelSettings;
+use serde_derive::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    location_list::{self, LocationEntry, LocationLists, LocationListsEvent},
+    Workspace,
+};
+
+const LOCATION_PANEL_KEY: &str = "LocationPanel";
+const EMPTY_MESSAGE: &str =
+    "Find references, search, or send diagnostics or terminal output here to list their locations.";
+
+actions!(location_panel, [ToggleFocus]);
+
+pub fn init(cx: &mut AppContext) {
+    LocationPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<LocationPanel>(cx);
+            })
+            .register_action(|workspace, _: &location_list::Next, cx| {
+                let entry = workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.select_next(cx));
+                if let Some(entry) = entry {
+                    open_location(workspace, &entry, cx);
+                }
+            })
+            .register_action(|workspace, _: &location_list::Prev, cx| {
+                let entry = workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.select_prev(cx));
+                if let Some(entry) = entry {
+                    open_location(workspace, &entry, cx);
+                }
+            })
+            .register_action(|workspace, action: &location_list::GoTo, cx| {
+                let entry = workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| match action.index {
+                        Some(ix) => lists.select(ix, cx),
+                        None => lists.select_current(cx),
+                    });
+                if let Some(entry) = entry {
+                    open_location(workspace, &entry, cx);
+                }
+            })
+            .register_action(|workspace, _: &location_list::Older, cx| {
+                workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.activate_older(cx));
+            })
+            .register_action(|workspace, _: &location_list::Newer, cx| {
+                workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.activate_newer(cx));
+            });
+    })
+    .detach();
+}
+
+/// Opens the buffer of an entry in the active pane, with the cursor at the start of its range.
+/// Buffers closed since the entry was produced are reopened.
+fn open_location(
+    workspace: &mut Workspace,
+    entry: &LocationEntry,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let open_buffer = entry.open(workspace.project(), cx);
+    let pane = workspace.active_pane().downgrade();
+    cx.spawn(|workspace, mut cx| async move {
+        let (buffer, position) = open_buffer.await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            workspace
+                .location_lists()
+                .update(cx, |lists, cx| lists.reattach_buffer(&buffer, cx));
+            let pane = pane
+                .upgrade()
+                .unwrap_or_else(|| workspace.active_pane().clone());
+            let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([position..position])
+                });
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+pub struct LocationPanel {
+    workspace: WeakView<Workspace>,
+    location_lists: Model<LocationLists>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedLocationPanel {
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+}
+
+impl LocationPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let location_lists = workspace.location_lists().clone();
+        let workspace_handle = workspace.weak_handle();
+
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let subscriptions = vec![
+                cx.observe(&location_lists, |_, _, cx| cx.notify()),
+                cx.subscribe(&location_lists, |this, _, event, _| match event {
+                    LocationListsEvent::ListAdded => this.scroll_handle.scroll_to_item(0),
+                    LocationListsEvent::EntrySelected(ix) => this.scroll_handle.scroll_to_item(*ix),
+                }),
+            ];
+            Self {
+                workspace: workspace_handle,
+                location_lists,
+                fs,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                height: None,
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(LOCATION_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedLocationPanel>(&panel)?)
+            } else {
+                None
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let panel = Self::new(workspace, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        panel.height = serialized_panel.height.map(|h| h.round());
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let height = self.height;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        LOCATION_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedLocationPanel { width, height })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn open_entry(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self
+            .location_lists
+            .update(cx, |lists, cx| lists.select(ix, cx))
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| open_location(workspace, &entry, cx))
+            .log_err();
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let lists = self.location_lists.read(cx);
+        let active_index = lists.active_index();
+        let has_older = active_index > 0;
+        let has_newer = active_index + 1 < lists.lists().len();
+        let (title, position) = match lists.active_list() {
+            Some(list) => (
+                list.title.clone(),
+                format!(
+                    "{}/{}",
+                    list.selected.map_or(0, |ix| ix + 1),
+                    list.entries.len()
+                ),
+            ),
+            None => ("Locations".into(), String::new()),
+        };
+
+        h_flex()
+            .justify_between()
+            .px_2()
+            .py_1()
+            // Match the height of the tab bar so they line up.
+            .h(rems(ui::Tab::CONTAINER_HEIGHT_IN_REMS))
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(title))
+                    .child(Label::new(position).color(Color::Muted)),
+            )
+            .child(
+                h_flex()
+                    .child(
+                        IconButton::new("older-list", IconName::ChevronLeft)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_older)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Older List", &location_list::Older, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.location_lists
+                                    .update(cx, |lists, cx| lists.activate_older(cx));
+                            })),
+                    )
+                    .child(
+                        IconButton::new("newer-list", IconName::ChevronRight)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_newer)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Newer List", &location_list::Newer, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.location_lists
+                                    .update(cx, |lists, cx| lists.activate_newer(cx));
+                            })),
+                    ),
+            )
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut ViewContext<Self>) -> Option<ListItem> {
+        let lists = self.location_lists.read(cx);
+        let list = lists.active_list()?;
+        let entry = list.entries.get(ix)?;
+        let point = entry.position(cx);
+        let path = entry
+            .path(cx)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+
+        Some(
+            ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(list.selected == Some(ix))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(format!(
+                            "{path}:{}:{}",
+                            point.row + 1,
+                            point.column + 1
+                        )))
+                        .child(Label::new(entry.label(cx)).color(Color::Muted)),
+                )
+                .on_click(cx.listener(move |this, _, cx| this.open_entry(ix, cx))),
+        )
+    }
+}
+
+impl Render for LocationPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let entry_count = self
+            .location_lists
+            .read(cx)
+            .active_list()
+            .map_or(0, |list| list.entries.len());
+
+        v_flex()
+            .key_context("LocationPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(self.render_header(cx))
+            .map(|this| {
+                if entry_count == 0 {
+                    this.child(
+                        v_flex()
+                            .p_4()
+                            .child(Label::new(EMPTY_MESSAGE).color(Color::Muted)),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "entries",
+                            entry_count,
+                            |this, range, cx| {
+                                range.filter_map(|ix| this.render_entry(ix, cx)).collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+impl FocusableView for LocationPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for LocationPanel {}
+
+impl Panel for LocationPanel {
+    fn persistent_name() -> &'static str {
+        "LocationPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        LocationPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<LocationPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = LocationPanelSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or_else(|| settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or_else(|| settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        LocationPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Menu)
+    }
+
+    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
+        Some("Location Panel")
+    }
+
+    fn icon_label(&self, cx: &WindowContext) -> Option<String> {
+        let count = self.location_lists.read(cx).active_list()?.entries.len();
+        (count > 0).then(|| count.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+}
@@ -75,6 +75,9 @@ pub enum Event {
     ExcerptsRemoved {
         ids: Vec<ExcerptId>,
     },
+    ExcerptsExpanded {
+        ids: Vec<ExcerptId>,
+    },
     ExcerptsEdited {
         ids: Vec<ExcerptId>,
     },
@@ -218,6 +221,24 @@ pub struct ExcerptRange<T> {
     pub primary: Option<Range<T>>,
 }
 
+/// The direction in which [`MultiBuffer::expand_excerpts`] grows the context of excerpts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExpandExcerptDirection {
+    Up,
+    Down,
+    UpAndDown,
+}
+
+impl ExpandExcerptDirection {
+    pub fn should_expand_up(&self) -> bool {
+        matches!(self, Self::Up | Self::UpAndDown)
+    }
+
+    pub fn should_expand_down(&self) -> bool {
+        matches!(self, Self::Down | Self::UpAndDown)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct ExcerptSummary {
     excerpt_id: ExcerptId,
@@ -1397,6 +1418,112 @@ impl MultiBuffer {
         cx.notify();
     }
 
+    /// Grows the context of the given excerpts by `line_count` lines in the given direction,
+    /// without going past the start or the end of their buffers.
+    pub fn expand_excerpts(
+        &mut self,
+        excerpt_ids: impl IntoIterator<Item = ExcerptId>,
+        line_count: u32,
+        direction: ExpandExcerptDirection,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if line_count == 0 {
+            return;
+        }
+        self.sync(cx);
+
+        let mut snapshot = self.snapshot.borrow_mut();
+        let mut excerpt_ids = excerpt_ids.into_iter().collect::<Vec<_>>();
+        excerpt_ids.sort_unstable_by(|a, b| a.cmp(b, &snapshot));
+        excerpt_ids.dedup();
+        if excerpt_ids.is_empty() {
+            return;
+        }
+
+        let mut new_excerpts = SumTree::new();
+        let mut cursor = snapshot.excerpts.cursor::<(Option<&Locator>, usize)>();
+        let mut edits = Vec::<Edit<usize>>::new();
+        let mut expanded_ids = Vec::new();
+        let mut changed_trailing_excerpt = false;
+
+        for excerpt_id in excerpt_ids {
+            let locator = snapshot.excerpt_locator_for_id(excerpt_id);
+            new_excerpts.append(cursor.slice(&Some(locator), Bias::Left, &()), &());
+            let Some(excerpt) = cursor.item() else {
+                continue;
+            };
+            if excerpt.id != excerpt_id {
+                continue;
+            }
+
+            let buffer = &excerpt.buffer;
+            let max_point = buffer.max_point();
+            let mut context = excerpt.range.context.start.to_point(buffer)
+                ..excerpt.range.context.end.to_point(buffer);
+            if direction.should_expand_up() {
+                context.start = Point::new(context.start.row.saturating_sub(line_count), 0);
+            }
+            if direction.should_expand_down() {
+                let end_row = context.end.row + line_count;
+                context.end = if end_row > max_point.row {
+                    max_point
+                } else if context.end.column == 0 {
+                    Point::new(end_row, 0)
+                } else {
+                    Point::new(end_row, buffer.line_len(end_row))
+                };
+            }
+
+            let range = ExcerptRange {
+                context: buffer.anchor_before(context.start)..buffer.anchor_after(context.end),
+                primary: excerpt.range.primary.clone(),
+            };
+            let new_excerpt = Excerpt::new(
+                excerpt.id,
+                excerpt.locator.clone(),
+                excerpt.buffer_id,
+                buffer.clone(),
+                range,
+                excerpt.has_trailing_newline,
+            );
+            if new_excerpt.text_summary.len == excerpt.text_summary.len {
+                new_excerpts.push(excerpt.clone(), &());
+                cursor.next(&());
+                continue;
+            }
+
+            let old_start = cursor.start().1;
+            let old_end = old_start + excerpt.text_summary.len;
+            let new_start = new_excerpts.summary().text.len;
+            let new_end = new_start + new_excerpt.text_summary.len;
+            edits.push(Edit {
+                old: old_start..old_end,
+                new: new_start..new_end,
+            });
+            expanded_ids.push(excerpt_id);
+            new_excerpts.push(new_excerpt, &());
+            cursor.next(&());
+            changed_trailing_excerpt = cursor.item().is_none();
+        }
+        new_excerpts.append(cursor.suffix(&()), &());
+        drop(cursor);
+        snapshot.excerpts = new_excerpts;
+        if changed_trailing_excerpt {
+            snapshot.trailing_excerpt_update_count += 1;
+        }
+        drop(snapshot);
+
+        if expanded_ids.is_empty() {
+            return;
+        }
+        self.subscriptions.publish_mut(edits);
+        cx.emit(Event::Edited {
+            singleton_buffer_edited: false,
+        });
+        cx.emit(Event::ExcerptsExpanded { ids: expanded_ids });
+        cx.notify();
+    }
+
     pub fn wait_for_anchors<'a>(
         &self,
         anchors: impl 'a + Iterator<Item = Anchor>,
@@ -4785,6 +4912,94 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_expand_excerpts(cx: &mut AppContext) {
+        let buffer = cx.new_model(|cx| {
+            Buffer::new(
+                0,
+                BufferId::new(cx.entity_id().as_u64()).unwrap(),
+                sample_text(20, 3, 'a'),
+            )
+        });
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let events = Arc::new(RwLock::new(Vec::<Event>::new()));
+        multibuffer.update(cx, |_, cx| {
+            let events = events.clone();
+            cx.subscribe(&multibuffer, move |_, _, event, _| {
+                if let Event::ExcerptsExpanded { .. } = event {
+                    events.write().push(event.clone())
+                }
+            })
+            .detach();
+        });
+
+        let anchor_ranges = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(
+                buffer.clone(),
+                vec![
+                    Point::new(3, 2)..Point::new(4, 2),
+                    Point::new(15, 0)..Point::new(15, 0),
+                ],
+                2,
+                cx,
+            )
+        });
+        let excerpt_ids = multibuffer.read(cx).excerpt_ids();
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            "bbb\nccc\nddd\neee\nfff\nggg\n\nnnn\nooo\nppp\nqqq\nrrr\n"
+        );
+
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.expand_excerpts([excerpt_ids[0]], 2, ExpandExcerptDirection::Up, cx);
+            multibuffer.expand_excerpts([excerpt_ids[1]], 5, ExpandExcerptDirection::Down, cx);
+        });
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        assert_eq!(
+            snapshot.text(),
+            "aaa\nbbb\nccc\nddd\neee\nfff\nggg\n\nnnn\nooo\nppp\nqqq\nrrr\nsss\nttt"
+        );
+        assert_eq!(
+            anchor_ranges
+                .iter()
+                .map(|range| range.to_point(&snapshot))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(3, 2)..Point::new(4, 2),
+                Point::new(10, 0)..Point::new(10, 0),
+            ]
+        );
+
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.expand_excerpts(
+                [excerpt_ids[1], excerpt_ids[0]],
+                1,
+                ExpandExcerptDirection::UpAndDown,
+                cx,
+            );
+            // Excerpts that can't grow any further are left untouched.
+            multibuffer.expand_excerpts([excerpt_ids[1]], 3, ExpandExcerptDirection::Down, cx);
+        });
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            "aaa\nbbb\nccc\nddd\neee\nfff\nggg\nhhh\n\nmmm\nnnn\nooo\nppp\nqqq\nrrr\nsss\nttt"
+        );
+        assert_eq!(
+            events.read().as_slice(),
+            &[
+                Event::ExcerptsExpanded {
+                    ids: vec![excerpt_ids[0]]
+                },
+                Event::ExcerptsExpanded {
+                    ids: vec![excerpt_ids[1]]
+                },
+                Event::ExcerptsExpanded {
+                    ids: vec![excerpt_ids[0], excerpt_ids[1]]
+                },
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_stream_excerpts_with_context_lines(cx: &mut TestAppContext) {
         let buffer = cx.new_model(|cx| {
@@ -188,3 +188,51 @@ pub async fn stream_completion(
         }
     }
 }
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: Vec<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbeddingResponse {
+    pub data: Vec<Embedding>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Embedding {
+    pub embedding: Vec<f32>,
+}
+
+pub async fn embed<'a>(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: &str,
+    model: &str,
+    texts: impl IntoIterator<Item = &'a str>,
+) -> Result<EmbeddingResponse> {
+    let uri = format!("{api_url}/embeddings");
+    let request = EmbeddingRequest {
+        model,
+        input: texts.into_iter().collect(),
+    };
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    if response.status().is_success() {
+        Ok(serde_json::from_str(&body)?)
+    } else {
+        Err(anyhow!(
+            "error during embedding, status: {:?}, body: {:?}",
+            response.status(),
+            body
+        ))
+    }
+}
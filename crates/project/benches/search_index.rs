@@ -0,0 +1,131 @@
+//! Compares scanning every file's contents for a match, as project search does without an
+//! index, with prefiltering the files through a [`SearchIndex`] first.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use project::{
+    search::SearchQuery,
+    search_index::{QueryTrigrams, SearchIndex},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use util::test::temp_tree;
+
+const FILE_COUNT: usize = 2_000;
+const LINES_PER_FILE: usize = 200;
+const WORDS: &[&str] = &[
+    "let", "mut", "fn", "struct", "impl", "self", "return", "match", "buffer", "worktree",
+    "snapshot", "entry", "path", "range", "offset", "cursor", "editor", "project", "query",
+    "result", "error", "option", "vec", "string", "usize", "clone", "iter", "map", "filter",
+];
+const RARE_LINE: &str = "let needle = find_the_needle(haystack);";
+
+struct Corpus {
+    files: Vec<(Arc<Path>, PathBuf, SystemTime, u64)>,
+    index: SearchIndex,
+}
+
+fn build_corpus(root: &Path) -> Corpus {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut files = Vec::new();
+    let mut index = SearchIndex::default();
+    for file_ix in 0..FILE_COUNT {
+        let mut content = String::new();
+        for _ in 0..LINES_PER_FILE {
+            let word_count = rng.gen_range(2..12);
+            for _ in 0..word_count {
+                content.push_str(WORDS.choose(&mut rng).unwrap());
+                content.push(' ');
+            }
+            content.push('\n');
+        }
+        // Only one file in a hundred contains a match.
+        if file_ix % 100 == 0 {
+            content.push_str(RARE_LINE);
+        }
+
+        let path: Arc<Path> = Path::new(&format!("src/file_{file_ix}.rs")).into();
+        let abs_path = root.join(&path);
+        fs::write(&abs_path, &content).unwrap();
+        let mtime = fs::metadata(&abs_path).unwrap().modified().unwrap();
+        index.insert(path.clone(), mtime, content.as_bytes());
+        files.push((path, abs_path, mtime, content.len() as u64));
+    }
+    Corpus { files, index }
+}
+
+fn scan(corpus: &Corpus, query: &SearchQuery) -> usize {
+    corpus
+        .files
+        .iter()
+        .filter(|(_, abs_path, _, _)| {
+            let file = fs::File::open(abs_path).unwrap();
+            query.detect(file).unwrap()
+        })
+        .count()
+}
+
+fn scan_with_index(corpus: &Corpus, query: &SearchQuery) -> usize {
+    let trigrams = QueryTrigrams::new(query).unwrap();
+    corpus
+        .files
+        .iter()
+        .filter(|(path, abs_path, mtime, len)| {
+            if corpus.index.excludes(path, Some(*mtime), *len, &trigrams) {
+                return false;
+            }
+            let file = fs::File::open(abs_path).unwrap();
+            query.detect(file).unwrap()
+        })
+        .count()
+}
+
+fn search_benchmark(c: &mut Criterion) {
+    let dir = temp_tree(json!({ "src": {} }));
+    let corpus = build_corpus(dir.path());
+
+    let queries = [
+        (
+            "text",
+            SearchQuery::text("needle", false, true, false, Vec::new(), Vec::new()).unwrap(),
+        ),
+        (
+            "regex",
+            SearchQuery::regex(
+                r"find_the_\w+\(",
+                false,
+                true,
+                false,
+                Vec::new(),
+                Vec::new(),
+            )
+            .unwrap(),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("project_search");
+    for (name, query) in &queries {
+        assert_eq!(scan(&corpus, query), scan_with_index(&corpus, query));
+        group.bench_with_input(BenchmarkId::new("scan", name), query, |b, query| {
+            b.iter(|| scan(&corpus, query))
+        });
+        group.bench_with_input(BenchmarkId::new("indexed", name), query, |b, query| {
+            b.iter(|| scan_with_index(&corpus, query))
+        });
+    }
+    group.finish();
+
+    let content = fs::read(&corpus.files[0].1).unwrap();
+    c.bench_function("search_index_insert", |b| {
+        let mut index = SearchIndex::default();
+        b.iter(|| index.insert(corpus.files[0].0.clone(), corpus.files[0].2, &content))
+    });
+}
+
+criterion_group!(benches, search_benchmark);
+criterion_main!(benches);
@@ -4,6 +4,9 @@ pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
 pub mod search;
+pub mod search_index;
+mod shared_tasks;
+mod snippet_inventory;
 mod task_inventory;
 pub mod terminals;
 
@@ -66,8 +69,11 @@ use worktree::LocalSnapshot;
 
 use rpc::{ErrorCode, ErrorExt as _};
 use search::SearchQuery;
+use search_index::{QueryTrigrams, SearchIndex, WorktreeSearchIndex};
 use serde::Serialize;
-use settings::{watch_config_file, Settings, SettingsLocation, SettingsStore};
+use settings::{
+    watch_config_file, Settings, SettingsLocation, SettingsStore, EDITORCONFIG_FILE_NAME,
+};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
@@ -90,7 +96,10 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use task::static_source::{StaticSource, TrackedFile};
+use task::{
+    static_source::{StaticSource, TrackedFile},
+    SpawnInTerminal, TaskId,
+};
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use util::{
@@ -98,7 +107,8 @@ use util::{
     http::HttpClient,
     maybe, merge_json_value_into,
     paths::{
-        LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH, LOCAL_VSCODE_TASKS_RELATIVE_PATH,
+        LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_SNIPPETS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH,
+        LOCAL_VSCODE_TASKS_RELATIVE_PATH,
     },
     post_inc, ResultExt, TryFutureExt as _,
 };
@@ -108,6 +118,9 @@ pub use fs::*;
 pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use snippet_inventory::{
+    is_snippet_file, SnippetInventory, SnippetSourceKind, NON_LSP_COMPLETION_SERVER_ID,
+};
 #[cfg(feature = "test-support")]
 pub use task_inventory::test_inventory::*;
 pub use task_inventory::{Inventory, TaskSourceKind};
@@ -118,6 +131,7 @@ pub use worktree::{
 };
 
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
+
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
@@ -202,7 +216,11 @@ pub struct Project {
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
     prettier_instances: HashMap<PathBuf, PrettierInstance>,
+    search_indices: HashMap<WorktreeId, WorktreeSearchIndex>,
     tasks: Model<Inventory>,
+    snippets: Model<SnippetInventory>,
+    /// The last task this guest asked the host of the project to run.
+    last_host_task: Option<TaskId>,
     hosted_project_id: Option<ProjectId>,
 }
 
@@ -326,6 +344,10 @@ pub enum Event {
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    /// The host of a remote project shared one of its terminals.
+    TerminalShared(Model<terminal::Terminal>),
+    /// A collaborator asked the host to run one of its tasks.
+    SpawnTask(SpawnInTerminal),
 }
 
 pub enum LanguageServerState {
@@ -593,6 +615,13 @@ impl Project {
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
+        client.add_model_message_handler(Self::handle_share_terminal);
+        client.add_model_message_handler(Self::handle_update_terminal);
+        client.add_model_message_handler(Self::handle_unshare_terminal);
+        client.add_model_request_handler(Self::handle_get_shared_terminals);
+        client.add_model_request_handler(Self::handle_terminal_input);
+        client.add_model_request_handler(Self::handle_get_project_tasks);
+        client.add_model_request_handler(Self::handle_run_project_task);
     }
 
     pub fn local(
@@ -610,6 +639,7 @@ impl Project {
             let copilot_lsp_subscription =
                 Copilot::global(cx).map(|copilot| subscribe_for_copilot_events(&copilot, cx));
             let tasks = Inventory::new(cx);
+            let snippets = SnippetInventory::new(cx);
 
             Self {
                 worktrees: Vec::new(),
@@ -655,9 +685,7 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
@@ -665,7 +693,10 @@ impl Project {
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
+                search_indices: HashMap::default(),
                 tasks,
+                snippets,
+                last_host_task: None,
                 hosted_project_id: None,
             }
         })
@@ -712,6 +743,7 @@ impl Project {
         let this = cx.new_model(|cx| {
             let replica_id = response.payload.replica_id as ReplicaId;
             let tasks = Inventory::new(cx);
+            let snippets = SnippetInventory::new(cx);
             // BIG CAUTION NOTE: The order in which we initialize fields here matters and it should match what's done in Self::local.
             // Otherwise, you might run into issues where worktree id on remote is different than what's on local host.
             // That's because Worktree's identifier is entity id, which should probably be changed.
@@ -790,9 +822,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
@@ -800,7 +830,10 @@ impl Project {
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
+                search_indices: HashMap::default(),
                 tasks,
+                snippets,
+                last_host_task: None,
                 hosted_project_id: None,
             };
             this.set_role(role, cx);
@@ -824,6 +857,7 @@ impl Project {
         this.update(&mut cx, |this, cx| {
             this.set_collaborators_from_proto(response.payload.collaborators, cx)?;
             this.client_subscriptions.push(subscription);
+            this.fetch_shared_terminals(cx);
             anyhow::Ok(())
         })??;
 
@@ -999,6 +1033,14 @@ impl Project {
         }
         self.current_lsp_settings = new_lsp_settings;
 
+        if ProjectSettings::get_global(cx).search_index.enabled() {
+            for worktree in self.visible_worktrees(cx).collect::<Vec<_>>() {
+                self.start_search_index(&worktree, cx);
+            }
+        } else {
+            self.search_indices.clear();
+        }
+
         // Stop all newly-disabled language servers.
         for (worktree_id, adapter_name) in language_servers_to_stop {
             self.stop_language_server(worktree_id, adapter_name, cx)
@@ -1124,6 +1166,10 @@ impl Project {
         &self.tasks
     }
 
+    pub fn snippet_inventory(&self) -> &Model<SnippetInventory> {
+        &self.snippets
+    }
+
     pub fn collaborators(&self) -> &HashMap<proto::PeerId, Collaborator> {
         &self.collaborators
     }
@@ -1459,13 +1505,21 @@ impl Project {
         let store = cx.global::<SettingsStore>();
         for worktree in self.worktrees() {
             let worktree_id = worktree.read(cx).id().to_proto();
-            for (path, content) in store.local_settings(worktree.entity_id().as_u64() as usize) {
+            let root_id = worktree.entity_id().as_u64() as usize;
+            let settings_files = store
+                .local_settings(root_id)
+                .map(|(path, content)| (proto::LocalSettingsKind::Settings, path, content))
+                .chain(store.local_editorconfigs(root_id).map(|(path, content)| {
+                    (proto::LocalSettingsKind::Editorconfig, path, content)
+                }));
+            for (kind, path, content) in settings_files {
                 self.client
                     .send(proto::UpdateWorktreeSettings {
                         project_id,
                         worktree_id,
                         path: path.to_string_lossy().into(),
                         content: Some(content),
+                        kind: kind.into(),
                     })
                     .log_err();
             }
@@ -1632,6 +1686,7 @@ impl Project {
             self.collaborators.clear();
             self.shared_buffers.clear();
             self.client_subscriptions.clear();
+            self.stop_sharing_terminals(cx);
 
             for worktree_handle in self.worktrees.iter_mut() {
                 if let WorktreeHandle::Strong(worktree) = worktree_handle {
@@ -1700,6 +1755,7 @@ impl Project {
             *sharing_has_stopped = true;
 
             self.collaborators.clear();
+            self.close_remote_terminals(cx);
 
             for worktree in &self.worktrees {
                 if let Some(worktree) = worktree.upgrade() {
@@ -5251,15 +5307,81 @@ impl Project {
                         completions.extend_from_slice(&new_completions);
                     }
                 }
+                this.update(&mut cx, |this, cx| {
+                    completions.extend(this.snippet_completions(&buffer, position, cx));
+                })?;
 
                 Ok(completions)
             })
         } else if let Some(project_id) = self.remote_id() {
-            self.send_lsp_proto_request(buffer.clone(), project_id, GetCompletions { position }, cx)
+            let snippet_completions = self.snippet_completions(buffer, position, cx);
+            let completions = self.send_lsp_proto_request(
+                buffer.clone(),
+                project_id,
+                GetCompletions { position },
+                cx,
+            );
+            cx.background_executor().spawn(async move {
+                let mut completions = completions.await?;
+                completions.extend(snippet_completions);
+                Ok(completions)
+            })
         } else {
             Task::ready(Ok(Default::default()))
         }
     }
+
+    /// Returns completions for the user snippets that apply to the word before the given position.
+    fn snippet_completions(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &AppContext,
+    ) -> Vec<Completion> {
+        let buffer = buffer.read(cx);
+        let snapshot = buffer.snapshot();
+        let offset = position.to_offset(&snapshot);
+        let (word_range, _) = snapshot.surrounding_word(offset);
+        if word_range.start >= offset {
+            return Vec::new();
+        }
+
+        let language_name = snapshot.language_at(offset).map(|language| language.name());
+        let worktree_id = buffer
+            .file()
+            .map(|file| WorktreeId::from_usize(file.worktree_id()));
+        let old_range = snapshot.anchor_before(word_range.start)..snapshot.anchor_after(offset);
+        self.snippets
+            .read(cx)
+            .snippets_for(language_name.as_deref(), worktree_id)
+            .into_iter()
+            .flat_map(|snippet| {
+                let old_range = old_range.clone();
+                snippet.prefixes.clone().into_iter().map(move |prefix| {
+                    let documentation = snippet
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| snippet.body.clone());
+                    Completion {
+                        old_range: old_range.clone(),
+                        new_text: snippet.body.clone(),
+                        label: CodeLabel::plain(prefix.clone(), None),
+                        server_id: NON_LSP_COMPLETION_SERVER_ID,
+                        documentation: Some(Documentation::MultiLinePlainText(documentation)),
+                        lsp_completion: lsp::CompletionItem {
+                            label: prefix.clone(),
+                            kind: Some(lsp::CompletionItemKind::SNIPPET),
+                            detail: Some(snippet.name.clone()),
+                            filter_text: Some(prefix),
+                            insert_text: Some(snippet.body.clone()),
+                            insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                            ..Default::default()
+                        },
+                    }
+                })
+            })
+            .collect()
+    }
     pub fn completions<T: ToOffset + ToPointUtf16>(
         &self,
         buffer: &Model<Buffer>,
@@ -5447,6 +5569,10 @@ impl Project {
         push_to_history: bool,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Option<Transaction>>> {
+        if completion.server_id == NON_LSP_COMPLETION_SERVER_ID {
+            return Task::ready(Ok(None));
+        }
+
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
 
@@ -6232,6 +6358,11 @@ impl Project {
             })
             .collect::<Vec<_>>();
         let include_root = snapshots.len() > 1;
+        let search_indices = self
+            .search_indices
+            .iter()
+            .map(|(worktree_id, index)| (*worktree_id, index.index()))
+            .collect::<HashMap<_, _>>();
 
         let background = cx.background_executor().clone();
         let path_count: usize = snapshots
@@ -6294,6 +6425,7 @@ impl Project {
                 include_root,
                 path_count,
                 snapshots,
+                search_indices,
                 matching_paths_tx,
             ))
             .detach();
@@ -6338,8 +6470,32 @@ impl Project {
                         })?,
                     };
 
-                    chunk_results.push(cx.spawn(|cx| async move {
+                    let this = this.clone();
+                    chunk_results.push(cx.spawn(|mut cx| async move {
                         let buffer = buffer.await?;
+                        if query.is_structural() {
+                            // Structural queries match syntax trees, so buffers that were opened
+                            // for the search need their language before they can be matched.
+                            let language = this.update(&mut cx, |this, cx| {
+                                let buffer = buffer.read(cx);
+                                if buffer.language().is_some() {
+                                    return None;
+                                }
+                                let file = buffer.file()?;
+                                Some(this.languages.language_for_file(
+                                    file,
+                                    Some(buffer.as_rope()),
+                                    cx,
+                                ))
+                            })?;
+                            if let Some(language) = language {
+                                if let Ok(language) = language.await {
+                                    this.update(&mut cx, |this, cx| {
+                                        this.set_language_for_buffer(&buffer, language, cx)
+                                    })?;
+                                }
+                            }
+                        }
                         let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                         let ranges = cx
                             .background_executor()
@@ -6397,12 +6553,20 @@ impl Project {
         include_root: bool,
         path_count: usize,
         snapshots: Vec<LocalSnapshot>,
+        search_indices: HashMap<WorktreeId, Arc<RwLock<SearchIndex>>>,
         matching_paths_tx: Sender<SearchMatchCandidate>,
     ) {
         let fs = &fs;
         let query = &query;
         let matching_paths_tx = &matching_paths_tx;
         let snapshots = &snapshots;
+        let search_indices = &search_indices;
+        let query_trigrams = if search_indices.is_empty() {
+            None
+        } else {
+            QueryTrigrams::new(query)
+        };
+        let query_trigrams = &query_trigrams;
         for buffer in unnamed_buffers {
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
@@ -6444,6 +6608,8 @@ impl Project {
                                 matching_paths_tx,
                                 &opened_buffers,
                                 include_root,
+                                search_indices,
+                                query_trigrams.as_ref(),
                                 fs,
                             )
                             .await;
@@ -6675,6 +6841,8 @@ impl Project {
             cx.emit(Event::LanguageServerRemoved(server_id_to_remove));
         }
 
+        self.search_indices.remove(&id_to_remove);
+
         let mut prettier_instances_to_clean = FuturesUnordered::new();
         if let Some(prettier_paths) = self.prettiers_per_worktree.remove(&id_to_remove) {
             for path in prettier_paths.iter().flatten() {
@@ -6707,6 +6875,9 @@ impl Project {
         self.task_inventory().update(cx, |inventory, _| {
             inventory.remove_worktree_sources(id_to_remove);
         });
+        self.snippet_inventory().update(cx, |inventory, _| {
+            inventory.remove_worktree_sources(id_to_remove);
+        });
 
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
@@ -6735,6 +6906,7 @@ impl Project {
                         this.update_local_worktree_language_servers(&worktree, changes, cx);
                         this.update_local_worktree_settings(&worktree, changes, cx);
                         this.update_prettier_settings(&worktree, changes, cx);
+                        this.update_local_worktree_search_index(&worktree, changes, cx);
                     }
 
                     cx.emit(Event::WorktreeUpdatedEntries(
@@ -6774,10 +6946,48 @@ impl Project {
         })
         .detach();
 
+        if ProjectSettings::get_global(cx).search_index.enabled() {
+            self.start_search_index(worktree, cx);
+        }
+
         cx.emit(Event::WorktreeAdded);
         self.metadata_changed(cx);
     }
 
+    fn start_search_index(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        let worktree_id = worktree.read(cx).id();
+        if worktree.read(cx).is_local()
+            && worktree.read(cx).is_visible()
+            && !self.search_indices.contains_key(&worktree_id)
+        {
+            let index = WorktreeSearchIndex::new(worktree, self.fs.clone(), cx);
+            self.search_indices.insert(worktree_id, index);
+        }
+    }
+
+    fn update_local_worktree_search_index(
+        &mut self,
+        worktree_handle: &Model<Worktree>,
+        changes: &[(Arc<Path>, ProjectEntryId, PathChange)],
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree = worktree_handle.read(cx);
+        let Some(index) = self.search_indices.get(&worktree.id()) else {
+            return;
+        };
+        let changes = changes
+            .iter()
+            .map(|(path, _, _)| {
+                let version = worktree
+                    .entry_for_path(path)
+                    .filter(|entry| entry.is_file() && !entry.is_ignored)
+                    .and_then(|entry| Some((entry.mtime?, entry.size)));
+                (path.clone(), version)
+            })
+            .collect();
+        index.update(changes);
+    }
+
     fn update_local_worktree_buffers(
         &mut self,
         worktree_handle: &Model<Worktree>,
@@ -7098,6 +7308,7 @@ impl Project {
                 let fs = self.fs.clone();
                 settings_contents.push(async move {
                     (
+                        proto::LocalSettingsKind::Settings,
                         settings_dir,
                         if removed {
                             None
@@ -7106,6 +7317,22 @@ impl Project {
                         },
                     )
                 });
+            } else if abs_path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                let Some(editorconfig_dir) = path.parent().map(Arc::from) else {
+                    continue;
+                };
+                let fs = self.fs.clone();
+                settings_contents.push(async move {
+                    (
+                        proto::LocalSettingsKind::Editorconfig,
+                        editorconfig_dir,
+                        if removed {
+                            None
+                        } else {
+                            Some(async move { fs.load(&abs_path).await }.await)
+                        },
+                    )
+                });
             } else if abs_path.ends_with(&*LOCAL_TASKS_RELATIVE_PATH) {
                 self.task_inventory().update(cx, |task_inventory, cx| {
                     if removed {
@@ -7161,6 +7388,35 @@ impl Project {
                         );
                     }
                 })
+            } else if abs_path
+                .parent()
+                .map_or(false, |dir| dir.ends_with(&*LOCAL_SNIPPETS_RELATIVE_PATH))
+                && is_snippet_file(&abs_path)
+            {
+                if removed {
+                    self.snippet_inventory().update(cx, |inventory, cx| {
+                        inventory.remove_snippet_file(&abs_path);
+                        cx.notify();
+                    });
+                } else {
+                    let fs = self.fs.clone();
+                    let snippets = self.snippet_inventory().downgrade();
+                    cx.spawn(move |_, mut cx| async move {
+                        let content = fs.load(&abs_path).await?;
+                        snippets.update(&mut cx, |inventory, cx| {
+                            inventory.set_snippet_file(
+                                SnippetSourceKind::Worktree {
+                                    id: remote_worktree_id,
+                                    abs_path,
+                                },
+                                &content,
+                            )?;
+                            cx.notify();
+                            anyhow::Ok(())
+                        })?
+                    })
+                    .detach_and_log_err(cx);
+                }
             }
         }
 
@@ -7170,20 +7426,26 @@ impl Project {
 
         let client = self.client.clone();
         cx.spawn(move |_, cx| async move {
-            let settings_contents: Vec<(Arc<Path>, _)> =
+            let settings_contents: Vec<(proto::LocalSettingsKind, Arc<Path>, _)> =
                 futures::future::join_all(settings_contents).await;
             cx.update(|cx| {
                 cx.update_global::<SettingsStore, _>(|store, cx| {
-                    for (directory, file_content) in settings_contents {
+                    for (kind, directory, file_content) in settings_contents {
                         let file_content = file_content.and_then(|content| content.log_err());
-                        store
-                            .set_local_settings(
+                        match kind {
+                            proto::LocalSettingsKind::Settings => store.set_local_settings(
                                 worktree_id.as_u64() as usize,
                                 directory.clone(),
                                 file_content.as_deref(),
                                 cx,
-                            )
-                            .log_err();
+                            ),
+                            proto::LocalSettingsKind::Editorconfig => store.set_local_editorconfig(
+                                worktree_id.as_u64() as usize,
+                                directory.clone(),
+                                file_content.as_deref(),
+                            ),
+                        }
+                        .log_err();
                         if let Some(remote_id) = project_id {
                             client
                                 .send(proto::UpdateWorktreeSettings {
@@ -7191,6 +7453,7 @@ impl Project {
                                     worktree_id: remote_worktree_id.to_proto(),
                                     path: directory.to_string_lossy().into_owned(),
                                     content: file_content,
+                                    kind: kind.into(),
                                 })
                                 .log_err();
                         }
@@ -7487,15 +7750,19 @@ impl Project {
         this.update(&mut cx, |this, cx| {
             let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
             if let Some(worktree) = this.worktree_for_id(worktree_id, cx) {
+                let root_id = worktree.entity_id().as_u64() as usize;
+                let path = Arc::<Path>::from(PathBuf::from(&envelope.payload.path));
+                let content = envelope.payload.content.as_deref();
                 cx.update_global::<SettingsStore, _>(|store, cx| {
-                    store
-                        .set_local_settings(
-                            worktree.entity_id().as_u64() as usize,
-                            PathBuf::from(&envelope.payload.path).into(),
-                            envelope.payload.content.as_deref(),
-                            cx,
-                        )
-                        .log_err();
+                    match envelope.payload.kind() {
+                        proto::LocalSettingsKind::Settings => {
+                            store.set_local_settings(root_id, path, content, cx)
+                        }
+                        proto::LocalSettingsKind::Editorconfig => {
+                            store.set_local_editorconfig(root_id, path, content)
+                        }
+                    }
+                    .log_err();
                 });
             }
             Ok(())
@@ -7952,6 +8219,7 @@ impl Project {
             version: serialize_version(buffer.saved_version()),
             mtime: buffer.saved_mtime().map(|time| time.into()),
             fingerprint: language::proto::serialize_fingerprint(buffer.saved_version_fingerprint()),
+            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -8050,6 +8318,7 @@ impl Project {
                             line_ending: language::proto::serialize_line_ending(
                                 buffer.line_ending(),
                             ) as i32,
+                            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
                         })
                         .log_err();
 
@@ -8907,6 +9176,12 @@ impl Project {
         let version = deserialize_version(&envelope.payload.version);
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let mtime = envelope.payload.mtime.map(|time| time.into());
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_ref()
+            .map(language::proto::deserialize_encoding)
+            .transpose()?;
 
         this.update(&mut cx, |this, cx| {
             let buffer = this
@@ -8916,6 +9191,9 @@ impl Project {
                 .or_else(|| this.incomplete_remote_buffers.get(&buffer_id).cloned());
             if let Some(buffer) = buffer {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_save(version, fingerprint, mtime, cx);
                 });
             }
@@ -8937,6 +9215,11 @@ impl Project {
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
         let mtime = payload.mtime.map(|time| time.into());
+        let encoding = payload
+            .encoding
+            .as_ref()
+            .map(language::proto::deserialize_encoding)
+            .transpose()?;
         let buffer_id = BufferId::new(payload.buffer_id)?;
         this.update(&mut cx, |this, cx| {
             let buffer = this
@@ -8946,6 +9229,9 @@ impl Project {
                 .or_else(|| this.incomplete_remote_buffers.get(&buffer_id).cloned());
             if let Some(buffer) = buffer {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_reload(version, fingerprint, line_ending, mtime, cx);
                 });
             }
@@ -9198,12 +9484,15 @@ async fn search_snapshots(
     results_tx: &Sender<SearchMatchCandidate>,
     opened_buffers: &HashMap<Arc<Path>, (Model<Buffer>, BufferSnapshot)>,
     include_root: bool,
+    search_indices: &HashMap<WorktreeId, Arc<RwLock<SearchIndex>>>,
+    query_trigrams: Option<&QueryTrigrams>,
     fs: &Arc<dyn Fs>,
 ) {
     let mut snapshot_start_ix = 0;
     let mut abs_path = PathBuf::new();
 
     for snapshot in snapshots {
+        let search_index = query_trigrams.zip(search_indices.get(&snapshot.id()));
         let snapshot_end_ix = snapshot_start_ix
             + if query.include_ignored() {
                 snapshot.file_count()
@@ -9238,7 +9527,15 @@ async fn search_snapshots(
                     query.file_matches(Some(&entry.path))
                 };
 
-                let matches = if matched_path {
+                // Skip reading files that the index proves can't match, and scan the ones
+                // it has no up-to-date entry for.
+                let excluded_by_index = search_index.map_or(false, |(trigrams, index)| {
+                    index
+                        .read()
+                        .excludes(&entry.path, entry.mtime, entry.size, trigrams)
+                });
+
+                let matches = if matched_path && !excluded_by_index {
                     abs_path.clear();
                     abs_path.push(&snapshot.abs_path());
                     abs_path.push(&entry.path);
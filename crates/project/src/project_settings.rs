@@ -20,6 +20,14 @@ pub struct ProjectSettings {
     /// Configuration for Git-related features
     #[serde(default)]
     pub git: GitSettings,
+
+    /// Configuration for the index used to speed up project search
+    #[serde(default)]
+    pub search_index: SearchIndexSettings,
+
+    /// Configuration for running tasks on behalf of the guests of shared projects
+    #[serde(default)]
+    pub guest_tasks: GuestTaskSettings,
 }
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -31,6 +39,39 @@ pub struct GitSettings {
     pub gutter_debounce: Option<u64>,
 }
 
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndexSettings {
+    /// Whether to maintain a trigram index of the files in local worktrees, and use it
+    /// to skip files that can't match a project search without reading them.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+}
+
+impl SearchIndexSettings {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GuestTaskSettings {
+    /// Whether the guests of the projects you share, with write access, can list your
+    /// tasks and run them on your machine.
+    ///
+    /// Only your user settings are considered: the settings of a project, which
+    /// guests may edit, can't enable this.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+}
+
+impl GuestTaskSettings {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum GitGutterSetting {
@@ -4,7 +4,7 @@ use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
     language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::Url;
@@ -87,6 +87,55 @@ async fn test_symlinks(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".editorconfig": "root = true\n[*]\nindent_style = space\nindent_size = 4\n[*.md]\ntrim_trailing_whitespace = false\n",
+            ".zed": {
+                "settings.json": r#"{ "tab_size": 8, "hard_tabs": true }"#,
+            },
+            "a.rs": "fn a() {}",
+            "README.md": "# Readme",
+            "web": {
+                ".editorconfig": "[*.js]\nindent_size = 2\n",
+                "app.js": "",
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let worktree = project.update(cx, |project, _| project.worktrees().next().unwrap());
+
+    cx.executor().run_until_parked();
+    let settings_for = |path: &str, cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| {
+            let tree = worktree.read(cx);
+            let file =
+                File::for_entry(tree.entry_for_path(path).unwrap().clone(), worktree.clone());
+            language_settings(None, Some(&(file as _)), cx).clone()
+        })
+    };
+
+    let settings = settings_for("a.rs", cx);
+    assert_eq!(settings.tab_size.get(), 4);
+    assert!(!settings.hard_tabs);
+    assert!(settings.remove_trailing_whitespace_on_save);
+    assert!(!settings_for("README.md", cx).remove_trailing_whitespace_on_save);
+    assert_eq!(settings_for("web/app.js", cx).tab_size.get(), 2);
+
+    fs.remove_file("/the-root/web/.editorconfig".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(settings_for("web/app.js", cx).tab_size.get(), 4);
+}
+
 #[gpui::test]
 async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3358,6 +3407,88 @@ async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_buffer_encodings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({})).await;
+    fs.insert_file("/dir/legacy.c", b"// caf\xe9\n".to_vec())
+        .await;
+    fs.insert_file("/dir/bom.txt", b"\xEF\xBB\xBFhello\n".to_vec())
+        .await;
+    fs.insert_file("/dir/image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec())
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let legacy = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/legacy.c", cx))
+        .await
+        .unwrap();
+    let bom = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/bom.txt", cx))
+        .await
+        .unwrap();
+
+    // Binary files can't be opened.
+    assert!(project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/image.png", cx))
+        .await
+        .is_err());
+
+    let windows_1252 = Encoding::for_label("windows-1252").unwrap();
+    legacy.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "// caf\u{e9}\n");
+        assert_eq!(buffer.encoding(), windows_1252);
+    });
+    bom.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "hello\n");
+        assert_eq!(buffer.encoding(), Encoding::utf8().with_bom(true));
+    });
+
+    // Files are saved back with the encoding and byte order mark they were loaded with.
+    legacy.update(cx, |buffer, cx| buffer.edit([(0..2, "/*")], None, cx));
+    bom.update(cx, |buffer, cx| buffer.edit([(0..0, "\u{e9}")], None, cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(legacy.clone(), cx))
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| project.save_buffer(bom.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes("/dir/legacy.c".as_ref()).await.unwrap(),
+        b"/* caf\xe9\n"
+    );
+    assert_eq!(
+        fs.load_bytes("/dir/bom.txt".as_ref()).await.unwrap(),
+        "\u{feff}\u{e9}hello\n".as_bytes()
+    );
+
+    // Reopening a file with a different encoding decodes its contents again.
+    legacy
+        .update(cx, |buffer, cx| {
+            buffer.reload_with_encoding(Some(Encoding::utf8()), cx)
+        })
+        .await
+        .unwrap();
+    legacy.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "/* caf\u{fffd}\n");
+        assert_eq!(buffer.encoding(), Encoding::utf8());
+    });
+
+    // Saving fails when the text can't be represented in the buffer's encoding.
+    legacy.update(cx, |buffer, cx| {
+        buffer.set_encoding(windows_1252, cx);
+        buffer.edit([(0..0, "\u{3053}")], None, cx);
+    });
+    assert!(project
+        .update(cx, |project, cx| project.save_buffer(legacy.clone(), cx))
+        .await
+        .is_err());
+}
+
 #[gpui::test]
 async fn test_grouped_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3790,6 +3921,75 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_with_index(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.search_index.enabled = Some(true);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.run_until_parked();
+    project.read_with(cx, |project, _| {
+        let (_, index) = project.search_indices.iter().next().unwrap();
+        assert_eq!(index.index().read().len(), 3);
+    });
+
+    let query = || SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(
+        search(&project, query(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![37..40])
+        ])
+    );
+
+    // Files changed on disk are re-indexed.
+    fs.save(
+        "/dir/one.rs".as_ref(),
+        &"const ONE: usize = two::TWO;".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        search(&project, query(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![24..27]),
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![37..40])
+        ])
+    );
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::regex(r"two::\w+", false, true, false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![19..27]),
+            ("dir/three.rs".to_string(), vec![32..40])
+        ])
+    );
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4333,6 +4533,49 @@ fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("foo/bar/baz.js"), "foo/bar/baz.js");
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() -> Option<u32> { let b = parse(c).unwrap(); Some(b) }",
+            "b.rs": "fn b() { c.unwrap_or(1); }",
+            "c.txt": "parse(c).unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("$X.unwrap()", false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("dir/a.rs".to_string(), vec![32..49])])
+    );
+
+    let query = SearchQuery::structural("$X.unwrap()", false, Vec::new(), Vec::new())
+        .unwrap()
+        .with_replacement("$X?".to_string());
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            query.structural_replacement(&buffer.snapshot(), 32..49),
+            Some("parse(c)?".to_string())
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -0,0 +1,611 @@
+use crate::search::SearchQuery;
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use fs::{Fs, RenameOptions};
+use futures::{channel::mpsc, FutureExt, StreamExt};
+use gpui::{AppContext, BackgroundExecutor, Model, Task};
+use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::{paths::SEARCH_INDEX_DIR, ResultExt};
+use worktree::{LocalSnapshot, Worktree};
+
+const MAGIC: &[u8; 4] = b"ZSTI";
+const FORMAT_VERSION: u32 = 2;
+/// Files larger than this are not indexed and are always scanned.
+const MAX_INDEXED_FILE_SIZE: usize = 8 * 1024 * 1024;
+const BITS_PER_TRIGRAM: usize = 10;
+const HASHES_PER_TRIGRAM: u64 = 3;
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// A per-worktree index of the byte trigrams contained in each file, used to skip files
+/// that cannot contain a match of a project search query without reading them.
+///
+/// Each file is summarized by a bloom filter over its ASCII-lowercased trigrams, so the
+/// index can report false positives (which are then ruled out by scanning the file) but
+/// never false negatives. Entries are keyed by the file's mtime and length: a file whose
+/// mtime or length no longer matches the worktree entry is considered stale and is scanned
+/// as usual. The length catches rewrites that land within the file system's mtime
+/// resolution.
+#[derive(Default)]
+pub struct SearchIndex {
+    files: HashMap<Arc<Path>, IndexedFile>,
+}
+
+struct IndexedFile {
+    mtime: SystemTime,
+    len: u64,
+    filter: TrigramFilter,
+}
+
+/// The trigrams every file matching a query must contain.
+#[derive(Debug, PartialEq, Eq)]
+pub struct QueryTrigrams(Vec<u32>);
+
+struct TrigramFilter {
+    bits: Vec<u64>,
+}
+
+impl SearchIndex {
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn insert(&mut self, path: Arc<Path>, mtime: SystemTime, content: &[u8]) {
+        if content.len() > MAX_INDEXED_FILE_SIZE {
+            self.files.remove(&path);
+        } else {
+            let filter = TrigramFilter::new(content);
+            let len = content.len() as u64;
+            self.files.insert(path, IndexedFile { mtime, len, filter });
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    pub fn is_current(&self, path: &Path, mtime: SystemTime, len: u64) -> bool {
+        self.files
+            .get(path)
+            .map_or(false, |file| file.mtime == mtime && file.len == len)
+    }
+
+    /// Returns `true` if the index is up to date for the given file and proves that it
+    /// cannot contain a match for the query.
+    pub fn excludes(
+        &self,
+        path: &Path,
+        mtime: Option<SystemTime>,
+        len: u64,
+        query: &QueryTrigrams,
+    ) -> bool {
+        let Some(file) = self.files.get(path) else {
+            return false;
+        };
+        if Some(file.mtime) != mtime || file.len != len {
+            return false;
+        }
+        query
+            .0
+            .iter()
+            .any(|&trigram| !file.filter.contains(trigram))
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        let files = self
+            .files
+            .iter()
+            .filter_map(|(path, file)| {
+                let mtime = file.mtime.duration_since(UNIX_EPOCH).ok()?;
+                Some((path.to_str()?, mtime, file.len, &file.filter))
+            })
+            .collect::<Vec<_>>();
+        bytes.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        for (path, mtime, len, filter) in files {
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.extend_from_slice(&mtime.as_secs().to_le_bytes());
+            bytes.extend_from_slice(&mtime.subsec_nanos().to_le_bytes());
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.extend_from_slice(&(filter.bits.len() as u32).to_le_bytes());
+            for word in &filter.bits {
+                bytes.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(anyhow!("not a search index"));
+        }
+        let version = reader.read_u32()?;
+        if version != FORMAT_VERSION {
+            return Err(anyhow!("unsupported search index version {version}"));
+        }
+
+        let file_count = reader.read_u32()? as usize;
+        let mut files = HashMap::default();
+        for _ in 0..file_count {
+            let path_len = reader.read_u32()? as usize;
+            let path = std::str::from_utf8(reader.take(path_len)?)?;
+            let secs = reader.read_u64()?;
+            let nanos = reader.read_u32()?;
+            let len = reader.read_u64()?;
+            let word_count = reader.read_u32()? as usize;
+            let bits = (0..word_count)
+                .map(|_| reader.read_u64())
+                .collect::<Result<Vec<_>>>()?;
+            if !bits.len().is_power_of_two() {
+                return Err(anyhow!("invalid trigram filter for {path}"));
+            }
+            files.insert(
+                Arc::from(Path::new(path)),
+                IndexedFile {
+                    mtime: UNIX_EPOCH + Duration::new(secs, nanos),
+                    len,
+                    filter: TrigramFilter { bits },
+                },
+            );
+        }
+        Ok(Self { files })
+    }
+}
+
+impl QueryTrigrams {
+    /// Extracts the trigrams required by the query, or returns `None` if the query can't
+    /// be prefiltered (e.g. it is too short, or a regex without a required literal).
+    pub fn new(query: &SearchQuery) -> Option<Self> {
+        let mut trigrams = match query {
+            SearchQuery::Text { .. } => trigrams(query.as_str().as_bytes()).collect::<Vec<_>>(),
+            SearchQuery::Regex { case_sensitive, .. } => {
+                regex_literals(query.as_str(), *case_sensitive)?
+                    .iter()
+                    .flat_map(|literal| trigrams(literal.as_bytes()))
+                    .collect()
+            }
+            SearchQuery::Structural { .. } => return None,
+        };
+        trigrams.sort_unstable();
+        trigrams.dedup();
+        if trigrams.is_empty() {
+            None
+        } else {
+            Some(Self(trigrams))
+        }
+    }
+}
+
+impl TrigramFilter {
+    fn new(content: &[u8]) -> Self {
+        let trigrams = trigrams(content).collect::<HashSet<_>>();
+        let bit_count = (trigrams.len() * BITS_PER_TRIGRAM)
+            .next_power_of_two()
+            .max(64);
+        let mut filter = Self {
+            bits: vec![0; bit_count / 64],
+        };
+        for trigram in trigrams {
+            for bit in filter.bit_indices(trigram) {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+
+    fn contains(&self, trigram: u32) -> bool {
+        self.bit_indices(trigram)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn bit_indices(&self, trigram: u32) -> impl Iterator<Item = usize> {
+        let mask = (self.bits.len() * 64 - 1) as u64;
+        let hash = mix(trigram as u64);
+        let (h1, h2) = (hash, (hash >> 32) | 1);
+        (0..HASHES_PER_TRIGRAM).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) & mask) as usize)
+    }
+}
+
+/// The SplitMix64 finalizer, which spreads the trigram's bits across the whole hash.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+fn trigrams(bytes: &[u8]) -> impl '_ + Iterator<Item = u32> {
+    bytes.windows(3).map(|window| {
+        let [a, b, c] = [window[0], window[1], window[2]].map(|b| b.to_ascii_lowercase());
+        (a as u32) << 16 | (b as u32) << 8 | c as u32
+    })
+}
+
+/// Returns literal strings that every match of the regex must contain.
+///
+/// This is deliberately conservative: alternations and inline flags disable prefiltering
+/// altogether, and only literal characters outside of groups and classes that aren't made
+/// optional by a quantifier are taken into account.
+fn regex_literals(pattern: &str, case_sensitive: bool) -> Option<Vec<String>> {
+    if pattern.contains('|') || pattern.contains("(?") {
+        return None;
+    }
+
+    let mut literals = Vec::new();
+    let mut literal = String::new();
+    let mut group_depth = 0_usize;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let literal_char = match c {
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_ascii_punctuation() => Some(escaped),
+                Some(escaped) => {
+                    // Skip the argument of escapes like `\x41`, `\u{1F600}` or `\pL`.
+                    let argument_len = match escaped {
+                        'x' => 2,
+                        'u' => 4,
+                        'U' => 8,
+                        'p' | 'P' => 1,
+                        _ => 0,
+                    };
+                    if argument_len > 0 && chars.peek() == Some(&'{') {
+                        chars.by_ref().find(|&c| c == '}');
+                    } else {
+                        chars.by_ref().take(argument_len).for_each(drop);
+                    }
+                    None
+                }
+                None => None,
+            },
+            '[' => {
+                // Skip the class, taking care of escapes, nested classes and leading
+                // closing brackets.
+                let mut depth = 1;
+                let mut first = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '[' => depth += 1,
+                        ']' if !first => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    first = c == '^' && first;
+                }
+                None
+            }
+            '{' => {
+                chars.by_ref().find(|&c| c == '}');
+                None
+            }
+            '(' => {
+                group_depth += 1;
+                None
+            }
+            ')' => {
+                group_depth = group_depth.saturating_sub(1);
+                None
+            }
+            '.' | '^' | '$' | '*' | '+' | '?' | '}' => None,
+            c => Some(c),
+        }
+        .filter(|_| group_depth == 0)
+        // Case-insensitive regexes use Unicode case folding, under which non-ASCII characters,
+        // as well as `k` and `s` (the Kelvin sign and the long s), may match other bytes.
+        .filter(|&c| case_sensitive || (c.is_ascii() && !matches!(c, 'k' | 'K' | 's' | 'S')));
+
+        match (literal_char, chars.peek()) {
+            (Some(c), Some('+')) => {
+                literal.push(c);
+                literals.push(std::mem::take(&mut literal));
+            }
+            (Some(c), next) if !matches!(next, Some('?' | '*' | '{')) => literal.push(c),
+            _ => literals.push(std::mem::take(&mut literal)),
+        }
+    }
+    literals.push(literal);
+    literals.retain(|literal| literal.len() >= 3);
+    Some(literals)
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(anyhow!("unexpected end of search index"));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
+
+/// A [`SearchIndex`] kept up to date with the changes of a local worktree, and persisted
+/// under the app's support directory between sessions.
+pub(crate) struct WorktreeSearchIndex {
+    index: Arc<RwLock<SearchIndex>>,
+    changes_tx: mpsc::UnboundedSender<Vec<(Arc<Path>, Option<(SystemTime, u64)>)>>,
+    _maintain_index: Task<()>,
+}
+
+impl WorktreeSearchIndex {
+    pub(crate) fn new(worktree: &Model<Worktree>, fs: Arc<dyn Fs>, cx: &mut AppContext) -> Self {
+        let index = Arc::new(RwLock::new(SearchIndex::default()));
+        let (changes_tx, changes_rx) = mpsc::unbounded();
+        let scan_complete = worktree
+            .read(cx)
+            .as_local()
+            .map(|tree| tree.scan_complete());
+        let worktree = worktree.downgrade();
+        let maintain_index = cx.spawn({
+            let index = index.clone();
+            |mut cx| async move {
+                if let Some(scan_complete) = scan_complete {
+                    scan_complete.await;
+                }
+                let Some(snapshot) = worktree
+                    .update(&mut cx, |tree, _| {
+                        tree.as_local().map(|tree| tree.snapshot())
+                    })
+                    .ok()
+                    .flatten()
+                else {
+                    return;
+                };
+                let executor = cx.background_executor().clone();
+                executor
+                    .spawn(maintain_index(
+                        index,
+                        snapshot,
+                        fs,
+                        changes_rx,
+                        executor.clone(),
+                    ))
+                    .await;
+            }
+        });
+        Self {
+            index,
+            changes_tx,
+            _maintain_index: maintain_index,
+        }
+    }
+
+    pub(crate) fn index(&self) -> Arc<RwLock<SearchIndex>> {
+        self.index.clone()
+    }
+
+    /// Queues files to be re-indexed, or removed from the index when no mtime and length are
+    /// given.
+    pub(crate) fn update(&self, changes: Vec<(Arc<Path>, Option<(SystemTime, u64)>)>) {
+        self.changes_tx.unbounded_send(changes).ok();
+    }
+}
+
+async fn maintain_index(
+    index: Arc<RwLock<SearchIndex>>,
+    snapshot: LocalSnapshot,
+    fs: Arc<dyn Fs>,
+    mut changes_rx: mpsc::UnboundedReceiver<Vec<(Arc<Path>, Option<(SystemTime, u64)>)>>,
+    executor: BackgroundExecutor,
+) {
+    let root = snapshot.abs_path().clone();
+    let index_path = index_path(&root);
+    if let Ok(bytes) = fs.load_bytes(&index_path).await {
+        if let Some(loaded) = SearchIndex::deserialize(&bytes).log_err() {
+            *index.write() = loaded;
+        }
+    }
+
+    let mut stale_files = Vec::new();
+    let mut worktree_files = HashSet::default();
+    {
+        let index = index.read();
+        for entry in snapshot.files(false, 0) {
+            worktree_files.insert(entry.path.clone());
+            if let Some(mtime) = entry.mtime {
+                if !index.is_current(&entry.path, mtime, entry.size) {
+                    stale_files.push((entry.path.clone(), Some((mtime, entry.size))));
+                }
+            }
+        }
+    }
+    index
+        .write()
+        .files
+        .retain(|path, _| worktree_files.contains(path));
+    drop(worktree_files);
+    update_index(&index, &root, stale_files, fs.as_ref()).await;
+    save_index(&index, &index_path, fs.as_ref()).await.log_err();
+
+    while let Some(changes) = changes_rx.next().await {
+        update_index(&index, &root, changes, fs.as_ref()).await;
+        // Coalesce bursts of file system events into a single write.
+        loop {
+            futures::select_biased! {
+                changes = changes_rx.next() => match changes {
+                    Some(changes) => update_index(&index, &root, changes, fs.as_ref()).await,
+                    None => break,
+                },
+                _ = executor.timer(SAVE_DEBOUNCE).fuse() => break,
+            }
+        }
+        save_index(&index, &index_path, fs.as_ref()).await.log_err();
+    }
+}
+
+async fn update_index(
+    index: &RwLock<SearchIndex>,
+    root: &Path,
+    changes: Vec<(Arc<Path>, Option<(SystemTime, u64)>)>,
+    fs: &dyn Fs,
+) {
+    for (path, version) in changes {
+        let Some((mtime, len)) = version else {
+            index.write().remove(&path);
+            continue;
+        };
+        if index.read().is_current(&path, mtime, len) {
+            continue;
+        }
+        match fs.load_bytes(&root.join(&path)).await {
+            Ok(content) => index.write().insert(path, mtime, &content),
+            Err(_) => index.write().remove(&path),
+        }
+    }
+}
+
+async fn save_index(index: &RwLock<SearchIndex>, index_path: &Path, fs: &dyn Fs) -> Result<()> {
+    let bytes = index.read().serialize();
+    fs.create_dir(&SEARCH_INDEX_DIR).await?;
+    let temp_path = index_path.with_extension("tmp");
+    let mut reader = bytes.as_slice();
+    fs.create_file_with(&temp_path, Pin::new(&mut reader))
+        .await?;
+    fs.rename(
+        &temp_path,
+        index_path,
+        RenameOptions {
+            overwrite: true,
+            ignore_if_exists: false,
+        },
+    )
+    .await
+}
+
+fn index_path(worktree_abs_path: &Path) -> PathBuf {
+    let digest = Sha256::digest(worktree_abs_path.to_string_lossy().as_bytes());
+    SEARCH_INDEX_DIR.join(format!("{digest:x}.bin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_query(query: &str) -> SearchQuery {
+        SearchQuery::text(query, false, false, false, Vec::new(), Vec::new()).unwrap()
+    }
+
+    fn regex_query(query: &str, case_sensitive: bool) -> SearchQuery {
+        SearchQuery::regex(query, false, case_sensitive, false, Vec::new(), Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_regex_literals() {
+        assert_eq!(
+            regex_literals(r"foo\(\d+\)bar", true),
+            Some(vec!["foo(".to_string(), ")bar".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"colou?r", true),
+            Some(vec!["colo".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"ab+cde", true),
+            Some(vec!["cde".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"(optional)?[xyz]+needle", true),
+            Some(vec!["needle".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"fn [a-z\]]+\s*main", true),
+            Some(vec!["fn ".to_string(), "main".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"Kelvin", false),
+            Some(vec!["elvin".to_string()])
+        );
+        assert_eq!(
+            regex_literals(r"x{100}\x41\pLyz\p{Greek}[[:alpha:]]tail", true),
+            Some(vec!["tail".to_string()])
+        );
+        assert_eq!(regex_literals(r"foo|bar", true), None);
+        assert_eq!(regex_literals(r"(?i)foo", true), None);
+
+        assert_eq!(QueryTrigrams::new(&regex_query(r"\w+\d", true)), None);
+        assert_eq!(QueryTrigrams::new(&text_query("ab")), None);
+        assert!(QueryTrigrams::new(&text_query("abc")).is_some());
+    }
+
+    #[test]
+    fn test_index_excludes_files_without_query_trigrams() {
+        let mtime = UNIX_EPOCH + Duration::from_secs(100);
+        let a = b"fn main() { println!(\"Hello\"); }";
+        let b = b"struct Goodbye;";
+        let (a_len, b_len) = (a.len() as u64, b.len() as u64);
+        let mut index = SearchIndex::default();
+        index.insert(Path::new("a.rs").into(), mtime, a);
+        index.insert(Path::new("b.rs").into(), mtime, b);
+
+        let query = QueryTrigrams::new(&text_query("HELLO")).unwrap();
+        assert!(!index.excludes(Path::new("a.rs"), Some(mtime), a_len, &query));
+        assert!(index.excludes(Path::new("b.rs"), Some(mtime), b_len, &query));
+        // Stale and unknown files are never excluded.
+        let new_mtime = mtime + Duration::from_secs(1);
+        assert!(!index.excludes(Path::new("b.rs"), Some(new_mtime), b_len, &query));
+        assert!(!index.excludes(Path::new("c.rs"), Some(mtime), b_len, &query));
+
+        let query = QueryTrigrams::new(&regex_query(r"println!\(.*\)", true)).unwrap();
+        assert!(!index.excludes(Path::new("a.rs"), Some(mtime), a_len, &query));
+        assert!(index.excludes(Path::new("b.rs"), Some(mtime), b_len, &query));
+
+        let index = SearchIndex::deserialize(&index.serialize()).unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(index.is_current(Path::new("a.rs"), mtime, a_len));
+        assert!(!index.excludes(Path::new("a.rs"), Some(mtime), a_len, &query));
+        assert!(index.excludes(Path::new("b.rs"), Some(mtime), b_len, &query));
+
+        assert!(SearchIndex::deserialize(b"ZSTI").is_err());
+        assert!(SearchIndex::deserialize(b"garbage").is_err());
+    }
+
+    #[test]
+    fn test_index_detects_rewrites_with_the_same_mtime() {
+        let mtime = UNIX_EPOCH + Duration::from_secs(100);
+        let mut index = SearchIndex::default();
+        index.insert(Path::new("a.rs").into(), mtime, b"struct Goodbye;");
+
+        // The file is rewritten within the mtime resolution of the file system, so only its
+        // length tells the entry apart from the indexed contents.
+        let rewritten = b"struct Goodbye; // Hello";
+        let rewritten_len = rewritten.len() as u64;
+        let query = QueryTrigrams::new(&text_query("hello")).unwrap();
+        assert!(!index.is_current(Path::new("a.rs"), mtime, rewritten_len));
+        assert!(!index.excludes(Path::new("a.rs"), Some(mtime), rewritten_len, &query));
+
+        index.insert(Path::new("a.rs").into(), mtime, rewritten);
+        assert!(index.is_current(Path::new("a.rs"), mtime, rewritten_len));
+        assert!(!index.excludes(Path::new("a.rs"), Some(mtime), rewritten_len, &query));
+        let query = QueryTrigrams::new(&text_query("farewell")).unwrap();
+        assert!(index.excludes(Path::new("a.rs"), Some(mtime), rewritten_len, &query));
+    }
+}
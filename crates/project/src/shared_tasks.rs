@@ -0,0 +1,306 @@
+//! Running the tasks of the host of a shared project on behalf of its guests.
+//!
+//! Guests list the host's tasks and ask the host to run one of them. The host runs the task
+//! in its own terminal, and shares that terminal with the guests, letting the one that asked
+//! for the task type into it. As guests may edit the tasks of a project, hosts have to opt in
+//! with the `guest_tasks` setting.
+
+use crate::{project_settings::ProjectSettings, Event, Project, TaskSourceKind};
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use gpui::{AsyncAppContext, Model, ModelContext};
+use language::{
+    proto::{deserialize_anchor, serialize_anchor},
+    BufferId, Location, Point, ToPoint,
+};
+use settings::Settings;
+use std::{path::PathBuf, sync::Arc};
+use task::{SpawnInTerminal, Task, TaskContext, TaskId, TaskVariables};
+use util::ResultExt;
+use worktree::WorktreeId;
+
+/// A task of the host of a remote project. It can only be run by the host.
+struct HostTask {
+    id: TaskId,
+    name: String,
+}
+
+impl Task for HostTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&str> {
+        None
+    }
+
+    fn exec(&self, _: TaskContext) -> Option<SpawnInTerminal> {
+        None
+    }
+}
+
+impl Project {
+    /// Lists the tasks of the host, in a remote project.
+    pub fn host_tasks(
+        &self,
+        cx: &mut ModelContext<Self>,
+    ) -> gpui::Task<Result<Vec<(TaskSourceKind, Arc<dyn Task>)>>> {
+        let Some(project_id) = self.remote_id().filter(|_| self.is_remote()) else {
+            return gpui::Task::ready(Err(anyhow!("not a remote project")));
+        };
+        let request = self.client.request(proto::GetProjectTasks { project_id });
+        cx.background_executor().spawn(async move {
+            let response = request.await?;
+            Ok(response
+                .tasks
+                .into_iter()
+                .map(|task| {
+                    let kind = match task.source_path {
+                        Some(path) => TaskSourceKind::AbsPath(PathBuf::from(path)),
+                        None => TaskSourceKind::Buffer,
+                    };
+                    let task: Arc<dyn Task> = Arc::new(HostTask {
+                        id: TaskId(task.id),
+                        name: task.name,
+                    });
+                    (kind, task)
+                })
+                .collect())
+        })
+    }
+
+    /// Asks the host of a remote project to run one of its tasks. The host shares the terminal
+    /// running it, which then opens in the terminal panel.
+    ///
+    /// The host derives the task's variables, e.g. `ZED_FILE`, from the given location.
+    pub fn run_host_task(
+        &mut self,
+        task_id: TaskId,
+        location: Option<Location>,
+        cx: &mut ModelContext<Self>,
+    ) -> gpui::Task<Result<()>> {
+        let Some(project_id) = self.remote_id().filter(|_| self.is_remote()) else {
+            return gpui::Task::ready(Err(anyhow!("not a remote project")));
+        };
+        if self.is_read_only() {
+            return gpui::Task::ready(Err(anyhow!("cannot run tasks in a read-only project")));
+        }
+
+        self.last_host_task = Some(task_id.clone());
+        let location = location.map(|location| proto::ProjectTaskLocation {
+            buffer_id: location.buffer.read(cx).remote_id().into(),
+            start: Some(serialize_anchor(&location.range.start)),
+            end: Some(serialize_anchor(&location.range.end)),
+        });
+        let request = self.client.request(proto::RunProjectTask {
+            project_id,
+            task_id: task_id.0,
+            location,
+        });
+        cx.background_executor().spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    /// The last task this guest asked the host to run.
+    pub fn last_host_task(&self) -> Option<&TaskId> {
+        self.last_host_task.as_ref()
+    }
+
+    /// The variables describing a location to the tasks run there, e.g. `ZED_FILE` and
+    /// `ZED_ROW`, along with the ones provided by the language of its buffer.
+    pub fn task_variables(&self, location: Location, cx: &mut ModelContext<Self>) -> TaskVariables {
+        let buffer = location.buffer.read(cx);
+        let Point { row, column } = location.range.start.to_point(buffer);
+        let selected_text = buffer
+            .text_for_range(location.range.clone())
+            .collect::<String>();
+        let file = buffer.file().cloned();
+        let language = buffer.language().cloned();
+
+        let mut task_variables = TaskVariables::from_iter([
+            ("ZED_ROW".into(), (row + 1).to_string()),
+            ("ZED_COLUMN".into(), (column + 1).to_string()),
+            ("ZED_SELECTED_TEXT".into(), selected_text),
+        ]);
+        if let Some(file) = file.as_ref().and_then(|file| file.as_local()) {
+            task_variables.0.insert(
+                "ZED_FILE".into(),
+                file.abs_path(cx).to_string_lossy().to_string(),
+            );
+        }
+        let worktree = file
+            .and_then(|file| self.worktree_for_id(WorktreeId::from_usize(file.worktree_id()), cx));
+        if let Some(worktree) = worktree {
+            task_variables.0.insert(
+                "ZED_WORKTREE_ROOT".into(),
+                worktree.read(cx).abs_path().to_string_lossy().to_string(),
+            );
+        }
+        if let Some(language_context) = language
+            .and_then(|language| language.context_provider())
+            .and_then(|provider| provider.build_context(location, cx).ok())
+        {
+            task_variables.0.extend(language_context.0);
+        }
+        task_variables
+    }
+
+    /// The tasks guests may run: everything but the one-off commands typed by the host.
+    fn tasks_for_guests(
+        &self,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<Vec<(TaskSourceKind, Arc<dyn Task>)>> {
+        // Only the host's user settings count, as guests with write access may edit the
+        // settings of the project.
+        if !ProjectSettings::get_global(cx).guest_tasks.enabled() {
+            return Err(anyhow!("the host doesn't let guests run its tasks"));
+        }
+        Ok(self
+            .task_inventory()
+            .update(cx, |inventory, cx| {
+                inventory.list_tasks(None, None, false, cx)
+            })
+            .into_iter()
+            .filter(|(kind, _)| *kind != TaskSourceKind::UserInput)
+            .collect())
+    }
+
+    /// The context the host runs a task requested by a guest in. It is built by the host, from
+    /// the buffer location the guest ran the task at, as task variables end up in the
+    /// environment of the spawned process.
+    fn guest_task_context(
+        &self,
+        kind: &TaskSourceKind,
+        location: Option<Location>,
+        cx: &mut ModelContext<Self>,
+    ) -> TaskContext {
+        let worktree = match kind {
+            TaskSourceKind::Worktree { id, .. } => self.worktree_for_id(*id, cx),
+            _ => {
+                let mut worktrees = self.visible_worktrees(cx);
+                worktrees.next().filter(|_| worktrees.next().is_none())
+            }
+        };
+        let worktree_root = worktree.map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+        let mut task_variables = worktree_root
+            .iter()
+            .map(|root| {
+                (
+                    "ZED_WORKTREE_ROOT".to_string(),
+                    root.to_string_lossy().to_string(),
+                )
+            })
+            .collect::<TaskVariables>();
+        if let Some(location) = location {
+            task_variables.0.extend(self.task_variables(location, cx).0);
+        }
+        TaskContext {
+            cwd: worktree_root,
+            task_variables,
+        }
+    }
+
+    /// Resolves the location a guest ran a task at, which must be in a buffer shared with it.
+    async fn guest_task_location(
+        this: &Model<Self>,
+        peer_id: proto::PeerId,
+        location: proto::ProjectTaskLocation,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Location> {
+        let buffer_id = BufferId::new(location.buffer_id)?;
+        let buffer = this.update(cx, |this, _| {
+            this.shared_buffers
+                .get(&peer_id)
+                .filter(|buffer_ids| buffer_ids.contains(&buffer_id))
+                .and_then(|_| this.buffer_for_id(buffer_id))
+                .with_context(|| format!("buffer {buffer_id} is not shared with {peer_id:?}"))
+        })??;
+        let start = location
+            .start
+            .and_then(deserialize_anchor)
+            .context("invalid start")?;
+        let end = location
+            .end
+            .and_then(deserialize_anchor)
+            .context("invalid end")?;
+        buffer
+            .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+            .await?;
+        Ok(Location {
+            buffer,
+            range: start..end,
+        })
+    }
+
+    pub(crate) async fn handle_get_project_tasks(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::GetProjectTasks>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetProjectTasksResponse> {
+        this.update(&mut cx, |this, cx| {
+            Ok(proto::GetProjectTasksResponse {
+                tasks: this
+                    .tasks_for_guests(cx)?
+                    .into_iter()
+                    .map(|(kind, task)| proto::ProjectTask {
+                        id: task.id().0.clone(),
+                        name: task.name().to_string(),
+                        source_path: match kind {
+                            TaskSourceKind::AbsPath(abs_path)
+                            | TaskSourceKind::Worktree { abs_path, .. } => {
+                                Some(abs_path.to_string_lossy().to_string())
+                            }
+                            TaskSourceKind::UserInput | TaskSourceKind::Buffer => None,
+                        },
+                    })
+                    .collect(),
+            })
+        })?
+    }
+
+    pub(crate) async fn handle_run_project_task(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RunProjectTask>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let peer_id = envelope.original_sender_id()?;
+        let task_id = TaskId(envelope.payload.task_id);
+        let location = match envelope.payload.location {
+            Some(location) => Self::guest_task_location(&this, peer_id, location, &mut cx)
+                .await
+                .log_err(),
+            None => None,
+        };
+        this.update(&mut cx, |this, cx| {
+            let user_id = this
+                .collaborators
+                .get(&peer_id)
+                .ok_or_else(|| anyhow!("unknown peer {peer_id:?}"))?
+                .user_id;
+            let (kind, task) = this
+                .tasks_for_guests(cx)?
+                .into_iter()
+                .find(|(_, task)| task.id() == &task_id)
+                .ok_or_else(|| anyhow!("no task with id {task_id:?}"))?;
+            let task_context = this.guest_task_context(&kind, location, cx);
+            let spawn_in_terminal = task
+                .exec(task_context.clone())
+                .ok_or_else(|| anyhow!("task {task_id:?} cannot be run in a terminal"))?;
+
+            this.task_inventory().update(cx, |inventory, _| {
+                inventory.task_scheduled(task_id.clone(), task_context)
+            });
+            this.terminals.guest_task_runs.insert(task_id, user_id);
+            cx.emit(Event::SpawnTask(spawn_in_terminal));
+            Ok(proto::Ack {})
+        })?
+    }
+}
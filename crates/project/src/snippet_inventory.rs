@@ -0,0 +1,261 @@
+//! Project-wide storage of user-defined snippets, loaded from VS Code-compatible snippet files.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{AppContext, Context, Model, ModelContext, Task};
+use lsp::LanguageServerId;
+use snippet::{parse_snippet_file, UserSnippet};
+use util::ResultExt;
+use worktree::WorktreeId;
+
+/// The server id of completions that don't come from a language server, such as
+/// user snippets. No language server is ever assigned this id.
+pub const NON_LSP_COMPLETION_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+/// Inventory tracks the user snippets available for a given project.
+pub struct SnippetInventory {
+    sources: Vec<SnippetSource>,
+    _watch_global_snippets: Option<Task<()>>,
+}
+
+struct SnippetSource {
+    kind: SnippetSourceKind,
+    /// The language the file's snippets apply to, derived from its name.
+    /// `None` for `.code-snippets` files, which apply to every language.
+    language: Option<String>,
+    snippets: Vec<Arc<UserSnippet>>,
+}
+
+/// Kind of a file the snippets are loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnippetSourceKind {
+    /// A file in `~/.config/zed/snippets`, applicable to any project.
+    AbsPath(PathBuf),
+    /// A file in a worktree's `.zed/snippets` directory.
+    Worktree { id: WorktreeId, abs_path: PathBuf },
+}
+
+impl SnippetSourceKind {
+    fn abs_path(&self) -> &Path {
+        match self {
+            Self::AbsPath(abs_path) | Self::Worktree { abs_path, .. } => abs_path,
+        }
+    }
+
+    fn worktree(&self) -> Option<WorktreeId> {
+        match self {
+            Self::Worktree { id, .. } => Some(*id),
+            Self::AbsPath(_) => None,
+        }
+    }
+}
+
+/// Whether the given path has the extension of a snippet file.
+pub fn is_snippet_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("json" | "code-snippets")
+    )
+}
+
+/// Converts a language name into the identifier used to name snippet files,
+/// e.g. `Rust` into `rust` and `C++` into `cpp`.
+fn language_id(language_name: &str) -> String {
+    match language_name {
+        "C++" => "cpp".into(),
+        "C#" => "csharp".into(),
+        _ => language_name.to_lowercase().replace(' ', ""),
+    }
+}
+
+impl SnippetInventory {
+    pub fn new(cx: &mut AppContext) -> Model<Self> {
+        cx.new_model(|_| Self {
+            sources: Vec::new(),
+            _watch_global_snippets: None,
+        })
+    }
+
+    /// Loads every snippet file in the given directory, reloading them whenever the directory changes.
+    pub fn watch_global_snippets(
+        &mut self,
+        fs: Arc<dyn Fs>,
+        snippets_dir: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self._watch_global_snippets = Some(cx.spawn(|this, mut cx| async move {
+            fs.create_dir(&snippets_dir).await.log_err();
+            let mut events = fs.watch(&snippets_dir, Duration::from_millis(100)).await;
+            loop {
+                let mut files = Vec::new();
+                if let Some(mut paths) = fs.read_dir(&snippets_dir).await.log_err() {
+                    while let Some(path) = paths.next().await {
+                        let Some(path) = path.log_err() else {
+                            continue;
+                        };
+                        if !is_snippet_file(&path) {
+                            continue;
+                        }
+                        if let Some(content) = fs.load(&path).await.log_err() {
+                            files.push((path, content));
+                        }
+                    }
+                }
+
+                let updated = this.update(&mut cx, |this, cx| {
+                    this.sources
+                        .retain(|source| matches!(source.kind, SnippetSourceKind::Worktree { .. }));
+                    for (path, content) in files {
+                        this.set_snippet_file(SnippetSourceKind::AbsPath(path), &content)
+                            .log_err();
+                    }
+                    cx.notify();
+                });
+                if updated.is_err() || events.next().await.is_none() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// Adds or replaces the snippets loaded from the file at the kind's path.
+    pub fn set_snippet_file(&mut self, kind: SnippetSourceKind, content: &str) -> Result<()> {
+        let snippets = parse_snippet_file(content)?;
+        let abs_path = kind.abs_path();
+        let language = if abs_path.extension() == Some(OsStr::new("code-snippets")) {
+            None
+        } else {
+            abs_path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .map(|stem| stem.to_lowercase())
+        };
+
+        self.remove_snippet_file(abs_path);
+        self.sources.push(SnippetSource {
+            language,
+            snippets: snippets.into_iter().map(Arc::new).collect(),
+            kind,
+        });
+        Ok(())
+    }
+
+    pub fn remove_snippet_file(&mut self, abs_path: &Path) {
+        self.sources
+            .retain(|source| source.kind.abs_path() != abs_path);
+    }
+
+    pub fn remove_worktree_sources(&mut self, worktree: WorktreeId) {
+        self.sources
+            .retain(|source| source.kind.worktree() != Some(worktree));
+    }
+
+    /// Returns the snippets that apply to the given language, including the ones
+    /// defined in the given worktree. Worktree snippets are listed first.
+    pub fn snippets_for(
+        &self,
+        language_name: Option<&str>,
+        worktree: Option<WorktreeId>,
+    ) -> Vec<Arc<UserSnippet>> {
+        let language_id = language_name.map(language_id);
+        let mut sources = self
+            .sources
+            .iter()
+            .filter(|source| {
+                source
+                    .kind
+                    .worktree()
+                    .map_or(true, |id| Some(id) == worktree)
+            })
+            .filter(|source| match &source.language {
+                Some(language) => language_id.as_ref() == Some(language),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|source| source.kind.worktree().is_none());
+
+        sources
+            .into_iter()
+            .flat_map(|source| source.snippets.iter())
+            .filter(|snippet| {
+                language_id
+                    .as_deref()
+                    .map_or(snippet.scopes.is_none(), |language_id| {
+                        snippet.applies_to_language(language_id)
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_snippets_for_language_and_worktree(cx: &mut TestAppContext) {
+        let inventory = cx.update(SnippetInventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .set_snippet_file(
+                    SnippetSourceKind::AbsPath("/snippets/rust.json".into()),
+                    r##"{ "Test": { "prefix": "test", "body": "#[test]" } }"##,
+                )
+                .unwrap();
+            inventory
+                .set_snippet_file(
+                    SnippetSourceKind::AbsPath("/snippets/global.code-snippets".into()),
+                    r#"{
+                        "Todo": { "prefix": "todo", "body": "TODO: $0" },
+                        "Log": { "prefix": "log", "body": "console.log($1)", "scope": "javascript,typescript" }
+                    }"#,
+                )
+                .unwrap();
+            inventory
+                .set_snippet_file(
+                    SnippetSourceKind::Worktree {
+                        id: worktree_1,
+                        abs_path: "/project/.zed/snippets/rust.json".into(),
+                    },
+                    r##"{ "Bench": { "prefix": "bench", "body": "#[bench]" } }"##,
+                )
+                .unwrap();
+        });
+
+        let names = |language: Option<&str>, worktree: Option<WorktreeId>| {
+            inventory.read_with(cx, |inventory, _| {
+                inventory
+                    .snippets_for(language, worktree)
+                    .iter()
+                    .map(|snippet| snippet.name.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            names(Some("Rust"), Some(worktree_1)),
+            ["Bench", "Test", "Todo"]
+        );
+        assert_eq!(names(Some("Rust"), Some(worktree_2)), ["Test", "Todo"]);
+        assert_eq!(names(Some("TypeScript"), None), ["Log", "Todo"]);
+        assert_eq!(names(None, None), ["Todo"]);
+
+        inventory.update(cx, |inventory, _| {
+            inventory.remove_worktree_sources(worktree_1);
+            inventory.remove_snippet_file(Path::new("/snippets/rust.json"));
+        });
+        assert_eq!(names(Some("Rust"), Some(worktree_1)), ["Todo"]);
+    }
+}
@@ -1,20 +1,64 @@
-use crate::Project;
+use crate::{Event, Project};
+use anyhow::{anyhow, bail, Result};
+use client::{proto, Client, TypedEnvelope};
 use collections::HashMap;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use futures::{channel::mpsc, StreamExt};
+use gpui::{
+    AnyWindowHandle, AppContext, AsyncAppContext, Context, Entity, Model, ModelContext,
+    Subscription, WeakModel,
+};
 use settings::Settings;
 use smol::channel::bounded;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use task::TaskId;
 use terminal::{
     terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
-    SpawnTask, TaskState, Terminal, TerminalBuilder,
+    RestoredScrollback, SharedTerminalState, SpawnTask, TaskState, Terminal, TerminalBuilder,
 };
 use util::ResultExt;
 
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
+/// How often the output of a shared terminal is sent to collaborators, at most.
+const SHARED_TERMINAL_UPDATE_INTERVAL: Duration = Duration::from_millis(30);
+
+#[derive(Default)]
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// Local terminals shared with the collaborators of the project, by entity id.
+    shared: HashMap<u64, SharedTerminal>,
+    /// Terminals shared by the host of a remote project, by their entity id on the host.
+    remote: HashMap<u64, RemoteTerminal>,
+    /// Updates of remote terminals that arrived before their contents did, as a guest
+    /// fetches the contents of the terminals shared before it joined.
+    pending_remote_updates: HashMap<u64, Vec<proto::UpdateTerminal>>,
+    /// Tasks run on behalf of collaborators, with the user that asked for each. Their
+    /// terminals are shared once created.
+    pub(crate) guest_task_runs: HashMap<TaskId, u64>,
+}
+
+struct SharedTerminal {
+    terminal: WeakModel<Terminal>,
+    /// The collaborators allowed to type into the terminal.
+    writer_user_ids: BTreeSet<u64>,
+    /// The number of updates sent to collaborators so far.
+    version: u64,
+    update_scheduled: bool,
+    _subscriptions: [Subscription; 2],
+}
+
+struct RemoteTerminal {
+    terminal: Model<Terminal>,
+    /// The version of the host's terminal the contents are at.
+    version: u64,
+    /// Updates that arrived before the ones preceding them, by version.
+    deferred_updates: BTreeMap<u64, proto::UpdateTerminal>,
 }
 
 impl Project {
@@ -22,6 +66,7 @@ impl Project {
         &mut self,
         working_directory: Option<PathBuf>,
         spawn_task: Option<SpawnTask>,
+        restored_scrollback: Option<RestoredScrollback>,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
     ) -> anyhow::Result<Model<Terminal>> {
@@ -31,6 +76,7 @@ impl Project {
         );
 
         let is_terminal = spawn_task.is_none();
+        let task_id = spawn_task.as_ref().map(|spawn_task| spawn_task.id.clone());
         let settings = TerminalSettings::get_global(cx);
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
@@ -73,6 +119,7 @@ impl Project {
             Some(settings.blinking.clone()),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            restored_scrollback,
             window,
             completion_tx,
         )
@@ -97,6 +144,18 @@ impl Project {
             })
             .detach();
 
+            if let Some(user_id) =
+                task_id.and_then(|task_id| self.terminals.guest_task_runs.remove(&task_id))
+            {
+                if self
+                    .share_terminal(&terminal_handle, cx)
+                    .log_err()
+                    .is_some()
+                {
+                    self.set_terminal_writer(&terminal_handle, user_id, true, cx);
+                }
+            }
+
             // if the terminal is not a task, activate full Python virtual environment
             if is_terminal {
                 if let Some(python_settings) = &python_settings.as_option() {
@@ -208,6 +267,449 @@ impl Project {
     pub fn local_terminal_handles(&self) -> &Vec<WeakModel<terminal::Terminal>> {
         &self.terminals.local_handles
     }
+
+    /// Shares a local terminal with the collaborators of the project. They see its output,
+    /// and can type into it once allowed to with [`Project::set_terminal_writer`].
+    pub fn share_terminal(
+        &mut self,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let Some(project_id) = self.remote_id().filter(|_| self.is_local()) else {
+            bail!("only terminals of a shared project can be shared");
+        };
+        let terminal_id = terminal.entity_id().as_u64();
+        if self.terminals.shared.contains_key(&terminal_id) {
+            return Ok(());
+        }
+
+        let (output, title, (columns, lines)) = terminal.update(cx, |terminal, _| {
+            (
+                terminal.start_sharing(),
+                terminal.title(false),
+                terminal.grid_size(),
+            )
+        });
+        self.client.send(proto::ShareTerminal {
+            project_id,
+            terminal: Some(proto::SharedTerminal {
+                id: terminal_id,
+                title,
+                columns: columns as u32,
+                lines: lines as u32,
+                writer_user_ids: Vec::new(),
+                output,
+                version: 0,
+            }),
+        })?;
+
+        let subscriptions = [
+            cx.subscribe(terminal, move |project, _, event, cx| match event {
+                terminal::Event::Wakeup | terminal::Event::TitleChanged => {
+                    project.schedule_terminal_update(terminal_id, cx)
+                }
+                _ => {}
+            }),
+            cx.observe_release(terminal, move |project, _, cx| {
+                project.unshare_terminal(terminal_id, cx)
+            }),
+        ];
+        self.terminals.shared.insert(
+            terminal_id,
+            SharedTerminal {
+                terminal: terminal.downgrade(),
+                writer_user_ids: BTreeSet::new(),
+                version: 0,
+                update_scheduled: false,
+                _subscriptions: subscriptions,
+            },
+        );
+        cx.notify();
+        Ok(())
+    }
+
+    pub fn unshare_terminal(&mut self, terminal_id: u64, cx: &mut ModelContext<Self>) {
+        let Some(shared) = self.terminals.shared.remove(&terminal_id) else {
+            return;
+        };
+        if let Some(terminal) = shared.terminal.upgrade() {
+            terminal.update(cx, |terminal, _| terminal.stop_sharing());
+        }
+        if let Some(project_id) = self.remote_id() {
+            self.client
+                .send(proto::UnshareTerminal {
+                    project_id,
+                    terminal_id,
+                })
+                .log_err();
+        }
+        cx.notify();
+    }
+
+    pub fn is_terminal_shared(&self, terminal: &Model<Terminal>) -> bool {
+        self.terminals
+            .shared
+            .contains_key(&terminal.entity_id().as_u64())
+    }
+
+    /// The collaborators allowed to type into a shared terminal.
+    pub fn terminal_writers(&self, terminal: &Model<Terminal>) -> Option<&BTreeSet<u64>> {
+        self.terminals
+            .shared
+            .get(&terminal.entity_id().as_u64())
+            .map(|shared| &shared.writer_user_ids)
+    }
+
+    /// Allows or forbids a collaborator to type into a shared terminal. Guests that can't
+    /// edit the project are never allowed to, regardless.
+    pub fn set_terminal_writer(
+        &mut self,
+        terminal: &Model<Terminal>,
+        user_id: u64,
+        allowed: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let terminal_id = terminal.entity_id().as_u64();
+        let Some(shared) = self.terminals.shared.get_mut(&terminal_id) else {
+            return;
+        };
+        let changed = if allowed {
+            shared.writer_user_ids.insert(user_id)
+        } else {
+            shared.writer_user_ids.remove(&user_id)
+        };
+        if changed {
+            self.send_terminal_update(terminal_id, cx);
+            cx.notify();
+        }
+    }
+
+    /// The terminals shared by the host, in a remote project.
+    pub fn remote_terminals(&self) -> impl Iterator<Item = &Model<Terminal>> {
+        self.terminals
+            .remote
+            .values()
+            .map(|remote| &remote.terminal)
+    }
+
+    pub(crate) fn stop_sharing_terminals(&mut self, cx: &mut AppContext) {
+        for (_, shared) in self.terminals.shared.drain() {
+            if let Some(terminal) = shared.terminal.upgrade() {
+                terminal.update(cx, |terminal, _| terminal.stop_sharing());
+            }
+        }
+    }
+
+    pub(crate) fn close_remote_terminals(&mut self, cx: &mut AppContext) {
+        self.terminals.pending_remote_updates.clear();
+        for (_, remote) in self.terminals.remote.drain() {
+            remote
+                .terminal
+                .update(cx, |_, cx| cx.emit(terminal::Event::CloseTerminal));
+        }
+    }
+
+    fn schedule_terminal_update(&mut self, terminal_id: u64, cx: &mut ModelContext<Self>) {
+        let Some(shared) = self.terminals.shared.get_mut(&terminal_id) else {
+            return;
+        };
+        if shared.update_scheduled {
+            return;
+        }
+
+        shared.update_scheduled = true;
+        cx.spawn(|project, mut cx| async move {
+            cx.background_executor()
+                .timer(SHARED_TERMINAL_UPDATE_INTERVAL)
+                .await;
+            project.update(&mut cx, |project, cx| {
+                project.send_terminal_update(terminal_id, cx)
+            })
+        })
+        .detach();
+    }
+
+    fn send_terminal_update(&mut self, terminal_id: u64, cx: &mut ModelContext<Self>) {
+        let Some(project_id) = self.remote_id() else {
+            return;
+        };
+        let Some(shared) = self.terminals.shared.get_mut(&terminal_id) else {
+            return;
+        };
+        shared.update_scheduled = false;
+        let Some(terminal) = shared.terminal.upgrade() else {
+            return;
+        };
+        shared.version += 1;
+
+        let (output, title, (columns, lines)) = terminal.update(cx, |terminal, _| {
+            (
+                terminal.take_shared_output(),
+                terminal.title(false),
+                terminal.grid_size(),
+            )
+        });
+        self.client
+            .send(proto::UpdateTerminal {
+                project_id,
+                terminal_id,
+                title,
+                columns: columns as u32,
+                lines: lines as u32,
+                writer_user_ids: shared.writer_user_ids.iter().copied().collect(),
+                output,
+                version: shared.version,
+            })
+            .log_err();
+    }
+
+    /// Fetches the terminals the host shared before this guest joined the project.
+    pub(crate) fn fetch_shared_terminals(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(project_id) = self.remote_id() else {
+            return;
+        };
+        let request = self
+            .client
+            .request(proto::GetSharedTerminals { project_id });
+        cx.spawn(|project, mut cx| async move {
+            let response = request.await?;
+            project.update(&mut cx, |project, cx| {
+                for terminal in response.terminals {
+                    project.add_remote_terminal(terminal, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_remote_terminal(&mut self, shared: proto::SharedTerminal, cx: &mut ModelContext<Self>) {
+        let Some(project_id) = self.remote_id() else {
+            return;
+        };
+        let terminal_id = shared.id;
+        if self.terminals.remote.contains_key(&terminal_id) {
+            return;
+        }
+
+        let state = self.shared_terminal_state(
+            shared.title,
+            shared.columns,
+            shared.lines,
+            &shared.writer_user_ids,
+        );
+        let max_scroll_history_lines = TerminalSettings::get_global(cx).max_scroll_history_lines;
+        let (input_tx, mut input_rx) = mpsc::unbounded::<Vec<u8>>();
+        let terminal = cx.new_model(|cx| {
+            let mut terminal =
+                TerminalBuilder::new_remote(state, max_scroll_history_lines, input_tx)
+                    .subscribe(cx);
+            terminal.advance_remote_output(&shared.output, cx);
+            terminal
+        });
+
+        // Send the input in order, batching the keystrokes typed while waiting for the host.
+        let client = self.client.clone();
+        cx.background_executor()
+            .spawn(async move {
+                while let Some(mut input) = input_rx.next().await {
+                    while let Ok(Some(more_input)) = input_rx.try_next() {
+                        input.extend(more_input);
+                    }
+                    client
+                        .request(proto::TerminalInput {
+                            project_id,
+                            terminal_id,
+                            input,
+                        })
+                        .await
+                        .log_err();
+                }
+            })
+            .detach();
+
+        self.terminals.remote.insert(
+            terminal_id,
+            RemoteTerminal {
+                terminal: terminal.clone(),
+                version: shared.version,
+                deferred_updates: BTreeMap::new(),
+            },
+        );
+        let pending_updates = self
+            .terminals
+            .pending_remote_updates
+            .remove(&terminal_id)
+            .unwrap_or_default();
+        for update in pending_updates {
+            self.apply_remote_terminal_update(update, cx);
+        }
+        cx.emit(Event::TerminalShared(terminal));
+    }
+
+    /// Applies the updates of a remote terminal in the order the host sent them, skipping
+    /// those already contained in the contents the terminal was created with.
+    fn apply_remote_terminal_update(
+        &mut self,
+        update: proto::UpdateTerminal,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(remote) = self.terminals.remote.get_mut(&update.terminal_id) else {
+            self.terminals
+                .pending_remote_updates
+                .entry(update.terminal_id)
+                .or_default()
+                .push(update);
+            return;
+        };
+        if update.version > remote.version {
+            remote.deferred_updates.insert(update.version, update);
+        }
+
+        let mut updates = Vec::new();
+        while let Some(update) = remote.deferred_updates.remove(&(remote.version + 1)) {
+            remote.version = update.version;
+            updates.push(update);
+        }
+        let terminal = remote.terminal.clone();
+        for update in updates {
+            let state = self.shared_terminal_state(
+                update.title,
+                update.columns,
+                update.lines,
+                &update.writer_user_ids,
+            );
+            terminal.update(cx, |terminal, cx| {
+                terminal.set_shared_state(state, cx);
+                terminal.advance_remote_output(&update.output, cx);
+            });
+        }
+    }
+
+    fn shared_terminal_state(
+        &self,
+        title: String,
+        columns: u32,
+        lines: u32,
+        writer_user_ids: &[u64],
+    ) -> SharedTerminalState {
+        let is_writer = self
+            .client
+            .user_id()
+            .map_or(false, |user_id| writer_user_ids.contains(&user_id));
+        SharedTerminalState {
+            title,
+            columns: columns as usize,
+            lines: lines as usize,
+            writable: is_writer && !self.is_read_only(),
+        }
+    }
+
+    pub(crate) async fn handle_share_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ShareTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let terminal = envelope
+            .payload
+            .terminal
+            .ok_or_else(|| anyhow!("missing terminal"))?;
+        this.update(&mut cx, |this, cx| this.add_remote_terminal(terminal, cx))
+    }
+
+    pub(crate) async fn handle_update_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.apply_remote_terminal_update(envelope.payload, cx)
+        })
+    }
+
+    pub(crate) async fn handle_unshare_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnshareTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let terminal_id = envelope.payload.terminal_id;
+            this.terminals.pending_remote_updates.remove(&terminal_id);
+            if let Some(remote) = this.terminals.remote.remove(&terminal_id) {
+                remote
+                    .terminal
+                    .update(cx, |_, cx| cx.emit(terminal::Event::CloseTerminal));
+            }
+        })
+    }
+
+    pub(crate) async fn handle_get_shared_terminals(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::GetSharedTerminals>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSharedTerminalsResponse> {
+        this.update(&mut cx, |this, cx| {
+            let terminal_ids = this.terminals.shared.keys().copied().collect::<Vec<_>>();
+            let mut terminals = Vec::new();
+            for terminal_id in terminal_ids {
+                // Flush the output collected so far to the other guests, as the snapshot
+                // already contains it. Updates sent after it have a greater version, so the
+                // guest applies them on top of the snapshot, whichever arrives first.
+                this.send_terminal_update(terminal_id, cx);
+                let Some(shared) = this.terminals.shared.get(&terminal_id) else {
+                    continue;
+                };
+                let Some(terminal) = shared.terminal.upgrade() else {
+                    continue;
+                };
+                let terminal = terminal.read(cx);
+                let (columns, lines) = terminal.grid_size();
+                terminals.push(proto::SharedTerminal {
+                    id: terminal_id,
+                    title: terminal.title(false),
+                    columns: columns as u32,
+                    lines: lines as u32,
+                    writer_user_ids: shared.writer_user_ids.iter().copied().collect(),
+                    output: terminal.shared_snapshot(),
+                    version: shared.version,
+                });
+            }
+            proto::GetSharedTerminalsResponse { terminals }
+        })
+    }
+
+    pub(crate) async fn handle_terminal_input(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::TerminalInput>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let peer_id = envelope.original_sender_id()?;
+        this.update(&mut cx, |this, cx| {
+            let user_id = this
+                .collaborators
+                .get(&peer_id)
+                .ok_or_else(|| anyhow!("unknown peer {peer_id:?}"))?
+                .user_id;
+            let shared = this
+                .terminals
+                .shared
+                .get(&envelope.payload.terminal_id)
+                .ok_or_else(|| anyhow!("terminal is not shared"))?;
+            if !shared.writer_user_ids.contains(&user_id) {
+                bail!("not allowed to type into this terminal");
+            }
+            if let Some(terminal) = shared.terminal.upgrade() {
+                terminal.update(cx, |terminal, _| {
+                    terminal.input_bytes(envelope.payload.input)
+                });
+            }
+            Ok(proto::Ack {})
+        })?
+    }
 }
 
 // TODO: Add a few tests for adding and removing terminal tabs
@@ -194,8 +194,12 @@ messages!(
     (GetPrivateUserInfoResponse, Foreground),
     (GetProjectSymbols, Background),
     (GetProjectSymbolsResponse, Background),
+    (GetProjectTasks, Foreground),
+    (GetProjectTasksResponse, Foreground),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
+    (GetSharedTerminals, Foreground),
+    (GetSharedTerminalsResponse, Foreground),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
     (GetImplementation, Background),
@@ -259,6 +263,7 @@ messages!(
     (RespondToChannelInvite, Foreground),
     (RespondToContactRequest, Foreground),
     (RoomUpdated, Foreground),
+    (RunProjectTask, Foreground),
     (SaveBuffer, Foreground),
     (SetChannelMemberRole, Foreground),
     (SetChannelVisibility, Foreground),
@@ -268,13 +273,16 @@ messages!(
     (SendChannelMessageResponse, Background),
     (ShareProject, Foreground),
     (ShareProjectResponse, Foreground),
+    (ShareTerminal, Foreground),
     (ShowContacts, Foreground),
     (StartLanguageServer, Foreground),
     (SynchronizeBuffers, Foreground),
     (SynchronizeBuffersResponse, Foreground),
+    (TerminalInput, Foreground),
     (Test, Foreground),
     (Unfollow, Foreground),
     (UnshareProject, Foreground),
+    (UnshareTerminal, Foreground),
     (UpdateBuffer, Foreground),
     (UpdateBufferFile, Foreground),
     (UpdateChannelBuffer, Foreground),
@@ -290,6 +298,7 @@ messages!(
     (UpdateParticipantLocation, Foreground),
     (UpdateProject, Foreground),
     (UpdateProjectCollaborator, Foreground),
+    (UpdateTerminal, Foreground),
     (UpdateWorktree, Foreground),
     (UpdateWorktreeSettings, Foreground),
     (UsersResponse, Foreground),
@@ -331,7 +340,9 @@ request_messages!(
     (GetNotifications, GetNotificationsResponse),
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
+    (GetProjectTasks, GetProjectTasksResponse),
     (GetReferences, GetReferencesResponse),
+    (GetSharedTerminals, GetSharedTerminalsResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (GetUsers, UsersResponse),
     (IncomingCall, Ack),
@@ -372,6 +383,7 @@ request_messages!(
     (ResolveInlayHint, ResolveInlayHintResponse),
     (RespondToChannelInvite, Ack),
     (RespondToContactRequest, Ack),
+    (RunProjectTask, Ack),
     (SaveBuffer, BufferSaved),
     (SearchProject, SearchProjectResponse),
     (SendChannelMessage, SendChannelMessageResponse),
@@ -379,6 +391,7 @@ request_messages!(
     (SetChannelVisibility, Ack),
     (ShareProject, ShareProjectResponse),
     (SynchronizeBuffers, SynchronizeBuffersResponse),
+    (TerminalInput, Ack),
     (Test, Test),
     (UpdateBuffer, Ack),
     (UpdateParticipantLocation, Ack),
@@ -408,7 +421,9 @@ entity_messages!(
     GetDocumentHighlights,
     GetHover,
     GetProjectSymbols,
+    GetProjectTasks,
     GetReferences,
+    GetSharedTerminals,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
@@ -425,11 +440,15 @@ entity_messages!(
     RenameProjectEntry,
     ResolveCompletionDocumentation,
     ResolveInlayHint,
+    RunProjectTask,
     SaveBuffer,
     SearchProject,
+    ShareTerminal,
     StartLanguageServer,
     SynchronizeBuffers,
+    TerminalInput,
     UnshareProject,
+    UnshareTerminal,
     UpdateBuffer,
     UpdateBufferFile,
     UpdateDiagnosticSummary,
@@ -437,6 +456,7 @@ entity_messages!(
     UpdateLanguageServer,
     UpdateProject,
     UpdateProjectCollaborator,
+    UpdateTerminal,
     UpdateWorktree,
     UpdateWorktreeSettings,
     LspExtExpandMacro,
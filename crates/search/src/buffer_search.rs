@@ -4,9 +4,9 @@ use crate::{
     history::SearchHistory,
     mode::{next_mode, SearchMode},
     search_bar::render_nav_button,
-    ActivateRegexMode, ActivateTextMode, CycleMode, NextHistoryQuery, PreviousHistoryQuery,
-    ReplaceAll, ReplaceNext, SearchOptions, SelectAllMatches, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleReplace, ToggleWholeWord,
+    ActivateRegexMode, ActivateStructuralMode, ActivateTextMode, CycleMode, NextHistoryQuery,
+    PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions, SelectAllMatches,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleReplace, ToggleWholeWord,
 };
 use collections::HashMap;
 use editor::{
@@ -278,8 +278,36 @@ impl Render for BufferSearchBar {
                                             cx,
                                         )
                                     })
+                                    .map(|button| {
+                                        if supported_options.structural {
+                                            button.middle()
+                                        } else {
+                                            button.last()
+                                        }
+                                    }),
+                            )
+                            .when(supported_options.structural, |this| {
+                                this.child(
+                                    ToggleButton::new(
+                                        "search-mode-structural",
+                                        SearchMode::Structural.label(),
+                                    )
+                                    .style(ButtonStyle::Filled)
+                                    .size(ButtonSize::Large)
+                                    .selected(self.current_mode == SearchMode::Structural)
+                                    .on_click(cx.listener(move |_, _event, cx| {
+                                        cx.dispatch_action(SearchMode::Structural.action())
+                                    }))
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            SearchMode::Structural.tooltip(),
+                                            &*SearchMode::Structural.action(),
+                                            cx,
+                                        )
+                                    })
                                     .last(),
-                            ),
+                                )
+                            }),
                     )
                     .when(supported_options.replacement, |this| {
                         this.child(
@@ -388,6 +416,11 @@ impl Render for BufferSearchBar {
             .on_action(cx.listener(|this, _: &ActivateTextMode, cx| {
                 this.activate_search_mode(SearchMode::Text, cx);
             }))
+            .when(self.supported_options().structural, |this| {
+                this.on_action(cx.listener(|this, _: &ActivateStructuralMode, cx| {
+                    this.activate_search_mode(SearchMode::Structural, cx);
+                }))
+            })
             .when(self.supported_options().replacement, |this| {
                 this.on_action(cx.listener(Self::toggle_replace))
                     .when(in_replace, |this| {
@@ -489,6 +522,11 @@ impl BufferSearchBar {
         registrar.register_handler(ForDeployed(|this, _: &ActivateTextMode, cx| {
             this.activate_search_mode(SearchMode::Text, cx);
         }));
+        registrar.register_handler(ForDeployed(|this, _: &ActivateStructuralMode, cx| {
+            if this.supported_options().structural {
+                this.activate_search_mode(SearchMode::Structural, cx);
+            }
+        }));
         registrar.register_handler(ForDeployed(|this, action: &CycleMode, cx| {
             if this.supported_options().regex {
                 // If regex is not supported then search has just one mode (text) - in that case there's no point in supporting
@@ -881,7 +919,19 @@ impl BufferSearchBar {
                 let _ = done_tx.send(());
                 cx.notify();
             } else {
-                let query: Arc<_> = if self.current_mode == SearchMode::Regex {
+                let structural = self.current_mode == SearchMode::Structural
+                    && self.supported_options().structural;
+                let query: Arc<_> = if structural {
+                    match SearchQuery::structural(query, false, Vec::new(), Vec::new()) {
+                        Ok(query) => query.with_replacement(self.replacement(cx)),
+                        Err(_) => {
+                            self.query_contains_error = true;
+                            self.clear_active_searchable_item_matches(cx);
+                            cx.notify();
+                            return done_rx;
+                        }
+                    }
+                } else if self.current_mode == SearchMode::Regex {
                     match SearchQuery::regex(
                         query,
                         self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1017,7 +1067,14 @@ impl BufferSearchBar {
         }
     }
     fn cycle_mode(&mut self, _: &CycleMode, cx: &mut ViewContext<Self>) {
-        self.activate_search_mode(next_mode(&self.current_mode), cx);
+        let mut mode = next_mode(&self.current_mode);
+        // Semantic search only applies to whole projects.
+        while mode == SearchMode::Semantic
+            || (mode == SearchMode::Structural && !self.supported_options().structural)
+        {
+            mode = next_mode(&mode);
+        }
+        self.activate_search_mode(mode, cx);
     }
     fn toggle_replace(&mut self, _: &ToggleReplace, cx: &mut ViewContext<Self>) {
         if let Some(_) = &self.active_searchable_item {
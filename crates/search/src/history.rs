@@ -30,6 +30,15 @@ impl SearchHistory {
         self.selected = Some(self.history.len() - 1);
     }
 
+    /// Replaces the history with the given queries, the most recent last, selecting none of them.
+    pub fn set_queries(&mut self, queries: impl IntoIterator<Item = String>) {
+        self.history = queries.into_iter().collect();
+        self.history.dedup();
+        let excess = self.history.len().saturating_sub(SEARCH_HISTORY_LIMIT);
+        self.history.drain(..excess);
+        self.selected = None;
+    }
+
     pub fn next(&mut self) -> Option<&str> {
         let history_size = self.history.len();
         if history_size == 0 {
@@ -181,4 +190,19 @@ mod tests {
         search_history.reset_selection();
         assert_eq!(search_history.current(), None);
     }
+
+    #[test]
+    fn test_set_queries() {
+        let mut search_history = SearchHistory::default();
+        search_history.add("Rust".to_string());
+        search_history.set_queries(["Go", "Zig", "Zig", "C"].into_iter().map(str::to_string));
+        assert_eq!(search_history.current(), None);
+        assert_eq!(search_history.previous(), Some("C"));
+        assert_eq!(search_history.previous(), Some("Zig"));
+        assert_eq!(search_history.previous(), Some("Go"));
+        assert_eq!(search_history.previous(), None);
+
+        search_history.set_queries((0..SEARCH_HISTORY_LIMIT * 2).map(|i| format!("item{i}")));
+        assert_eq!(search_history.history.len(), SEARCH_HISTORY_LIMIT);
+    }
 }
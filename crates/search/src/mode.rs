@@ -1,13 +1,20 @@
 use gpui::{Action, SharedString};
+use serde::{Deserialize, Serialize};
 
-use crate::{ActivateRegexMode, ActivateTextMode};
+use crate::{ActivateRegexMode, ActivateSemanticMode, ActivateStructuralMode, ActivateTextMode};
 
 // TODO: Update the default search mode to get from config
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SearchMode {
     #[default]
     Text,
     Regex,
+    /// Matches code patterns with metavariables, or raw Tree-sitter queries, against syntax trees.
+    Structural,
+    /// Ranks chunks of the project's files by how similar their meaning is to the query.
+    /// Only available in project search, when the semantic index is enabled.
+    Semantic,
 }
 
 impl SearchMode {
@@ -15,6 +22,8 @@ impl SearchMode {
         match self {
             SearchMode::Text => "Text",
             SearchMode::Regex => "Regex",
+            SearchMode::Structural => "Structural",
+            SearchMode::Semantic => "Semantic",
         }
     }
     pub(crate) fn tooltip(&self) -> SharedString {
@@ -24,6 +33,8 @@ impl SearchMode {
         match self {
             SearchMode::Text => ActivateTextMode.boxed_clone(),
             SearchMode::Regex => ActivateRegexMode.boxed_clone(),
+            SearchMode::Structural => ActivateStructuralMode.boxed_clone(),
+            SearchMode::Semantic => ActivateSemanticMode.boxed_clone(),
         }
     }
 }
@@ -31,6 +42,8 @@ impl SearchMode {
 pub(crate) fn next_mode(mode: &SearchMode) -> SearchMode {
     match mode {
         SearchMode::Text => SearchMode::Regex,
-        SearchMode::Regex => SearchMode::Text,
+        SearchMode::Regex => SearchMode::Structural,
+        SearchMode::Structural => SearchMode::Semantic,
+        SearchMode::Semantic => SearchMode::Text,
     }
 }
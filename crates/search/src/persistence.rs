@@ -0,0 +1,248 @@
+use anyhow::{Context as _, Result};
+use db::sqlez::{
+    bindable::{Bind, Column, StaticColumnCount},
+    statement::Statement,
+};
+use db::{define_connection, query, sqlez_macros::sql};
+use serde::{Deserialize, Serialize};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+use crate::mode::SearchMode;
+
+/// Number of searches kept in the history of a workspace, saved searches excluded.
+const SEARCH_HISTORY_LIMIT: usize = 100;
+
+/// A project search with its filters and toggles, stored as JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SerializedSearch {
+    pub query: String,
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub include_ignored: bool,
+    #[serde(default)]
+    pub files_to_include: String,
+    #[serde(default)]
+    pub files_to_exclude: String,
+}
+
+impl StaticColumnCount for SerializedSearch {}
+impl Bind for &SerializedSearch {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        serde_json::to_string(self)
+            .context("Serializing search")?
+            .bind(statement, start_index)
+    }
+}
+
+impl Column for SerializedSearch {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (search, next_index) = String::column(statement, start_index)?;
+        Ok((
+            serde_json::from_str(&search).context("Deserializing search")?,
+            next_index,
+        ))
+    }
+}
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // project_searches(
+    //     search_id: usize, // Primary key, increasing with the time of the search
+    //     workspace_id: usize, // References workspaces table
+    //     name: Option<String>, // Unique per workspace. None for the searches of the history
+    //     pinned: bool,
+    //     search: String, // A JSON encoded SerializedSearch
+    // )
+    pub static ref DB: SearchDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE project_searches (
+                search_id INTEGER PRIMARY KEY,
+                workspace_id INTEGER NOT NULL,
+                name TEXT,
+                pinned INTEGER NOT NULL DEFAULT FALSE,
+                search TEXT NOT NULL,
+                UNIQUE(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl SearchDb {
+    /// Returns the searches of the workspace's history, the most recent first.
+    pub(crate) fn search_history(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<SerializedSearch>> {
+        self.select_bound::<WorkspaceId, SerializedSearch>(sql!(
+            SELECT search
+            FROM project_searches
+            WHERE workspace_id = ? AND name IS NULL
+            ORDER BY search_id DESC
+        ))?(workspace_id)
+    }
+
+    /// Adds a search to the history of the workspace, moving it to the front if it was already
+    /// there and dropping the oldest searches past the limit.
+    pub(crate) async fn add_to_history(
+        &self,
+        workspace_id: WorkspaceId,
+        search: SerializedSearch,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("add_search_to_history", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM project_searches
+                    WHERE workspace_id = ? AND name IS NULL AND search = ?
+                ))?((workspace_id, &search))?;
+                conn.exec_bound(sql!(
+                    INSERT INTO project_searches(workspace_id, search) VALUES (?, ?)
+                ))?((workspace_id, &search))?;
+                conn.exec_bound(sql!(
+                    DELETE FROM project_searches
+                    WHERE workspace_id = ?1 AND name IS NULL AND search_id NOT IN (
+                        SELECT search_id
+                        FROM project_searches
+                        WHERE workspace_id = ?1 AND name IS NULL
+                        ORDER BY search_id DESC
+                        LIMIT ?2
+                    )
+                ))?((workspace_id, SEARCH_HISTORY_LIMIT as i64))
+            })
+        })
+        .await
+    }
+
+    /// Returns the named searches of the workspace, the pinned ones first.
+    pub(crate) fn saved_searches(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<(String, bool, SerializedSearch)>> {
+        self.select_bound::<WorkspaceId, (String, bool, SerializedSearch)>(sql!(
+            SELECT name, pinned, search
+            FROM project_searches
+            WHERE workspace_id = ? AND name IS NOT NULL
+            ORDER BY pinned DESC, name
+        ))?(workspace_id)
+    }
+
+    /// Saves a search under the given name, replacing the search saved with that name but keeping
+    /// whether it is pinned.
+    pub(crate) async fn save_search(
+        &self,
+        workspace_id: WorkspaceId,
+        name: String,
+        search: SerializedSearch,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT INTO project_searches(workspace_id, name, search)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(workspace_id, name) DO UPDATE SET
+                    search = ?3
+            ))?((workspace_id, name.as_str(), &search))
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) async fn set_search_pinned(workspace_id: WorkspaceId, name: String, pinned: bool) -> Result<()> {
+            UPDATE project_searches
+            SET pinned = ?3
+            WHERE workspace_id = ?1 AND name = ?2
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM project_searches
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::open_test_db;
+
+    #[gpui::test]
+    async fn test_search_history_and_saved_searches() {
+        let db = SearchDb(open_test_db("test_search_history_and_saved_searches").await);
+        let workspace_id = db
+            .select_row::<WorkspaceId>(sql!(
+                INSERT INTO workspaces DEFAULT VALUES RETURNING workspace_id
+            ))
+            .unwrap()()
+        .unwrap()
+        .unwrap();
+
+        let search = |query: &str| SerializedSearch {
+            query: query.to_string(),
+            files_to_include: "*.rs".to_string(),
+            ..Default::default()
+        };
+
+        // Searching again moves a search to the front of the history.
+        db.add_to_history(workspace_id, search("one"))
+            .await
+            .unwrap();
+        db.add_to_history(workspace_id, search("two"))
+            .await
+            .unwrap();
+        db.add_to_history(workspace_id, search("one"))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.search_history(workspace_id).unwrap(),
+            vec![search("one"), search("two")]
+        );
+
+        for ix in 0..SEARCH_HISTORY_LIMIT {
+            db.add_to_history(workspace_id, search(&ix.to_string()))
+                .await
+                .unwrap();
+        }
+        let history = db.search_history(workspace_id).unwrap();
+        assert_eq!(history.len(), SEARCH_HISTORY_LIMIT);
+        assert_eq!(history[0], search(&(SEARCH_HISTORY_LIMIT - 1).to_string()));
+
+        // Saving a search again replaces it but keeps it pinned.
+        db.save_search(workspace_id, "b".into(), search("todo"))
+            .await
+            .unwrap();
+        db.save_search(workspace_id, "a".into(), search("fixme"))
+            .await
+            .unwrap();
+        db.set_search_pinned(workspace_id, "b".into(), true)
+            .await
+            .unwrap();
+        db.save_search(workspace_id, "b".into(), search("TODO"))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.saved_searches(workspace_id).unwrap(),
+            vec![
+                ("b".to_string(), true, search("TODO")),
+                ("a".to_string(), false, search("fixme")),
+            ]
+        );
+        assert_eq!(
+            db.search_history(workspace_id).unwrap().len(),
+            SEARCH_HISTORY_LIMIT
+        );
+
+        db.delete_saved_search(workspace_id, "b".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.saved_searches(workspace_id).unwrap(),
+            vec![("a".to_string(), false, search("fixme"))]
+        );
+    }
+}
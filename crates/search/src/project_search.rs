@@ -1,8 +1,11 @@
 use crate::{
-    history::SearchHistory, mode::SearchMode, ActivateRegexMode, ActivateTextMode, CycleMode,
-    NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
-    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleReplace,
-    ToggleWholeWord,
+    history::SearchHistory,
+    mode::SearchMode,
+    persistence::{SerializedSearch, DB},
+    saved_searches, ActivateRegexMode, ActivateSemanticMode, ActivateStructuralMode,
+    ActivateTextMode, CycleMode, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
+    SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored,
+    ToggleReplace, ToggleWholeWord,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
@@ -10,17 +13,19 @@ use editor::{
     actions::SelectAll,
     items::active_match_index,
     scroll::{Autoscroll, Axis},
-    Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer, MAX_TAB_TITLE_LEN,
+    Anchor, AnchorRangeExt, Bias, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer,
+    MultiBufferSnapshot, ToOffset, MAX_TAB_TITLE_LEN,
 };
 use gpui::{
     actions, div, Action, AnyElement, AnyView, AppContext, Context as _, Element, EntityId,
-    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, Global, Hsla,
+    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, Global, HighlightStyle, Hsla,
     InteractiveElement, IntoElement, KeyContext, Model, ModelContext, ParentElement, Point, Render,
-    SharedString, Styled, Subscription, Task, TextStyle, View, ViewContext, VisualContext,
-    WeakModel, WeakView, WhiteSpace, WindowContext,
+    SharedString, StrikethroughStyle, Styled, Subscription, Task, TextStyle, View, ViewContext,
+    VisualContext, WeakModel, WeakView, WhiteSpace, WindowContext,
 };
 use menu::Confirm;
-use project::{search::SearchQuery, Project};
+use project::{search::SearchQuery, Project, ProjectPath};
+use semantic_index::SemanticIndex;
 use settings::Settings;
 use smol::stream::StreamExt;
 use std::{
@@ -28,15 +33,18 @@ use std::{
     mem,
     ops::{Not, Range},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use theme::ThemeSettings;
 use ui::{
     h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
     Selectable, ToggleButton, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
+    location_list::{LocationEntry, LocationList},
+    notifications::NotifyResultExt,
     searchable::{Direction, SearchableItem, SearchableItemHandle},
     ItemNavHistory, Pane, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
     WorkspaceId,
@@ -45,12 +53,27 @@ use workspace::{DeploySearch, NewSearch};
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+/// The number of chunks shown for a semantic search.
+const SEMANTIC_SEARCH_LIMIT: usize = 32;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ExportResults,
+        OpenResult,
+        ToggleReplacePreview,
+        ToggleMatchReplacement,
+        ToggleFileReplacements
+    ]
 );
 
+/// Highlights the text of the matches that a replacement previewed inline would remove.
+enum ReplacePreview {}
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
@@ -58,7 +81,13 @@ impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut AppContext) {
     cx.set_global(ActiveSettings::default());
-    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        // Start indexing the project right away, so that it's ready for the first semantic search.
+        if SemanticIndex::enabled(cx) && workspace.project().read(cx).is_local() {
+            let project = workspace.project().clone();
+            SemanticIndex::global(cx).update(cx, |index, cx| index.project_index(project, cx));
+        }
+
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
@@ -71,12 +100,40 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleReplacePreview, cx| search_bar.toggle_replace_preview(cx),
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleMatchReplacement, cx| {
+                search_bar.toggle_match_replacement(false, cx)
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleFileReplacements, cx| {
+                search_bar.toggle_match_replacement(true, cx)
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ActivateRegexMode, cx| {
             search_bar.activate_search_mode(SearchMode::Regex, cx)
         });
         register_workspace_action(workspace, move |search_bar, _: &ActivateTextMode, cx| {
             search_bar.activate_search_mode(SearchMode::Text, cx)
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ActivateStructuralMode, cx| {
+                search_bar.activate_search_mode(SearchMode::Structural, cx)
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ActivateSemanticMode, cx| {
+                search_bar.activate_search_mode(SearchMode::Semantic, cx)
+            },
+        );
         register_workspace_action(workspace, move |search_bar, action: &CycleMode, cx| {
             search_bar.cycle_mode(action, cx)
         });
@@ -97,6 +154,10 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
             ProjectSearchView::search_in_new(workspace, action, cx)
         });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::export_results(workspace, action, cx)
+        });
+        workspace.register_action(open_exported_result);
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, cx| {
@@ -153,7 +214,17 @@ pub struct ProjectSearchView {
     excluded_files_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    /// Whether the replacement of each match is shown inline, before being applied.
+    replace_preview_enabled: bool,
+    /// Indices of the matches left out of the previewed replacements.
+    rejected_replacements: HashSet<usize>,
     current_mode: SearchMode,
+    workspace_id: Option<WorkspaceId>,
+    workspace: Option<WeakView<Workspace>>,
+    /// The last search whose matches were made a location list of the workspace.
+    location_list_search_id: usize,
+    /// Re-renders the landing text as the project is being indexed for semantic search.
+    semantic_index_subscription: Option<Subscription>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -259,6 +330,72 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Searches the project's semantic index for the chunks most similar to the query, and
+    /// shows them from the most to the least similar.
+    fn semantic_search(&mut self, query: String, cx: &mut ModelContext<Self>) {
+        let project = self.project.clone();
+        let project_index =
+            SemanticIndex::global(cx).update(cx, |index, cx| index.project_index(project, cx));
+        let search = project_index
+            .read(cx)
+            .search(query.clone(), SEMANTIC_SEARCH_LIMIT, cx);
+        self.search_id += 1;
+        self.search_history.add(query);
+        self.active_query = None;
+        self.match_ranges.clear();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            let results = search.await.log_err().unwrap_or_default();
+            let this = this.upgrade()?;
+            this.update(&mut cx, |this, cx| {
+                this.match_ranges.clear();
+                this.excerpts.update(cx, |this, cx| this.clear(cx));
+                this.no_results = Some(true);
+                this.limit_reached = false;
+            })
+            .ok()?;
+
+            for result in results {
+                let open_buffer = this
+                    .update(&mut cx, |this, cx| {
+                        this.project.update(cx, |project, cx| {
+                            let project_path = ProjectPath {
+                                worktree_id: result.worktree_id,
+                                path: result.path.clone(),
+                            };
+                            project.open_buffer(project_path, cx)
+                        })
+                    })
+                    .ok()?;
+                let Some(buffer) = open_buffer.await.log_err() else {
+                    continue;
+                };
+                this.update(&mut cx, |this, cx| {
+                    // The file may have changed since it was indexed.
+                    let snapshot = buffer.read(cx).snapshot();
+                    let len = snapshot.len();
+                    let range = snapshot.clip_offset(result.range.start.min(len), Bias::Left)
+                        ..snapshot.clip_offset(result.range.end.min(len), Bias::Right);
+                    let match_ranges = this.excerpts.update(cx, |excerpts, cx| {
+                        excerpts.push_excerpts_with_context_lines(buffer, vec![range], 1, cx)
+                    });
+                    this.match_ranges.extend(match_ranges);
+                    this.no_results = Some(false);
+                    cx.notify();
+                })
+                .ok()?;
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -300,7 +437,7 @@ impl Render for ProjectSearchView {
                     None
                 }
             } else {
-                Some(self.landing_text_minor())
+                Some(self.landing_text_minor(cx))
             };
             let minor_text = minor_text.map(|text| {
                 div()
@@ -465,6 +602,18 @@ impl Item for ProjectSearchView {
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
         self.results_editor
             .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+
+        self.workspace = Some(workspace.weak_handle());
+        let workspace_id = workspace.database_id();
+        if self.workspace_id.replace(workspace_id).is_none() && self.model.read(cx).search_id == 0 {
+            if let Some(history) = DB.search_history(workspace_id).log_err() {
+                self.model.update(cx, |model, _| {
+                    model
+                        .search_history
+                        .set_queries(history.into_iter().rev().map(|search| search.query))
+                });
+            }
+        }
     }
 
     fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
@@ -562,12 +711,15 @@ impl ProjectSearchView {
 
     fn activate_search_mode(&mut self, mode: SearchMode, cx: &mut ViewContext<Self>) {
         let previous_mode = self.current_mode;
-        if previous_mode == mode {
+        if previous_mode == mode || (mode == SearchMode::Semantic && !SemanticIndex::enabled(cx)) {
             return;
         }
 
         self.clear_search(cx);
         self.current_mode = mode;
+        if mode == SearchMode::Semantic {
+            self.observe_semantic_index(cx);
+        }
         self.active_match_index = None;
         self.search(cx);
 
@@ -601,6 +753,10 @@ impl ProjectSearchView {
         self.replacement_editor.read(cx).text(cx)
     }
     fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
+        if self.replace_preview_enabled {
+            self.apply_previewed_replacements(cx);
+            return;
+        }
         let model = self.model.read(cx);
         if let Some(query) = model.active_query.as_ref() {
             if model.match_ranges.is_empty() {
@@ -620,6 +776,144 @@ impl ProjectSearchView {
         }
     }
 
+    /// The replacement text of each match along with its index and range in the results, with
+    /// regex capture groups and structural metavariables expanded.
+    fn match_replacements(&self, cx: &AppContext) -> Vec<(usize, Range<Anchor>, String)> {
+        let model = self.model.read(cx);
+        let Some(query) = model.active_query.as_ref() else {
+            return Vec::new();
+        };
+        let query = query.clone().with_replacement(self.replacement(cx));
+        let snapshot = model.excerpts.read(cx).snapshot(cx);
+        model
+            .match_ranges
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, range)| {
+                let replacement = replacement_for_match(&query, &snapshot, range)?;
+                Some((ix, range.clone(), replacement))
+            })
+            .collect()
+    }
+
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.replace_preview_enabled = !self.replace_preview_enabled;
+        self.refresh_replace_preview(cx);
+        cx.notify();
+    }
+
+    /// Leaves the active match out of the previewed replacements or brings it back. With
+    /// `whole_file`, does so for all the matches in the file of the active match.
+    fn toggle_match_replacement(&mut self, whole_file: bool, cx: &mut ViewContext<Self>) {
+        if !self.replace_preview_enabled {
+            return;
+        }
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        let match_ranges = &self.model.read(cx).match_ranges;
+        let Some(active_range) = match_ranges.get(active_index) else {
+            return;
+        };
+        let indices = if whole_file {
+            let buffer_id = active_range.start.buffer_id;
+            match_ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| range.start.buffer_id == buffer_id)
+                .map(|(ix, _)| ix)
+                .collect::<Vec<_>>()
+        } else {
+            vec![active_index]
+        };
+
+        let reject = indices
+            .iter()
+            .any(|ix| !self.rejected_replacements.contains(ix));
+        for ix in indices {
+            if reject {
+                self.rejected_replacements.insert(ix);
+            } else {
+                self.rejected_replacements.remove(&ix);
+            }
+        }
+        self.refresh_replace_preview(cx);
+    }
+
+    /// Shows the replacement of every accepted match inline, after its struck out text.
+    fn refresh_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        let replacements = if self.replace_enabled && self.replace_preview_enabled {
+            self.match_replacements(cx)
+                .into_iter()
+                .filter(|(ix, _, _)| !self.rejected_replacements.contains(ix))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.results_editor.update(cx, |editor, cx| {
+            if replacements.is_empty() {
+                editor.clear_highlights::<ReplacePreview>(cx);
+                editor.clear_inline_previews(cx);
+                return;
+            }
+
+            let status = cx.theme().status();
+            let removed_style = HighlightStyle {
+                color: Some(status.deleted),
+                strikethrough: Some(StrikethroughStyle {
+                    thickness: px(1.),
+                    color: Some(status.deleted),
+                }),
+                ..Default::default()
+            };
+            let inserted_style = HighlightStyle {
+                color: Some(status.created),
+                background_color: Some(status.created_background),
+                ..Default::default()
+            };
+            let (ranges, previews): (Vec<_>, Vec<_>) = replacements
+                .into_iter()
+                .map(|(_, range, replacement)| (range.clone(), (range.end, replacement)))
+                .unzip();
+            editor.highlight_text::<ReplacePreview>(ranges, removed_style, cx);
+            editor.set_inline_previews(previews, inserted_style, cx);
+        });
+    }
+
+    /// Applies the accepted replacements of the preview, as a single edit per buffer so that
+    /// each file can undo them at once.
+    fn apply_previewed_replacements(&mut self, cx: &mut ViewContext<Self>) {
+        let excerpts = self.model.read(cx).excerpts.clone();
+        let mut edits_by_buffer = HashMap::default();
+        for (ix, range, replacement) in self.match_replacements(cx) {
+            if self.rejected_replacements.contains(&ix) {
+                continue;
+            }
+            let Some(buffer_id) = range.start.buffer_id else {
+                continue;
+            };
+            let Some(buffer) = excerpts.read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            edits_by_buffer
+                .entry(buffer_id)
+                .or_insert_with(|| (buffer, Vec::new()))
+                .1
+                .push((range.start.text_anchor..range.end.text_anchor, replacement));
+        }
+
+        for (buffer, edits) in edits_by_buffer.into_values() {
+            buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+        }
+        self.replace_preview_enabled = false;
+        self.rejected_replacements.clear();
+        self.refresh_replace_preview(cx);
+        cx.notify();
+    }
+
     fn new(
         model: Model<ProjectSearch>,
         cx: &mut ViewContext<Self>,
@@ -674,6 +968,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if matches!(event, EditorEvent::BufferEdited) {
+                    this.refresh_replace_preview(cx);
+                }
+            },
+        ));
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), cx);
             editor.set_searchable(false);
@@ -733,6 +1035,7 @@ impl ProjectSearchView {
             focus_handle,
             replacement_editor,
             search_id: model.read(cx).search_id,
+            location_list_search_id: model.read(cx).search_id,
             model,
             query_editor,
             results_editor,
@@ -745,8 +1048,16 @@ impl ProjectSearchView {
             filters_enabled,
             current_mode,
             replace_enabled: false,
+            replace_preview_enabled: false,
+            rejected_replacements: HashSet::default(),
+            workspace_id: None,
+            workspace: None,
+            semantic_index_subscription: None,
             _subscriptions: subscriptions,
         };
+        if this.current_mode == SearchMode::Semantic {
+            this.observe_semantic_index(cx);
+        }
         this.model_changed(cx);
         this
     }
@@ -796,6 +1107,7 @@ impl ProjectSearchView {
             let new_query = search_view.update(cx, |search_view, cx| {
                 let new_query = search_view.build_search_query(cx);
                 if new_query.is_some() {
+                    search_view.add_to_history(cx);
                     if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
                         search_view.query_editor.update(cx, |editor, cx| {
                             editor.set_text(old_query.as_str(), cx);
@@ -843,7 +1155,21 @@ impl ProjectSearchView {
             }
         });
 
-        let search = if let Some(existing) = existing {
+        let search = Self::activate_or_add_search(workspace, existing, cx);
+        search.update(cx, |search, cx| {
+            if let Some(query) = query {
+                search.set_query(&query, cx);
+            }
+            search.focus_query_editor(cx)
+        });
+    }
+
+    fn activate_or_add_search(
+        workspace: &mut Workspace,
+        existing: Option<View<ProjectSearchView>>,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<ProjectSearchView> {
+        if let Some(existing) = existing {
             workspace.activate_item(&existing, cx);
             existing
         } else {
@@ -863,20 +1189,181 @@ impl ProjectSearchView {
 
             workspace.add_item_to_active_pane(Box::new(view.clone()), cx);
             view
-        };
+        }
+    }
 
-        search.update(cx, |search, cx| {
-            if let Some(query) = query {
-                search.set_query(&query, cx);
-            }
-            search.focus_query_editor(cx)
+    /// Runs a search of the history or a saved one in the project search of the active pane, or
+    /// in a new one if there is none.
+    pub(crate) fn deploy_serialized_search(
+        workspace: &mut Workspace,
+        search: &SerializedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        let search_view = Self::activate_or_add_search(workspace, existing, cx);
+        search_view.update(cx, |search_view, cx| {
+            search_view.focus_query_editor(cx);
+            search_view.apply_serialized_search(search, cx);
         });
     }
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
+        if self.current_mode == SearchMode::Semantic {
+            let query = self.query_editor.read(cx).text(cx);
+            if !query.trim().is_empty() && SemanticIndex::enabled(cx) {
+                self.model
+                    .update(cx, |model, cx| model.semantic_search(query, cx));
+                self.add_to_history(cx);
+            }
+            return;
+        }
+
         if let Some(query) = self.build_search_query(cx) {
             self.model.update(cx, |model, cx| model.search(query, cx));
+            self.add_to_history(cx);
+        }
+    }
+
+    /// Returns the query of the search along with its filters and toggles.
+    pub(crate) fn serialized_search(&self, cx: &AppContext) -> SerializedSearch {
+        SerializedSearch {
+            query: self.query_editor.read(cx).text(cx),
+            mode: self.current_mode,
+            whole_word: self.search_options.contains(SearchOptions::WHOLE_WORD),
+            case_sensitive: self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+            include_ignored: self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+        }
+    }
+
+    fn apply_serialized_search(&mut self, search: &SerializedSearch, cx: &mut ViewContext<Self>) {
+        self.set_query(&search.query, cx);
+        self.included_files_editor.update(cx, |editor, cx| {
+            editor.set_text(search.files_to_include.as_str(), cx)
+        });
+        self.excluded_files_editor.update(cx, |editor, cx| {
+            editor.set_text(search.files_to_exclude.as_str(), cx)
+        });
+        self.filters_enabled |=
+            !search.files_to_include.is_empty() || !search.files_to_exclude.is_empty();
+        self.search_options
+            .set(SearchOptions::WHOLE_WORD, search.whole_word);
+        self.search_options
+            .set(SearchOptions::CASE_SENSITIVE, search.case_sensitive);
+        self.search_options
+            .set(SearchOptions::INCLUDE_IGNORED, search.include_ignored);
+        self.current_mode = search.mode;
+        if self.current_mode == SearchMode::Semantic {
+            if SemanticIndex::enabled(cx) {
+                self.observe_semantic_index(cx);
+            } else {
+                self.current_mode = SearchMode::Text;
+            }
+        }
+        self.active_match_index = None;
+        self.search(cx);
+        cx.notify();
+    }
+
+    fn observe_semantic_index(&mut self, cx: &mut ViewContext<Self>) {
+        if self.semantic_index_subscription.is_some() {
+            return;
+        }
+        let Some(semantic_index) = SemanticIndex::try_global(cx) else {
+            return;
+        };
+        let project = self.model.read(cx).project.clone();
+        let project_index = semantic_index.update(cx, |index, cx| index.project_index(project, cx));
+        self.semantic_index_subscription = Some(cx.observe(&project_index, |_, _, cx| cx.notify()));
+    }
+
+    fn add_to_history(&self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        let search = self.serialized_search(cx);
+        cx.background_executor()
+            .spawn(async move { DB.add_to_history(workspace_id, search).await.log_err() })
+            .detach();
+    }
+
+    /// Writes the matches of the search into a new buffer, one `path:line:column: text` line per
+    /// match, whose lines open their match on `project_search::OpenResult`.
+    fn export_results(
+        workspace: &mut Workspace,
+        _: &ExportResults,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let search_view = search_view.read(cx);
+        let text = search_view.exported_results(cx);
+        let title = match search_view.model.read(cx).active_query.as_ref() {
+            Some(query) => format!("Results: {}", query.as_str()),
+            None => "Results".to_string(),
+        };
+
+        let project = workspace.project().clone();
+        let Some(buffer) = project
+            .update(cx, |project, cx| project.create_buffer(&text, None, cx))
+            .notify_err(workspace, cx)
+        else {
+            return;
+        };
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(buffer, Some(project), cx);
+            let mut key_context = KeyContext::default();
+            key_context.add("exported_search_results");
+            editor.set_keymap_context_layer::<ExportResults>(key_context, cx);
+            editor
+        });
+        workspace.add_item_to_active_pane(Box::new(editor), cx);
+    }
+
+    fn exported_results(&self, cx: &AppContext) -> String {
+        let model = self.model.read(cx);
+        let include_root = model.project.read(cx).visible_worktrees(cx).count() > 1;
+        let snapshot = model.excerpts.read(cx).snapshot(cx);
+        let mut text = String::new();
+        for range in &model.match_ranges {
+            let start = range.start.to_offset(&snapshot);
+            let Some(excerpt) = snapshot.excerpt_containing(start..start) else {
+                continue;
+            };
+            let buffer = excerpt.buffer();
+            let Some(file) = buffer.file() else {
+                continue;
+            };
+            let path = if include_root {
+                file.full_path(cx)
+            } else {
+                file.path().to_path_buf()
+            };
+            let position = buffer.offset_to_point(excerpt.map_offset_to_buffer(start));
+            let line_start = language::Point::new(position.row, 0);
+            let line_end = language::Point::new(position.row, buffer.line_len(position.row));
+            let line = buffer
+                .text_for_range(line_start..line_end)
+                .collect::<String>();
+            text.push_str(&format!(
+                "{}:{}:{}: {}\n",
+                path.display(),
+                position.row + 1,
+                position.column + 1,
+                line.trim()
+            ));
         }
+        text
     }
 
     fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
@@ -920,6 +1407,32 @@ impl ProjectSearchView {
 
         let current_mode = self.current_mode;
         let query = match current_mode {
+            SearchMode::Structural => {
+                match SearchQuery::structural(
+                    text,
+                    self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                    included_files,
+                    excluded_files,
+                ) {
+                    Ok(query) => {
+                        let should_unmark_error =
+                            self.panels_with_errors.remove(&InputPanel::Query);
+                        if should_unmark_error {
+                            cx.notify();
+                        }
+
+                        Some(query)
+                    }
+                    Err(_e) => {
+                        let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                        if should_mark_error {
+                            cx.notify();
+                        }
+
+                        None
+                    }
+                }
+            }
             SearchMode::Regex => {
                 match SearchQuery::regex(
                     text,
@@ -1045,6 +1558,9 @@ impl ProjectSearchView {
             self.update_match_index(cx);
             let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
             let is_new_search = self.search_id != prev_search_id;
+            if is_new_search {
+                self.rejected_replacements.clear();
+            }
             self.results_editor.update(cx, |editor, cx| {
                 if is_new_search {
                     let range_to_select = match_ranges
@@ -1065,11 +1581,54 @@ impl ProjectSearchView {
                 self.focus_results_editor(cx);
             }
         }
+        self.refresh_replace_preview(cx);
+        self.push_location_list(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
 
+    /// Makes the matches of a finished search the active location list of the workspace.
+    fn push_location_list(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let search_id = model.search_id;
+        if model.pending_search.is_some()
+            || model.match_ranges.is_empty()
+            || search_id == self.location_list_search_id
+        {
+            return;
+        }
+        let Some(workspace) = self.workspace.as_ref().and_then(|w| w.upgrade()) else {
+            return;
+        };
+        self.location_list_search_id = search_id;
+
+        let excerpts = model.excerpts.read(cx);
+        let entries = model
+            .match_ranges
+            .iter()
+            .filter_map(|range| {
+                let buffer = excerpts.buffer(range.start.buffer_id?)?;
+                Some(LocationEntry::new(
+                    buffer,
+                    range.start.text_anchor..range.end.text_anchor,
+                    cx,
+                ))
+            })
+            .collect();
+        let query = model
+            .active_query
+            .as_ref()
+            .map(|query| query.as_str().to_string())
+            .unwrap_or_default();
+        let list = LocationList::new(format!("Search for `{query}`"), entries);
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .location_lists()
+                .update(cx, |lists, cx| lists.push(list, cx))
+        });
+    }
+
     fn update_match_index(&mut self, cx: &mut ViewContext<Self>) {
         let results_editor = self.results_editor.read(cx);
         let new_index = active_match_index(
@@ -1087,9 +1646,21 @@ impl ProjectSearchView {
         self.active_match_index.is_some()
     }
 
-    fn landing_text_minor(&self) -> SharedString {
+    fn landing_text_minor(&self, cx: &AppContext) -> SharedString {
         match self.current_mode {
             SearchMode::Text | SearchMode::Regex => "Include/exclude specific paths with the filter option. Matching exact word and/or casing is available too.".into(),
+            SearchMode::Structural => "Search for code patterns like `$X.unwrap()`, where `$X` matches any expression and can be reused in the replacement. Patterns starting with `(` are Tree-sitter queries.".into(),
+            SearchMode::Semantic => {
+                let project = &self.model.read(cx).project;
+                let pending_file_count = SemanticIndex::try_global(cx)
+                    .and_then(|index| index.read(cx).existing_project_index(project))
+                    .map_or(0, |index| index.read(cx).pending_file_count());
+                if pending_file_count > 0 {
+                    format!("Indexing {pending_file_count} files. Results may be incomplete until indexing finishes.").into()
+                } else {
+                    "Describe the code you are looking for, like `where are settings loaded from disk`. Results are ranked by similarity.".into()
+                }
+            }
         }
     }
     fn border_color_for(&self, panel: InputPanel, cx: &WindowContext) -> Hsla {
@@ -1120,7 +1691,10 @@ impl ProjectSearchBar {
     fn cycle_mode(&self, _: &CycleMode, cx: &mut ViewContext<Self>) {
         if let Some(view) = self.active_project_search.as_ref() {
             view.update(cx, |this, cx| {
-                let new_mode = crate::mode::next_mode(&this.current_mode);
+                let mut new_mode = crate::mode::next_mode(&this.current_mode);
+                if new_mode == SearchMode::Semantic && !SemanticIndex::enabled(cx) {
+                    new_mode = crate::mode::next_mode(&new_mode);
+                }
                 this.activate_search_mode(new_mode, cx);
                 let editor_handle = this.query_editor.focus_handle(cx);
                 cx.focus(&editor_handle);
@@ -1209,6 +1783,7 @@ impl ProjectSearchBar {
         if let Some(search) = &self.active_project_search {
             search.update(cx, |this, cx| {
                 this.replace_enabled = !this.replace_enabled;
+                this.refresh_replace_preview(cx);
                 let editor_to_focus = if !this.replace_enabled {
                     this.query_editor.focus_handle(cx)
                 } else {
@@ -1220,6 +1795,20 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |search, cx| search.toggle_replace_preview(cx));
+        }
+    }
+
+    fn toggle_match_replacement(&mut self, whole_file: bool, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |search, cx| {
+                search.toggle_match_replacement(whole_file, cx)
+            });
+        }
+    }
+
     fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1468,6 +2057,7 @@ impl Render for ProjectSearchBar {
                     ),
             );
 
+        let semantic_enabled = SemanticIndex::enabled(cx);
         let mode_column = v_flex().items_start().justify_start().child(
             h_flex()
                 .gap_2()
@@ -1501,8 +2091,50 @@ impl Render for ProjectSearchBar {
                                         cx,
                                     )
                                 })
-                                .last(),
-                        ),
+                                .middle(),
+                        )
+                        .child(
+                            ToggleButton::new("project-search-structural-button", "Structural")
+                                .style(ButtonStyle::Filled)
+                                .size(ButtonSize::Large)
+                                .selected(search.current_mode == SearchMode::Structural)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.activate_search_mode(SearchMode::Structural, cx)
+                                }))
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Toggle structural search",
+                                        &ActivateStructuralMode,
+                                        cx,
+                                    )
+                                })
+                                .map(|button| {
+                                    if semantic_enabled {
+                                        button.middle()
+                                    } else {
+                                        button.last()
+                                    }
+                                }),
+                        )
+                        .when(semantic_enabled, |this| {
+                            this.child(
+                                ToggleButton::new("project-search-semantic-button", "Semantic")
+                                    .style(ButtonStyle::Filled)
+                                    .size(ButtonSize::Large)
+                                    .selected(search.current_mode == SearchMode::Semantic)
+                                    .on_click(cx.listener(|this, _, cx| {
+                                        this.activate_search_mode(SearchMode::Semantic, cx)
+                                    }))
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Toggle semantic search",
+                                            &ActivateSemanticMode,
+                                            cx,
+                                        )
+                                    })
+                                    .last(),
+                            )
+                        }),
                 )
                 .child(
                     IconButton::new("project-search-toggle-replace", IconName::Replace)
@@ -1557,6 +2189,25 @@ impl Render for ProjectSearchBar {
                     }))
                     .tooltip(|cx| Tooltip::for_action("Go to next match", &SelectNextMatch, cx)),
             )
+            .child(
+                IconButton::new("project-search-export-results", IconName::FileDoc)
+                    .disabled(search.active_match_index.is_none())
+                    .on_click(|_, cx| cx.dispatch_action(ExportResults.boxed_clone()))
+                    .tooltip(|cx| Tooltip::for_action("Export results", &ExportResults, cx)),
+            )
+            .child(
+                IconButton::new("project-search-saved-searches", IconName::Pin)
+                    .on_click(|_, cx| {
+                        cx.dispatch_action(saved_searches::ToggleSavedSearches.boxed_clone())
+                    })
+                    .tooltip(|cx| {
+                        Tooltip::for_action(
+                            "Saved searches",
+                            &saved_searches::ToggleSavedSearches,
+                            cx,
+                        )
+                    }),
+            )
             .when(limit_reached, |this| {
                 this.child(
                     div()
@@ -1584,8 +2235,17 @@ impl Render for ProjectSearchBar {
                 .border_color(cx.theme().colors().border)
                 .rounded_lg()
                 .child(self.render_text_input(&search.replacement_editor, cx));
+            let replace_preview_enabled = search.replace_preview_enabled;
             let replace_actions = h_flex().when(search.replace_enabled, |this| {
                 this.child(
+                    IconButton::new("project-search-replace-preview", IconName::InlayHint)
+                        .selected(replace_preview_enabled)
+                        .on_click(cx.listener(|this, _, cx| this.toggle_replace_preview(cx)))
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Preview replacements", &ToggleReplacePreview, cx)
+                        }),
+                )
+                .child(
                     IconButton::new("project-search-replace-next", IconName::ReplaceNext)
                         .on_click(cx.listener(|this, _, cx| {
                             if let Some(search) = this.active_project_search.as_ref() {
@@ -1605,7 +2265,13 @@ impl Render for ProjectSearchBar {
                                 })
                             }
                         }))
-                        .tooltip(|cx| Tooltip::for_action("Replace all matches", &ReplaceAll, cx)),
+                        .tooltip(move |cx| {
+                            if replace_preview_enabled {
+                                Tooltip::for_action("Apply previewed replacements", &ReplaceAll, cx)
+                            } else {
+                                Tooltip::for_action("Replace all matches", &ReplaceAll, cx)
+                            }
+                        }),
                 )
             });
             h_flex()
@@ -1668,6 +2334,12 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, _: &ActivateRegexMode, cx| {
                 this.activate_search_mode(SearchMode::Regex, cx)
             }))
+            .on_action(cx.listener(|this, _: &ActivateStructuralMode, cx| {
+                this.activate_search_mode(SearchMode::Structural, cx)
+            }))
+            .on_action(cx.listener(|this, _: &ActivateSemanticMode, cx| {
+                this.activate_search_mode(SearchMode::Semantic, cx)
+            }))
             .capture_action(cx.listener(|this, action, cx| {
                 this.tab(action, cx);
                 cx.stop_propagation();
@@ -1748,6 +2420,114 @@ impl ToolbarItemView for ProjectSearchBar {
     }
 }
 
+/// Opens the match of the exported search result under the cursor.
+fn open_exported_result(
+    workspace: &mut Workspace,
+    _: &OpenResult,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        cx.propagate();
+        return;
+    };
+    let line = {
+        let editor = editor.read(cx);
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let row = editor.selections.newest::<language::Point>(cx).head().row;
+        snapshot
+            .text_for_range(
+                language::Point::new(row, 0)..language::Point::new(row, snapshot.line_len(row)),
+            )
+            .collect::<String>()
+    };
+    let Some((path, row, column)) = parse_exported_result(&line) else {
+        return;
+    };
+    let Some(project_path) =
+        project_path_for_exported_result(workspace.project().read(cx), &path, cx)
+    else {
+        return;
+    };
+
+    let open_task = workspace.open_path(project_path, None, true, cx);
+    cx.spawn(|_, mut cx| async move {
+        let item = open_task.await?;
+        if let Some(editor) = item.downcast::<Editor>() {
+            editor.update(&mut cx, |editor, cx| {
+                let point = editor
+                    .buffer()
+                    .read(cx)
+                    .snapshot(cx)
+                    .clip_point(language::Point::new(row, column), Bias::Left);
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Parses a `path:line:column: text` line of exported results into the path and the zero-based
+/// row and column of its match.
+fn parse_exported_result(line: &str) -> Option<(PathBuf, u32, u32)> {
+    line.match_indices(':').find_map(|(ix, _)| {
+        let mut parts = line[ix + 1..].splitn(3, ':');
+        let row = parts.next()?.parse::<u32>().ok()?;
+        let column = parts.next()?.parse::<u32>().ok()?;
+        parts.next()?;
+        Some((
+            PathBuf::from(&line[..ix]),
+            row.checked_sub(1)?,
+            column.checked_sub(1)?,
+        ))
+    })
+}
+
+/// Finds the file of an exported result, whose path is relative to its worktree and starts with
+/// the name of the worktree when the project has several.
+fn project_path_for_exported_result(
+    project: &Project,
+    path: &Path,
+    cx: &AppContext,
+) -> Option<ProjectPath> {
+    project.visible_worktrees(cx).find_map(|worktree| {
+        let worktree = worktree.read(cx);
+        [path.strip_prefix(worktree.root_name()).ok(), Some(path)]
+            .into_iter()
+            .flatten()
+            .find(|path| {
+                worktree
+                    .entry_for_path(path)
+                    .is_some_and(|entry| entry.is_file())
+            })
+            .map(|path| ProjectPath {
+                worktree_id: worktree.id(),
+                path: Arc::from(path),
+            })
+    })
+}
+
+fn replacement_for_match(
+    query: &SearchQuery,
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+) -> Option<String> {
+    let range = range.to_offset(snapshot);
+    if query.is_structural() {
+        let excerpt = snapshot.excerpt_containing(range.start..range.start)?;
+        return query.structural_replacement(excerpt.buffer(), excerpt.map_range_to_buffer(range));
+    }
+    let text = snapshot.text_for_range(range).collect::<String>();
+    query
+        .replacement_for(&text)
+        .map(|replacement| replacement.into_owned())
+}
+
 fn register_workspace_action<A: Action>(
     workspace: &mut Workspace,
     callback: fn(&mut ProjectSearchBar, &A, &mut ViewContext<ProjectSearchBar>),
@@ -3125,6 +3905,306 @@ pub mod tests {
             .expect("unable to update search view");
     }
 
+    #[gpui::test]
+    async fn test_export_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), cx);
+                search_view
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.set_query("TWO", cx);
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::export_results(workspace, &ExportResults, cx)
+            })
+            .unwrap();
+        let results_editor = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_item_as::<Editor>(cx)
+                .expect("exported results should be open")
+        });
+        window
+            .update(cx, |_, cx| {
+                results_editor.update(cx, |editor, cx| {
+                    assert_eq!(
+                        editor.text(cx),
+                        "three.rs:1:33: const THREE: usize = one::ONE + two::TWO;\n\
+                         three.rs:1:38: const THREE: usize = one::ONE + two::TWO;\n\
+                         two.rs:1:7: const TWO: usize = one::ONE + one::ONE;\n"
+                    );
+                    editor.change_selections(None, cx, |s| {
+                        s.select_ranges([language::Point::new(2, 3)..language::Point::new(2, 3)])
+                    });
+                })
+            })
+            .unwrap();
+
+        window
+            .update(cx, |workspace, cx| {
+                open_exported_result(workspace, &OpenResult, cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |workspace, cx| {
+                let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+                let editor = editor.read(cx);
+                assert_eq!(
+                    editor
+                        .buffer()
+                        .read(cx)
+                        .as_singleton()
+                        .map(|buffer| { buffer.read(cx).file().unwrap().path().to_path_buf() }),
+                    Some(PathBuf::from("two.rs"))
+                );
+                assert_eq!(
+                    editor.selections.newest::<language::Point>(cx).head(),
+                    language::Point::new(0, 6)
+                );
+            })
+            .unwrap();
+
+        assert_eq!(
+            parse_exported_result("src/a:b.rs:12:4: let x = y::z;"),
+            Some((PathBuf::from("src/a:b.rs"), 11, 3))
+        );
+        assert_eq!(parse_exported_result("no result here"), None);
+    }
+
+    #[gpui::test]
+    async fn test_search_populates_location_list(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), cx);
+                search_view
+            })
+            .unwrap();
+        for query in ["ONE", "TWO"] {
+            window
+                .update(cx, |_, cx| {
+                    search_view.update(cx, |search_view, cx| {
+                        search_view.set_query(query, cx);
+                        search_view.search(cx);
+                    })
+                })
+                .unwrap();
+            cx.background_executor.run_until_parked();
+        }
+
+        cx.read(|cx| {
+            let lists = workspace.read(cx).location_lists().read(cx);
+            let summary = lists
+                .lists()
+                .iter()
+                .map(|list| {
+                    // Search results come in no particular order.
+                    let mut labels = list
+                        .entries
+                        .iter()
+                        .map(|entry| entry.label.to_string())
+                        .collect::<Vec<_>>();
+                    labels.sort();
+                    (list.title.to_string(), labels)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                summary,
+                [
+                    (
+                        "Search for `ONE`".to_string(),
+                        vec![
+                            "const ONE: usize = 1;".to_string(),
+                            "const TWO: usize = one::ONE + one::ONE;".to_string(),
+                            "const TWO: usize = one::ONE + one::ONE;".to_string(),
+                        ]
+                    ),
+                    (
+                        "Search for `TWO`".to_string(),
+                        vec!["const TWO: usize = one::ONE + one::ONE;".to_string()]
+                    ),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "let a = foo(1);\nlet b = foo(2);",
+                "two.rs": "let c = foo(3);",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.current_mode = SearchMode::Regex;
+                search_view.replace_enabled = true;
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("bar($1)", cx));
+            })
+            .unwrap();
+        perform_search(search_view, r"foo\((\d)\)", cx);
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_replace_preview(cx);
+                let mut replacements = search_view
+                    .match_replacements(cx)
+                    .into_iter()
+                    .map(|(_, _, replacement)| replacement)
+                    .collect::<Vec<_>>();
+                replacements.sort();
+                assert_eq!(replacements, ["bar(1)", "bar(2)", "bar(3)"]);
+                let display_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(display_text.contains("foo(1)bar(1)"), "{display_text}");
+                assert!(display_text.contains("foo(3)bar(3)"), "{display_text}");
+
+                // Leave out the replacements of the file of the match in `two.rs`.
+                let model = search_view.model.read(cx);
+                let excerpts = model.excerpts.read(cx);
+                let two_index = model
+                    .match_ranges
+                    .iter()
+                    .position(|range| {
+                        let buffer = excerpts.buffer(range.start.buffer_id.unwrap()).unwrap();
+                        let path = buffer.read(cx).file().unwrap().path().clone();
+                        path.as_ref() == Path::new("two.rs")
+                    })
+                    .unwrap();
+                search_view.active_match_index = Some(two_index);
+                search_view.toggle_match_replacement(true, cx);
+                let display_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(display_text.contains("foo(1)bar(1)"), "{display_text}");
+                assert!(!display_text.contains("bar(3)"), "{display_text}");
+
+                search_view.replace_all(&ReplaceAll, cx);
+                assert!(!search_view.replace_preview_enabled);
+            })
+            .unwrap();
+
+        let one = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        let two = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/two.rs", cx)
+            })
+            .await
+            .unwrap();
+        one.update(cx, |buffer, cx| {
+            assert_eq!(buffer.text(), "let a = bar(1);\nlet b = bar(2);");
+            buffer.undo(cx);
+            assert_eq!(buffer.text(), "let a = foo(1);\nlet b = foo(2);");
+        });
+        two.update(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "let c = foo(3);");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_semantic_search(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            semantic_index::init_test(Arc::new(semantic_index::FakeEmbeddingProvider), cx)
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "fruits.rs": "fn apple() {} fn banana() {}",
+                "vehicles.rs": "fn car() {} fn truck() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.activate_search_mode(SearchMode::Semantic, cx)
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        perform_search(search_view, "truck and car", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nfn car() {} fn truck() {}\n\n\nfn apple() {} fn banana() {}"
+                );
+                assert_eq!(search_view.active_match_index, Some(0));
+                assert!(search.read(cx).active_query.is_none());
+            })
+            .unwrap();
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -0,0 +1,343 @@
+use crate::{
+    persistence::{SerializedSearch, DB},
+    ProjectSearchView,
+};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task,
+    View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::{future::Future, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, IconButton, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+actions!(project_search, [ToggleSavedSearches]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(SavedSearches::register).detach();
+}
+
+/// A search of the picker, either saved under a name or from the history of the workspace.
+enum SearchEntry {
+    Saved {
+        name: String,
+        pinned: bool,
+        search: SerializedSearch,
+    },
+    Recent(SerializedSearch),
+}
+
+impl SearchEntry {
+    fn label(&self) -> &str {
+        match self {
+            SearchEntry::Saved { name, .. } => name,
+            SearchEntry::Recent(search) => &search.query,
+        }
+    }
+
+    fn search(&self) -> &SerializedSearch {
+        match self {
+            SearchEntry::Saved { search, .. } | SearchEntry::Recent(search) => search,
+        }
+    }
+}
+
+fn search_entries(workspace_id: WorkspaceId) -> anyhow::Result<Vec<SearchEntry>> {
+    let saved = DB
+        .saved_searches(workspace_id)?
+        .into_iter()
+        .map(|(name, pinned, search)| SearchEntry::Saved {
+            name,
+            pinned,
+            search,
+        });
+    let recent = DB
+        .search_history(workspace_id)?
+        .into_iter()
+        .map(SearchEntry::Recent);
+    Ok(saved.chain(recent).collect())
+}
+
+pub struct SavedSearches {
+    picker: View<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearches {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ToggleSavedSearches, cx| {
+            let workspace_id = workspace.database_id();
+            let Some(entries) = search_entries(workspace_id).log_err() else {
+                return;
+            };
+            let current_search = workspace
+                .active_item_as::<ProjectSearchView>(cx)
+                .map(|search_view| search_view.read(cx).serialized_search(cx))
+                .filter(|search| !search.query.is_empty());
+            let handle = cx.view().downgrade();
+            workspace.toggle_modal(cx, move |cx| {
+                SavedSearches::new(handle, workspace_id, entries, current_search, cx)
+            });
+        });
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        entries: Vec<SearchEntry>,
+        current_search: Option<SerializedSearch>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = SavedSearchesDelegate {
+            saved_searches: cx.view().downgrade(),
+            workspace,
+            workspace_id,
+            entries,
+            current_search,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for SavedSearches {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for SavedSearches {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearches {}
+impl ModalView for SavedSearches {}
+
+pub struct SavedSearchesDelegate {
+    saved_searches: WeakView<SavedSearches>,
+    workspace: WeakView<Workspace>,
+    workspace_id: WorkspaceId,
+    entries: Vec<SearchEntry>,
+    /// The search of the active project search, which can be saved under the query.
+    current_search: Option<SerializedSearch>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+}
+
+impl SavedSearchesDelegate {
+    fn save_current_search(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let name = self.query.trim().to_string();
+        let Some(search) = self.current_search.clone().filter(|_| !name.is_empty()) else {
+            return;
+        };
+        let workspace_id = self.workspace_id;
+        cx.background_executor()
+            .spawn(async move { DB.save_search(workspace_id, name, search).await.log_err() })
+            .detach();
+        self.dismissed(cx);
+    }
+
+    /// Applies a change to the saved searches, then reloads the entries of the picker.
+    fn update_saved_searches(
+        &mut self,
+        update: impl Future<Output = anyhow::Result<()>> + 'static,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let workspace_id = self.workspace_id;
+        cx.spawn(|picker, mut cx| async move {
+            update.await?;
+            let entries = search_entries(workspace_id)?;
+            picker.update(&mut cx, |picker, cx| {
+                picker.delegate.entries = entries;
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_saved_search_buttons(
+        &self,
+        name: &str,
+        pinned: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> impl IntoElement {
+        let workspace_id = self.workspace_id;
+        h_flex()
+            .child(
+                IconButton::new(SharedString::from(format!("pin-{name}")), IconName::Pin)
+                    .icon_size(IconSize::Small)
+                    .selected(pinned)
+                    .tooltip(move |cx| {
+                        Tooltip::text(if pinned { "Unpin Search" } else { "Pin Search" }, cx)
+                    })
+                    .on_click(cx.listener({
+                        let name = name.to_string();
+                        move |picker, _, cx| {
+                            let update = DB.set_search_pinned(workspace_id, name.clone(), !pinned);
+                            picker.delegate.update_saved_searches(update, cx);
+                        }
+                    })),
+            )
+            .child(
+                IconButton::new(
+                    SharedString::from(format!("delete-{name}")),
+                    IconName::Close,
+                )
+                .icon_size(IconSize::Small)
+                .tooltip(|cx| Tooltip::text("Delete Search", cx))
+                .on_click(cx.listener({
+                    let name = name.to_string();
+                    move |picker, _, cx| {
+                        let update = DB.delete_saved_search(workspace_id, name.clone());
+                        picker.delegate.update_saved_searches(update, cx);
+                    }
+                })),
+            )
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Run a saved or recent search, or name the current one...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        // Confirming a name that matches no search saves the current one under it.
+        let Some(mat) = self.matches.get(self.selected_index).filter(|_| !secondary) else {
+            self.save_current_search(cx);
+            return;
+        };
+        let search = self.entries[mat.candidate_id].search().clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::deploy_serialized_search(workspace, &search, cx)
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.saved_searches
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, entry.label().to_string()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.query = query;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let entry = &self.entries[mat.candidate_id];
+        let label = HighlightedLabel::new(entry.label().replace('\n', " "), mat.positions.clone());
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+        Some(match entry {
+            SearchEntry::Saved {
+                name,
+                pinned,
+                search,
+            } => item
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(label)
+                        .child(Label::new(search.query.replace('\n', " ")).color(Color::Muted)),
+                )
+                .end_slot(self.render_saved_search_buttons(name, *pinned, cx)),
+            SearchEntry::Recent(search) => item.child(label).end_slot(
+                Label::new(format!("recent {}", search.mode.label().to_lowercase()))
+                    .color(Color::Muted),
+            ),
+        })
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.query.trim().is_empty() || self.current_search.is_none() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .p_2()
+                .justify_end()
+                .child(
+                    Button::new("save-search", "Save Current Search")
+                        .style(ButtonStyle::Filled)
+                        .on_click(
+                            cx.listener(|picker, _, cx| picker.delegate.save_current_search(cx)),
+                        ),
+                )
+                .into_any_element(),
+        )
+    }
+}
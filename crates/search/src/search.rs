@@ -10,13 +10,16 @@ use ui::{ButtonStyle, IconButton};
 pub mod buffer_search;
 mod history;
 mod mode;
+mod persistence;
 pub mod project_search;
+pub mod saved_searches;
 pub(crate) mod search_bar;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
     buffer_search::init(cx);
     project_search::init(cx);
+    saved_searches::init(cx);
 }
 
 actions!(
@@ -34,6 +37,8 @@ actions!(
         PreviousHistoryQuery,
         ActivateTextMode,
         ActivateRegexMode,
+        ActivateStructuralMode,
+        ActivateSemanticMode,
         ReplaceAll,
         ReplaceNext,
     ]
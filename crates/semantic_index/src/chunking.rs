@@ -0,0 +1,270 @@
+use language::{EmbeddingConfig, Grammar, Language};
+use sha2::{Digest, Sha256};
+use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
+use tree_sitter::QueryCursor;
+
+/// The maximum length of the code embedded for a single chunk.
+const MAX_CHUNK_LEN: usize = 6000;
+/// Files that can't be chunked along their syntax are split into chunks of this many lines.
+const FALLBACK_CHUNK_LINES: usize = 60;
+const COLLAPSED_TEXT: &str = "...";
+
+/// A part of a file that is embedded and searched as a unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    /// The byte range of the chunk in the file.
+    pub range: Range<usize>,
+    /// The text whose embedding represents the chunk.
+    pub text: String,
+    /// A hash of [`Self::text`], identifying the chunk's embedding.
+    pub digest: [u8; 32],
+}
+
+/// Splits a file into chunks using the embedding query of its language, which captures
+/// the items to embed along with their context, and the parts of nested items that should
+/// be collapsed to keep chunks small. Files in languages without an embedding query, or
+/// without any item, are split into chunks of consecutive lines.
+pub fn chunk_text(text: &str, language: Option<&Arc<Language>>, path: &Path) -> Vec<Chunk> {
+    let mut chunks = language
+        .and_then(|language| {
+            let grammar = language.grammar()?;
+            let config = grammar.embedding_config.as_ref()?;
+            Some(syntax_chunks(text, grammar, config))
+        })
+        .unwrap_or_default();
+    if chunks.is_empty() {
+        chunks = line_chunks(text);
+    }
+
+    let language_name = language
+        .map(|language| language.name().to_lowercase())
+        .unwrap_or_default();
+    chunks
+        .into_iter()
+        .map(|(range, mut content)| {
+            if content.len() > MAX_CHUNK_LEN {
+                let mut end = MAX_CHUNK_LEN;
+                while !content.is_char_boundary(end) {
+                    end -= 1;
+                }
+                content.truncate(end);
+            }
+            let text = format!(
+                "The below code snippet is from file '{}'\n\n```{language_name}\n{content}\n```",
+                path.display()
+            );
+            let digest = Sha256::digest(text.as_bytes()).into();
+            Chunk {
+                range,
+                text,
+                digest,
+            }
+        })
+        .collect()
+}
+
+struct Item {
+    range: Range<usize>,
+    context_ranges: Vec<Range<usize>>,
+    /// The ranges collapsed when the item is part of another item, but not in its own chunk.
+    own_collapse_ranges: Vec<Range<usize>>,
+}
+
+fn syntax_chunks(
+    text: &str,
+    grammar: &Grammar,
+    config: &EmbeddingConfig,
+) -> Vec<(Range<usize>, String)> {
+    let tree = grammar.parse_str(text);
+    let mut cursor = QueryCursor::new();
+    let mut items = Vec::<Item>::new();
+    let mut collapse_ranges = Vec::new();
+    let mut keep_ranges = Vec::new();
+    for mat in cursor.matches(&config.query, tree.root_node(), text.as_bytes()) {
+        let mut item_range = None;
+        let mut context_ranges = Vec::new();
+        let mut own_collapse_ranges = Vec::new();
+        for capture in mat.captures {
+            let range = capture.node.byte_range();
+            if capture.index == config.item_capture_ix {
+                item_range = Some(range);
+            } else if Some(capture.index) == config.context_capture_ix {
+                context_ranges.push(range);
+            } else if Some(capture.index) == config.collapse_capture_ix {
+                own_collapse_ranges.push(range);
+            } else if Some(capture.index) == config.keep_capture_ix {
+                keep_ranges.push(range);
+            }
+        }
+        collapse_ranges.extend(own_collapse_ranges.iter().cloned());
+        if let Some(range) = item_range {
+            // The same item can be matched with and without its context, so keep the match
+            // with the most context.
+            if let Some(item) = items.iter_mut().find(|item| item.range == range) {
+                if context_ranges.len() > item.context_ranges.len() {
+                    item.context_ranges = context_ranges;
+                }
+            } else {
+                items.push(Item {
+                    range,
+                    context_ranges,
+                    own_collapse_ranges,
+                });
+            }
+        }
+    }
+    collapse_ranges.sort_unstable_by_key(|range| (range.start, Reverse(range.end)));
+    collapse_ranges.dedup();
+
+    items
+        .into_iter()
+        .map(|item| {
+            let mut content = String::new();
+            for context_range in &item.context_ranges {
+                content.push_str(text[context_range.clone()].trim_end());
+                content.push('\n');
+            }
+
+            let mut offset = item.range.start;
+            for collapse_range in &collapse_ranges {
+                if collapse_range.start < offset
+                    || collapse_range.end > item.range.end
+                    || item.own_collapse_ranges.contains(collapse_range)
+                {
+                    continue;
+                }
+                content.push_str(&text[offset..collapse_range.start]);
+                let kept_ranges = keep_ranges
+                    .iter()
+                    .filter(|keep_range| {
+                        collapse_range.start <= keep_range.start
+                            && keep_range.end <= collapse_range.end
+                    })
+                    .collect::<Vec<_>>();
+                if kept_ranges.is_empty() {
+                    content.push_str(COLLAPSED_TEXT);
+                } else {
+                    for (ix, keep_range) in kept_ranges.into_iter().enumerate() {
+                        if ix > 0 {
+                            content.push(' ');
+                            content.push_str(COLLAPSED_TEXT);
+                            content.push(' ');
+                        }
+                        content.push_str(&text[keep_range.clone()]);
+                    }
+                }
+                offset = collapse_range.end;
+            }
+            content.push_str(&text[offset..item.range.end]);
+
+            let start = item
+                .context_ranges
+                .iter()
+                .map(|range| range.start)
+                .fold(item.range.start, usize::min);
+            (start..item.range.end, content)
+        })
+        .collect()
+}
+
+fn line_chunks(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut line_count = 0;
+    for (ix, _) in text.match_indices('\n') {
+        line_count += 1;
+        if line_count == FALLBACK_CHUNK_LINES {
+            chunks.push(start..ix + 1);
+            start = ix + 1;
+            line_count = 0;
+        }
+    }
+    if start < text.len() {
+        chunks.push(start..text.len());
+    }
+    chunks
+        .into_iter()
+        .filter(|range| !text[range.clone()].trim().is_empty())
+        .map(|range| (range.clone(), text[range].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::{LanguageConfig, LanguageMatcher};
+    use unindent::Unindent as _;
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_embedding_query(include_str!("../../languages/src/rust/embedding.scm"))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_chunk_rust_items() {
+        let text = "
+            use std::fmt;
+
+            /// A point.
+            #[derive(Debug)]
+            struct Point {
+                x: i32,
+            }
+
+            impl Point {
+                fn norm(&self) -> i32 {
+                    self.x.abs()
+                }
+            }
+        "
+        .unindent();
+        let chunks = chunk_text(&text, Some(&rust_lang()), Path::new("src/point.rs"));
+        let contents = chunks
+            .iter()
+            .map(|chunk| {
+                let content = chunk.text.split("```rust\n").nth(1).unwrap();
+                content.strip_suffix("\n```").unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            contents,
+            [
+                "/// A point.\n#[derive(Debug)]\nstruct Point {\n    x: i32,\n}",
+                "impl Point {\n    fn norm(&self) -> i32 { ... }\n}",
+                "fn norm(&self) -> i32 {\n        self.x.abs()\n    }",
+            ]
+        );
+        let struct_start = text.find("/// A point.").unwrap();
+        let struct_end = text.find("}\n").unwrap() + 1;
+        assert_eq!(chunks[0].range, struct_start..struct_end);
+        assert!(chunks[0]
+            .text
+            .starts_with("The below code snippet is from file 'src/point.rs'"));
+        assert_ne!(chunks[1].digest, chunks[2].digest);
+    }
+
+    #[test]
+    fn test_chunk_plain_text_by_lines() {
+        let text = (0..150)
+            .map(|ix| format!("line {ix}\n"))
+            .collect::<String>();
+        let chunks = chunk_text(&text, None, Path::new("notes.txt"));
+        assert_eq!(chunks.len(), 3);
+        assert!(text[chunks[0].range.clone()].starts_with("line 0\n"));
+        assert!(text[chunks[1].range.clone()].starts_with("line 60\n"));
+        assert_eq!(chunks[2].range.end, text.len());
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{anyhow, Result};
+use futures::{future::BoxFuture, AsyncReadExt, FutureExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::http::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+
+/// An embedding vector, normalized so that the similarity of two embeddings is their dot product.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embedding(Vec<f32>);
+
+impl Embedding {
+    pub fn new(mut values: Vec<f32>) -> Self {
+        let norm = values.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0. {
+            for value in &mut values {
+                *value /= norm;
+            }
+        }
+        Self(values)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the cosine similarity of the two embeddings, between -1 and 1.
+    pub fn similarity(&self, other: &Self) -> f32 {
+        self.0.iter().zip(&other.0).map(|(a, b)| a * b).sum()
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() % 4 != 0 {
+            return Err(anyhow!("invalid embedding of {} bytes", bytes.len()));
+        }
+        Ok(Self(
+            bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+        ))
+    }
+}
+
+/// A service turning text into embeddings.
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifies the model computing the embeddings. Embeddings computed by different
+    /// models can't be compared with each other, so they are stored separately.
+    fn model_id(&self) -> &str;
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>>;
+    /// The maximum number of texts to pass to a single call to [`Self::embed`].
+    fn batch_size(&self) -> usize;
+}
+
+/// Computes embeddings with a local server exposing the Ollama API.
+pub struct OllamaEmbeddingProvider {
+    client: Arc<dyn HttpClient>,
+    api_url: String,
+    model: String,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(client: Arc<dyn HttpClient>, api_url: String, model: String) -> Self {
+        Self {
+            client,
+            api_url,
+            model,
+        }
+    }
+
+    async fn embed_text(&self, text: &str) -> Result<Embedding> {
+        #[derive(Serialize)]
+        struct OllamaEmbeddingRequest<'a> {
+            model: &'a str,
+            prompt: &'a str,
+        }
+
+        #[derive(Deserialize)]
+        struct OllamaEmbeddingResponse {
+            embedding: Vec<f32>,
+        }
+
+        let request = OllamaEmbeddingRequest {
+            model: &self.model,
+            prompt: text,
+        };
+        let request = HttpRequest::builder()
+            .method(Method::POST)
+            .uri(format!("{}/api/embeddings", self.api_url))
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(serde_json::to_string(&request)?))?;
+        let mut response = self.client.send(request).await?;
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        if response.status().is_success() {
+            let response: OllamaEmbeddingResponse = serde_json::from_str(&body)?;
+            Ok(Embedding::new(response.embedding))
+        } else {
+            Err(anyhow!(
+                "error during embedding, status: {:?}, body: {:?}",
+                response.status(),
+                body
+            ))
+        }
+    }
+}
+
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        async move {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for text in texts {
+                embeddings.push(self.embed_text(text).await?);
+            }
+            Ok(embeddings)
+        }
+        .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        16
+    }
+}
+
+/// Computes embeddings with the OpenAI API, or a server compatible with it.
+pub struct OpenAiEmbeddingProvider {
+    client: Arc<dyn HttpClient>,
+    api_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiEmbeddingProvider {
+    pub fn new(
+        client: Arc<dyn HttpClient>,
+        api_url: String,
+        api_key: String,
+        model: String,
+    ) -> Self {
+        Self {
+            client,
+            api_url,
+            api_key,
+            model,
+        }
+    }
+}
+
+impl EmbeddingProvider for OpenAiEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        async move {
+            let response = open_ai::embed(
+                self.client.as_ref(),
+                &self.api_url,
+                &self.api_key,
+                &self.model,
+                texts.iter().map(String::as_str),
+            )
+            .await?;
+            if response.data.len() != texts.len() {
+                return Err(anyhow!(
+                    "expected {} embeddings, got {}",
+                    texts.len(),
+                    response.data.len()
+                ));
+            }
+            Ok(response
+                .data
+                .into_iter()
+                .map(|embedding| Embedding::new(embedding.embedding))
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        64
+    }
+}
+
+/// Embeds texts as the counts of their words hashed into a few buckets, so that texts
+/// sharing words are similar.
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeEmbeddingProvider;
+
+#[cfg(any(test, feature = "test-support"))]
+impl EmbeddingProvider for FakeEmbeddingProvider {
+    fn model_id(&self) -> &str {
+        "fake"
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        const DIMENSIONS: usize = 64;
+
+        let embeddings = texts
+            .iter()
+            .map(|text| {
+                let mut values = vec![0.; DIMENSIONS];
+                for word in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                {
+                    let hash = word.to_lowercase().bytes().fold(0_usize, |hash, byte| {
+                        hash.wrapping_mul(31).wrapping_add(byte as usize)
+                    });
+                    values[hash % DIMENSIONS] += 1.;
+                }
+                Embedding::new(values)
+            })
+            .collect();
+        futures::future::ready(Ok(embeddings)).boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_similarity() {
+        let a = Embedding::new(vec![3., 4.]);
+        let b = Embedding::new(vec![6., 8.]);
+        let c = Embedding::new(vec![-4., 3.]);
+        assert!((a.similarity(&b) - 1.).abs() < 1e-6);
+        assert!(a.similarity(&c).abs() < 1e-6);
+        assert_eq!(Embedding::from_bytes(&a.to_bytes()).unwrap(), a);
+        assert!(Embedding::from_bytes(&[0, 1, 2]).is_err());
+    }
+}
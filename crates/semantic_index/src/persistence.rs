@@ -0,0 +1,304 @@
+use crate::embedding::Embedding;
+use anyhow::Result;
+use collections::HashMap;
+use db::{
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use std::{
+    ops::{Deref, Range},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::paths::EMBEDDINGS_DIR;
+
+/// The embeddings of project files, stored in their own database since they can grow large
+/// and can be recomputed at any time.
+///
+/// Embeddings are keyed by the digest of the chunk they were computed for, so that chunks
+/// that didn't change when a file is edited, or that appear in several worktrees, are only
+/// embedded once.
+#[derive(Clone)]
+pub struct SemanticIndexDb(ThreadSafeConnection<SemanticIndexDb>);
+
+impl Deref for SemanticIndexDb {
+    type Target = ThreadSafeConnection<SemanticIndexDb>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Domain for SemanticIndexDb {
+    fn name() -> &'static str {
+        "SemanticIndexDb"
+    }
+
+    fn migrations() -> &'static [&'static str] {
+        &[sql!(
+            CREATE TABLE semantic_index_files(
+                worktree_path BLOB NOT NULL,
+                path BLOB NOT NULL,
+                mtime_seconds INTEGER NOT NULL,
+                mtime_nanos INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                PRIMARY KEY(worktree_path, path)
+            ) STRICT;
+
+            CREATE TABLE semantic_index_chunks(
+                worktree_path BLOB NOT NULL,
+                path BLOB NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                digest BLOB NOT NULL,
+                FOREIGN KEY(worktree_path, path) REFERENCES semantic_index_files(worktree_path, path)
+                ON DELETE CASCADE
+            ) STRICT;
+            CREATE INDEX semantic_index_chunks_by_file ON semantic_index_chunks(worktree_path, path);
+
+            CREATE TABLE semantic_index_embeddings(
+                model TEXT NOT NULL,
+                digest BLOB NOT NULL,
+                embedding BLOB NOT NULL,
+                PRIMARY KEY(model, digest)
+            ) STRICT;
+        )]
+    }
+}
+
+/// A chunk of a file, along with its embedding.
+pub struct EmbeddedChunk {
+    pub range: Range<usize>,
+    pub digest: [u8; 32],
+    pub embedding: Embedding,
+}
+
+impl SemanticIndexDb {
+    pub async fn open() -> Self {
+        Self(db::open_db(&EMBEDDINGS_DIR, &db::RELEASE_CHANNEL).await)
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub async fn open_test(name: &str) -> Self {
+        Self(db::open_test_db(name).await)
+    }
+
+    /// Returns the modification time of the files of the given worktree whose embeddings
+    /// were computed by the given model.
+    pub fn indexed_files(
+        &self,
+        worktree_path: &Path,
+        model: &str,
+    ) -> Result<HashMap<Arc<Path>, SystemTime>> {
+        let files = self.select_bound::<(&Path, &str), (PathBuf, i64, u32)>(sql!(
+            SELECT path, mtime_seconds, mtime_nanos
+            FROM semantic_index_files
+            WHERE worktree_path = ? AND model = ?
+        ))?((worktree_path, model))?;
+        Ok(files
+            .into_iter()
+            .map(|(path, seconds, nanos)| {
+                let mtime = UNIX_EPOCH + Duration::new(seconds as u64, nanos);
+                (path.into(), mtime)
+            })
+            .collect())
+    }
+
+    /// Returns the embeddings already computed by the given model for chunks with the given digests.
+    pub fn cached_embeddings(
+        &self,
+        model: &str,
+        digests: &[[u8; 32]],
+    ) -> Result<HashMap<[u8; 32], Embedding>> {
+        let mut select = self.select_row_bound::<(&str, &[u8; 32]), Vec<u8>>(sql!(
+            SELECT embedding FROM semantic_index_embeddings WHERE model = ? AND digest = ?
+        ))?;
+        let mut embeddings = HashMap::default();
+        for digest in digests {
+            if let Some(bytes) = select((model, digest))? {
+                embeddings.insert(*digest, Embedding::from_bytes(&bytes)?);
+            }
+        }
+        Ok(embeddings)
+    }
+
+    /// Returns the chunks of all the files of the given worktree whose embeddings were
+    /// computed by the given model.
+    pub fn chunks(
+        &self,
+        worktree_path: &Path,
+        model: &str,
+    ) -> Result<Vec<(PathBuf, Range<usize>, Embedding)>> {
+        let rows = self.select_bound::<(&Path, &str), (PathBuf, usize, usize, Vec<u8>)>(sql!(
+            SELECT chunks.path, chunks.start_offset, chunks.end_offset, embeddings.embedding
+            FROM semantic_index_chunks AS chunks
+            JOIN semantic_index_files AS files
+                ON files.worktree_path = chunks.worktree_path AND files.path = chunks.path
+            JOIN semantic_index_embeddings AS embeddings
+                ON embeddings.model = files.model AND embeddings.digest = chunks.digest
+            WHERE chunks.worktree_path = ? AND files.model = ?
+        ))?((worktree_path, model))?;
+        rows.into_iter()
+            .map(|(path, start, end, embedding)| {
+                Ok((path, start..end, Embedding::from_bytes(&embedding)?))
+            })
+            .collect()
+    }
+
+    /// Replaces the chunks of a file with the given ones, storing their embeddings.
+    pub async fn save_file(
+        &self,
+        worktree_path: Arc<Path>,
+        path: Arc<Path>,
+        mtime: SystemTime,
+        model: String,
+        chunks: Vec<EmbeddedChunk>,
+    ) -> Result<()> {
+        let mtime = mtime.duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.write(move |conn| {
+            conn.with_savepoint("save_semantic_index_file", || {
+                conn.exec_bound::<(&Path, &Path)>(sql!(
+                    DELETE FROM semantic_index_files WHERE worktree_path = ? AND path = ?
+                ))?((worktree_path.as_ref(), path.as_ref()))?;
+                conn.exec_bound::<(&Path, &Path, i64, u32, &str)>(sql!(
+                    INSERT INTO semantic_index_files
+                        (worktree_path, path, mtime_seconds, mtime_nanos, model)
+                    VALUES (?, ?, ?, ?, ?)
+                ))?((
+                    worktree_path.as_ref(),
+                    path.as_ref(),
+                    mtime.as_secs() as i64,
+                    mtime.subsec_nanos(),
+                    model.as_str(),
+                ))?;
+
+                let mut insert_chunk =
+                    conn.exec_bound::<(&Path, &Path, usize, usize, &[u8; 32])>(sql!(
+                        INSERT INTO semantic_index_chunks
+                            (worktree_path, path, start_offset, end_offset, digest)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?;
+                let mut insert_embedding = conn.exec_bound::<(&str, &[u8; 32], Vec<u8>)>(sql!(
+                    INSERT OR REPLACE INTO semantic_index_embeddings (model, digest, embedding)
+                    VALUES (?, ?, ?)
+                ))?;
+                for chunk in &chunks {
+                    insert_chunk((
+                        worktree_path.as_ref(),
+                        path.as_ref(),
+                        chunk.range.start,
+                        chunk.range.end,
+                        &chunk.digest,
+                    ))?;
+                    insert_embedding((model.as_str(), &chunk.digest, chunk.embedding.to_bytes()))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Removes the given files of a worktree, along with their chunks.
+    pub async fn remove_files(
+        &self,
+        worktree_path: Arc<Path>,
+        paths: Vec<Arc<Path>>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("remove_semantic_index_files", || {
+                let mut delete = conn.exec_bound::<(&Path, &Path)>(sql!(
+                    DELETE FROM semantic_index_files WHERE worktree_path = ? AND path = ?
+                ))?;
+                for path in &paths {
+                    delete((worktree_path.as_ref(), path.as_ref()))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_remove_files() {
+        let db = SemanticIndexDb::open_test("test_save_and_remove_files").await;
+        let worktree_path: Arc<Path> = Path::new("/root").into();
+        let mtime = UNIX_EPOCH + Duration::new(100, 5);
+        let chunk = |range: Range<usize>, byte: u8, values: Vec<f32>| EmbeddedChunk {
+            range,
+            digest: [byte; 32],
+            embedding: Embedding::new(values),
+        };
+
+        db.save_file(
+            worktree_path.clone(),
+            Path::new("a.rs").into(),
+            mtime,
+            "model".into(),
+            vec![
+                chunk(0..10, 1, vec![1., 0.]),
+                chunk(10..20, 2, vec![0., 1.]),
+            ],
+        )
+        .await
+        .unwrap();
+        db.save_file(
+            worktree_path.clone(),
+            Path::new("b.rs").into(),
+            mtime,
+            "model".into(),
+            vec![chunk(0..5, 1, vec![1., 0.])],
+        )
+        .await
+        .unwrap();
+
+        let files = db.indexed_files(&worktree_path, "model").unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[Path::new("a.rs")], mtime);
+        assert!(db
+            .indexed_files(&worktree_path, "other")
+            .unwrap()
+            .is_empty());
+
+        let cached = db.cached_embeddings("model", &[[1; 32], [3; 32]]).unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[&[1; 32]], Embedding::new(vec![1., 0.]));
+
+        // Saving a file again replaces its chunks.
+        db.save_file(
+            worktree_path.clone(),
+            Path::new("a.rs").into(),
+            mtime,
+            "model".into(),
+            vec![chunk(0..20, 2, vec![0., 1.])],
+        )
+        .await
+        .unwrap();
+        let mut chunks = db
+            .chunks(&worktree_path, "model")
+            .unwrap()
+            .into_iter()
+            .map(|(path, range, _)| (path, range))
+            .collect::<Vec<_>>();
+        chunks.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            chunks,
+            [
+                (PathBuf::from("a.rs"), 0..20),
+                (PathBuf::from("b.rs"), 0..5)
+            ]
+        );
+
+        db.remove_files(worktree_path.clone(), vec![Path::new("a.rs").into()])
+            .await
+            .unwrap();
+        let chunks = db.chunks(&worktree_path, "model").unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].0, PathBuf::from("b.rs"));
+    }
+}
@@ -0,0 +1,771 @@
+mod chunking;
+mod embedding;
+mod persistence;
+mod semantic_index_settings;
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use futures::{channel::mpsc, StreamExt};
+use gpui::{
+    AppContext, AsyncAppContext, Context, EntityId, Global, Model, ModelContext, Subscription,
+    Task, WeakModel,
+};
+use language::LanguageRegistry;
+use persistence::{EmbeddedChunk, SemanticIndexDb};
+use project::{Fs, Project, UpdatedEntriesSet, Worktree, WorktreeId};
+use settings::{Settings, SettingsStore};
+use std::{
+    env,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use util::{http::HttpClient, ResultExt};
+
+pub use chunking::{chunk_text, Chunk};
+#[cfg(any(test, feature = "test-support"))]
+pub use embedding::FakeEmbeddingProvider;
+pub use embedding::{
+    Embedding, EmbeddingProvider, OllamaEmbeddingProvider, OpenAiEmbeddingProvider,
+};
+pub use semantic_index_settings::{EmbeddingProviderKind, SemanticIndexSettings};
+
+/// Files larger than this are not indexed, as they are usually generated.
+const MAX_FILE_SIZE: usize = 1024 * 1024;
+/// How many times indexing a file is attempted, e.g. when the embedding provider is
+/// unreachable, before giving up until the file changes again.
+const MAX_INDEXING_ATTEMPTS: u32 = 3;
+/// The delay before retrying to index a file, doubled after each failed attempt.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub fn init(http_client: Arc<dyn HttpClient>, cx: &mut AppContext) {
+    SemanticIndexSettings::register(cx);
+    // The index becomes available once its database is opened, which can take a while when
+    // it has to be migrated.
+    let db = cx.background_executor().spawn(SemanticIndexDb::open());
+    cx.spawn(|mut cx| async move {
+        let db = db.await;
+        cx.update(|cx| {
+            let semantic_index = cx.new_model(|cx| SemanticIndex::new(db, http_client, cx));
+            cx.set_global(GlobalSemanticIndex(semantic_index));
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(any(test, feature = "test-support"))]
+pub fn init_test(provider: Arc<dyn EmbeddingProvider>, cx: &mut AppContext) {
+    SemanticIndexSettings::register(cx);
+    let db = cx
+        .background_executor()
+        .block(SemanticIndexDb::open_test("semantic_index_test"));
+    let semantic_index = cx.new_model(|_| SemanticIndex {
+        db,
+        http_client: util::http::FakeHttpClient::with_404_response(),
+        provider: Some(provider),
+        provider_settings: None,
+        project_indices: HashMap::default(),
+        _settings_subscription: None,
+    });
+    cx.set_global(GlobalSemanticIndex(semantic_index));
+}
+
+struct GlobalSemanticIndex(Model<SemanticIndex>);
+
+impl Global for GlobalSemanticIndex {}
+
+/// Indexes the files of local projects by the embeddings of their chunks, so that they can be
+/// searched by meaning rather than by their exact text.
+pub struct SemanticIndex {
+    db: SemanticIndexDb,
+    http_client: Arc<dyn HttpClient>,
+    provider: Option<Arc<dyn EmbeddingProvider>>,
+    provider_settings: Option<SemanticIndexSettings>,
+    project_indices: HashMap<EntityId, Model<ProjectIndex>>,
+    _settings_subscription: Option<Subscription>,
+}
+
+impl SemanticIndex {
+    pub fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalSemanticIndex>().0.clone()
+    }
+
+    pub fn try_global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalSemanticIndex>()
+            .map(|semantic_index| semantic_index.0.clone())
+    }
+
+    /// Whether semantic search is available, i.e. the index is enabled and has an embedding provider.
+    pub fn enabled(cx: &AppContext) -> bool {
+        Self::try_global(cx).map_or(false, |semantic_index| {
+            semantic_index.read(cx).provider.is_some()
+        })
+    }
+
+    fn new(
+        db: SemanticIndexDb,
+        http_client: Arc<dyn HttpClient>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            db,
+            http_client,
+            provider: None,
+            provider_settings: None,
+            project_indices: HashMap::default(),
+            _settings_subscription: Some(
+                cx.observe_global::<SettingsStore>(Self::on_settings_changed),
+            ),
+        };
+        this.on_settings_changed(cx);
+        this
+    }
+
+    fn on_settings_changed(&mut self, cx: &mut ModelContext<Self>) {
+        let settings = SemanticIndexSettings::get_global(cx);
+        let settings = settings.enabled.then(|| settings.clone());
+        if settings.as_ref().map(provider_key) == self.provider_settings.as_ref().map(provider_key)
+        {
+            return;
+        }
+        self.provider_settings = settings.clone();
+        self.set_provider(None, cx);
+
+        let Some(settings) = settings else {
+            return;
+        };
+        let api_url = settings.api_url().to_string();
+        let model = settings.model().to_string();
+        match settings.provider {
+            EmbeddingProviderKind::Ollama => {
+                let provider =
+                    OllamaEmbeddingProvider::new(self.http_client.clone(), api_url, model);
+                self.set_provider(Some(Arc::new(provider)), cx);
+            }
+            EmbeddingProviderKind::OpenAi => {
+                let http_client = self.http_client.clone();
+                cx.spawn(|this, mut cx| async move {
+                    let api_key = if let Ok(api_key) = env::var("OPENAI_API_KEY") {
+                        api_key
+                    } else {
+                        let (_, api_key) = cx
+                            .update(|cx| cx.read_credentials(&api_url))?
+                            .await?
+                            .ok_or_else(|| anyhow!("no OpenAI API key found for semantic index"))?;
+                        String::from_utf8(api_key)?
+                    };
+                    this.update(&mut cx, |this, cx| {
+                        // Ignore the key if the settings changed while it was being read.
+                        if this.provider_settings.as_ref().map(provider_key)
+                            == Some(provider_key(&settings))
+                        {
+                            let provider =
+                                OpenAiEmbeddingProvider::new(http_client, api_url, api_key, model);
+                            this.set_provider(Some(Arc::new(provider)), cx);
+                        }
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+    }
+
+    fn set_provider(
+        &mut self,
+        provider: Option<Arc<dyn EmbeddingProvider>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.provider = provider.clone();
+        for project_index in self.project_indices.values() {
+            project_index.update(cx, |project_index, cx| {
+                project_index.set_provider(provider.clone(), cx)
+            });
+        }
+        cx.notify();
+    }
+
+    /// Returns the index of the given project, creating it if needed. Only local projects are indexed.
+    pub fn project_index(
+        &mut self,
+        project: Model<Project>,
+        cx: &mut ModelContext<Self>,
+    ) -> Model<ProjectIndex> {
+        let project_id = project.entity_id();
+        if let Some(project_index) = self.project_indices.get(&project_id) {
+            return project_index.clone();
+        }
+
+        cx.observe_release(&project, move |this, _, _| {
+            this.project_indices.remove(&project_id);
+        })
+        .detach();
+        let db = self.db.clone();
+        let provider = self.provider.clone();
+        let project_index = cx.new_model(|cx| ProjectIndex::new(project, db, provider, cx));
+        self.project_indices
+            .insert(project_id, project_index.clone());
+        project_index
+    }
+
+    /// Returns the index of the given project, if it was already created.
+    pub fn existing_project_index(&self, project: &Model<Project>) -> Option<Model<ProjectIndex>> {
+        self.project_indices.get(&project.entity_id()).cloned()
+    }
+}
+
+fn provider_key(settings: &SemanticIndexSettings) -> (EmbeddingProviderKind, String, String) {
+    (
+        settings.provider,
+        settings.api_url().to_string(),
+        settings.model().to_string(),
+    )
+}
+
+/// A chunk of a project file that is similar to a search query.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub worktree_id: WorktreeId,
+    pub path: Arc<Path>,
+    /// The byte range of the chunk in the file, when it was indexed.
+    pub range: Range<usize>,
+    /// The similarity of the chunk to the query, between -1 and 1.
+    pub score: f32,
+}
+
+/// The embeddings of the files of a project's local worktrees, kept up to date as files change.
+pub struct ProjectIndex {
+    project: WeakModel<Project>,
+    db: SemanticIndexDb,
+    provider: Option<Arc<dyn EmbeddingProvider>>,
+    worktree_indices: HashMap<WorktreeId, WorktreeIndex>,
+    /// The number of files queued for indexing, across all worktrees.
+    pending_file_count: usize,
+    _subscription: Subscription,
+}
+
+struct WorktreeIndex {
+    abs_path: Arc<Path>,
+    changes_tx: mpsc::UnboundedSender<Vec<(Arc<Path>, Option<SystemTime>)>>,
+    _maintain_index: Task<()>,
+}
+
+impl ProjectIndex {
+    fn new(
+        project: Model<Project>,
+        db: SemanticIndexDb,
+        provider: Option<Arc<dyn EmbeddingProvider>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&project, |this, project, event, cx| match event {
+            project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                this.update_worktree_indices(&project, cx)
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                this.update_worktree_index(&project, *worktree_id, changes, cx)
+            }
+            _ => {}
+        });
+        let mut this = Self {
+            project: project.downgrade(),
+            db,
+            provider,
+            worktree_indices: HashMap::default(),
+            pending_file_count: 0,
+            _subscription: subscription,
+        };
+        this.update_worktree_indices(&project, cx);
+        this
+    }
+
+    /// The number of files that still have to be indexed.
+    pub fn pending_file_count(&self) -> usize {
+        self.pending_file_count
+    }
+
+    fn set_provider(
+        &mut self,
+        provider: Option<Arc<dyn EmbeddingProvider>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.provider = provider;
+        // Indexing restarts from scratch, since embeddings computed by another model are stale.
+        self.worktree_indices.clear();
+        self.pending_file_count = 0;
+        if let Some(project) = self.project.upgrade() {
+            self.update_worktree_indices(&project, cx);
+        }
+        cx.notify();
+    }
+
+    fn update_worktree_indices(&mut self, project: &Model<Project>, cx: &mut ModelContext<Self>) {
+        let Some(provider) = self.provider.clone() else {
+            return;
+        };
+        let worktrees = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter(|worktree| worktree.read(cx).as_local().is_some())
+            .collect::<Vec<_>>();
+        self.worktree_indices.retain(|worktree_id, _| {
+            worktrees
+                .iter()
+                .any(|worktree| worktree.read(cx).id() == *worktree_id)
+        });
+
+        let fs = project.read(cx).fs().clone();
+        let languages = project.read(cx).languages().clone();
+        for worktree in worktrees {
+            let worktree_id = worktree.read(cx).id();
+            if self.worktree_indices.contains_key(&worktree_id) {
+                continue;
+            }
+            let (changes_tx, changes_rx) = mpsc::unbounded();
+            let abs_path = worktree.read(cx).abs_path();
+            let maintain_index = cx.spawn({
+                let db = self.db.clone();
+                let provider = provider.clone();
+                let fs = fs.clone();
+                let languages = languages.clone();
+                |this, cx| async move {
+                    maintain_worktree_index(
+                        this, worktree, db, provider, fs, languages, changes_rx, cx,
+                    )
+                    .await
+                    .log_err();
+                }
+            });
+            self.worktree_indices.insert(
+                worktree_id,
+                WorktreeIndex {
+                    abs_path,
+                    changes_tx,
+                    _maintain_index: maintain_index,
+                },
+            );
+        }
+    }
+
+    fn update_worktree_index(
+        &mut self,
+        project: &Model<Project>,
+        worktree_id: WorktreeId,
+        changes: &UpdatedEntriesSet,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(index) = self.worktree_indices.get(&worktree_id) else {
+            return;
+        };
+        let Some(worktree) = project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let changes = changes
+            .iter()
+            .map(|(path, _, _)| {
+                let mtime = worktree
+                    .entry_for_path(path)
+                    .filter(|entry| entry.is_file() && !entry.is_ignored)
+                    .and_then(|entry| entry.mtime);
+                (path.clone(), mtime)
+            })
+            .collect();
+        index.changes_tx.unbounded_send(changes).ok();
+    }
+
+    /// Returns the indexed chunks most similar to the query, from the most to the least similar.
+    pub fn search(
+        &self,
+        query: String,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let Some(provider) = self.provider.clone() else {
+            return Task::ready(Err(anyhow!("semantic index is disabled")));
+        };
+        let db = self.db.clone();
+        let worktrees = self
+            .worktree_indices
+            .iter()
+            .map(|(worktree_id, index)| (*worktree_id, index.abs_path.clone()))
+            .collect::<Vec<_>>();
+        cx.background_executor().spawn(async move {
+            let query_embedding = provider
+                .embed(&[query])
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("no embedding returned for query"))?;
+
+            let mut results = Vec::new();
+            for (worktree_id, abs_path) in worktrees {
+                for (path, range, embedding) in db.chunks(&abs_path, provider.model_id())? {
+                    results.push(SearchResult {
+                        worktree_id,
+                        path: path.into(),
+                        range,
+                        score: query_embedding.similarity(&embedding),
+                    });
+                }
+            }
+            results.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn maintain_worktree_index(
+    this: WeakModel<ProjectIndex>,
+    worktree: Model<Worktree>,
+    db: SemanticIndexDb,
+    provider: Arc<dyn EmbeddingProvider>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    mut changes_rx: mpsc::UnboundedReceiver<Vec<(Arc<Path>, Option<SystemTime>)>>,
+    mut cx: AsyncAppContext,
+) -> Result<()> {
+    let scan_complete = worktree.update(&mut cx, |tree, _| {
+        tree.as_local().map(|tree| tree.scan_complete())
+    })?;
+    if let Some(scan_complete) = scan_complete {
+        scan_complete.await;
+    }
+    let Some(snapshot) = worktree.update(&mut cx, |tree, _| {
+        tree.as_local().map(|tree| tree.snapshot())
+    })?
+    else {
+        return Ok(());
+    };
+    drop(worktree);
+    let root = snapshot.abs_path().clone();
+
+    let mut indexed_files = cx
+        .background_executor()
+        .spawn({
+            let db = db.clone();
+            let root = root.clone();
+            let model = provider.model_id().to_string();
+            async move { db.indexed_files(&root, &model) }
+        })
+        .await?;
+    let mut stale_files = Vec::new();
+    for entry in snapshot.files(false, 0) {
+        let indexed_mtime = indexed_files.remove(&entry.path);
+        if let Some(mtime) = entry.mtime {
+            if indexed_mtime != Some(mtime) {
+                stale_files.push((entry.path.clone(), Some(mtime)));
+            }
+        }
+    }
+    drop(snapshot);
+    // Files that were indexed but are no longer part of the worktree.
+    stale_files.extend(indexed_files.into_keys().map(|path| (path, None)));
+    let indexer = Indexer {
+        this,
+        root,
+        db,
+        provider,
+        fs,
+        languages,
+    };
+    indexer.index_files(stale_files, &mut cx).await?;
+
+    while let Some(changes) = changes_rx.next().await {
+        indexer.index_files(changes, &mut cx).await?;
+    }
+    Ok(())
+}
+
+struct Indexer {
+    this: WeakModel<ProjectIndex>,
+    root: Arc<Path>,
+    db: SemanticIndexDb,
+    provider: Arc<dyn EmbeddingProvider>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+}
+
+impl Indexer {
+    /// Re-indexes the given files, or removes them from the index when no mtime is given.
+    /// Files that fail to be indexed are logged and skipped, so that they're retried the next
+    /// time they change. Only fails when the project index was dropped.
+    async fn index_files(
+        &self,
+        changes: Vec<(Arc<Path>, Option<SystemTime>)>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.update_pending_file_count(changes.len() as isize, cx)?;
+        let mut removed_files = Vec::new();
+        for (path, mtime) in changes {
+            let Some(mtime) = mtime else {
+                removed_files.push(path);
+                continue;
+            };
+            self.index_file_with_retries(path, mtime, cx).await;
+            self.update_pending_file_count(-1, cx)?;
+        }
+        if !removed_files.is_empty() {
+            let removed_count = removed_files.len();
+            self.db
+                .remove_files(self.root.clone(), removed_files)
+                .await
+                .log_err();
+            self.update_pending_file_count(-(removed_count as isize), cx)?;
+        }
+        Ok(())
+    }
+
+    async fn index_file_with_retries(
+        &self,
+        path: Arc<Path>,
+        mtime: SystemTime,
+        cx: &mut AsyncAppContext,
+    ) {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        for attempt in 1..=MAX_INDEXING_ATTEMPTS {
+            let error = match self.index_file(path.clone(), mtime, cx).await {
+                Ok(()) => return,
+                Err(error) => error,
+            };
+            if attempt == MAX_INDEXING_ATTEMPTS {
+                log::error!("failed to index {path:?}: {error:#}");
+            } else {
+                log::warn!("failed to index {path:?}, retrying in {retry_delay:?}: {error:#}");
+                cx.background_executor().timer(retry_delay).await;
+                retry_delay *= 2;
+            }
+        }
+    }
+
+    async fn index_file(
+        &self,
+        path: Arc<Path>,
+        mtime: SystemTime,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        // Files that can't be read as text are saved without chunks, so they aren't retried
+        // until they change.
+        let text = self
+            .fs
+            .load(&self.root.join(&path))
+            .await
+            .ok()
+            .filter(|text| text.len() <= MAX_FILE_SIZE);
+        let language = self.languages.language_for_file_path(&path).await.ok();
+        let chunks = match text {
+            Some(text) => {
+                let path = path.clone();
+                cx.background_executor()
+                    .spawn(async move { chunk_text(&text, language.as_ref(), &path) })
+                    .await
+            }
+            None => Vec::new(),
+        };
+
+        let model = self.provider.model_id().to_string();
+        let digests = chunks.iter().map(|chunk| chunk.digest).collect::<Vec<_>>();
+        let mut embeddings = cx
+            .background_executor()
+            .spawn({
+                let db = self.db.clone();
+                let model = model.clone();
+                async move { db.cached_embeddings(&model, &digests) }
+            })
+            .await?;
+        let missing_chunks = chunks
+            .iter()
+            .filter(|chunk| !embeddings.contains_key(&chunk.digest))
+            .collect::<Vec<_>>();
+        for batch in missing_chunks.chunks(self.provider.batch_size().max(1)) {
+            let texts = batch
+                .iter()
+                .map(|chunk| chunk.text.clone())
+                .collect::<Vec<_>>();
+            let batch_embeddings = self.provider.embed(&texts).await?;
+            embeddings.extend(batch.iter().map(|chunk| chunk.digest).zip(batch_embeddings));
+        }
+
+        let chunks = chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                Some(EmbeddedChunk {
+                    embedding: embeddings.get(&chunk.digest)?.clone(),
+                    range: chunk.range,
+                    digest: chunk.digest,
+                })
+            })
+            .collect();
+        self.db
+            .save_file(self.root.clone(), path, mtime, model, chunks)
+            .await
+    }
+
+    fn update_pending_file_count(&self, delta: isize, cx: &mut AsyncAppContext) -> Result<()> {
+        self.this.update(cx, |this, cx| {
+            this.pending_file_count = this.pending_file_count.saturating_add_signed(delta);
+            cx.notify();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{future::BoxFuture, FutureExt};
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    fn init_test(cx: &mut TestAppContext) {
+        init_test_with_provider(Arc::new(FakeEmbeddingProvider), cx);
+    }
+
+    fn init_test_with_provider(provider: Arc<dyn EmbeddingProvider>, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            super::init_test(provider, cx);
+        });
+    }
+
+    /// Never embeds texts mentioning "unembeddable", and fails the given number of other calls.
+    struct FlakyEmbeddingProvider {
+        failures_left: AtomicUsize,
+    }
+
+    impl EmbeddingProvider for FlakyEmbeddingProvider {
+        fn model_id(&self) -> &str {
+            "flaky"
+        }
+
+        fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+            let fails = texts.iter().any(|text| text.contains("unembeddable"))
+                || self
+                    .failures_left
+                    .fetch_update(SeqCst, SeqCst, |failures| failures.checked_sub(1))
+                    .is_ok();
+            if fails {
+                futures::future::ready(Err(anyhow!("embedding failed"))).boxed()
+            } else {
+                FakeEmbeddingProvider.embed(texts)
+            }
+        }
+
+        fn batch_size(&self) -> usize {
+            4
+        }
+    }
+
+    #[gpui::test]
+    async fn test_search_ranks_similar_chunks_first(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "fruits.txt": "apple banana cherry",
+                "vehicles.txt": "car truck bicycle",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let project_index = cx.update(|cx| {
+            SemanticIndex::global(cx).update(cx, |index, cx| index.project_index(project, cx))
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            project_index.read_with(cx, |index, _| index.pending_file_count()),
+            0
+        );
+
+        let results = project_index
+            .read_with(cx, |index, cx| index.search("truck and car".into(), 10, cx))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path.as_ref(), Path::new("vehicles.txt"));
+        assert!(results[0].score > results[1].score);
+
+        // Changed files are re-indexed.
+        fs.save(
+            "/dir/fruits.txt".as_ref(),
+            &"car car truck".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let results = project_index
+            .read_with(cx, |index, cx| index.search("car".into(), 1, cx))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path.as_ref(), Path::new("fruits.txt"));
+    }
+
+    #[gpui::test]
+    async fn test_indexing_errors(cx: &mut TestAppContext) {
+        init_test_with_provider(
+            Arc::new(FlakyEmbeddingProvider {
+                failures_left: AtomicUsize::new(1),
+            }),
+            cx,
+        );
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/flaky",
+            json!({
+                "broken.txt": "unembeddable",
+                "fruits.txt": "apple banana",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/flaky".as_ref()], cx).await;
+        let project_index = cx.update(|cx| {
+            SemanticIndex::global(cx).update(cx, |index, cx| index.project_index(project, cx))
+        });
+        for _ in 0..10 {
+            cx.executor().advance_clock(INITIAL_RETRY_DELAY);
+            cx.run_until_parked();
+        }
+        assert_eq!(
+            project_index.read_with(cx, |index, _| index.pending_file_count()),
+            0
+        );
+
+        // The file whose embedding failed once was indexed when retried, and the one that
+        // kept failing was skipped.
+        let results = project_index
+            .read_with(cx, |index, cx| index.search("apple".into(), 10, cx))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path.as_ref(), Path::new("fruits.txt"));
+
+        // Indexing goes on after a failure.
+        fs.save(
+            "/flaky/broken.txt".as_ref(),
+            &"cherry".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let results = project_index
+            .read_with(cx, |index, cx| index.search("cherry".into(), 1, cx))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path.as_ref(), Path::new("broken.txt"));
+    }
+}
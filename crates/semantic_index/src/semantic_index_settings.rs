@@ -0,0 +1,85 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderKind {
+    /// A local server exposing the Ollama embeddings API.
+    #[default]
+    Ollama,
+    /// The OpenAI embeddings API, or a server compatible with it.
+    OpenAi,
+}
+
+impl EmbeddingProviderKind {
+    pub fn default_api_url(&self) -> &'static str {
+        match self {
+            Self::Ollama => "http://localhost:11434",
+            Self::OpenAi => "https://api.openai.com/v1",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Self::Ollama => "nomic-embed-text",
+            Self::OpenAi => "text-embedding-3-small",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SemanticIndexSettings {
+    pub enabled: bool,
+    pub provider: EmbeddingProviderKind,
+    pub api_url: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SemanticIndexSettingsContent {
+    /// Whether to index local projects for semantic search.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The service used to compute the embeddings of code chunks and queries.
+    ///
+    /// Default: ollama
+    pub provider: Option<EmbeddingProviderKind>,
+    /// The URL of the embedding service. Defaults to the provider's own URL.
+    ///
+    /// Default: null
+    pub api_url: Option<String>,
+    /// The embedding model to use. Defaults to the provider's recommended model.
+    ///
+    /// Default: null
+    pub model: Option<String>,
+}
+
+impl SemanticIndexSettings {
+    pub fn api_url(&self) -> &str {
+        self.api_url
+            .as_deref()
+            .unwrap_or_else(|| self.provider.default_api_url())
+    }
+
+    pub fn model(&self) -> &str {
+        self.model
+            .as_deref()
+            .unwrap_or_else(|| self.provider.default_model())
+    }
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = SemanticIndexSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
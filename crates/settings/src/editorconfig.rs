@@ -0,0 +1,299 @@
+use anyhow::{Context as _, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use std::{num::NonZeroU32, path::Path};
+
+/// The name of the files EditorConfig properties are read from.
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// The contents of a single `.editorconfig` file.
+///
+/// See <https://spec.editorconfig.org> for the format.
+#[derive(Clone, Debug, Default)]
+pub struct Editorconfig {
+    /// Whether `.editorconfig` files in parent directories should be ignored.
+    pub root: bool,
+    sections: Vec<EditorconfigSection>,
+}
+
+#[derive(Clone, Debug)]
+struct EditorconfigSection {
+    glob: GlobMatcher,
+    properties: Vec<(String, String)>,
+}
+
+/// The EditorConfig properties that apply to a file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EditorconfigProperties {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<IndentWidth>,
+    pub tab_width: Option<NonZeroU32>,
+    /// The lowercased name of the file's character set, e.g. `utf-8` or `latin1`.
+    ///
+    /// Unlike the other properties, which override language settings, this one is
+    /// used by the worktree as the preferred encoding when loading the file.
+    pub charset: Option<String>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    pub max_line_length: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndentWidth {
+    Columns(NonZeroU32),
+    /// Use the value of `tab_width`.
+    Tab,
+}
+
+impl Editorconfig {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut editorconfig = Self::default();
+        for (ix, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[') {
+                let pattern = section
+                    .strip_suffix(']')
+                    .with_context(|| format!("unterminated section header on line {}", ix + 1))?;
+                let glob = section_glob(pattern)
+                    .with_context(|| format!("invalid section glob on line {}", ix + 1))?;
+                editorconfig.sections.push(EditorconfigSection {
+                    glob,
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .with_context(|| format!("expected a key-value pair on line {}", ix + 1))?;
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_string();
+            match editorconfig.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => editorconfig.root = value.eq_ignore_ascii_case("true"),
+                None => {}
+            }
+        }
+        Ok(editorconfig)
+    }
+
+    /// Applies the properties of every section matching the given path, which
+    /// must be relative to the directory containing this file.
+    pub fn apply_to(&self, path: &Path, properties: &mut EditorconfigProperties) {
+        for section in &self.sections {
+            if section.glob.is_match(path) {
+                for (key, value) in &section.properties {
+                    properties.set(key, value);
+                }
+            }
+        }
+    }
+}
+
+impl EditorconfigProperties {
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.to_lowercase();
+        let unset = value == "unset";
+        match key {
+            "indent_style" => {
+                self.indent_style = match value.as_str() {
+                    "tab" => Some(IndentStyle::Tab),
+                    "space" => Some(IndentStyle::Space),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value.as_str() {
+                    "tab" => Some(IndentWidth::Tab),
+                    _ if unset => None,
+                    _ => match value.parse() {
+                        Ok(columns) => Some(IndentWidth::Columns(columns)),
+                        Err(_) => return,
+                    },
+                }
+            }
+            "tab_width" => {
+                self.tab_width = match value.parse() {
+                    Ok(width) => Some(width),
+                    Err(_) if unset => None,
+                    Err(_) => return,
+                }
+            }
+            "charset" => self.charset = (!unset).then_some(value),
+            "trim_trailing_whitespace" => {
+                self.trim_trailing_whitespace = match value.as_str() {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "insert_final_newline" => {
+                self.insert_final_newline = match value.as_str() {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "max_line_length" => {
+                self.max_line_length = match value.parse() {
+                    Ok(length) => Some(length),
+                    Err(_) if unset || value == "off" => None,
+                    Err(_) => return,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The number of columns a tab should occupy, as described by the spec:
+    /// `indent_size` defaults to `tab_width` when set to `tab`, and `tab_width`
+    /// defaults to `indent_size` otherwise.
+    pub fn tab_size(&self) -> Option<NonZeroU32> {
+        match self.indent_size {
+            Some(IndentWidth::Columns(columns)) => Some(columns),
+            Some(IndentWidth::Tab) | None => self.tab_width,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Converts an EditorConfig section name into a glob matching paths relative
+/// to the `.editorconfig` file's directory.
+fn section_glob(pattern: &str) -> Result<GlobMatcher> {
+    let pattern = expand_numeric_ranges(pattern);
+    // EditorConfig allows `**` anywhere, while the glob matcher only supports it as a whole path component.
+    let pattern = pattern
+        .split("**")
+        .enumerate()
+        .fold(String::new(), |mut glob, (ix, part)| {
+            if ix > 0 {
+                if !glob.is_empty() && !glob.ends_with('/') {
+                    glob.push_str("*/");
+                }
+                glob.push_str("**");
+                if !part.is_empty() && !part.starts_with('/') {
+                    glob.push_str("/*");
+                }
+            }
+            glob.push_str(part);
+            glob
+        });
+    let pattern = if let Some(pattern) = pattern.strip_prefix('/') {
+        pattern.to_string()
+    } else if pattern.contains('/') {
+        pattern
+    } else {
+        format!("**/{pattern}")
+    };
+    Ok(GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .backslash_escape(true)
+        .build()?
+        .compile_matcher())
+}
+
+/// Expands `{n1..n2}` ranges into alternations, which the glob matcher supports.
+fn expand_numeric_ranges(pattern: &str) -> String {
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut remaining = pattern;
+    while let Some(start) = remaining.find('{') {
+        expanded.push_str(&remaining[..start]);
+        let after_start = &remaining[start + 1..];
+        let range = after_start.find('}').and_then(|end| {
+            let (low, high) = after_start[..end].split_once("..")?;
+            let low = low.parse::<i64>().ok()?;
+            let high = high.parse::<i64>().ok()?;
+            (low <= high && high - low <= 1024).then_some((low, high, end))
+        });
+        if let Some((low, high, end)) = range {
+            let numbers = (low..=high).map(|n| n.to_string()).collect::<Vec<_>>();
+            expanded.push('{');
+            expanded.push_str(&numbers.join(","));
+            expanded.push('}');
+            remaining = &after_start[end + 1..];
+        } else {
+            expanded.push('{');
+            remaining = after_start;
+        }
+    }
+    expanded.push_str(remaining);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editorconfig_sections() {
+        let editorconfig = Editorconfig::parse(
+            r#"
+            # top-most EditorConfig file
+            root = true
+
+            [*]
+            indent_style = space
+            indent_size = 4
+            insert_final_newline = true
+
+            [*.{js,ts}]
+            indent_size = 2
+
+            [Makefile]
+            indent_style = tab
+            indent_size = unset
+            tab_width = 8
+
+            [/lib/**.py]
+            max_line_length = 120
+
+            [test{1..3}.txt]
+            trim_trailing_whitespace = false
+            "#,
+        )
+        .unwrap();
+        assert!(editorconfig.root);
+
+        let properties = |path: &str| {
+            let mut properties = EditorconfigProperties::default();
+            editorconfig.apply_to(Path::new(path), &mut properties);
+            properties
+        };
+
+        let rust = properties("src/main.rs");
+        assert_eq!(rust.indent_style, Some(IndentStyle::Space));
+        assert_eq!(rust.tab_size(), NonZeroU32::new(4));
+        assert_eq!(rust.insert_final_newline, Some(true));
+
+        assert_eq!(properties("web/app.ts").tab_size(), NonZeroU32::new(2));
+
+        let makefile = properties("build/Makefile");
+        assert_eq!(makefile.indent_style, Some(IndentStyle::Tab));
+        assert_eq!(makefile.indent_size, None);
+        assert_eq!(makefile.tab_size(), NonZeroU32::new(8));
+
+        assert_eq!(properties("lib/a/b.py").max_line_length, Some(120));
+        assert_eq!(properties("src/lib/b.py").max_line_length, None);
+
+        assert_eq!(
+            properties("test2.txt").trim_trailing_whitespace,
+            Some(false)
+        );
+        assert_eq!(properties("test4.txt").trim_trailing_whitespace, None);
+    }
+}
@@ -1,3 +1,4 @@
+mod editorconfig;
 mod keymap_file;
 mod settings_file;
 mod settings_store;
@@ -6,6 +7,7 @@ use rust_embed::RustEmbed;
 use std::{borrow::Cow, str};
 use util::asset_str;
 
+pub use editorconfig::*;
 pub use keymap_file::KeymapFile;
 pub use settings_file::*;
 pub use settings_store::{Settings, SettingsJsonSchemaParams, SettingsLocation, SettingsStore};
@@ -7,6 +7,7 @@ use serde::{de::DeserializeOwned, Deserialize as _, Serialize};
 use smallvec::SmallVec;
 use std::{
     any::{type_name, Any, TypeId},
+    cell::RefCell,
     fmt::Debug,
     ops::Range,
     path::Path,
@@ -15,6 +16,8 @@ use std::{
 };
 use util::{merge_non_null_json_value_into, RangeExt, ResultExt as _};
 
+use crate::{Editorconfig, EditorconfigProperties};
+
 /// A value that can be defined as a user setting.
 ///
 /// Settings can be loaded from a combination of multiple JSON files.
@@ -137,6 +140,13 @@ pub struct SettingsStore {
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    raw_editorconfigs: BTreeMap<(usize, Arc<Path>), (String, Editorconfig)>,
+    /// The EditorConfig properties resolved for each file, cleared whenever an `.editorconfig` changes.
+    editorconfig_properties_cache:
+        RefCell<HashMap<(usize, Arc<Path>), Option<Arc<EditorconfigProperties>>>>,
+    /// The values derived from settings and EditorConfig properties, like the language settings
+    /// of files under an `.editorconfig`, cleared whenever settings or an `.editorconfig` change.
+    editorconfig_values_cache: RefCell<HashMap<EditorconfigValueKey, Box<dyn Any>>>,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
@@ -152,11 +162,24 @@ impl Default for SettingsStore {
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
+            raw_editorconfigs: Default::default(),
+            editorconfig_properties_cache: Default::default(),
+            editorconfig_values_cache: Default::default(),
             tab_size_callback: Default::default(),
         }
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
+struct EditorconfigValueKey {
+    type_id: TypeId,
+    root_id: usize,
+    /// The innermost directory with local settings containing the file.
+    settings_directory: Option<Arc<Path>>,
+    properties: Arc<EditorconfigProperties>,
+    name: Option<Arc<str>>,
+}
+
 #[derive(Debug)]
 struct SettingValue<T> {
     global_value: Option<T>,
@@ -255,7 +278,8 @@ impl SettingsStore {
         self.setting_values
             .get_mut(&TypeId::of::<T>())
             .unwrap_or_else(|| panic!("unregistered setting type {}", type_name::<T>()))
-            .set_global_value(Box::new(value))
+            .set_global_value(Box::new(value));
+        self.editorconfig_values_cache.get_mut().clear();
     }
 
     /// Get the user's settings as a raw JSON value.
@@ -428,6 +452,8 @@ impl SettingsStore {
     /// Add or remove a set of local settings via a JSON string.
     pub fn clear_local_settings(&mut self, root_id: usize, cx: &mut AppContext) -> Result<()> {
         self.raw_local_settings.retain(|k, _| k.0 != root_id);
+        self.raw_editorconfigs.retain(|k, _| k.0 != root_id);
+        self.clear_editorconfig_caches();
         self.recompute_values(Some((root_id, "".as_ref())), cx)?;
         Ok(())
     }
@@ -438,6 +464,131 @@ impl SettingsStore {
             .map(|((_, path), content)| (path.clone(), serde_json::to_string(content).unwrap()))
     }
 
+    /// Add or remove the `.editorconfig` file located in the given directory.
+    pub fn set_local_editorconfig(
+        &mut self,
+        root_id: usize,
+        directory: Arc<Path>,
+        content: Option<&str>,
+    ) -> Result<()> {
+        if let Some(content) = content {
+            let editorconfig = Editorconfig::parse(content)?;
+            self.raw_editorconfigs
+                .insert((root_id, directory), (content.to_string(), editorconfig));
+        } else {
+            self.raw_editorconfigs.remove(&(root_id, directory));
+        }
+        self.clear_editorconfig_caches();
+        Ok(())
+    }
+
+    pub fn local_editorconfigs(
+        &self,
+        root_id: usize,
+    ) -> impl '_ + Iterator<Item = (Arc<Path>, String)> {
+        self.raw_editorconfigs
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .map(|((_, path), (content, _))| (path.clone(), content.clone()))
+    }
+
+    /// Get the EditorConfig properties for the file at the given path.
+    ///
+    /// The `.editorconfig` files in the file's ancestor directories are applied from
+    /// the outermost to the innermost one, starting at the innermost file marked as `root`.
+    /// The properties are resolved once per file, as they're needed every time the file's
+    /// language settings are read.
+    pub fn editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Arc<Path>,
+    ) -> Option<Arc<EditorconfigProperties>> {
+        let worktree_range = (root_id, Path::new("").into())..(root_id + 1, Path::new("").into());
+        if self
+            .raw_editorconfigs
+            .range(worktree_range)
+            .next()
+            .is_none()
+        {
+            return None;
+        }
+
+        self.editorconfig_properties_cache
+            .borrow_mut()
+            .entry((root_id, path.clone()))
+            .or_insert_with(|| {
+                self.resolve_editorconfig_properties(root_id, path)
+                    .map(Arc::new)
+            })
+            .clone()
+    }
+
+    /// Get a value derived from the settings and the EditorConfig properties of the file at the
+    /// given path, like its language settings, or `None` if no `.editorconfig` applies to it.
+    ///
+    /// Values are derived once per worktree, local settings directory, set of properties and
+    /// `name`, so files matching the same `.editorconfig` sections share them.
+    pub fn editorconfig_value<T: 'static>(
+        &self,
+        root_id: usize,
+        path: &Arc<Path>,
+        name: Option<Arc<str>>,
+        derive: impl FnOnce(&EditorconfigProperties) -> T,
+    ) -> Option<&T> {
+        let properties = self.editorconfig_properties(root_id, path)?;
+        let settings_directory = self
+            .raw_local_settings
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .filter(|((_, directory), _)| path.starts_with(directory))
+            .last()
+            .map(|((_, directory), _)| directory.clone());
+        let key = EditorconfigValueKey {
+            type_id: TypeId::of::<T>(),
+            root_id,
+            settings_directory,
+            properties: properties.clone(),
+            name,
+        };
+
+        let mut cache = self.editorconfig_values_cache.borrow_mut();
+        let value = cache
+            .entry(key)
+            .or_insert_with(|| Box::new(derive(&properties)));
+        let value: *const T = value.downcast_ref::<T>()?;
+        // SAFETY: The values are boxed, so they stay in place when the cache grows, and they're
+        // only dropped when the cache is cleared, which requires `&mut self`.
+        Some(unsafe { &*value })
+    }
+
+    fn clear_editorconfig_caches(&mut self) {
+        self.editorconfig_properties_cache.get_mut().clear();
+        self.editorconfig_values_cache.get_mut().clear();
+    }
+
+    fn resolve_editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Path,
+    ) -> Option<EditorconfigProperties> {
+        let editorconfigs = self
+            .raw_editorconfigs
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .filter(|((_, directory), _)| path.starts_with(directory))
+            .map(|((_, directory), (_, editorconfig))| (directory, editorconfig))
+            .collect::<Vec<_>>();
+        let start = editorconfigs
+            .iter()
+            .rposition(|(_, editorconfig)| editorconfig.root)
+            .unwrap_or(0);
+
+        let mut properties = EditorconfigProperties::default();
+        for (directory, editorconfig) in &editorconfigs[start..] {
+            if let Ok(relative_path) = path.strip_prefix(directory) {
+                editorconfig.apply_to(relative_path, &mut properties);
+            }
+        }
+        (!properties.is_empty()).then_some(properties)
+    }
+
     pub fn json_schema(
         &self,
         schema_params: &SettingsJsonSchemaParams,
@@ -550,6 +701,8 @@ impl SettingsStore {
         changed_local_path: Option<(usize, &Path)>,
         cx: &mut AppContext,
     ) -> Result<()> {
+        self.editorconfig_values_cache.get_mut().clear();
+
         // Reload the global and local values for every setting.
         let mut user_settings_stack = Vec::<DeserializedSetting>::new();
         let mut paths_stack = Vec::<Option<(usize, &Path)>>::new();
@@ -948,7 +1101,9 @@ pub fn parse_json_with_comments<T: DeserializeOwned>(content: &str) -> Result<T>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::IndentStyle;
     use serde_derive::Deserialize;
+    use std::{cell::Cell, num::NonZeroU32};
     use unindent::Unindent;
 
     #[gpui::test]
@@ -1080,6 +1235,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_editorconfig_properties() {
+        let mut store = SettingsStore::default();
+        store
+            .set_local_editorconfig(
+                1,
+                Path::new("").into(),
+                Some("[*]\nindent_style = space\nindent_size = 4\ninsert_final_newline = true"),
+            )
+            .unwrap();
+        store
+            .set_local_editorconfig(1, Path::new("web").into(), Some("[*.js]\nindent_size = 2"))
+            .unwrap();
+        store
+            .set_local_editorconfig(
+                1,
+                Path::new("vendor").into(),
+                Some("root = true\n[*]\nindent_style = tab"),
+            )
+            .unwrap();
+
+        let properties = store
+            .editorconfig_properties(1, &Path::new("web/src/app.js").into())
+            .unwrap();
+        assert_eq!(properties.indent_style, Some(IndentStyle::Space));
+        assert_eq!(properties.tab_size(), NonZeroU32::new(2));
+        assert_eq!(properties.insert_final_newline, Some(true));
+
+        let properties = store
+            .editorconfig_properties(1, &Path::new("vendor/lib.c").into())
+            .unwrap();
+        assert_eq!(properties.indent_style, Some(IndentStyle::Tab));
+        assert_eq!(properties.tab_size(), None);
+        assert_eq!(properties.insert_final_newline, None);
+
+        assert!(store
+            .editorconfig_properties(2, &Path::new("web/src/app.js").into())
+            .is_none());
+
+        store
+            .set_local_editorconfig(1, Path::new("web").into(), None)
+            .unwrap();
+        let properties = store
+            .editorconfig_properties(1, &Path::new("web/src/app.js").into())
+            .unwrap();
+        assert_eq!(properties.tab_size(), NonZeroU32::new(4));
+    }
+
+    #[test]
+    fn test_editorconfig_value() {
+        let mut store = SettingsStore::default();
+        store
+            .set_local_editorconfig(
+                1,
+                Path::new("").into(),
+                Some("[*]\nindent_size = 4\n[*.js]\nindent_size = 2"),
+            )
+            .unwrap();
+
+        let derivations = Cell::new(0);
+        let tab_size = |path: &str, name: &str| {
+            store
+                .editorconfig_value(1, &Path::new(path).into(), Some(name.into()), |p| {
+                    derivations.set(derivations.get() + 1);
+                    p.tab_size()
+                })
+                .copied()
+        };
+        assert_eq!(tab_size("a.js", "JavaScript"), Some(NonZeroU32::new(2)));
+        assert_eq!(tab_size("src/b.js", "JavaScript"), Some(NonZeroU32::new(2)));
+        assert_eq!(derivations.get(), 1);
+        assert_eq!(tab_size("a.rs", "Rust"), Some(NonZeroU32::new(4)));
+        assert_eq!(tab_size("a.js", "TypeScript"), Some(NonZeroU32::new(2)));
+        assert_eq!(derivations.get(), 3);
+
+        store
+            .set_local_editorconfig(1, Path::new("").into(), Some("[*]\nindent_size = 8"))
+            .unwrap();
+        let value = store.editorconfig_value(1, &Path::new("a.js").into(), None, |p| {
+            derivations.set(derivations.get() + 1);
+            p.tab_size()
+        });
+        assert_eq!(value, Some(&NonZeroU32::new(8)));
+        assert_eq!(derivations.get(), 4);
+    }
+
     #[gpui::test]
     fn test_setting_store_assign_json_before_register(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
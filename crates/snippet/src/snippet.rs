@@ -1,55 +1,97 @@
 use anyhow::{anyhow, Context, Result};
+use regex::{Regex, RegexBuilder};
 use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+mod snippet_file;
+
+pub use snippet_file::*;
 
 #[derive(Default)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// The choices offered for each tabstop, in the same order as `tabstops`.
+    /// Tabstops written as `${1|one,two|}` have their choices listed here,
+    /// and `None` is stored for every other tabstop.
+    pub choices: Vec<Option<Vec<String>>>,
 }
 
 type TabStop = SmallVec<[Range<isize>; 2]>;
 
 impl Snippet {
+    /// Parses a snippet without resolving any variables. Variables such as
+    /// `$TM_FILENAME` are replaced by their default value if they have one,
+    /// and by their own name otherwise.
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &HashMap::default())
+    }
 
+    /// Parses a snippet, replacing variables such as `$TM_FILENAME` or
+    /// `${TM_SELECTED_TEXT:default}` with the given values.
+    pub fn parse_with_variables(source: &str, variables: &HashMap<String, String>) -> Result<Self> {
+        let mut state = ParseState {
+            text: String::with_capacity(source.len()),
+            tabstops: BTreeMap::new(),
+            choices: BTreeMap::new(),
+            variables,
+        };
+        parse_snippet(source, false, &mut state).context("failed to parse snippet")?;
+
+        let ParseState {
+            text,
+            mut tabstops,
+            mut choices,
+            ..
+        } = state;
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
-        let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+        let final_choices = choices.remove(&0);
+        let (mut tabstops, mut choices): (Vec<_>, Vec<_>) = tabstops
+            .into_iter()
+            .map(|(index, tabstop)| (tabstop, choices.remove(&index)))
+            .unzip();
 
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
+            choices.push(final_choices);
         } else {
             let end_tabstop = [len..len].into_iter().collect();
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
                 tabstops.push(end_tabstop);
+                choices.push(None);
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        Ok(Snippet {
+            text,
+            tabstops,
+            choices,
+        })
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+struct ParseState<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    choices: BTreeMap<usize, Vec<String>>,
+    variables: &'a HashMap<String, String>,
+}
+
+fn parse_snippet<'a>(mut source: &'a str, nested: bool, state: &mut ParseState) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                source = parse_tabstop_or_variable(&source[1..], state)?;
             }
             Some('\\') => {
                 source = &source[1..];
                 if let Some(c) = source.chars().next() {
-                    text.push(c);
+                    state.text.push(c);
                     source = &source[c.len_utf8()..];
                 }
             }
@@ -57,26 +99,31 @@ fn parse_snippet<'a>(
                 if nested {
                     return Ok(source);
                 } else {
-                    text.push('}');
+                    state.text.push('}');
                     source = &source[1..];
                 }
             }
             Some(_) => {
                 let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
                 let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
+                state.text.push_str(chunk);
                 source = rest;
             }
         }
     }
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
+fn parse_tabstop_or_variable<'a>(source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let name_start = source.strip_prefix('{').unwrap_or(source);
+    if name_start.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+        parse_variable(source, state)
+    } else {
+        parse_tabstop(source, state)
+    }
+}
+
+fn parse_tabstop<'a>(mut source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let tabstop_start = state.text.len();
     let tabstop_index;
     if source.starts_with('{') {
         let (index, rest) = parse_int(&source[1..])?;
@@ -84,7 +131,19 @@ fn parse_tabstop<'a>(
         source = rest;
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            source = parse_snippet(&source[1..], true, state)?;
+        } else if source.starts_with('|') {
+            let (choices, rest) = parse_choices(&source[1..])?;
+            if let Some(first_choice) = choices.first() {
+                state.text.push_str(first_choice);
+            }
+            state.choices.insert(tabstop_index, choices);
+            source = rest;
+        } else if source.starts_with('/') {
+            // Transforms on tabstops are parsed but not applied: the tabstop
+            // simply mirrors the text of the other tabstops with this index.
+            let (_, rest) = parse_transform(&source[1..])?;
+            source = rest;
         }
 
         if source.starts_with('}') {
@@ -98,13 +157,348 @@ fn parse_tabstop<'a>(
         source = rest;
     }
 
-    tabstops
+    state
+        .tabstops
         .entry(tabstop_index)
         .or_default()
-        .push(tabstop_start as isize..text.len() as isize);
+        .push(tabstop_start as isize..state.text.len() as isize);
     Ok(source)
 }
 
+fn parse_variable<'a>(mut source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let braced = source.starts_with('{');
+    if braced {
+        source = &source[1..];
+    }
+
+    let name_len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    let (name, rest) = source.split_at(name_len);
+    source = rest;
+    let value = state
+        .variables
+        .get(name)
+        .filter(|value| !value.is_empty())
+        .cloned();
+
+    if !braced {
+        state.text.push_str(value.as_deref().unwrap_or(name));
+        return Ok(source);
+    }
+
+    if source.starts_with(':') {
+        if let Some(value) = value {
+            // Skip the default value, but parse it to find where it ends.
+            let mut default = ParseState {
+                text: String::new(),
+                tabstops: BTreeMap::new(),
+                choices: BTreeMap::new(),
+                variables: state.variables,
+            };
+            source = parse_snippet(&source[1..], true, &mut default)?;
+            state.text.push_str(&value);
+        } else {
+            source = parse_snippet(&source[1..], true, state)?;
+        }
+    } else if source.starts_with('/') {
+        let (transform, rest) = parse_transform(&source[1..])?;
+        source = rest;
+        if let Some(value) = value {
+            state.text.push_str(&transform.apply(&value));
+        } else {
+            state.text.push_str(name);
+        }
+    } else {
+        state.text.push_str(value.as_deref().unwrap_or(name));
+    }
+
+    source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))
+}
+
+fn parse_choices(mut source: &str) -> Result<(Vec<String>, &str)> {
+    let mut choices = Vec::new();
+    let mut choice = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected the end of the choice list")),
+            Some('\\') => {
+                if let Some(c) = chars.next() {
+                    if !matches!(c, ',' | '|' | '\\' | '$' | '}') {
+                        choice.push('\\');
+                    }
+                    choice.push(c);
+                }
+            }
+            Some(',') => choices.push(std::mem::take(&mut choice)),
+            Some('|') if chars.as_str().starts_with('}') => {
+                choices.push(choice);
+                return Ok((choices, chars.as_str()));
+            }
+            Some(c) => choice.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
+/// A regular expression transform, written as `/regex/format/options`.
+struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+enum FormatItem {
+    Text(String),
+    Group { index: usize, kind: FormatKind },
+}
+
+enum FormatKind {
+    Plain,
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+    IfElse { if_text: String, else_text: String },
+    Default(String),
+}
+
+impl Transform {
+    fn apply(&self, value: &str) -> String {
+        let mut result = String::new();
+        let mut last_end = 0;
+        for captures in self.regex.captures_iter(value) {
+            let Some(matched) = captures.get(0) else {
+                continue;
+            };
+            result.push_str(&value[last_end..matched.start()]);
+            for item in &self.format {
+                match item {
+                    FormatItem::Text(text) => result.push_str(text),
+                    FormatItem::Group { index, kind } => {
+                        let group = captures.get(*index).map_or("", |group| group.as_str());
+                        result.push_str(&kind.format(group));
+                    }
+                }
+            }
+            last_end = matched.end();
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&value[last_end..]);
+        result
+    }
+}
+
+impl FormatKind {
+    fn format(&self, text: &str) -> String {
+        match self {
+            FormatKind::Plain => text.to_string(),
+            FormatKind::Upcase => text.to_uppercase(),
+            FormatKind::Downcase => text.to_lowercase(),
+            FormatKind::Capitalize => {
+                let mut chars = text.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            FormatKind::Camelcase | FormatKind::Pascalcase => {
+                let mut result = String::new();
+                for (ix, word) in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .enumerate()
+                {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        if ix == 0 && matches!(self, FormatKind::Camelcase) {
+                            result.extend(first.to_lowercase());
+                        } else {
+                            result.extend(first.to_uppercase());
+                        }
+                        result.extend(chars);
+                    }
+                }
+                result
+            }
+            FormatKind::IfElse { if_text, else_text } => {
+                if text.is_empty() {
+                    else_text.clone()
+                } else {
+                    if_text.clone()
+                }
+            }
+            FormatKind::Default(default) => {
+                if text.is_empty() {
+                    default.clone()
+                } else {
+                    text.to_string()
+                }
+            }
+        }
+    }
+}
+
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let (regex, source) = parse_transform_section(source)?;
+    let (format, source) = parse_format(source)?;
+    let options_len = source.find('}').unwrap_or(source.len());
+    let (options, source) = source.split_at(options_len);
+
+    let regex = RegexBuilder::new(&regex)
+        .case_insensitive(options.contains('i'))
+        .multi_line(options.contains('m'))
+        .build()
+        .context("invalid regex in snippet transform")?;
+    Ok((
+        Transform {
+            regex,
+            format,
+            global: options.contains('g'),
+        },
+        source,
+    ))
+}
+
+/// Reads up to the next unescaped `/`, unescaping `\/` along the way.
+fn parse_transform_section(mut source: &str) -> Result<(String, &str)> {
+    let mut section = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected '/' in snippet transform")),
+            Some('/') => return Ok((section, chars.as_str())),
+            Some('\\') if chars.as_str().starts_with('/') => {
+                section.push('/');
+                chars.next();
+            }
+            Some('\\') => {
+                section.push('\\');
+                if let Some(c) = chars.next() {
+                    section.push(c);
+                }
+            }
+            Some(c) => section.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected '/' after snippet transform format")),
+            Some('/') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(text));
+                }
+                return Ok((items, chars.as_str()));
+            }
+            Some('\\') => {
+                if let Some(c) = chars.next() {
+                    match c {
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        c => text.push(c),
+                    }
+                }
+            }
+            Some('$') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(std::mem::take(&mut text)));
+                }
+                let (item, rest) = parse_format_group(chars.as_str())?;
+                items.push(item);
+                source = rest;
+                continue;
+            }
+            Some(c) => text.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
+fn parse_format_group(source: &str) -> Result<(FormatItem, &str)> {
+    let Some(source) = source.strip_prefix('{') else {
+        let (index, rest) = parse_int(source)?;
+        return Ok((
+            FormatItem::Group {
+                index,
+                kind: FormatKind::Plain,
+            },
+            rest,
+        ));
+    };
+
+    let (index, source) = parse_int(source)?;
+    let (kind, source) = if let Some(source) = source.strip_prefix(":/") {
+        let name_len = source.find('}').unwrap_or(source.len());
+        let kind = match &source[..name_len] {
+            "upcase" => FormatKind::Upcase,
+            "downcase" => FormatKind::Downcase,
+            "capitalize" => FormatKind::Capitalize,
+            "camelcase" => FormatKind::Camelcase,
+            "pascalcase" => FormatKind::Pascalcase,
+            other => return Err(anyhow!("unknown snippet format modifier '{other}'")),
+        };
+        (kind, &source[name_len..])
+    } else if let Some(source) = source.strip_prefix(":+") {
+        let (if_text, source) = parse_format_text(source, &['}'])?;
+        (
+            FormatKind::IfElse {
+                if_text,
+                else_text: String::new(),
+            },
+            source,
+        )
+    } else if let Some(source) = source.strip_prefix(":?") {
+        let (if_text, source) = parse_format_text(source, &[':'])?;
+        let (else_text, source) = parse_format_text(&source[1..], &['}'])?;
+        (FormatKind::IfElse { if_text, else_text }, source)
+    } else if let Some(source) = source
+        .strip_prefix(":-")
+        .or_else(|| source.strip_prefix(':'))
+    {
+        let (default, source) = parse_format_text(source, &['}'])?;
+        (FormatKind::Default(default), source)
+    } else {
+        (FormatKind::Plain, source)
+    };
+
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    Ok((FormatItem::Group { index, kind }, source))
+}
+
+/// Reads format text up to (but not including) one of the given terminators.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("unterminated snippet format group")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                if let Some(c) = chars.next() {
+                    text.push(c);
+                }
+            }
+            Some(c) => text.push(c),
+        }
+        source = chars.as_str();
+    }
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -199,6 +593,81 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![3..3]]);
     }
 
+    #[test]
+    fn test_snippet_with_choices() {
+        let snippet = Snippet::parse("let x = ${1|one,two\\,three,four|};$0").unwrap();
+        assert_eq!(snippet.text, "let x = one;");
+        assert_eq!(tabstops(&snippet), &[vec![8..11], vec![12..12]]);
+        assert_eq!(
+            snippet.choices,
+            &[
+                Some(vec!["one".to_string(), "two,three".into(), "four".into()]),
+                None
+            ]
+        );
+
+        assert!(Snippet::parse("${1|one,two}").is_err());
+    }
+
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = HashMap::from_iter([
+            ("TM_FILENAME".to_string(), "main.rs".to_string()),
+            ("TM_LINE_NUMBER".to_string(), "42".to_string()),
+            ("TM_SELECTED_TEXT".to_string(), String::new()),
+        ]);
+        let snippet = Snippet::parse_with_variables(
+            "$TM_FILENAME:${TM_LINE_NUMBER} ${TM_SELECTED_TEXT:${1:fallback}} ${UNKNOWN}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "main.rs:42 fallback UNKNOWN");
+        assert_eq!(tabstops(&snippet), &[vec![11..19], vec![27..27]]);
+
+        // Without any variables, defaults are used.
+        let snippet = Snippet::parse("${TM_FILENAME:untitled}").unwrap();
+        assert_eq!(snippet.text, "untitled");
+    }
+
+    #[test]
+    fn test_snippet_with_variable_transforms() {
+        let variables = HashMap::from_iter([
+            ("TM_FILENAME".to_string(), "user_profile.rs".to_string()),
+            ("TM_CURRENT_WORD".to_string(), "a/b/c".to_string()),
+        ]);
+        let parse = |source: &str| Snippet::parse_with_variables(source, &variables).unwrap();
+
+        assert_eq!(parse("${TM_FILENAME/(.*)\\..+$/$1/}").text, "user_profile");
+        assert_eq!(
+            parse("${TM_FILENAME/(.*)\\..+$/${1:/pascalcase}/}").text,
+            "UserProfile"
+        );
+        assert_eq!(
+            parse("${TM_FILENAME/(.*)\\..+$/${1:/camelcase}/}").text,
+            "userProfile"
+        );
+        assert_eq!(
+            parse("${TM_FILENAME/([a-z]+)/${1:/upcase}/}").text,
+            "USER_profile.rs"
+        );
+        assert_eq!(
+            parse("${TM_FILENAME/([a-z]+)/${1:/capitalize}/g}").text,
+            "User_Profile.Rs"
+        );
+        assert_eq!(parse("${TM_CURRENT_WORD/\\//::/g}").text, "a::b::c");
+        assert_eq!(
+            parse("${TM_FILENAME/^(test_)?.*$/${1:?test:module}/}").text,
+            "module"
+        );
+        assert_eq!(parse("${TM_FILENAME/^(x)?.*$/${1:-none}/}").text, "none");
+        assert_eq!(
+            parse("${TM_FILENAME/^(USER)/${1:+found}/i}").text,
+            "found_profile.rs"
+        );
+
+        assert!(Snippet::parse_with_variables("${TM_FILENAME/(/x/}", &variables).is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.to_vec()).collect()
     }
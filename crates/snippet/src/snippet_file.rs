@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A snippet defined by the user in a snippet file.
+///
+/// Snippet files use the same JSON format as VS Code: an object mapping each
+/// snippet's name to its prefixes, body and optional description and scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserSnippet {
+    pub name: String,
+    /// The words that trigger this snippet when typed.
+    pub prefixes: Vec<String>,
+    /// The snippet source, in the syntax understood by [`crate::Snippet::parse`].
+    pub body: String,
+    pub description: Option<String>,
+    /// The lowercased names of the languages this snippet applies to. When
+    /// `None`, the snippet applies to every language the file is used for.
+    pub scopes: Option<Vec<String>>,
+}
+
+impl UserSnippet {
+    pub fn applies_to_language(&self, language_name: &str) -> bool {
+        self.scopes.as_ref().map_or(true, |scopes| {
+            scopes
+                .iter()
+                .any(|scope| scope.eq_ignore_ascii_case(language_name))
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SnippetDefinition {
+    prefix: Option<StringOrList>,
+    body: StringOrList,
+    description: Option<StringOrList>,
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl StringOrList {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            StringOrList::Single(string) => vec![string],
+            StringOrList::List(list) => list,
+        }
+    }
+
+    fn join(self, separator: &str) -> String {
+        match self {
+            StringOrList::Single(string) => string,
+            StringOrList::List(list) => list.join(separator),
+        }
+    }
+}
+
+/// Parses the contents of a VS Code-compatible snippet file. Comments and
+/// trailing commas are allowed.
+pub fn parse_snippet_file(content: &str) -> Result<Vec<UserSnippet>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let definitions: BTreeMap<String, SnippetDefinition> =
+        serde_json_lenient::from_str(content).context("failed to parse snippet file")?;
+    Ok(definitions
+        .into_iter()
+        .filter_map(|(name, definition)| {
+            let prefixes = definition.prefix?.into_vec();
+            if prefixes.is_empty() {
+                return None;
+            }
+
+            Some(UserSnippet {
+                name,
+                prefixes,
+                body: definition.body.join("\n"),
+                description: definition.description.map(|d| d.join("\n")),
+                scopes: definition.scope.map(|scope| {
+                    scope
+                        .split(',')
+                        .map(|scope| scope.trim().to_lowercase())
+                        .filter(|scope| !scope.is_empty())
+                        .collect()
+                }),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snippet_file() {
+        let snippets = parse_snippet_file(
+            r##"{
+                // Comments are allowed, like in VS Code.
+                "Print to console": {
+                    "scope": "javascript, TypeScript",
+                    "prefix": ["log", "lg"],
+                    "body": ["console.log('$1');", "$2"],
+                    "description": "Log output to console",
+                },
+                "Test function": {
+                    "prefix": "test",
+                    "body": "#[test]\nfn ${1:name}() {\n\t$0\n}"
+                },
+                "Prefixless": {
+                    "body": "ignored"
+                }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            snippets,
+            [
+                UserSnippet {
+                    name: "Print to console".into(),
+                    prefixes: vec!["log".into(), "lg".into()],
+                    body: "console.log('$1');\n$2".into(),
+                    description: Some("Log output to console".into()),
+                    scopes: Some(vec!["javascript".into(), "typescript".into()]),
+                },
+                UserSnippet {
+                    name: "Test function".into(),
+                    prefixes: vec!["test".into()],
+                    body: "#[test]\nfn ${1:name}() {\n\t$0\n}".into(),
+                    description: None,
+                    scopes: None,
+                },
+            ]
+        );
+        assert!(snippets[0].applies_to_language("TypeScript"));
+        assert!(!snippets[0].applies_to_language("Rust"));
+        assert!(snippets[1].applies_to_language("Rust"));
+        assert!(parse_snippet_file("").unwrap().is_empty());
+    }
+}
@@ -2,12 +2,12 @@ use std::path::PathBuf;
 
 use editor::Editor;
 use gpui::{AppContext, ViewContext, WindowContext};
-use language::Point;
 use modal::{Spawn, TasksModal};
-use project::{Location, WorktreeId};
+use project::Location;
 use task::{Task, TaskContext, TaskVariables};
+use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use util::ResultExt;
-use workspace::Workspace;
+use workspace::{notifications::NotifyTaskExt, Workspace};
 
 mod modal;
 
@@ -17,6 +17,11 @@ pub fn init(cx: &mut AppContext) {
             workspace
                 .register_action(spawn_task_or_modal)
                 .register_action(move |workspace, action: &modal::Rerun, cx| {
+                    if workspace.project().read(cx).is_remote() {
+                        rerun_host_task(workspace, cx);
+                        return;
+                    }
+
                     if let Some((task, old_context)) =
                         workspace.project().update(cx, |project, cx| {
                             project
@@ -40,6 +45,11 @@ pub fn init(cx: &mut AppContext) {
 }
 
 fn spawn_task_or_modal(workspace: &mut Workspace, action: &Spawn, cx: &mut ViewContext<Workspace>) {
+    if workspace.project().read(cx).is_remote() {
+        spawn_host_task_or_modal(workspace, action, cx);
+        return;
+    }
+
     let inventory = workspace.project().read(cx).task_inventory().clone();
     let workspace_handle = workspace.weak_handle();
     let cwd = task_cwd(workspace, cx).log_err().flatten();
@@ -49,11 +59,47 @@ fn spawn_task_or_modal(workspace: &mut Workspace, action: &Spawn, cx: &mut ViewC
         spawn_task_with_name(name.clone(), cx);
     } else {
         workspace.toggle_modal(cx, |cx| {
-            TasksModal::new(inventory, task_context, workspace_handle, cx)
+            TasksModal::new(inventory, None, task_context, workspace_handle, cx)
         })
     }
 }
 
+/// Guests of a shared project pick among the tasks of the host, which runs them.
+fn spawn_host_task_or_modal(
+    workspace: &mut Workspace,
+    action: &Spawn,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let host_tasks = workspace
+        .project()
+        .update(cx, |project, cx| project.host_tasks(cx));
+    let task_name = action.task_name.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let tasks = host_tasks.await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            if let Some(name) = task_name {
+                if let Some((_, task)) = tasks.iter().find(|(_, task)| task.name() == name) {
+                    schedule_task(workspace, task.as_ref(), TaskContext::default(), cx);
+                    return;
+                }
+            }
+
+            let inventory = workspace.project().read(cx).task_inventory().clone();
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(cx, |cx| {
+                TasksModal::new(
+                    inventory,
+                    Some(tasks),
+                    TaskContext::default(),
+                    workspace_handle,
+                    cx,
+                )
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 fn spawn_task_with_name(name: String, cx: &mut ViewContext<Workspace>) {
     cx.spawn(|workspace, mut cx| async move {
         let did_spawn = workspace
@@ -92,103 +138,98 @@ fn task_context(
     cwd: Option<PathBuf>,
     cx: &mut WindowContext<'_>,
 ) -> TaskContext {
-    let current_editor = workspace
-        .active_item(cx)
-        .and_then(|item| item.act_as::<Editor>(cx))
-        .clone();
-    if let Some(current_editor) = current_editor {
-        (|| {
-            let editor = current_editor.read(cx);
-            let selection = editor.selections.newest::<usize>(cx);
-            let (buffer, _, _) = editor
-                .buffer()
-                .read(cx)
-                .point_to_buffer_offset(selection.start, cx)?;
-
-            current_editor.update(cx, |editor, cx| {
-                let snapshot = editor.snapshot(cx);
-                let selection_range = selection.range();
-                let start = snapshot
-                    .display_snapshot
-                    .buffer_snapshot
-                    .anchor_after(selection_range.start)
-                    .text_anchor;
-                let end = snapshot
-                    .display_snapshot
-                    .buffer_snapshot
-                    .anchor_after(selection_range.end)
-                    .text_anchor;
-                let Point { row, column } = snapshot
-                    .display_snapshot
-                    .buffer_snapshot
-                    .offset_to_point(selection_range.start);
-                let row = row + 1;
-                let column = column + 1;
-                let location = Location {
-                    buffer: buffer.clone(),
-                    range: start..end,
-                };
-
-                let current_file = location
-                    .buffer
-                    .read(cx)
-                    .file()
-                    .and_then(|file| file.as_local())
-                    .map(|file| file.abs_path(cx).to_string_lossy().to_string());
-                let worktree_id = location
-                    .buffer
-                    .read(cx)
-                    .file()
-                    .map(|file| WorktreeId::from_usize(file.worktree_id()));
-                let context = buffer
-                    .read(cx)
-                    .language()
-                    .and_then(|language| language.context_provider())
-                    .and_then(|provider| provider.build_context(location, cx).ok());
+    let mut task_context = editor_task_context(workspace, cwd, cx);
+    task_context
+        .task_variables
+        .0
+        .extend(terminal_task_variables(workspace, cx).0);
+    task_context
+}
 
-                let worktree_path = worktree_id.and_then(|worktree_id| {
-                    workspace
-                        .project()
-                        .read(cx)
-                        .worktree_for_id(worktree_id, cx)
-                        .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().to_string())
-                });
+fn editor_task_context(
+    workspace: &Workspace,
+    cwd: Option<PathBuf>,
+    cx: &mut WindowContext<'_>,
+) -> TaskContext {
+    let task_variables = active_location(workspace, cx)
+        .map(|location| {
+            workspace
+                .project()
+                .update(cx, |project, cx| project.task_variables(location, cx))
+        })
+        .unwrap_or_default();
+    TaskContext {
+        cwd,
+        task_variables,
+    }
+}
 
-                let selected_text = buffer.read(cx).chars_for_range(selection_range).collect();
+/// The location of the newest selection of the active editor.
+fn active_location(workspace: &Workspace, cx: &WindowContext<'_>) -> Option<Location> {
+    let editor = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))?;
+    let editor = editor.read(cx);
+    let selection = editor.selections.newest::<usize>(cx);
+    let multi_buffer = editor.buffer().read(cx);
+    let (buffer, _, _) = multi_buffer.point_to_buffer_offset(selection.start, cx)?;
+    let snapshot = multi_buffer.snapshot(cx);
+    let start = snapshot.anchor_after(selection.start).text_anchor;
+    let end = snapshot.anchor_after(selection.end).text_anchor;
+    Some(Location {
+        buffer,
+        range: start..end,
+    })
+}
 
-                let mut task_variables = TaskVariables::from_iter([
-                    ("ZED_ROW".into(), row.to_string()),
-                    ("ZED_COLUMN".into(), column.to_string()),
-                    ("ZED_SELECTED_TEXT".into(), selected_text),
-                ]);
-                if let Some(path) = current_file {
-                    task_variables.0.insert("ZED_FILE".into(), path);
-                }
-                if let Some(worktree_path) = worktree_path {
-                    task_variables
-                        .0
-                        .insert("ZED_WORKTREE_ROOT".into(), worktree_path);
-                }
-                if let Some(language_context) = context {
-                    task_variables.0.extend(language_context.0);
-                }
+/// Describes the last command run in the active terminal, as reported by the shell's integration.
+fn terminal_task_variables(workspace: &Workspace, cx: &WindowContext<'_>) -> TaskVariables {
+    let terminal_view = workspace
+        .active_item(cx)
+        .and_then(|item| item.downcast::<TerminalView>())
+        .or_else(|| {
+            workspace
+                .panel::<TerminalPanel>(cx)?
+                .read(cx)
+                .active_terminal(cx)
+        });
+    let Some(terminal_view) = terminal_view else {
+        return TaskVariables::default();
+    };
 
-                Some(TaskContext {
-                    cwd: cwd.clone(),
-                    task_variables,
-                })
-            })
-        })()
-        .unwrap_or_else(|| TaskContext {
-            cwd,
-            task_variables: Default::default(),
-        })
-    } else {
-        TaskContext {
-            cwd,
-            task_variables: Default::default(),
+    let command_history = terminal_view.read(cx).terminal().read(cx).command_history();
+    let mut task_variables = TaskVariables::default();
+    if let Some(cwd) = command_history.cwd() {
+        task_variables
+            .0
+            .insert("ZED_TERMINAL_CWD".into(), cwd.to_string_lossy().to_string());
+    }
+    if let Some(command) = command_history.last_finished_command() {
+        if let Some(command_line) = &command.command {
+            task_variables
+                .0
+                .insert("ZED_TERMINAL_LAST_COMMAND".into(), command_line.clone());
+        }
+        if let Some(exit_code) = command.exit_code {
+            task_variables
+                .0
+                .insert("ZED_TERMINAL_LAST_EXIT_CODE".into(), exit_code.to_string());
         }
     }
+    task_variables
+}
+
+fn rerun_host_task(workspace: &Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let project = workspace.project().clone();
+    let Some(task_id) = project.read(cx).last_host_task().cloned() else {
+        return;
+    };
+    let location = active_location(workspace, cx);
+    project
+        .update(cx, |project, cx| {
+            project.run_host_task(task_id, location, cx)
+        })
+        .detach_and_notify_err(cx);
 }
 
 fn schedule_task(
@@ -197,6 +238,18 @@ fn schedule_task(
     task_cx: TaskContext,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
+    if workspace.project().read(cx).is_remote() {
+        // The host runs the task, and shares the terminal running it with us.
+        let location = active_location(workspace, cx);
+        workspace
+            .project()
+            .update(cx, |project, cx| {
+                project.run_host_task(task.id().clone(), location, cx)
+            })
+            .detach_and_notify_err(cx);
+        return;
+    }
+
     let spawn_in_terminal = task.exec(task_cx.clone());
     if let Some(spawn_in_terminal) = spawn_in_terminal {
         workspace.project().update(cx, |project, cx| {
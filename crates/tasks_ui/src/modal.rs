@@ -56,13 +56,14 @@ pub(crate) struct TasksModalDelegate {
 impl TasksModalDelegate {
     fn new(
         inventory: Model<Inventory>,
+        candidates: Option<Vec<(TaskSourceKind, Arc<dyn Task>)>>,
         task_context: TaskContext,
         workspace: WeakView<Workspace>,
     ) -> Self {
         Self {
             inventory,
             workspace,
-            candidates: None,
+            candidates,
             matches: Vec::new(),
             selected_index: 0,
             prompt: String::default(),
@@ -105,15 +106,18 @@ pub(crate) struct TasksModal {
 }
 
 impl TasksModal {
+    /// Creates a modal listing the tasks of the inventory, or the `candidates` given instead,
+    /// e.g. the tasks of the host of a remote project.
     pub(crate) fn new(
         inventory: Model<Inventory>,
+        candidates: Option<Vec<(TaskSourceKind, Arc<dyn Task>)>>,
         task_context: TaskContext,
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let picker = cx.new_view(|cx| {
             Picker::uniform_list(
-                TasksModalDelegate::new(inventory, task_context, workspace),
+                TasksModalDelegate::new(inventory, candidates, task_context, workspace),
                 cx,
             )
         });
@@ -0,0 +1,342 @@
+//! Terminals shared with the collaborators of a project. The host streams everything its
+//! shell prints to the guests, whose terminals replay it into a grid of their own and send
+//! the input typed into them back to the host, if it allowed them to write.
+
+use std::{borrow::Cow, mem, sync::Arc};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, Notify, WindowSize},
+    event_loop::{Msg, Notifier},
+    grid::Dimensions,
+    index::{Column, Point as AlacPoint},
+    sync::FairMutex,
+    term::{Config, TermMode},
+    vte::ansi::Processor,
+    Term,
+};
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use gpui::{size, ModelContext};
+use parking_lot::Mutex;
+use util::truncate_and_trailoff;
+
+use crate::{
+    shell_integration::ShellIntegrationScanner, Event, Terminal, TerminalBuilder, TerminalSize,
+    ZedListener, DEFAULT_SCROLL_HISTORY_LINES, MAX_SCROLL_HISTORY_LINES,
+};
+
+/// What the host of a shared terminal tells its guests about it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharedTerminalState {
+    pub title: String,
+    pub columns: usize,
+    pub lines: usize,
+    /// Whether the host allows this participant to type into the terminal.
+    pub writable: bool,
+}
+
+/// The PTY output read while the terminal is shared, waiting to be sent to collaborators.
+#[derive(Clone, Default)]
+pub(crate) struct SharedOutput(Arc<Mutex<Option<Vec<u8>>>>);
+
+impl SharedOutput {
+    pub fn push(&self, bytes: &[u8]) {
+        if let Some(output) = self.0.lock().as_mut() {
+            output.extend_from_slice(bytes);
+        }
+    }
+
+    fn start(&self) {
+        self.0.lock().get_or_insert_with(Vec::new);
+    }
+
+    fn stop(&self) {
+        self.0.lock().take();
+    }
+
+    fn take(&self) -> Vec<u8> {
+        self.0.lock().as_mut().map(mem::take).unwrap_or_default()
+    }
+}
+
+/// A guest's end of a terminal shared by the host of a remote project.
+pub(crate) struct RemoteConnection {
+    input_tx: UnboundedSender<Vec<u8>>,
+    parser: Processor,
+    state: SharedTerminalState,
+}
+
+/// Where the input of a terminal goes.
+pub(crate) enum PtySender {
+    Pty(Notifier),
+    Remote(RemoteConnection),
+}
+
+impl PtySender {
+    pub fn notify<B: Into<Cow<'static, [u8]>>>(&self, bytes: B) {
+        match self {
+            PtySender::Pty(notifier) => notifier.notify(bytes),
+            PtySender::Remote(connection) => {
+                let bytes = bytes.into();
+                if connection.state.writable && !bytes.is_empty() {
+                    connection.input_tx.unbounded_send(bytes.into_owned()).ok();
+                }
+            }
+        }
+    }
+
+    pub fn resize(&self, window_size: WindowSize) {
+        if let PtySender::Pty(notifier) = self {
+            notifier.0.send(Msg::Resize(window_size)).ok();
+        }
+    }
+
+    pub fn shutdown(&self) {
+        if let PtySender::Pty(notifier) = self {
+            notifier.0.send(Msg::Shutdown).ok();
+        }
+    }
+
+    /// The columns and lines of the host's grid, for a terminal shared by a collaborator.
+    pub fn remote_grid_size(&self) -> Option<(usize, usize)> {
+        match self {
+            PtySender::Pty(_) => None,
+            PtySender::Remote(connection) => {
+                Some((connection.state.columns, connection.state.lines))
+            }
+        }
+    }
+}
+
+impl TerminalBuilder {
+    /// Creates a terminal mirroring one shared by the host of a remote project. Its grid is
+    /// fed with [`Terminal::advance_remote_output`], and the input typed into it is sent to
+    /// `input_tx` while the host allows this participant to write.
+    pub fn new_remote(
+        state: SharedTerminalState,
+        max_scroll_history_lines: Option<usize>,
+        input_tx: UnboundedSender<Vec<u8>>,
+    ) -> TerminalBuilder {
+        let config = Config {
+            scrolling_history: max_scroll_history_lines
+                .unwrap_or(DEFAULT_SCROLL_HISTORY_LINES)
+                .min(MAX_SCROLL_HISTORY_LINES),
+            ..Config::default()
+        };
+        let (events_tx, events_rx) = unbounded();
+        let shell_integration = Arc::new(Mutex::new(ShellIntegrationScanner::default()));
+        let listener = ZedListener {
+            events_tx,
+            shell_integration: shell_integration.clone(),
+        };
+        let grid_size = remote_grid_size(TerminalSize::default(), state.columns, state.lines);
+        let term = Term::new(config, &grid_size, listener);
+        // Remote terminals never run tasks, so nothing waits for them to complete.
+        let (completion_tx, _) = smol::channel::bounded(1);
+
+        let mut terminal = Terminal::new(
+            PtySender::Remote(RemoteConnection {
+                input_tx,
+                parser: Processor::new(),
+                state,
+            }),
+            None,
+            SharedOutput::default(),
+            None,
+            completion_tx,
+            Arc::new(FairMutex::new(term)),
+            shell_integration,
+        );
+        terminal.last_content.size = grid_size;
+
+        TerminalBuilder {
+            terminal,
+            events_rx,
+        }
+    }
+}
+
+impl Terminal {
+    /// Whether this terminal mirrors one shared by the host of a remote project.
+    pub fn is_remote(&self) -> bool {
+        matches!(self.pty_tx, PtySender::Remote(_))
+    }
+
+    /// What the host reported about the terminal, if it is shared by a collaborator.
+    pub fn shared_state(&self) -> Option<&SharedTerminalState> {
+        match &self.pty_tx {
+            PtySender::Pty(_) => None,
+            PtySender::Remote(connection) => Some(&connection.state),
+        }
+    }
+
+    pub(crate) fn remote_title(&self, truncate: bool, max_chars: usize) -> String {
+        let title = self
+            .shared_state()
+            .map_or("Terminal", |state| state.title.as_str());
+        if truncate {
+            truncate_and_trailoff(title, max_chars)
+        } else {
+            title.to_string()
+        }
+    }
+
+    /// Replays output printed by the host's shell.
+    pub fn advance_remote_output(&mut self, output: &[u8], cx: &mut ModelContext<Self>) {
+        let PtySender::Remote(connection) = &mut self.pty_tx else {
+            return;
+        };
+
+        self.shell_integration.lock().advance(output);
+        let mut term = self.term.lock();
+        for byte in output {
+            connection.parser.advance(&mut *term, *byte);
+        }
+        drop(term);
+        self.shell_integration.lock().commit();
+
+        self.process_event(&AlacTermEvent::Wakeup, cx);
+    }
+
+    pub fn set_shared_state(&mut self, state: SharedTerminalState, cx: &mut ModelContext<Self>) {
+        let PtySender::Remote(connection) = &mut self.pty_tx else {
+            return;
+        };
+
+        let resized =
+            (connection.state.columns, connection.state.lines) != (state.columns, state.lines);
+        let retitled = connection.state.title != state.title;
+        if resized {
+            // Resize right away, as the output that follows was printed for the new size.
+            let new_size = remote_grid_size(self.last_content.size, state.columns, state.lines);
+            self.last_content.size = new_size;
+            self.term.lock().resize(new_size);
+        }
+        connection.state = state;
+
+        if retitled {
+            cx.emit(Event::TitleChanged);
+        }
+        cx.notify();
+    }
+
+    /// Starts collecting the output of the shell for [`Terminal::take_shared_output`], and
+    /// returns the current contents of the terminal, for collaborators to replay first.
+    pub fn start_sharing(&mut self) -> Vec<u8> {
+        let term = self.term.lock();
+        self.shared_output.start();
+        snapshot(&term)
+    }
+
+    pub fn stop_sharing(&mut self) {
+        self.shared_output.stop();
+    }
+
+    /// The output the shell printed since the last call, while the terminal is shared.
+    pub fn take_shared_output(&mut self) -> Vec<u8> {
+        self.shared_output.take()
+    }
+
+    /// The current contents of the terminal, for collaborators that join while it is shared.
+    pub fn shared_snapshot(&self) -> Vec<u8> {
+        snapshot(&self.term.lock())
+    }
+
+    /// The number of columns and lines of the grid.
+    pub fn grid_size(&self) -> (usize, usize) {
+        let term = self.term.lock();
+        (term.columns(), term.screen_lines())
+    }
+}
+
+/// The size of a view showing a grid of the given dimensions with the given cell size.
+fn remote_grid_size(cell_size: TerminalSize, columns: usize, lines: usize) -> TerminalSize {
+    TerminalSize {
+        size: size(
+            cell_size.cell_width * columns.max(1) as f32,
+            cell_size.line_height * lines.max(1) as f32,
+        ),
+        ..cell_size
+    }
+}
+
+/// Renders the scrollback and the screen as escape sequences that reproduce their text,
+/// the cursor position and the modes that affect how input is encoded.
+///
+/// Colors and other cell attributes are not preserved.
+fn snapshot<T>(term: &Term<T>) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    let mode = *term.mode();
+    if mode.contains(TermMode::ALT_SCREEN) {
+        snapshot.extend_from_slice(b"\x1b[?1049h");
+    }
+    if mode.contains(TermMode::APP_CURSOR) {
+        snapshot.extend_from_slice(b"\x1b[?1h");
+    }
+    if mode.contains(TermMode::BRACKETED_PASTE) {
+        snapshot.extend_from_slice(b"\x1b[?2004h");
+    }
+
+    let start = AlacPoint::new(term.topmost_line(), Column(0));
+    let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+    let text = term.bounds_to_string(start, end);
+    // Every line ends with a newline, but printing the last one would scroll the screen.
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    for (ix, line) in text.split('\n').enumerate() {
+        if ix > 0 {
+            snapshot.extend_from_slice(b"\r\n");
+        }
+        snapshot.extend_from_slice(line.as_bytes());
+    }
+
+    let cursor = term.grid().cursor.point;
+    snapshot.extend_from_slice(
+        format!("\x1b[{};{}H", cursor.line.0 + 1, cursor.column.0 + 1).as_bytes(),
+    );
+    if !mode.contains(TermMode::SHOW_CURSOR) {
+        snapshot.extend_from_slice(b"\x1b[?25l");
+    }
+    snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, vte::ansi::Processor};
+
+    use super::*;
+
+    fn term_with_output(output: &[u8]) -> Term<VoidListener> {
+        let size = remote_grid_size(TerminalSize::default(), 20, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser: Processor = Processor::new();
+        for byte in output {
+            parser.advance(&mut term, *byte);
+        }
+        term
+    }
+
+    fn text<T>(term: &Term<T>) -> String {
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        term.bounds_to_string(start, end)
+    }
+
+    #[test]
+    fn test_snapshot_replays_text_and_cursor() {
+        let host = term_with_output(b"one\r\ntwo\r\nthree\r\nfour\r\nfive\r\n$ ls\x1b[1D");
+        let guest = term_with_output(&snapshot(&host));
+
+        assert_eq!(text(&guest), text(&host));
+        assert_eq!(guest.history_size(), host.history_size());
+        assert_eq!(guest.grid().cursor.point, host.grid().cursor.point);
+    }
+
+    #[test]
+    fn test_snapshot_replays_input_modes() {
+        let host = term_with_output(b"\x1b[?1049h\x1b[?1hvim");
+        let guest = term_with_output(&snapshot(&host));
+
+        assert!(guest.mode().contains(TermMode::ALT_SCREEN));
+        assert!(guest.mode().contains(TermMode::APP_CURSOR));
+        assert_eq!(text(&guest), text(&host));
+    }
+}
@@ -0,0 +1,1053 @@
+//! Shell integration: the semantic prompt sequences shells emit to mark where prompts,
+//! commands and their output begin (OSC 133, and the OSC 633 variant used by VS Code's
+//! shell scripts), and the working directory reports (OSC 7) they print at each prompt.
+//!
+//! Alacritty ignores these sequences, so the PTY output is scanned for them before it's
+//! handed to Alacritty's parser. Marks are positioned by counting the line feeds the shell
+//! printed before them, which maps each mark to a logical (unwrapped) line of the grid.
+
+use std::{
+    collections::VecDeque,
+    io, mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::{cell::Flags, TermMode},
+    tty::{self, ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use parking_lot::Mutex;
+use polling::{Event as PollEvent, PollMode, Poller};
+use unicode_width::UnicodeWidthChar;
+
+use crate::remote::SharedOutput;
+
+const MAX_CSI_LEN: usize = 64;
+const MAX_OSC_LEN: usize = 64 * 1024;
+const MAX_COMMAND_HISTORY: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MarkKind {
+    PromptStart,
+    CommandStart,
+    OutputStart { command: Option<String> },
+    CommandFinished { exit_code: Option<i32> },
+    CommandLine(String),
+    WorkingDirectory(PathBuf),
+    Cleared,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Mark {
+    pub kind: MarkKind,
+    /// The logical line the cursor was on when the mark was printed.
+    pub line: usize,
+    /// The column the cursor was at, counted in cells from the start of the logical line.
+    pub column: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Scans the PTY output for shell integration sequences, tracking just enough of the
+/// cursor's movement to tell which line each of them was printed on.
+#[derive(Debug, Default)]
+pub(crate) struct ShellIntegrationScanner {
+    state: ScanState,
+    sequence: Vec<u8>,
+    /// The bytes of a multi-byte UTF-8 character that is partially scanned.
+    utf8: Vec<u8>,
+    line: usize,
+    column: usize,
+    alt_screen: bool,
+    pending: Vec<Mark>,
+    committed: Vec<Mark>,
+    committed_line: usize,
+}
+
+impl ShellIntegrationScanner {
+    pub fn advance(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.advance_byte(byte);
+        }
+    }
+
+    /// Publishes the marks scanned so far. Called once Alacritty has parsed everything
+    /// that was read from the PTY, so that the published marks match the grid.
+    pub fn commit(&mut self) {
+        self.committed.append(&mut self.pending);
+        self.committed_line = self.line;
+    }
+
+    /// Takes the published marks, along with the logical line the cursor is on.
+    pub fn take_committed(&mut self) -> (Vec<Mark>, usize) {
+        (mem::take(&mut self.committed), self.committed_line)
+    }
+
+    fn advance_byte(&mut self, byte: u8) {
+        match self.state {
+            ScanState::Ground => match byte {
+                0x1b => self.state = ScanState::Escape,
+                b'\n' | 0x0b | 0x0c => self.move_down(1),
+                b'\r' => self.column = 0,
+                0x08 => self.column = self.column.saturating_sub(1),
+                b'\t' => self.column = (self.column / 8 + 1) * 8,
+                0x20..=0x7e => self.column += 1,
+                0x80..=0xff => self.advance_utf8(byte),
+                _ => {}
+            },
+            ScanState::Escape => {
+                self.state = ScanState::Ground;
+                match byte {
+                    b']' => {
+                        self.sequence.clear();
+                        self.state = ScanState::Osc;
+                    }
+                    b'[' => {
+                        self.sequence.clear();
+                        self.state = ScanState::Csi;
+                    }
+                    // IND, NEL and RI
+                    b'D' => self.move_down(1),
+                    b'E' => {
+                        self.move_down(1);
+                        self.column = 0;
+                    }
+                    b'M' => self.move_up(1),
+                    // RIS resets the whole terminal.
+                    b'c' => {
+                        self.alt_screen = false;
+                        self.push_mark(MarkKind::Cleared);
+                    }
+                    _ => {}
+                }
+            }
+            ScanState::Csi => match byte {
+                0x40..=0x7e => {
+                    self.state = ScanState::Ground;
+                    self.csi_dispatch(byte);
+                }
+                0x1b => self.state = ScanState::Escape,
+                0x18 | 0x1a => self.state = ScanState::Ground,
+                _ => {
+                    if self.sequence.len() < MAX_CSI_LEN {
+                        self.sequence.push(byte);
+                    }
+                }
+            },
+            ScanState::Osc => match byte {
+                0x07 => {
+                    self.state = ScanState::Ground;
+                    self.osc_dispatch();
+                }
+                0x1b => self.state = ScanState::OscEscape,
+                0x18 | 0x1a => self.state = ScanState::Ground,
+                _ => {
+                    if self.sequence.len() < MAX_OSC_LEN {
+                        self.sequence.push(byte);
+                    }
+                }
+            },
+            ScanState::OscEscape => {
+                if byte == b'\\' {
+                    self.state = ScanState::Ground;
+                    self.osc_dispatch();
+                } else {
+                    // An escape aborts the OSC sequence and starts a new one.
+                    self.state = ScanState::Escape;
+                    self.advance_byte(byte);
+                }
+            }
+        }
+    }
+
+    /// Advances the column by the number of cells a multi-byte character occupies in the
+    /// grid, once all its bytes have been scanned.
+    fn advance_utf8(&mut self, byte: u8) {
+        if byte & 0xc0 != 0x80 {
+            self.utf8.clear();
+        }
+        self.utf8.push(byte);
+        match std::str::from_utf8(&self.utf8) {
+            Ok(text) => {
+                self.column += text
+                    .chars()
+                    .map(|character| character.width().unwrap_or(0))
+                    .sum::<usize>();
+                self.utf8.clear();
+            }
+            // Alacritty prints invalid sequences as a replacement character.
+            Err(error) if error.error_len().is_some() => {
+                self.column += 1;
+                self.utf8.clear();
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn move_down(&mut self, lines: usize) {
+        if !self.alt_screen {
+            self.line += lines;
+        }
+    }
+
+    fn move_up(&mut self, lines: usize) {
+        if !self.alt_screen {
+            self.line = self.line.saturating_sub(lines);
+        }
+    }
+
+    fn csi_dispatch(&mut self, action: u8) {
+        let params = String::from_utf8_lossy(&self.sequence).into_owned();
+        if let Some(private_params) = params.strip_prefix('?') {
+            if matches!(action, b'h' | b'l')
+                && private_params
+                    .split(';')
+                    .any(|mode| matches!(mode, "47" | "1047" | "1049"))
+            {
+                self.alt_screen = action == b'h';
+            }
+            return;
+        }
+
+        let count = params
+            .split(';')
+            .next()
+            .and_then(|count| count.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+        match action {
+            b'A' => self.move_up(count),
+            b'B' => self.move_down(count),
+            b'E' => {
+                self.move_down(count);
+                self.column = 0;
+            }
+            b'F' => {
+                self.move_up(count);
+                self.column = 0;
+            }
+            b'G' => self.column = count - 1,
+            // Clearing the screen or the scrollback moves lines around without line feeds.
+            b'J' if !self.alt_screen && matches!(params.as_str(), "2" | "3") => {
+                self.push_mark(MarkKind::Cleared)
+            }
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self) {
+        let sequence = String::from_utf8_lossy(&self.sequence).into_owned();
+        if let Some(kind) = parse_osc(&sequence) {
+            self.push_mark(kind);
+        }
+    }
+
+    fn push_mark(&mut self, kind: MarkKind) {
+        self.pending.push(Mark {
+            kind,
+            line: self.line,
+            column: self.column,
+        });
+    }
+}
+
+fn parse_osc(sequence: &str) -> Option<MarkKind> {
+    let (code, params) = sequence.split_once(';').unwrap_or((sequence, ""));
+    match code {
+        "7" => parse_file_uri(params).map(MarkKind::WorkingDirectory),
+        "133" | "633" => {
+            let (action, args) = params.split_once(';').unwrap_or((params, ""));
+            match action {
+                "A" => Some(MarkKind::PromptStart),
+                "B" => Some(MarkKind::CommandStart),
+                "C" => {
+                    // Kitty's shell integration passes the command line as an argument.
+                    let command = args.split(';').find_map(|arg| {
+                        if let Some(command) = arg.strip_prefix("cmdline=") {
+                            Some(command.to_string())
+                        } else {
+                            arg.strip_prefix("cmdline_url=").map(percent_decode)
+                        }
+                    });
+                    Some(MarkKind::OutputStart { command })
+                }
+                "D" => Some(MarkKind::CommandFinished {
+                    exit_code: args.split(';').next().and_then(|code| code.parse().ok()),
+                }),
+                "E" if code == "633" => {
+                    let command = args.split(';').next().unwrap_or_default();
+                    Some(MarkKind::CommandLine(unescape_vscode_value(command)))
+                }
+                "P" if code == "633" => args
+                    .strip_prefix("Cwd=")
+                    .map(|cwd| MarkKind::WorkingDirectory(unescape_vscode_value(cwd).into())),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parses the `file://hostname/path` URIs reported with OSC 7.
+fn parse_file_uri(uri: &str) -> Option<PathBuf> {
+    let uri = uri.strip_prefix("file://")?;
+    let path = &uri[uri.find('/')?..];
+    let path = percent_decode(path);
+    // Windows paths are reported as `/C:/path`.
+    #[cfg(windows)]
+    let path = match path.strip_prefix('/') {
+        Some(stripped) if stripped.get(1..2) == Some(":") => stripped.to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] == b'%' {
+            if let Some(byte) = text
+                .get(ix + 1..ix + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                ix += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[ix]);
+        ix += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Unescapes the `\\` and `\xAB` escapes used in the values of VS Code's OSC 633 sequences.
+fn unescape_vscode_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] == b'\\' {
+            if bytes.get(ix + 1) == Some(&b'\\') {
+                unescaped.push(b'\\');
+                ix += 2;
+                continue;
+            }
+            if bytes.get(ix + 1) == Some(&b'x') {
+                if let Some(byte) = value
+                    .get(ix + 2..ix + 4)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    unescaped.push(byte);
+                    ix += 4;
+                    continue;
+                }
+            }
+        }
+        unescaped.push(bytes[ix]);
+        ix += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// Wraps the PTY, feeding everything read from it to the [`ShellIntegrationScanner`]
+/// (and to collaborators, while the terminal is shared) before Alacritty parses it.
+pub(crate) struct ShellIntegrationPty {
+    pty: tty::Pty,
+    scanner: Arc<Mutex<ShellIntegrationScanner>>,
+    shared_output: SharedOutput,
+}
+
+impl ShellIntegrationPty {
+    pub fn new(
+        pty: tty::Pty,
+        scanner: Arc<Mutex<ShellIntegrationScanner>>,
+        shared_output: SharedOutput,
+    ) -> Self {
+        Self {
+            pty,
+            scanner,
+            shared_output,
+        }
+    }
+}
+
+impl io::Read for ShellIntegrationPty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.pty.reader().read(buf)?;
+        self.scanner.lock().advance(&buf[..len]);
+        self.shared_output.push(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl EventedReadWrite for ShellIntegrationPty {
+    type Reader = Self;
+    type Writer = <tty::Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ShellIntegrationPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ShellIntegrationPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+/// A command run at the shell's prompt, as reported by its shell integration.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShellCommand {
+    /// The command line, if the shell reported it or it could be read from the terminal.
+    pub command: Option<String>,
+    /// The exit status of the command, if it finished and the shell reported it.
+    pub exit_code: Option<i32>,
+    /// The working directory the command was run in.
+    pub cwd: Option<PathBuf>,
+    finished: bool,
+    prompt_line: Option<usize>,
+    input_start: Option<(usize, usize)>,
+    output_start: Option<usize>,
+    output_end: Option<(usize, usize)>,
+}
+
+impl ShellCommand {
+    /// Whether the command was submitted and is still running.
+    pub fn is_running(&self) -> bool {
+        !self.finished && self.output_start.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// A finished command from a previous session, which has no position in the grid.
+    pub fn restored(command: Option<String>, exit_code: Option<i32>, cwd: Option<PathBuf>) -> Self {
+        Self {
+            command,
+            exit_code,
+            cwd,
+            finished: true,
+            ..Default::default()
+        }
+    }
+
+    fn forget_positions_before(&mut self, line: usize) {
+        if self
+            .prompt_line
+            .map_or(false, |prompt_line| prompt_line < line)
+        {
+            self.prompt_line = None;
+        }
+        if self
+            .input_start
+            .map_or(false, |(input_line, _)| input_line < line)
+        {
+            self.input_start = None;
+        }
+        if self
+            .output_start
+            .map_or(false, |output_line| output_line < line)
+        {
+            self.output_start = None;
+            self.output_end = None;
+        }
+    }
+}
+
+/// A prompt visible in the terminal, used to draw markers in the gutter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandMarker {
+    /// The display line the prompt starts at.
+    pub line: i32,
+    pub status: CommandStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The prompt is waiting for input.
+    Prompt,
+    Running,
+    Succeeded,
+    Failed,
+    /// The command finished without the shell reporting its exit status.
+    Finished,
+}
+
+/// The commands run in a terminal, along with where their prompts and output are in the grid.
+#[derive(Debug, Default)]
+pub struct CommandHistory {
+    commands: VecDeque<ShellCommand>,
+    cwd: Option<PathBuf>,
+    /// The logical line the cursor is on.
+    cursor_line: usize,
+}
+
+impl CommandHistory {
+    pub fn commands(&self) -> impl DoubleEndedIterator<Item = &ShellCommand> {
+        self.commands.iter()
+    }
+
+    pub fn last_finished_command(&self) -> Option<&ShellCommand> {
+        self.commands.iter().rev().find(|command| command.finished)
+    }
+
+    /// The working directory last reported by the shell.
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// Prepends commands restored from a previous session, keeping the most recent ones.
+    pub(crate) fn restore(&mut self, commands: Vec<ShellCommand>) {
+        let capacity = MAX_COMMAND_HISTORY.saturating_sub(self.commands.len());
+        for command in commands.into_iter().rev().take(capacity) {
+            self.commands.push_front(command);
+        }
+    }
+
+    /// Applies marks published by the scanner. The grid must reflect the same output as the marks.
+    pub(crate) fn apply_marks<T>(&mut self, marks: Vec<Mark>, cursor_line: usize, term: &Term<T>) {
+        self.cursor_line = cursor_line;
+        for mark in marks {
+            match mark.kind {
+                MarkKind::PromptStart => {
+                    if let Some(command) = self.commands.back_mut() {
+                        if command.is_running() {
+                            command.finished = true;
+                            command.output_end = Some((mark.line, mark.column));
+                        } else if !command.finished && command.input_start.is_none() {
+                            // The shell redrew its prompt before any command was entered.
+                            command.prompt_line = Some(mark.line);
+                            continue;
+                        }
+                    }
+                    self.push_command(Some(mark.line));
+                }
+                MarkKind::CommandStart => {
+                    if self.pending_command().is_none() {
+                        self.push_command(Some(mark.line));
+                    }
+                    if let Some(command) = self.pending_command() {
+                        command.input_start = Some((mark.line, mark.column));
+                    }
+                }
+                MarkKind::CommandLine(command_line) => {
+                    if let Some(command) = self.pending_command() {
+                        command.command = Some(command_line);
+                    }
+                }
+                MarkKind::OutputStart { command } => {
+                    if self.pending_command().is_none() {
+                        self.push_command(None);
+                    }
+                    let cwd = self.cwd.clone();
+                    let command_line = command.or_else(|| {
+                        let input_start = self.pending_command()?.input_start?;
+                        let input =
+                            self.text_between(term, input_start, (mark.line, mark.column))?;
+                        Some(input.trim().to_string())
+                    });
+                    if let Some(command) = self.pending_command() {
+                        command.output_start = Some(mark.line);
+                        command.cwd = command.cwd.take().or(cwd);
+                        if command.command.is_none() {
+                            command.command = command_line.filter(|line| !line.is_empty());
+                        }
+                    }
+                }
+                MarkKind::CommandFinished { exit_code } => {
+                    if let Some(command) = self.commands.back_mut() {
+                        if command.is_running() {
+                            command.finished = true;
+                            command.exit_code = exit_code;
+                            command.output_end = Some((mark.line, mark.column));
+                        } else if !command.finished && command.command.is_none() {
+                            // Nothing was run, e.g. an empty command line was submitted.
+                            self.commands.pop_back();
+                        }
+                    }
+                }
+                MarkKind::WorkingDirectory(cwd) => {
+                    if let Some(command) = self.pending_command() {
+                        command.cwd = Some(cwd.clone());
+                    }
+                    self.cwd = Some(cwd);
+                }
+                MarkKind::Cleared => self.forget_positions_before(mark.line),
+            }
+        }
+    }
+
+    /// Forgets where the commands are in the grid, for lines before the given one.
+    pub(crate) fn forget_positions_before(&mut self, line: usize) {
+        for command in &mut self.commands {
+            command.forget_positions_before(line);
+        }
+    }
+
+    pub(crate) fn cursor_line(&self) -> usize {
+        self.cursor_line
+    }
+
+    /// The grid lines of all prompts that are still in the scrollback, from the oldest.
+    pub(crate) fn prompt_lines<T>(&self, term: &Term<T>) -> Vec<Line> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+
+        let mut lines = LogicalLines::new(term, self.cursor_line).peekable();
+        let mut prompt_lines = self
+            .commands
+            .iter()
+            .rev()
+            .filter_map(|command| command.prompt_line)
+            .map_while(|prompt_line| {
+                while lines.next_if(|(line, _)| *line > prompt_line).is_some() {}
+                lines
+                    .peek()
+                    .filter(|(line, _)| *line == prompt_line)
+                    .map(|(_, grid_line)| *grid_line)
+            })
+            .collect::<Vec<_>>();
+        prompt_lines.reverse();
+        prompt_lines
+    }
+
+    /// Markers for the prompts in the viewport.
+    pub(crate) fn visible_markers<T>(&self, term: &Term<T>) -> Vec<CommandMarker> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+
+        let display_offset = term.grid().display_offset() as i32;
+        let viewport_top = Line(-display_offset);
+        let viewport_bottom = viewport_top + term.screen_lines() - 1;
+
+        let mut markers = Vec::new();
+        let mut lines = LogicalLines::new(term, self.cursor_line).peekable();
+        for command in self.commands.iter().rev() {
+            let Some(prompt_line) = command.prompt_line else {
+                continue;
+            };
+            while lines.next_if(|(line, _)| *line > prompt_line).is_some() {}
+            let Some(grid_line) = lines
+                .peek()
+                .filter(|(line, _)| *line == prompt_line)
+                .map(|(_, grid_line)| *grid_line)
+            else {
+                break;
+            };
+            if grid_line < viewport_top {
+                break;
+            }
+            if grid_line <= viewport_bottom {
+                let status = if command.is_running() {
+                    CommandStatus::Running
+                } else if !command.finished {
+                    CommandStatus::Prompt
+                } else {
+                    match command.exit_code {
+                        Some(0) => CommandStatus::Succeeded,
+                        Some(_) => CommandStatus::Failed,
+                        None => CommandStatus::Finished,
+                    }
+                };
+                markers.push(CommandMarker {
+                    line: grid_line.0 + display_offset,
+                    status,
+                });
+            }
+        }
+        markers
+    }
+
+    /// The output of the last finished command, if it's still in the scrollback.
+    pub(crate) fn last_command_output<T>(&self, term: &Term<T>) -> Option<String> {
+        let command = self.last_finished_command()?;
+        let text = self.text_between(term, (command.output_start?, 0), command.output_end?)?;
+        Some(text.trim_end().to_string())
+    }
+
+    /// Reads the text from a position in the grid up to another one, excluding the
+    /// line feed at the end if the range ends at the start of a logical line.
+    fn text_between<T>(
+        &self,
+        term: &Term<T>,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<String> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
+        }
+
+        let start_point = grid_point(term, self.cursor_line, start)?;
+        let end_point = if end.1 == 0 {
+            if end.0 <= start.0 {
+                return Some(String::new());
+            }
+            let line = grid_point(term, self.cursor_line, (end.0, 0))?.line - 1;
+            AlacPoint::new(line, term.last_column())
+        } else {
+            grid_point(term, self.cursor_line, (end.0, end.1 - 1))?
+        };
+        if end_point < start_point {
+            return Some(String::new());
+        }
+        Some(term.bounds_to_string(start_point, end_point))
+    }
+
+    fn pending_command(&mut self) -> Option<&mut ShellCommand> {
+        self.commands
+            .back_mut()
+            .filter(|command| !command.finished && command.output_start.is_none())
+    }
+
+    fn push_command(&mut self, prompt_line: Option<usize>) {
+        if self.commands.len() == MAX_COMMAND_HISTORY {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(ShellCommand {
+            cwd: self.cwd.clone(),
+            prompt_line,
+            ..Default::default()
+        });
+    }
+}
+
+/// Converts a position within a logical line into a point in the grid.
+fn grid_point<T>(
+    term: &Term<T>,
+    cursor_line: usize,
+    (line, column): (usize, usize),
+) -> Option<AlacPoint> {
+    let (_, start) = LogicalLines::new(term, cursor_line)
+        .find(|(logical_line, _)| *logical_line <= line)
+        .filter(|(logical_line, _)| *logical_line == line)?;
+    let columns = term.columns();
+    let point = AlacPoint::new(start + column / columns, Column(column % columns));
+    (point.line <= term.bottommost_line()).then_some(point)
+}
+
+/// Iterates over the logical lines of the grid from the cursor upwards, yielding the index
+/// of each line along with the grid line it starts at.
+struct LogicalLines<'a, T> {
+    term: &'a Term<T>,
+    next: Option<(usize, Line)>,
+}
+
+impl<'a, T> LogicalLines<'a, T> {
+    fn new(term: &'a Term<T>, cursor_line: usize) -> Self {
+        Self {
+            term,
+            next: Some((cursor_line, term.grid().cursor.point.line)),
+        }
+    }
+
+    fn is_wrapped(&self, line: Line) -> bool {
+        self.term.grid()[line][self.term.last_column()]
+            .flags
+            .contains(Flags::WRAPLINE)
+    }
+}
+
+impl<'a, T> Iterator for LogicalLines<'a, T> {
+    type Item = (usize, Line);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (logical_line, mut grid_line) = self.next.take()?;
+        let topmost_line = self.term.topmost_line();
+        while grid_line > topmost_line && self.is_wrapped(grid_line - 1) {
+            grid_line = grid_line - 1;
+        }
+        if grid_line > topmost_line && logical_line > 0 {
+            self.next = Some((logical_line - 1, grid_line - 1));
+        }
+        Some((logical_line, grid_line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, term::Config, vte::ansi::Processor};
+    use gpui::{px, size};
+
+    use super::*;
+    use crate::TerminalSize;
+
+    fn scan(output: &str) -> Vec<Mark> {
+        let mut scanner = ShellIntegrationScanner::default();
+        scanner.advance(output.as_bytes());
+        scanner.commit();
+        scanner.take_committed().0
+    }
+
+    /// A terminal of 20 columns and 5 lines, fed through the scanner like the PTY output.
+    struct ShellSession {
+        term: Term<VoidListener>,
+        parser: Processor,
+        scanner: ShellIntegrationScanner,
+        history: CommandHistory,
+    }
+
+    impl ShellSession {
+        fn new() -> Self {
+            let size = TerminalSize::new(px(10.), px(5.), size(px(100.), px(50.)));
+            Self {
+                term: Term::new(Config::default(), &size, VoidListener),
+                parser: Processor::new(),
+                scanner: ShellIntegrationScanner::default(),
+                history: CommandHistory::default(),
+            }
+        }
+
+        fn write(&mut self, output: &str) {
+            self.scanner.advance(output.as_bytes());
+            for byte in output.bytes() {
+                self.parser.advance(&mut self.term, byte);
+            }
+            self.scanner.commit();
+            let (marks, cursor_line) = self.scanner.take_committed();
+            self.history.apply_marks(marks, cursor_line, &self.term);
+        }
+
+        fn commands(&self) -> Vec<(Option<&str>, Option<i32>, bool)> {
+            self.history
+                .commands()
+                .map(|command| {
+                    (
+                        command.command.as_deref(),
+                        command.exit_code,
+                        command.is_finished(),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_scanning_semantic_prompts() {
+        let marks = scan(concat!(
+            "\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n",
+            "\x1b]133;C\x07a\r\nb\r\n",
+            "\x1b]133;D;1\x1b\\\x1b]133;A\x07$ ",
+        ));
+        assert_eq!(
+            marks,
+            vec![
+                Mark {
+                    kind: MarkKind::PromptStart,
+                    line: 0,
+                    column: 0
+                },
+                Mark {
+                    kind: MarkKind::CommandStart,
+                    line: 0,
+                    column: 2
+                },
+                Mark {
+                    kind: MarkKind::OutputStart { command: None },
+                    line: 1,
+                    column: 0
+                },
+                Mark {
+                    kind: MarkKind::CommandFinished { exit_code: Some(1) },
+                    line: 3,
+                    column: 0
+                },
+                Mark {
+                    kind: MarkKind::PromptStart,
+                    line: 3,
+                    column: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanning_ignores_alternate_screen() {
+        let marks = scan("\x1b[?1049h\n\n\n\x1b[?1049l\x1b]133;A\x07");
+        assert_eq!(marks[0].line, 0);
+
+        let marks = scan("a\nb\x1b[2Ac\n\x1b]133;A\x07");
+        assert_eq!(marks[0].line, 1);
+    }
+
+    #[test]
+    fn test_scanning_command_lines_and_directories() {
+        let marks = scan(concat!(
+            "\x1b]7;file://host/home/user/My%20Project\x07",
+            "\x1b]633;E;echo a\\x3bb\\\\\x07",
+            "\x1b]133;C;cmdline_url=cargo%20test\x07",
+        ));
+        assert_eq!(
+            marks.into_iter().map(|mark| mark.kind).collect::<Vec<_>>(),
+            vec![
+                MarkKind::WorkingDirectory(PathBuf::from("/home/user/My Project")),
+                MarkKind::CommandLine("echo a;b\\".into()),
+                MarkKind::OutputStart {
+                    command: Some("cargo test".into())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uncommitted_marks() {
+        let mut scanner = ShellIntegrationScanner::default();
+        scanner.advance(b"\x1b]133;A\x07\n");
+        scanner.commit();
+        scanner.advance(b"\n\x1b]133;B\x07");
+        let (marks, line) = scanner.take_committed();
+        assert_eq!(marks.len(), 1);
+        assert_eq!(line, 1);
+    }
+
+    #[test]
+    fn test_scanning_counts_cells() {
+        let marks = scan("日本$ \x1b]133;B\x07e\u{301}\x1b]133;C\x07");
+        assert_eq!(marks[0].column, 6);
+        assert_eq!(marks[1].column, 7);
+    }
+
+    #[test]
+    fn test_apply_marks() {
+        let mut session = ShellSession::new();
+        session.write("\x1b]133;A\x07$ \x1b]133;B\x07echo hi\r\n\x1b]133;C\x07hi\r\nthere\r\n");
+        assert_eq!(session.commands(), [(Some("echo hi"), None, false)]);
+        assert!(session.history.commands().next().unwrap().is_running());
+        assert_eq!(session.history.last_command_output(&session.term), None);
+
+        session.write("\x1b]133;D;0\x07\x1b]133;A\x07$ ");
+        assert_eq!(
+            session.commands(),
+            [(Some("echo hi"), Some(0), true), (None, None, false)]
+        );
+        assert_eq!(
+            session
+                .history
+                .last_command_output(&session.term)
+                .as_deref(),
+            Some("hi\nthere")
+        );
+
+        // Redrawing the prompt and submitting an empty command line don't record commands.
+        session.write("\r\x1b]133;A\x07$ \x1b]133;B\x07\r\n\x1b]133;D\x07");
+        session.write("\x1b]133;A\x07$ \x1b]133;B\x07");
+        assert_eq!(
+            session.commands(),
+            [(Some("echo hi"), Some(0), true), (None, None, false)]
+        );
+
+        session.write("false\r\n\x1b]133;C\x07\x1b]133;D;1\x07\x1b]133;A\x07$ ");
+        assert_eq!(
+            session.commands(),
+            [
+                (Some("echo hi"), Some(0), true),
+                (Some("false"), Some(1), true),
+                (None, None, false),
+            ]
+        );
+        assert_eq!(
+            session
+                .history
+                .last_command_output(&session.term)
+                .as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn test_command_line_after_wide_prompt() {
+        let mut session = ShellSession::new();
+        session.write("\x1b]133;A\x07日本 ✓ $ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\n");
+        session.write("\x1b]133;D;0\x07\x1b]133;A\x07");
+        assert_eq!(
+            session.commands(),
+            [(Some("ls"), Some(0), true), (None, None, false)]
+        );
+        assert_eq!(
+            session
+                .history
+                .last_command_output(&session.term)
+                .as_deref(),
+            Some("a")
+        );
+    }
+
+    #[test]
+    fn test_prompt_lines() {
+        let mut session = ShellSession::new();
+        session.write("\x1b]133;A\x07$ \x1b]133;B\x07cmd\r\n\x1b]133;C\x07");
+        // This line wraps, so the logical lines after it are one grid line further down.
+        session.write(&"x".repeat(25));
+        session.write("\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07cmd\r\n\x1b]133;C\x07");
+        session.write("a\r\nb\r\nc\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ ");
+
+        // The first prompt was scrolled into the scrollback.
+        assert_eq!(
+            session.history.prompt_lines(&session.term),
+            [Line(-3), Line(0), Line(4)]
+        );
+        assert_eq!(
+            session
+                .history
+                .last_command_output(&session.term)
+                .as_deref(),
+            Some("a\nb\nc")
+        );
+
+        session.write("\x1b[?1049h");
+        assert!(session.history.prompt_lines(&session.term).is_empty());
+        assert_eq!(session.history.last_command_output(&session.term), None);
+        session.write("\x1b[?1049l");
+        assert_eq!(session.history.prompt_lines(&session.term).len(), 3);
+    }
+}
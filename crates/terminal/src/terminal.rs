@@ -3,11 +3,14 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+mod remote;
+mod shell_integration;
 pub mod terminal_settings;
+mod vi_mode;
 
 use alacritty_terminal::{
-    event::{Event as AlacTermEvent, EventListener, Notify, WindowSize},
-    event_loop::{EventLoop, Msg, Notifier},
+    event::{Event as AlacTermEvent, EventListener, WindowSize},
+    event_loop::{EventLoop, Notifier},
     grid::{Dimensions, Scroll as AlacScroll},
     index::{Boundary, Column, Direction as AlacDirection, Line, Point as AlacPoint},
     selection::{Selection, SelectionRange, SelectionType},
@@ -18,7 +21,7 @@ use alacritty_terminal::{
         Config, RenderableCursor, TermMode,
     },
     tty::{self, setup_env},
-    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Rgb},
+    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Processor, Rgb},
     Term,
 };
 use anyhow::{bail, Result};
@@ -35,9 +38,12 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use parking_lot::Mutex;
 use pty_info::PtyProcessInfo;
+use remote::{PtySender, SharedOutput};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{ShellIntegrationPty, ShellIntegrationScanner};
 use smol::channel::{Receiver, Sender};
 use task::{static_source::RevealStrategy, TaskId};
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
@@ -55,16 +61,32 @@ use std::{
 use thiserror::Error;
 
 use gpui::{
-    actions, black, px, AnyWindowHandle, AppContext, Bounds, ClipboardItem, EventEmitter, Hsla,
-    Keystroke, ModelContext, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, Rgba, ScrollWheelEvent, Size, Task, TouchPhase,
+    actions, black, px, size, AnyWindowHandle, AppContext, Bounds, ClipboardItem, EventEmitter,
+    Hsla, Keystroke, ModelContext, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent,
+    MouseUpEvent, Pixels, Point, Rgba, ScrollWheelEvent, Size, Task, TouchPhase,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
+pub use remote::SharedTerminalState;
+pub use shell_integration::{CommandHistory, CommandMarker, CommandStatus, ShellCommand};
+pub use vi_mode::{ViMotion, ViSelection};
+
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        PopulateLocationList,
+        ToggleViMode,
+        ViYank,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -126,15 +148,30 @@ enum InternalEvent {
     // Adjusted mouse position, should open
     FindHyperlink(Point<Pixels>, bool),
     Copy,
+    ScrollToPrompt(AlacDirection),
+    CopyLastCommandOutput,
+    ToggleViMode,
+    ViMotion(ViMotion),
+    ViGoto(AlacPoint),
+    ToggleViSelection(ViSelection),
+    ViYank,
 }
 
 ///A translation struct for Alacritty to communicate with us from their event loop
 #[derive(Clone)]
-pub struct ZedListener(UnboundedSender<AlacTermEvent>);
+pub struct ZedListener {
+    events_tx: UnboundedSender<AlacTermEvent>,
+    shell_integration: Arc<Mutex<ShellIntegrationScanner>>,
+}
 
 impl EventListener for ZedListener {
     fn send_event(&self, event: AlacTermEvent) {
-        self.0.unbounded_send(event).ok();
+        // Alacritty's event loop wakes us up once it has parsed everything it read from the PTY,
+        // while still holding the terminal lock, so the shell integration marks match the grid.
+        if let AlacTermEvent::Wakeup = event {
+            self.shell_integration.lock().commit();
+        }
+        self.events_tx.unbounded_send(event).ok();
     }
 }
 
@@ -314,6 +351,7 @@ impl TerminalBuilder {
         blink_settings: Option<TerminalBlink>,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        restored_scrollback: Option<RestoredScrollback>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
     ) -> Result<TerminalBuilder> {
@@ -363,12 +401,13 @@ impl TerminalBuilder {
         //Spawn a task so the Alacritty EventLoop can communicate with us in a view context
         //TODO: Remove with a bounded sender which can be dispatched on &self
         let (events_tx, events_rx) = unbounded();
+        let shell_integration = Arc::new(Mutex::new(ShellIntegrationScanner::default()));
+        let listener = ZedListener {
+            events_tx,
+            shell_integration: shell_integration.clone(),
+        };
         //Set up the terminal...
-        let mut term = Term::new(
-            config,
-            &TerminalSize::default(),
-            ZedListener(events_tx.clone()),
-        );
+        let mut term = Term::new(config, &TerminalSize::default(), listener.clone());
 
         //Start off blinking if we need to
         if let Some(TerminalBlink::On) = blink_settings {
@@ -380,6 +419,12 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Restore the previous output before the shell starts writing to the terminal.
+        let restored_commands = restored_scrollback.map(|restored_scrollback| {
+            write_restored_scrollback(&mut term, &restored_scrollback.text);
+            restored_scrollback.commands
+        });
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -399,44 +444,28 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let shared_output = SharedOutput::default();
+        let pty = ShellIntegrationPty::new(pty, shell_integration.clone(), shared_output.clone());
 
         //And connect them together
-        let event_loop = EventLoop::new(
-            term.clone(),
-            ZedListener(events_tx.clone()),
-            pty,
-            pty_options.hold,
-            false,
-        )?;
+        let event_loop = EventLoop::new(term.clone(), listener, pty, pty_options.hold, false)?;
 
         //Kick things off
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
 
-        let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
-        let word_regex = RegexSearch::new(r#"[\$\+\w.\[\]:/@\-~]+"#).unwrap();
-
-        let terminal = Terminal {
+        let mut terminal = Terminal::new(
+            PtySender::Pty(Notifier(pty_tx)),
+            Some(pty_info),
+            shared_output,
             task,
-            pty_tx: Notifier(pty_tx),
             completion_tx,
             term,
-            events: VecDeque::with_capacity(10), //Should never get this high.
-            last_content: Default::default(),
-            last_mouse: None,
-            matches: Vec::new(),
-            selection_head: None,
-            pty_info,
-            breadcrumb_text: String::new(),
-            scroll_px: px(0.),
-            last_mouse_position: None,
-            next_link_id: 0,
-            selection_phase: SelectionPhase::Ended,
-            cmd_pressed: false,
-            hovered_word: false,
-            url_regex,
-            word_regex,
-        };
+            shell_integration,
+        );
+        if let Some(commands) = restored_commands {
+            terminal.command_history.restore(commands);
+        }
 
         Ok(TerminalBuilder {
             terminal,
@@ -534,6 +563,7 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_markers: Vec<CommandMarker>,
 }
 
 #[derive(Clone)]
@@ -558,6 +588,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_markers: Vec::new(),
         }
     }
 }
@@ -569,7 +600,7 @@ pub enum SelectionPhase {
 }
 
 pub struct Terminal {
-    pty_tx: Notifier,
+    pty_tx: PtySender,
     completion_tx: Sender<()>,
     term: Arc<FairMutex<Term<ZedListener>>>,
     events: VecDeque<InternalEvent>,
@@ -581,7 +612,8 @@ pub struct Terminal {
     pub last_content: TerminalContent,
     pub selection_head: Option<AlacPoint>,
     pub breadcrumb_text: String,
-    pub pty_info: PtyProcessInfo,
+    /// Information about the shell's process, unless the shell runs on a collaborator's machine.
+    pub pty_info: Option<PtyProcessInfo>,
     scroll_px: Pixels,
     next_link_id: usize,
     selection_phase: SelectionPhase,
@@ -590,6 +622,14 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    shell_integration: Arc<Mutex<ShellIntegrationScanner>>,
+    command_history: CommandHistory,
+    vi_mode_enabled: bool,
+    /// The size of the scrollback history when vi mode last saw it, used to hold the viewport
+    /// in place while output keeps arriving.
+    vi_mode_history_size: usize,
+    /// The PTY output not yet sent to collaborators, while the terminal is shared.
+    shared_output: SharedOutput,
 }
 
 pub struct TaskState {
@@ -599,7 +639,53 @@ pub struct TaskState {
     pub completion_rx: Receiver<()>,
 }
 
+/// The output and commands of a previous session of a terminal.
+pub struct RestoredScrollback {
+    pub text: String,
+    pub commands: Vec<ShellCommand>,
+}
+
 impl Terminal {
+    fn new(
+        pty_tx: PtySender,
+        pty_info: Option<PtyProcessInfo>,
+        shared_output: SharedOutput,
+        task: Option<TaskState>,
+        completion_tx: Sender<()>,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        shell_integration: Arc<Mutex<ShellIntegrationScanner>>,
+    ) -> Self {
+        let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
+        let word_regex = RegexSearch::new(r#"[\$\+\w.\[\]:/@\-~]+"#).unwrap();
+
+        Terminal {
+            task,
+            pty_tx,
+            completion_tx,
+            term,
+            events: VecDeque::with_capacity(10), //Should never get this high.
+            last_content: Default::default(),
+            last_mouse: None,
+            matches: Vec::new(),
+            selection_head: None,
+            pty_info,
+            breadcrumb_text: String::new(),
+            scroll_px: px(0.),
+            last_mouse_position: None,
+            next_link_id: 0,
+            selection_phase: SelectionPhase::Ended,
+            cmd_pressed: false,
+            hovered_word: false,
+            url_regex,
+            word_regex,
+            shell_integration,
+            command_history: CommandHistory::default(),
+            vi_mode_enabled: false,
+            vi_mode_history_size: 0,
+            shared_output,
+        }
+    }
+
     fn process_event(&mut self, event: &AlacTermEvent, cx: &mut ModelContext<Self>) {
         match event {
             AlacTermEvent::Title(title) => {
@@ -613,14 +699,14 @@ impl Terminal {
             AlacTermEvent::ClipboardStore(_, data) => {
                 cx.write_to_clipboard(ClipboardItem::new(data.to_string()))
             }
-            AlacTermEvent::ClipboardLoad(_, format) => self.write_to_pty(format(
+            AlacTermEvent::ClipboardLoad(_, format) => self.respond_to_pty(format(
                 &cx.read_from_clipboard()
                     .map(|ci| ci.text().to_string())
                     .unwrap_or_else(|| "".to_string()),
             )),
-            AlacTermEvent::PtyWrite(out) => self.write_to_pty(out.clone()),
+            AlacTermEvent::PtyWrite(out) => self.respond_to_pty(out.clone()),
             AlacTermEvent::TextAreaSizeRequest(format) => {
-                self.write_to_pty(format(self.last_content.size.into()))
+                self.respond_to_pty(format(self.last_content.size.into()))
             }
             AlacTermEvent::CursorBlinkingChange => {
                 cx.emit(Event::BlinkChanged);
@@ -641,7 +727,11 @@ impl Terminal {
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
 
-                if self.pty_info.has_changed() {
+                if self
+                    .pty_info
+                    .as_mut()
+                    .map_or(false, |pty_info| pty_info.has_changed())
+                {
                     cx.emit(Event::TitleChanged);
                 }
             }
@@ -660,7 +750,18 @@ impl Terminal {
     }
 
     pub fn get_cwd(&self) -> Option<PathBuf> {
-        self.pty_info.current.as_ref().map(|info| info.cwd.clone())
+        self.command_history
+            .cwd()
+            .map(|cwd| cwd.to_path_buf())
+            .or_else(|| {
+                let info = self.pty_info.as_ref()?.current.as_ref()?;
+                Some(info.cwd.clone())
+            })
+    }
+
+    /// The commands run in the terminal, as reported by the shell's integration.
+    pub fn command_history(&self) -> &CommandHistory {
+        &self.command_history
     }
 
     ///Takes events from Alacritty and translates them to behavior on this view
@@ -675,15 +776,24 @@ impl Terminal {
                 let color = term.colors()[*index].unwrap_or_else(|| {
                     to_alac_rgb(get_color_at_index(*index, cx.theme().as_ref()))
                 });
-                self.write_to_pty(format(color))
+                self.respond_to_pty(format(color))
             }
             InternalEvent::Resize(mut new_size) => {
                 new_size.size.height = cmp::max(new_size.line_height, new_size.height());
                 new_size.size.width = cmp::max(new_size.cell_width, new_size.width());
 
+                if let Some((columns, lines)) = self.pty_tx.remote_grid_size() {
+                    // A shared terminal keeps the size of the host's grid, whatever the size
+                    // of the view showing it.
+                    new_size.size = size(
+                        new_size.cell_width * columns as f32,
+                        new_size.line_height * lines as f32,
+                    );
+                }
+
                 self.last_content.size = new_size;
 
-                self.pty_tx.0.send(Msg::Resize(new_size.into())).ok();
+                self.pty_tx.resize(new_size.into());
 
                 term.resize(new_size);
             }
@@ -717,6 +827,9 @@ impl Terminal {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
                 }
 
+                let cursor_line = self.command_history.cursor_line();
+                self.command_history.forget_positions_before(cursor_line);
+
                 cx.emit(Event::Wakeup);
             }
             InternalEvent::Scroll(scroll) => {
@@ -752,10 +865,52 @@ impl Terminal {
                     cx.write_to_clipboard(ClipboardItem::new(txt))
                 }
             }
+            InternalEvent::ToggleViMode => {
+                if term.mode().contains(TermMode::VI) != self.vi_mode_enabled {
+                    term.toggle_vi_mode();
+                }
+                if self.vi_mode_enabled {
+                    self.vi_mode_history_size = term.history_size();
+                } else {
+                    term.selection = None;
+                    term.scroll_display(AlacScroll::Bottom);
+                }
+            }
+            InternalEvent::ViMotion(motion) => vi_mode::apply_motion(term, *motion),
+            InternalEvent::ViGoto(point) => term.vi_goto_point(*point),
+            InternalEvent::ToggleViSelection(kind) => vi_mode::toggle_selection(term, *kind),
+            InternalEvent::ViYank => {
+                let text = vi_mode::yank_text(term);
+                cx.write_to_clipboard(ClipboardItem::new(text));
+                term.selection = None;
+            }
             InternalEvent::ScrollToAlacPoint(point) => {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word();
             }
+            InternalEvent::ScrollToPrompt(direction) => {
+                let display_offset = term.grid().display_offset() as i32;
+                let viewport_top = Line(-display_offset);
+                let prompt_lines = self.command_history.prompt_lines(term);
+                let prompt_line = match direction {
+                    AlacDirection::Left => {
+                        prompt_lines.iter().rev().find(|line| **line < viewport_top)
+                    }
+                    AlacDirection::Right => prompt_lines.iter().find(|line| **line > viewport_top),
+                };
+
+                if let Some(prompt_line) = prompt_line {
+                    // Scroll the prompt to the top of the viewport
+                    let new_display_offset = (-prompt_line.0).max(0);
+                    term.scroll_display(AlacScroll::Delta(new_display_offset - display_offset));
+                    self.refresh_hovered_word();
+                }
+            }
+            InternalEvent::CopyLastCommandOutput => {
+                if let Some(output) = self.command_history.last_command_output(term) {
+                    cx.write_to_clipboard(ClipboardItem::new(output))
+                }
+            }
             InternalEvent::FindHyperlink(position, open) => {
                 let prev_hovered_word = self.last_content.last_hovered_word.take();
 
@@ -916,6 +1071,11 @@ impl Terminal {
 
     pub fn activate_match(&mut self, index: usize) {
         if let Some(search_match) = self.matches.get(index).cloned() {
+            if self.vi_mode_enabled {
+                self.events
+                    .push_back(InternalEvent::ViGoto(*search_match.start()));
+                return;
+            }
             self.set_selection(Some((make_selection(&search_match), *search_match.end())));
 
             self.events
@@ -946,6 +1106,16 @@ impl Terminal {
         self.set_selection(Some((make_selection(&(start..=end)), end)));
     }
 
+    /// The text of the scrollback and the screen, cut to the last `max_bytes` bytes.
+    pub fn scrollback_text(&self, max_bytes: usize) -> String {
+        let term = self.term.lock();
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        let text = term.bounds_to_string(start, end);
+        drop(term);
+        tail_at_line_start(text.trim_end(), max_bytes).to_string()
+    }
+
     fn set_selection(&mut self, selection: Option<(Selection, AlacPoint)>) {
         self.events
             .push_back(InternalEvent::SetSelection(selection));
@@ -959,6 +1129,51 @@ impl Terminal {
         self.events.push_back(InternalEvent::Clear)
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Left));
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Right));
+    }
+
+    pub fn copy_last_command_output(&mut self) {
+        self.events.push_back(InternalEvent::CopyLastCommandOutput);
+    }
+
+    pub fn vi_mode_enabled(&self) -> bool {
+        self.vi_mode_enabled
+    }
+
+    /// Enters or leaves vi mode, in which the keyboard moves a cursor over the scrollback
+    /// instead of sending input to the shell.
+    pub fn toggle_vi_mode(&mut self) {
+        self.vi_mode_enabled = !self.vi_mode_enabled;
+        self.events.push_back(InternalEvent::ToggleViMode);
+    }
+
+    pub fn vi_motion(&mut self, motion: ViMotion) {
+        if self.vi_mode_enabled {
+            self.events.push_back(InternalEvent::ViMotion(motion));
+        }
+    }
+
+    pub fn toggle_vi_selection(&mut self, kind: ViSelection) {
+        if self.vi_mode_enabled {
+            self.events
+                .push_back(InternalEvent::ToggleViSelection(kind));
+        }
+    }
+
+    /// Copies the selection, or the line under the vi mode cursor, to the clipboard.
+    pub fn vi_yank(&mut self) {
+        if self.vi_mode_enabled {
+            self.events.push_back(InternalEvent::ViYank);
+        }
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_size: TerminalSize) {
         self.events.push_back(InternalEvent::Resize(new_size))
@@ -969,6 +1184,14 @@ impl Terminal {
         self.pty_tx.notify(input.into_bytes());
     }
 
+    /// Answers a query the program running in the terminal made, which only the terminal
+    /// running that program can do.
+    fn respond_to_pty(&self, response: String) {
+        if !self.is_remote() {
+            self.write_to_pty(response);
+        }
+    }
+
     fn write_bytes_to_pty(&self, input: Vec<u8>) {
         self.pty_tx.notify(input);
     }
@@ -1022,12 +1245,22 @@ impl Terminal {
     pub fn sync(&mut self, cx: &mut ModelContext<Self>) {
         let term = self.term.clone();
         let mut terminal = term.lock_unfair();
+        let (marks, cursor_line) = self.shell_integration.lock().take_committed();
+        self.command_history
+            .apply_marks(marks, cursor_line, &terminal);
+
+        if self.vi_mode_enabled && terminal.mode().contains(TermMode::VI) {
+            self.vi_mode_history_size =
+                vi_mode::hold_viewport(&mut terminal, self.vi_mode_history_size);
+        }
+
         //Note that the ordering of events matters for event processing
         while let Some(e) = self.events.pop_front() {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.command_markers = self.command_history.visible_markers(&terminal);
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1055,19 +1288,20 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_markers: Vec::new(),
         }
     }
 
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
-            self.write_to_pty("\x1b[I".to_string());
+            self.respond_to_pty("\x1b[I".to_string());
         }
     }
 
     pub fn focus_out(&mut self) {
         self.last_mouse_position = None;
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
-            self.write_to_pty("\x1b[O".to_string());
+            self.respond_to_pty("\x1b[O".to_string());
         }
     }
 
@@ -1337,10 +1571,11 @@ impl Terminal {
                     task_state.label.clone()
                 }
             }
+            None if self.is_remote() => self.remote_title(truncate, MAX_CHARS),
             None => self
                 .pty_info
-                .current
                 .as_ref()
+                .and_then(|pty_info| pty_info.current.as_ref())
                 .map(|fpi| {
                     let process_file = fpi
                         .cwd
@@ -1399,7 +1634,7 @@ impl Terminal {
 
 impl Drop for Terminal {
     fn drop(&mut self) {
-        self.pty_tx.0.send(Msg::Shutdown).ok();
+        self.pty_tx.shutdown();
     }
 }
 
@@ -1429,6 +1664,39 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// Writes the output of a previous session, dimmed, to a terminal whose shell hasn't
+/// started yet, so that the shell's output follows it.
+fn write_restored_scrollback<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    let mut restored = String::from("\x1b[2m");
+    for line in scrollback.lines() {
+        restored.push_str(line);
+        restored.push_str("\r\n");
+    }
+    restored.push_str("\x1b[0m");
+
+    let mut parser: Processor = Processor::new();
+    for byte in restored.bytes() {
+        parser.advance(term, byte);
+    }
+}
+
+/// Returns at most the last `max_bytes` bytes of `text`, starting at the beginning of a line
+/// when possible.
+fn tail_at_line_start(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let start = text.len() - max_bytes;
+    if let Some(newline_ix) = text.as_bytes()[start..].iter().position(|&b| b == b'\n') {
+        &text[start + newline_ix + 1..]
+    } else {
+        let start = (start..text.len())
+            .find(|&ix| text.is_char_boundary(ix))
+            .unwrap_or(text.len());
+        &text[start..]
+    }
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1539,14 +1807,21 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{
+            cell::{Cell, Flags},
+            Config,
+        },
+        vte::ansi::Processor,
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, tail_at_line_start, write_restored_scrollback,
+        IndexedCell, TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -1558,6 +1833,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_restored_scrollback_precedes_shell_output() {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        write_restored_scrollback(&mut term, "$ make\nok\n");
+
+        let mut parser: Processor = Processor::new();
+        for byte in b"$ ls" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        assert_eq!(
+            term.bounds_to_string(start, end).trim_end(),
+            "$ make\nok\n$ ls"
+        );
+        assert_eq!(term.grid().cursor.point, AlacPoint::new(Line(2), Column(4)));
+
+        // The restored output is dimmed, unlike the shell's.
+        assert!(term.grid()[Line(0)][Column(0)].flags.contains(Flags::DIM));
+        assert!(!term.grid()[Line(2)][Column(0)].flags.contains(Flags::DIM));
+    }
+
+    #[test]
+    fn test_tail_at_line_start() {
+        assert_eq!(
+            tail_at_line_start("one\ntwo\nthree", 100),
+            "one\ntwo\nthree"
+        );
+        assert_eq!(tail_at_line_start("one\ntwo\nthree", 9), "three");
+        assert_eq!(tail_at_line_start("one\ntwo\nthree", 10), "two\nthree");
+        assert_eq!(tail_at_line_start("ééé", 3), "é");
+    }
+
     #[test]
     fn test_mouse_to_cell_test() {
         let mut rng = thread_rng();
@@ -23,6 +23,12 @@ pub struct Toolbar {
     pub title: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistScrollback {
+    pub enabled: bool,
+    pub max_bytes: usize,
+}
+
 #[derive(Deserialize)]
 pub struct TerminalSettings {
     pub shell: Shell,
@@ -42,6 +48,7 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub persist_scrollback: PersistScrollback,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -163,6 +170,9 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// Whether to save the scrollback and recent commands of each terminal,
+    /// and show them above a fresh shell when the workspace is reopened.
+    pub persist_scrollback: Option<PersistScrollbackContent>,
 }
 
 impl settings::Settings for TerminalSettings {
@@ -291,3 +301,16 @@ pub struct ToolbarContent {
     /// Default: true
     pub title: Option<bool>,
 }
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistScrollbackContent {
+    /// Whether to persist terminal scrollback.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum size of the scrollback saved for each terminal, in bytes.
+    /// Only the most recent output is kept.
+    ///
+    /// Default: 262144
+    pub max_bytes: Option<usize>,
+}
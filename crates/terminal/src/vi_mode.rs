@@ -0,0 +1,244 @@
+//! Keyboard navigation of the scrollback, built on top of alacritty's vi mode.
+
+use alacritty_terminal::{
+    event::EventListener,
+    grid::{Dimensions, Scroll},
+    index::{Column, Point, Side},
+    selection::{Selection, SelectionType},
+    vi_mode::ViMotion as AlacViMotion,
+    Term,
+};
+
+/// Moves the vi mode cursor. Motions are named after their vim equivalents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViMotion {
+    Left,
+    Down,
+    Up,
+    Right,
+    NextWordStart,
+    NextWordEnd,
+    PreviousWordStart,
+    PreviousWordEnd,
+    NextBigWordStart,
+    NextBigWordEnd,
+    PreviousBigWordStart,
+    PreviousBigWordEnd,
+    StartOfLine,
+    FirstNonWhitespace,
+    EndOfLine,
+    WindowTop,
+    WindowMiddle,
+    WindowBottom,
+    StartOfDocument,
+    EndOfDocument,
+    Matching,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+}
+
+/// The kinds of selections of vi mode, like vim's visual modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViSelection {
+    Character,
+    Line,
+    Block,
+}
+
+pub(crate) fn apply_motion<T: EventListener>(term: &mut Term<T>, motion: ViMotion) {
+    let half_page = (term.screen_lines() / 2) as i32;
+    let motion = match motion {
+        ViMotion::Left => AlacViMotion::Left,
+        ViMotion::Down => AlacViMotion::Down,
+        ViMotion::Up => AlacViMotion::Up,
+        ViMotion::Right => AlacViMotion::Right,
+        ViMotion::NextWordStart => AlacViMotion::SemanticRight,
+        ViMotion::NextWordEnd => AlacViMotion::SemanticRightEnd,
+        ViMotion::PreviousWordStart => AlacViMotion::SemanticLeft,
+        ViMotion::PreviousWordEnd => AlacViMotion::SemanticLeftEnd,
+        ViMotion::NextBigWordStart => AlacViMotion::WordRight,
+        ViMotion::NextBigWordEnd => AlacViMotion::WordRightEnd,
+        ViMotion::PreviousBigWordStart => AlacViMotion::WordLeft,
+        ViMotion::PreviousBigWordEnd => AlacViMotion::WordLeftEnd,
+        ViMotion::StartOfLine => AlacViMotion::First,
+        ViMotion::FirstNonWhitespace => AlacViMotion::FirstOccupied,
+        ViMotion::EndOfLine => AlacViMotion::Last,
+        ViMotion::WindowTop => AlacViMotion::High,
+        ViMotion::WindowMiddle => AlacViMotion::Middle,
+        ViMotion::WindowBottom => AlacViMotion::Low,
+        ViMotion::Matching => AlacViMotion::Bracket,
+        ViMotion::StartOfDocument => {
+            let point = Point::new(term.topmost_line(), Column(0));
+            term.vi_goto_point(point);
+            return;
+        }
+        ViMotion::EndOfDocument => {
+            let point = Point::new(term.bottommost_line(), Column(0));
+            term.vi_goto_point(point);
+            return;
+        }
+        // Scrolling the display keeps the vi mode cursor within the viewport.
+        ViMotion::PageUp => return term.scroll_display(Scroll::PageUp),
+        ViMotion::PageDown => return term.scroll_display(Scroll::PageDown),
+        ViMotion::HalfPageUp => return term.scroll_display(Scroll::Delta(half_page)),
+        ViMotion::HalfPageDown => return term.scroll_display(Scroll::Delta(-half_page)),
+    };
+    term.vi_motion(motion);
+    term.scroll_to_point(term.vi_mode_cursor.point);
+}
+
+/// Starts a selection of the given kind at the vi mode cursor, switches the current selection
+/// to that kind, or clears the selection if it already is of that kind.
+pub(crate) fn toggle_selection<T>(term: &mut Term<T>, kind: ViSelection) {
+    let ty = match kind {
+        ViSelection::Character => SelectionType::Simple,
+        ViSelection::Line => SelectionType::Lines,
+        ViSelection::Block => SelectionType::Block,
+    };
+    match term.selection.as_ref().map(|selection| selection.ty) {
+        Some(current_ty) if current_ty == ty => term.selection = None,
+        Some(_) => {
+            if let Some(selection) = term.selection.as_mut() {
+                selection.ty = ty;
+            }
+        }
+        None => {
+            let mut selection = Selection::new(ty, term.vi_mode_cursor.point, Side::Left);
+            selection.include_all();
+            term.selection = Some(selection);
+        }
+    }
+}
+
+/// The text to yank: the selection, or the line under the vi mode cursor if nothing is selected.
+pub(crate) fn yank_text<T>(term: &Term<T>) -> String {
+    term.selection_to_string().unwrap_or_else(|| {
+        let line = term.vi_mode_cursor.point.line;
+        let start = Point::new(line, Column(0));
+        let end = Point::new(line, term.last_column());
+        term.bounds_to_string(start, end)
+            .trim_end_matches('\n')
+            .to_string()
+    })
+}
+
+/// Scrolls the display by the number of lines the shell printed since the history had
+/// `last_history_size` lines, so that the text under the vi mode cursor doesn't move away.
+/// Returns the new size of the history.
+pub(crate) fn hold_viewport<T>(term: &mut Term<T>, last_history_size: usize) -> usize {
+    let history_size = term.history_size();
+    let new_lines = history_size.saturating_sub(last_history_size);
+    // When scrolled up, alacritty already keeps the display in place.
+    if new_lines > 0 && term.grid().display_offset() == 0 {
+        term.scroll_display(Scroll::Delta(new_lines as i32));
+    }
+    history_size
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::VoidListener,
+        index::Line,
+        term::{Config, TermMode},
+        vte::ansi::Processor,
+    };
+    use gpui::{px, size};
+
+    use super::*;
+    use crate::TerminalSize;
+
+    /// A terminal of 20 columns and 4 lines, in vi mode, showing the given output.
+    fn term_with_output(output: &str) -> Term<VoidListener> {
+        let size = TerminalSize::new(px(10.), px(5.), size(px(100.), px(40.)));
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        write(&mut term, output);
+        term.toggle_vi_mode();
+        assert!(term.mode().contains(TermMode::VI));
+        term
+    }
+
+    fn write(term: &mut Term<VoidListener>, output: &str) {
+        let mut parser: Processor = Processor::new();
+        for byte in output.bytes() {
+            parser.advance(term, byte);
+        }
+    }
+
+    fn cursor(term: &Term<VoidListener>) -> (i32, usize) {
+        let point = term.vi_mode_cursor.point;
+        (point.line.0, point.column.0)
+    }
+
+    #[test]
+    fn test_apply_motion() {
+        let mut term = term_with_output("one two three\r\n  (four)\r\nfive\r\nsix\r\nseven");
+        term.vi_goto_point(Point::new(Line(-1), Column(0)));
+
+        apply_motion(&mut term, ViMotion::NextWordStart);
+        assert_eq!(cursor(&term), (-1, 4));
+        apply_motion(&mut term, ViMotion::NextWordStart);
+        assert_eq!(cursor(&term), (-1, 8));
+        apply_motion(&mut term, ViMotion::StartOfLine);
+        assert_eq!(cursor(&term), (-1, 0));
+
+        apply_motion(&mut term, ViMotion::Down);
+        apply_motion(&mut term, ViMotion::FirstNonWhitespace);
+        assert_eq!(cursor(&term), (0, 2));
+        apply_motion(&mut term, ViMotion::EndOfLine);
+        assert_eq!(cursor(&term), (0, 7));
+        apply_motion(&mut term, ViMotion::Matching);
+        assert_eq!(cursor(&term), (0, 2));
+
+        // Moving out of the viewport scrolls the display.
+        apply_motion(&mut term, ViMotion::StartOfDocument);
+        assert_eq!(cursor(&term), (-1, 0));
+        assert_eq!(term.grid().display_offset(), 1);
+        apply_motion(&mut term, ViMotion::EndOfDocument);
+        assert_eq!(cursor(&term), (3, 0));
+        assert_eq!(term.grid().display_offset(), 0);
+    }
+
+    #[test]
+    fn test_toggle_selection_and_yank() {
+        let mut term = term_with_output("one two\r\nthree");
+        term.vi_goto_point(Point::new(Line(0), Column(0)));
+
+        // Without a selection, the line under the cursor is yanked.
+        assert_eq!(yank_text(&term), "one two");
+
+        // Motions extend the selection.
+        toggle_selection(&mut term, ViSelection::Character);
+        apply_motion(&mut term, ViMotion::NextWordEnd);
+        assert_eq!(yank_text(&term), "one");
+
+        // Toggling another kind switches the selection to it.
+        toggle_selection(&mut term, ViSelection::Line);
+        assert_eq!(yank_text(&term), "one two");
+
+        // Toggling the same kind clears the selection.
+        toggle_selection(&mut term, ViSelection::Line);
+        assert!(term.selection.is_none());
+    }
+
+    #[test]
+    fn test_hold_viewport() {
+        let mut term = term_with_output("one\r\ntwo\r\nthree\r\nfour");
+        let history_size = hold_viewport(&mut term, term.history_size());
+        assert_eq!(history_size, 0);
+
+        // New output pushes lines into the history, and the display follows them.
+        write(&mut term, "\r\nfive\r\nsix");
+        let history_size = hold_viewport(&mut term, history_size);
+        assert_eq!(history_size, 2);
+        assert_eq!(term.grid().display_offset(), 2);
+
+        // Once scrolled up, alacritty keeps the display in place by itself.
+        write(&mut term, "\r\nseven");
+        let history_size = hold_viewport(&mut term, history_size);
+        assert_eq!(history_size, 3);
+        assert_eq!(term.grid().display_offset(), 3);
+    }
+}
@@ -1,8 +1,84 @@
 use std::path::PathBuf;
 
 use db::{define_connection, query, sqlez_macros::sql};
+use gpui::{Axis, Pixels};
+use serde::{Deserialize, Serialize};
 use workspace::{ItemId, WorkspaceDb, WorkspaceId};
 
+/// A command of a terminal's history, stored as JSON alongside its scrollback.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SerializedShellCommand {
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// The state of the terminal panel, stored as JSON in the key-value store.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SerializedTerminalPanel {
+    /// Terminals of the single pane written by versions without splits.
+    #[serde(default)]
+    pub items: Vec<u64>,
+    pub active_item_id: Option<u64>,
+    #[serde(default)]
+    pub layout: Option<SerializedTerminalLayout>,
+    pub width: Option<Pixels>,
+    pub height: Option<Pixels>,
+}
+
+impl SerializedTerminalPanel {
+    /// The layout of the panel, falling back to the single pane of versions without splits.
+    pub fn into_layout(self) -> SerializedTerminalLayout {
+        self.layout
+            .unwrap_or_else(|| SerializedTerminalLayout::Pane {
+                items: self.items,
+                active_item_id: self.active_item_id,
+                active: true,
+            })
+    }
+}
+
+/// A split layout of terminal panes, mirroring `workspace::PaneLayout`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum SerializedTerminalLayout {
+    Group {
+        axis: SerializedAxis,
+        flexes: Option<Vec<f32>>,
+        children: Vec<SerializedTerminalLayout>,
+    },
+    Pane {
+        items: Vec<u64>,
+        active_item_id: Option<u64>,
+        active: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<Axis> for SerializedAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<SerializedAxis> for Axis {
+    fn from(axis: SerializedAxis) -> Self {
+        match axis {
+            SerializedAxis::Horizontal => Self::Horizontal,
+            SerializedAxis::Vertical => Self::Vertical,
+        }
+    }
+}
+
 define_connection! {
     pub static ref TERMINAL_DB: TerminalDb<WorkspaceDb> =
         &[sql!(
@@ -34,6 +110,10 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN commands TEXT;
         )];
 }
 
@@ -56,8 +136,33 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                working_directory = ?3
+        }
+    }
+
+    query! {
+        pub async fn save_scrollback(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            scrollback: String,
+            commands: String
+        ) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, scrollback, commands)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                scrollback = ?3,
+                commands = ?4
+        }
+    }
+
+    query! {
+        pub fn get_scrollback(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<String>, Option<String>)>> {
+            SELECT scrollback, commands
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
         }
     }
 
@@ -21,7 +21,7 @@ use terminal::{
         },
     },
     terminal_settings::TerminalSettings,
-    HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
+    CommandStatus, HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::Tooltip;
@@ -44,6 +44,7 @@ pub struct LayoutState {
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
+    command_markers: Vec<(i32, Hsla)>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -680,9 +681,25 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_markers,
                     ..
                 } = &self.terminal.read(cx).last_content;
 
+                // Mark the prompts reported by the shell in the gutter, colored by exit status
+                let status_colors = theme.status();
+                let command_markers = command_markers
+                    .iter()
+                    .map(|marker| {
+                        let color = match marker.status {
+                            CommandStatus::Prompt | CommandStatus::Finished => status_colors.hint,
+                            CommandStatus::Running => status_colors.info,
+                            CommandStatus::Succeeded => status_colors.success,
+                            CommandStatus::Failed => status_colors.error,
+                        };
+                        (marker.line, color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -768,6 +785,7 @@ impl Element for TerminalElement {
                     hyperlink_tooltip,
                     gutter,
                     last_hovered_word,
+                    command_markers,
                 }
             })
     }
@@ -824,6 +842,16 @@ impl Element for TerminalElement {
                     rect.paint(origin, &layout, cx);
                 }
 
+                for (line, color) in &layout.command_markers {
+                    let marker_width = (layout.gutter / 3.).floor().max(px(1.));
+                    let marker_origin = point(
+                        bounds.origin.x + (layout.gutter - marker_width) / 2.,
+                        origin.y + *line as f32 * layout.dimensions.line_height,
+                    );
+                    let marker_size = point(marker_width, layout.dimensions.line_height).into();
+                    cx.paint_quad(fill(Bounds::new(marker_origin, marker_size), *color));
+                }
+
                 for (relative_highlighted_range, color) in layout.relative_highlighted_ranges.iter()
                 {
                     if let Some((start_y, highlighted_range_lines)) =
@@ -1,32 +1,34 @@
 use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 
-use crate::TerminalView;
+use crate::{
+    persistence::{SerializedTerminalLayout, SerializedTerminalPanel},
+    TerminalView,
+};
 use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
 use futures::future::join_all;
 use gpui::{
-    actions, Action, AppContext, AsyncWindowContext, Entity, EventEmitter, ExternalPaths,
-    FocusHandle, FocusableView, IntoElement, ParentElement, Pixels, Render, Styled, Subscription,
-    Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, Action, AppContext, AsyncWindowContext, Entity, EntityId, EventEmitter, ExternalPaths,
+    FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render, Styled,
+    Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
-use serde::{Deserialize, Serialize};
 use settings::Settings;
 use task::{static_source::RevealStrategy, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{Shell, TerminalDockPosition, TerminalSettings},
-    SpawnTask,
+    SpawnTask, Terminal,
 };
 use ui::{h_flex, ButtonCommon, Clickable, IconButton, IconSize, Selectable, Tooltip};
 use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::Item,
-    pane,
+    pane, register_detachable_panel,
     ui::IconName,
-    DraggedTab, NewTerminal, Pane, Workspace,
+    DraggedTab, NewTerminal, Pane, PaneGroup, PaneLayout, SplitDirection, Workspace,
 };
 
 use anyhow::Result;
@@ -46,37 +48,74 @@ pub fn init(cx: &mut AppContext) {
         },
     )
     .detach();
+    register_detachable_panel(cx, TerminalPanel::load);
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    center: PaneGroup,
+    active_pane: View<Pane>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    pane_subscriptions: HashMap<EntityId, Vec<Subscription>>,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
 }
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let pane =
+            Self::new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+        let mut this = Self {
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
+            fs: workspace.app_state().fs.clone(),
+            workspace: workspace.weak_handle(),
+            pending_serialization: Task::ready(None),
+            width: None,
+            height: None,
+            pending_terminals_to_add: 0,
+            deferred_tasks: HashMap::default(),
+            pane_subscriptions: HashMap::default(),
+            _subscriptions: Vec::new(),
+        };
+        this.subscribe_to_pane(&pane, cx);
+        this
+    }
+
+    fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        self.pane_subscriptions.insert(
+            pane.entity_id(),
+            vec![
+                cx.observe(pane, |_, _, cx| cx.notify()),
+                cx.subscribe(pane, Self::handle_pane_event),
+            ],
+        );
+    }
+
+    fn new_terminal_pane(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> View<Pane> {
         let terminal_panel = cx.view().downgrade();
         let pane = cx.new_view(|cx| {
             let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
+                workspace.clone(),
+                project,
                 Default::default(),
                 None,
                 NewTerminal.boxed_clone(),
                 cx,
             );
-            pane.set_can_split(false, cx);
             pane.set_can_navigate(false, cx);
             pane.display_nav_history_buttons(false);
+            let tab_bar_terminal_panel = terminal_panel.clone();
             pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
-                let terminal_panel = terminal_panel.clone();
+                let terminal_panel = tab_bar_terminal_panel.clone();
                 h_flex()
                     .gap_2()
                     .child(
@@ -89,6 +128,21 @@ impl TerminalPanel {
                             })
                             .tooltip(|cx| Tooltip::text("New Terminal", cx)),
                     )
+                    .child(
+                        IconButton::new("split", IconName::Split)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(|pane, _, cx| {
+                                // Split along the long side of the dock.
+                                let direction = match TerminalSettings::get_global(cx).dock {
+                                    TerminalDockPosition::Bottom => SplitDirection::Right,
+                                    TerminalDockPosition::Left | TerminalDockPosition::Right => {
+                                        SplitDirection::Down
+                                    }
+                                };
+                                pane.split(direction, cx);
+                            }))
+                            .tooltip(|cx| Tooltip::text("Split Terminal", cx)),
+                    )
                     .child({
                         let zoomed = pane.is_zoomed();
                         IconButton::new("toggle_zoom", IconName::Maximize)
@@ -105,7 +159,6 @@ impl TerminalPanel {
                     .into_any_element()
             });
 
-            let workspace = workspace.weak_handle();
             pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
                 if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
                     let item = if &tab.pane == cx.view() {
@@ -115,7 +168,32 @@ impl TerminalPanel {
                     };
                     if let Some(item) = item {
                         if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
+                            let Some(split_direction) = pane.drag_split_direction() else {
+                                return ControlFlow::Continue(());
+                            };
+                            let from_pane = tab.pane.clone();
+                            let to_pane = cx.view().clone();
+                            let item_id = item.item_id();
+                            terminal_panel
+                                .update(cx, |_, cx| {
+                                    cx.defer(move |terminal_panel, cx| {
+                                        let Some(new_pane) = terminal_panel.split_pane(
+                                            &to_pane,
+                                            split_direction,
+                                            cx,
+                                        ) else {
+                                            return;
+                                        };
+                                        terminal_panel
+                                            .workspace
+                                            .update(cx, |workspace, cx| {
+                                                workspace
+                                                    .move_item(from_pane, new_pane, item_id, 0, cx)
+                                            })
+                                            .log_err();
+                                    });
+                                })
+                                .log_err();
                         } else if let Some(project_path) = item.project_path(cx) {
                             if let Some(entry_path) = workspace
                                 .update(cx, |workspace, cx| {
@@ -155,22 +233,7 @@ impl TerminalPanel {
                 .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
             pane
         });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let this = Self {
-            pane,
-            fs: workspace.app_state().fs.clone(),
-            workspace: workspace.weak_handle(),
-            pending_serialization: Task::ready(None),
-            width: None,
-            height: None,
-            pending_terminals_to_add: 0,
-            deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
-        };
-        this
+        pane
     }
 
     pub async fn load(
@@ -188,34 +251,19 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, panes) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some(serialized_panel) = serialized_panel.as_ref() {
+            let panes = if let Some(serialized_panel) = serialized_panel {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    workspace.database_id(),
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                    panel.restore_layout(serialized_panel.into_layout(), workspace, cx)
                 })
             } else {
                 Vec::new()
             };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            (panel, panes)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -228,52 +276,227 @@ impl TerminalPanel {
                                 terminal_panel.spawn_task(spawn_in_terminal, cx);
                             };
                         },
-                    ))
+                    ));
+                    let project = workspace.read(cx).project().clone();
+                    panel._subscriptions.push(cx.subscribe(
+                        &project,
+                        |terminal_panel, _, e, cx| match e {
+                            project::Event::TerminalShared(terminal) => {
+                                terminal_panel.add_remote_terminal(terminal.clone(), cx)
+                            }
+                            project::Event::SpawnTask(spawn_in_terminal) => {
+                                terminal_panel.spawn_task(spawn_in_terminal, cx)
+                            }
+                            _ => {}
+                        },
+                    ));
+                    let remote_terminals = project
+                        .read(cx)
+                        .remote_terminals()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    for terminal in remote_terminals {
+                        panel.add_remote_terminal(terminal, cx);
+                    }
                 })
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        for (pane, items, active_item_id) in panes {
+            let pane = pane.downgrade();
+            let items = futures::future::join_all(items).await;
+            pane.update(&mut cx, |pane, cx| {
+                let mut active_ix = None;
+                for item in items {
+                    if let Some(item) = item.log_err() {
+                        let item_id = item.entity_id().as_u64();
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        if Some(item_id) == active_item_id {
+                            active_ix = Some(pane.items_len() - 1);
+                        }
                     }
                 }
+
+                if let Some(active_ix) = active_ix {
+                    pane.activate_item(active_ix, false, false, cx)
+                }
+            })?;
+        }
+        panel.update(&mut cx, |panel, cx| panel.remove_empty_panes(cx))?;
+
+        Ok(panel)
+    }
+
+    /// Replaces the panes of the panel with the serialized ones, returning the terminals to
+    /// restore into each of them.
+    fn restore_layout(
+        &mut self,
+        layout: SerializedTerminalLayout,
+        workspace: &Workspace,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<(
+        View<Pane>,
+        Vec<Task<Result<View<TerminalView>>>>,
+        Option<u64>,
+    )> {
+        let mut panes = Vec::new();
+        let mut active_pane = None;
+        let layout = self.deserialize_layout(layout, workspace, &mut panes, &mut active_pane, cx);
+        let Some(center) = PaneGroup::from_layout(layout) else {
+            return panes;
+        };
+        for pane in self.center.panes() {
+            self.pane_subscriptions.remove(&pane.entity_id());
+        }
+        self.center = center;
+        self.active_pane = active_pane.unwrap_or_else(|| self.center.first_pane());
+        panes
+    }
+
+    fn deserialize_layout(
+        &mut self,
+        layout: SerializedTerminalLayout,
+        workspace: &Workspace,
+        panes: &mut Vec<(
+            View<Pane>,
+            Vec<Task<Result<View<TerminalView>>>>,
+            Option<u64>,
+        )>,
+        active_pane: &mut Option<View<Pane>>,
+        cx: &mut ViewContext<Self>,
+    ) -> PaneLayout {
+        match layout {
+            SerializedTerminalLayout::Group {
+                axis,
+                flexes,
+                children,
+            } => PaneLayout::Axis {
+                axis: axis.into(),
+                members: children
+                    .into_iter()
+                    .map(|child| self.deserialize_layout(child, workspace, panes, active_pane, cx))
+                    .collect(),
+                flexes,
+            },
+            SerializedTerminalLayout::Pane {
+                items,
+                active_item_id,
+                active,
+            } => {
+                let pane = Self::new_terminal_pane(
+                    workspace.weak_handle(),
+                    workspace.project().clone(),
+                    cx,
+                );
+                self.subscribe_to_pane(&pane, cx);
+                if active {
+                    *active_pane = Some(pane.clone());
+                }
+                let items = pane.update(cx, |_, cx| {
+                    items
+                        .iter()
+                        .map(|item_id| {
+                            TerminalView::deserialize(
+                                workspace.project().clone(),
+                                workspace.weak_handle(),
+                                workspace.database_id(),
+                                *item_id,
+                                cx,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                });
+                panes.push((pane.clone(), items, active_item_id));
+                PaneLayout::Pane(pane)
             }
+        }
+    }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+    /// Drops the panes that ended up without terminals after restoring, e.g. because
+    /// they only held task terminals, which are not serialized.
+    fn remove_empty_panes(&mut self, cx: &mut ViewContext<Self>) {
+        let empty_panes = self
+            .center
+            .panes()
+            .into_iter()
+            .filter(|pane| pane.read(cx).items_len() == 0)
+            .cloned()
+            .collect::<Vec<_>>();
+        for pane in empty_panes {
+            if self.center.panes().len() > 1 && self.center.remove(&pane).log_err() == Some(true) {
+                self.pane_subscriptions.remove(&pane.entity_id());
             }
-        })?;
+        }
+        if !self.center.panes().contains(&&self.active_pane) {
+            self.active_pane = self.center.first_pane();
+        }
+        cx.notify();
+    }
 
-        Ok(panel)
+    fn split_pane(
+        &mut self,
+        pane: &View<Pane>,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Pane>> {
+        let project = self.workspace.upgrade()?.read(cx).project().clone();
+        let new_pane = Self::new_terminal_pane(self.workspace.clone(), project, cx);
+        self.center.split(pane, &new_pane, direction).log_err()?;
+        self.subscribe_to_pane(&new_pane, cx);
+        self.active_pane = new_pane.clone();
+        cx.focus_view(&new_pane);
+        cx.notify();
+        Some(new_pane)
     }
 
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemoveItem { .. } => self.serialize(cx),
-            pane::Event::Remove => cx.emit(PanelEvent::Close),
-            pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
+            pane::Event::Remove => match self.center.remove(&pane).log_err() {
+                Some(true) => {
+                    self.pane_subscriptions.remove(&pane.entity_id());
+                    if self.active_pane == pane {
+                        self.active_pane = self.center.first_pane();
+                        cx.focus_view(&self.active_pane);
+                    }
+                    self.serialize(cx);
+                    cx.notify();
+                }
+                Some(false) => cx.emit(PanelEvent::Close),
+                None => {}
+            },
+            pane::Event::Split(direction) => {
+                let working_directory = pane
+                    .read(cx)
+                    .active_item()
+                    .and_then(|item| item.downcast::<TerminalView>())
+                    .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd());
+                if self.split_pane(&pane, *direction, cx).is_some() {
+                    self.add_terminal(working_directory, None, cx);
+                }
+            }
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    self.serialize(cx);
+                    cx.notify();
+                }
+            }
+            pane::Event::ZoomIn => {
+                // Any pane can be zoomed in from its tab bar, not only the active one.
+                self.active_pane = pane;
+                cx.emit(PanelEvent::ZoomIn);
+            }
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -296,6 +519,30 @@ impl TerminalPanel {
         })
     }
 
+    fn zoomed_pane(&self, cx: &AppContext) -> Option<View<Pane>> {
+        self.center
+            .panes()
+            .into_iter()
+            .find(|pane| pane.read(cx).is_zoomed())
+            .cloned()
+    }
+
+    /// The terminal that's active in the panel, if any.
+    pub fn active_terminal(&self, cx: &AppContext) -> Option<View<TerminalView>> {
+        self.active_pane
+            .read(cx)
+            .active_item()?
+            .downcast::<TerminalView>()
+    }
+
+    fn items_len(&self, cx: &AppContext) -> usize {
+        self.center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum()
+    }
+
     pub fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
         let mut spawn_task = SpawnTask {
             id: spawn_in_terminal.id.clone(),
@@ -339,7 +586,7 @@ impl TerminalPanel {
             self.spawn_in_new_terminal(spawn_task, working_directory, cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let existing_terminal = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -348,14 +595,9 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(
-                working_directory,
-                spawn_task,
-                existing_item_index,
-                existing_terminal,
-                cx,
-            );
+            self.replace_terminal(working_directory, spawn_task, existing_terminal, cx);
         } else {
+            let terminal_to_reveal = existing_terminal.clone();
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
                 cx.spawn(|terminal_panel, mut cx| async move {
@@ -372,7 +614,6 @@ impl TerminalPanel {
                                 terminal_panel.replace_terminal(
                                     working_directory,
                                     spawn_task,
-                                    existing_item_index,
                                     existing_terminal,
                                     cx,
                                 );
@@ -384,7 +625,7 @@ impl TerminalPanel {
 
             match reveal {
                 RevealStrategy::Always => {
-                    self.activate_terminal_view(existing_item_index, cx);
+                    self.activate_terminal_view(&terminal_to_reveal, cx);
                     let task_workspace = self.workspace.clone();
                     cx.spawn(|_, mut cx| async move {
                         task_workspace
@@ -433,31 +674,38 @@ impl TerminalPanel {
         this.update(cx, |this, cx| this.add_terminal(None, None, cx))
     }
 
-    fn terminals_for_task(
-        &self,
-        id: &TaskId,
-        cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.id == id {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    fn terminals_for_task(&self, id: &TaskId, cx: &mut AppContext) -> Vec<View<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| pane.read(cx).items())
+            .filter_map(|item| item.act_as::<TerminalView>(cx))
+            .filter(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .map_or(false, |task_state| &task_state.id == id)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
+    fn activate_terminal_view(
+        &mut self,
+        terminal_view: &View<TerminalView>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((pane, item_index)) = self.center.panes().into_iter().find_map(|pane| {
+            let item_index = pane.read(cx).index_for_item(terminal_view)?;
+            Some((pane.clone(), item_index))
+        }) else {
+            return;
+        };
+        pane.update(cx, |pane, cx| {
             pane.activate_item(item_index, true, true, cx)
-        })
+        });
+        self.active_pane = pane;
     }
 
     fn add_terminal(
@@ -469,7 +717,7 @@ impl TerminalPanel {
         let workspace = self.workspace.clone();
         self.pending_terminals_to_add += 1;
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             workspace.update(&mut cx, |workspace, cx| {
                 let working_directory = if let Some(working_directory) = working_directory {
                     Some(working_directory)
@@ -482,7 +730,7 @@ impl TerminalPanel {
                 let window = cx.window_handle();
                 if let Some(terminal) = workspace.project().update(cx, |project, cx| {
                     project
-                        .create_terminal(working_directory, spawn_task, window, cx)
+                        .create_terminal(working_directory, spawn_task, None, window, cx)
                         .log_err()
                 }) {
                     let terminal = Box::new(cx.new_view(|cx| {
@@ -508,29 +756,28 @@ impl TerminalPanel {
         .detach_and_log_err(cx);
     }
 
-    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
+    /// Opens a terminal shared by the host of a remote project.
+    fn add_remote_terminal(&mut self, terminal: Model<Terminal>, cx: &mut ViewContext<Self>) {
+        let pane = self.active_pane.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let terminal_view = Box::new(cx.new_view(|cx| {
+                    TerminalView::new(
+                        terminal,
+                        workspace.weak_handle(),
+                        workspace.database_id(),
+                        cx,
+                    )
+                }));
+                pane.update(cx, |pane, cx| {
+                    pane.add_item(terminal_view, true, false, None, cx);
+                });
             })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+            .log_err();
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let layout = serialize_layout(&self.center.layout(), &self.active_pane, cx);
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -539,8 +786,9 @@ impl TerminalPanel {
                     .write_kvp(
                         TERMINAL_PANEL_KEY.into(),
                         serde_json::to_string(&SerializedTerminalPanel {
-                            items,
-                            active_item_id,
+                            items: Vec::new(),
+                            active_item_id: None,
+                            layout: Some(layout),
                             height,
                             width,
                         })?,
@@ -553,10 +801,9 @@ impl TerminalPanel {
     }
 
     fn replace_terminal(
-        &self,
+        &mut self,
         working_directory: Option<PathBuf>,
         spawn_task: SpawnTask,
-        terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -568,7 +815,7 @@ impl TerminalPanel {
         let window = cx.window_handle();
         let new_terminal = project.update(cx, |project, cx| {
             project
-                .create_terminal(working_directory, Some(spawn_task), window, cx)
+                .create_terminal(working_directory, Some(spawn_task), None, window, cx)
                 .log_err()
         })?;
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
@@ -577,7 +824,7 @@ impl TerminalPanel {
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
+                self.activate_terminal_view(&terminal_to_replace, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -593,11 +840,60 @@ impl TerminalPanel {
     }
 }
 
+fn serialize_layout(
+    layout: &PaneLayout,
+    active_pane: &View<Pane>,
+    cx: &AppContext,
+) -> SerializedTerminalLayout {
+    match layout {
+        PaneLayout::Axis {
+            axis,
+            members,
+            flexes,
+        } => SerializedTerminalLayout::Group {
+            axis: (*axis).into(),
+            flexes: flexes.clone(),
+            children: members
+                .iter()
+                .map(|member| serialize_layout(member, active_pane, cx))
+                .collect(),
+        },
+        PaneLayout::Pane(pane) => {
+            let mut items_to_serialize = HashSet::default();
+            let items = pane
+                .read(cx)
+                .items()
+                .filter_map(|item| {
+                    let terminal_view = item.act_as::<TerminalView>(cx)?;
+                    let terminal = terminal_view.read(cx).terminal().read(cx);
+                    if terminal.task().is_some() || terminal.is_remote() {
+                        None
+                    } else {
+                        let id = item.item_id().as_u64();
+                        items_to_serialize.insert(id);
+                        Some(id)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let active_item_id = pane
+                .read(cx)
+                .active_item()
+                .map(|item| item.item_id().as_u64())
+                .filter(|active_id| items_to_serialize.contains(active_id));
+            SerializedTerminalLayout::Pane {
+                items,
+                active_item_id,
+                active: pane == active_pane,
+            }
+        }
+    }
+}
+
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<View<TerminalView>>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|terminal| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -629,10 +925,31 @@ impl EventEmitter<PanelEvent> for TerminalPanel {}
 
 impl Render for TerminalPanel {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if let Some(zoomed_pane) = self.zoomed_pane(cx) {
+            Some(zoomed_pane.into_any_element())
+        } else {
+            let panel = cx.view().downgrade();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    self.center
+                        .render_standalone(
+                            workspace.project(),
+                            &self.active_pane,
+                            None,
+                            workspace.app_state(),
+                            move |cx| {
+                                panel.update(cx, |panel, cx| panel.serialize(cx)).log_err();
+                            },
+                            cx,
+                        )
+                        .into_any_element()
+                })
+                .log_err()
+        };
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -641,13 +958,13 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        registrar.into_div().size_full().children(content)
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -695,21 +1012,24 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.zoomed_pane(cx).is_some()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            let zoomed = zoomed && pane == &self.active_pane;
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
-        if active && self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0 {
+        if active && self.items_len(cx) == 0 && self.pending_terminals_to_add == 0 {
             self.add_terminal(None, None, cx)
         }
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self.items_len(cx);
         if count == 0 {
             None
         } else {
@@ -734,10 +1054,214 @@ impl Panel for TerminalPanel {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct SerializedTerminalPanel {
-    items: Vec<u64>,
-    active_item_id: Option<u64>,
-    width: Option<Pixels>,
-    height: Option<Pixels>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::init_test;
+    use gpui::{
+        point, px, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+        TestAppContext, VisualTestContext,
+    };
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_split_panes_serialize_layout(cx: &mut TestAppContext) {
+        let (_, workspace) = init_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(cx.windows()[0], cx);
+        let panel = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| TerminalPanel::new(workspace, cx))
+        });
+
+        let layout = panel.update(cx, |panel, cx| {
+            let first_pane = panel.active_pane.clone();
+            let second_pane = panel
+                .split_pane(&first_pane, SplitDirection::Right, cx)
+                .unwrap();
+            let third_pane = panel
+                .split_pane(&second_pane, SplitDirection::Down, cx)
+                .unwrap();
+            assert_eq!(panel.center.panes().len(), 3);
+            assert_eq!(panel.active_pane, third_pane);
+            serialize_layout(&panel.center.layout(), &panel.active_pane, cx)
+        });
+        let empty_pane = |active: bool| json!({ "kind": "pane", "items": [], "active_item_id": null, "active": active });
+        assert_eq!(
+            serde_json::to_value(&layout).unwrap(),
+            json!({
+                "kind": "group",
+                "axis": "horizontal",
+                "flexes": [1.0, 1.0],
+                "children": [
+                    empty_pane(false),
+                    {
+                        "kind": "group",
+                        "axis": "vertical",
+                        "flexes": [1.0, 1.0],
+                        "children": [empty_pane(false), empty_pane(true)],
+                    },
+                ],
+            })
+        );
+
+        // Restoring the layout into another panel brings back the same splits.
+        let restored_panel = workspace.update(cx, |workspace, cx| {
+            let restored_panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            restored_panel.update(cx, |panel, cx| {
+                let panes = panel.restore_layout(layout, workspace, cx);
+                assert_eq!(panes.len(), 3);
+            });
+            restored_panel
+        });
+        restored_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 3);
+            assert_eq!(panel.pane_subscriptions.len(), 3);
+            assert_eq!(
+                Some(&panel.active_pane),
+                panel.center.panes().last().copied()
+            );
+            let layout = serialize_layout(&panel.center.layout(), &panel.active_pane, cx);
+            assert_eq!(
+                serde_json::to_value(&layout).unwrap()["children"][1]["children"][1],
+                empty_pane(true)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_resizing_split_serializes_layout(cx: &mut TestAppContext) {
+        let (_, workspace) = init_test(cx).await;
+        cx.update(terminal::init);
+        let cx = &mut VisualTestContext::from_window(cx.windows()[0], cx);
+        let panel = workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            workspace.add_panel(panel.clone(), cx);
+            panel
+        });
+        let first_pane = panel.update(cx, |panel, cx| {
+            let first_pane = panel.active_pane.clone();
+            panel
+                .split_pane(&first_pane, SplitDirection::Right, cx)
+                .unwrap();
+            first_pane
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.open_panel::<TerminalPanel>(cx)
+        });
+        cx.update(|cx| cx.refresh());
+        cx.run_until_parked();
+
+        // Drag the divider between the panes to the right.
+        let first_pane_bounds = panel
+            .update(cx, |panel, _| {
+                panel.center.bounding_box_for_pane(&first_pane)
+            })
+            .unwrap();
+        let divider = point(first_pane_bounds.right(), first_pane_bounds.center().y);
+        cx.simulate_event(MouseDownEvent {
+            button: MouseButton::Left,
+            position: divider,
+            modifiers: Modifiers::default(),
+            click_count: 1,
+            first_mouse: false,
+        });
+        cx.simulate_event(MouseMoveEvent {
+            position: divider + point(px(40.), px(0.)),
+            pressed_button: Some(MouseButton::Left),
+            modifiers: Modifiers::default(),
+        });
+        cx.simulate_event(MouseUpEvent {
+            button: MouseButton::Left,
+            position: divider + point(px(40.), px(0.)),
+            modifiers: Modifiers::default(),
+            click_count: 1,
+        });
+        cx.run_until_parked();
+
+        // The panel serialized its layout with the new flexes.
+        let flexes = panel.update(cx, |panel, _| match panel.center.layout() {
+            PaneLayout::Axis { flexes, .. } => flexes.unwrap(),
+            PaneLayout::Pane(_) => panic!("expected a split"),
+        });
+        assert!(flexes[0] > 1.);
+        let serialized_panel = KEY_VALUE_STORE
+            .read_kvp(TERMINAL_PANEL_KEY)
+            .unwrap()
+            .unwrap();
+        let serialized_panel =
+            serde_json::from_str::<serde_json::Value>(&serialized_panel).unwrap();
+        assert_eq!(serialized_panel["layout"]["flexes"], json!(flexes));
+    }
+
+    #[gpui::test]
+    async fn test_restore_layout_without_splits(cx: &mut TestAppContext) {
+        let (_, workspace) = init_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(cx.windows()[0], cx);
+
+        // Versions without splits only stored the terminals of a single pane.
+        let serialized_panel = serde_json::from_value::<SerializedTerminalPanel>(json!({
+            "items": [],
+            "active_item_id": null,
+            "width": null,
+            "height": 320.0,
+        }))
+        .unwrap();
+        assert!(serialized_panel.layout.is_none());
+        assert_eq!(serialized_panel.height, Some(gpui::px(320.)));
+
+        let panel = workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            panel.update(cx, |panel, cx| {
+                panel.restore_layout(serialized_panel.into_layout(), workspace, cx);
+            });
+            panel
+        });
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 1);
+            assert_eq!(panel.pane_subscriptions.len(), 1);
+            assert_eq!(panel.center.first_pane(), panel.active_pane);
+            let layout = serialize_layout(&panel.center.layout(), &panel.active_pane, cx);
+            assert_eq!(
+                serde_json::to_value(&layout).unwrap(),
+                json!({ "kind": "pane", "items": [], "active_item_id": null, "active": true })
+            );
+        });
+
+        let serialized_panel = serde_json::from_value::<SerializedTerminalPanel>(json!({
+            "items": [1, 2],
+            "active_item_id": 2,
+            "width": null,
+            "height": null,
+        }))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(serialized_panel.into_layout()).unwrap(),
+            json!({ "kind": "pane", "items": [1, 2], "active_item_id": 2, "active": true })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_zoom_any_pane(cx: &mut TestAppContext) {
+        let (_, workspace) = init_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(cx.windows()[0], cx);
+        let panel = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| TerminalPanel::new(workspace, cx))
+        });
+
+        panel.update(cx, |panel, cx| {
+            let first_pane = panel.active_pane.clone();
+            let second_pane = panel
+                .split_pane(&first_pane, SplitDirection::Right, cx)
+                .unwrap();
+            second_pane.update(cx, |pane, cx| pane.set_zoomed(true, cx));
+            assert!(panel.is_zoomed(cx));
+
+            panel.active_pane = first_pane.clone();
+            panel.set_zoomed(true, cx);
+            assert!(first_pane.read(cx).is_zoomed());
+            assert!(!second_pane.read(cx).is_zoomed());
+
+            panel.set_zoomed(false, cx);
+            assert!(!panel.is_zoomed(cx));
+        });
+    }
 }
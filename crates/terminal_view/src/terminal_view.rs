@@ -1,6 +1,7 @@
 mod persistence;
 pub mod terminal_element;
 pub mod terminal_panel;
+mod vi_mode;
 
 use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
@@ -11,8 +12,8 @@ use gpui::{
     Render, Styled, Subscription, Task, View, VisualContext, WeakView,
 };
 use language::Bias;
-use persistence::TERMINAL_DB;
-use project::{search::SearchQuery, Fs, LocalWorktree, Metadata, Project};
+use persistence::{SerializedShellCommand, TERMINAL_DB};
+use project::{search::SearchQuery, Fs, LocalWorktree, Metadata, Project, ProjectPath};
 use settings::SettingsStore;
 use terminal::{
     alacritty_terminal::{
@@ -20,13 +21,17 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, Terminal,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, PopulateLocationList,
+    RestoredScrollback, ScrollToNextPrompt, ScrollToPreviousPrompt, ShellCommand,
+    ShowCharacterPalette, Terminal, ToggleViMode, ViMotion, ViSelection, ViYank,
 };
 use terminal_element::TerminalElement;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label};
 use util::{paths::PathLikeWithPosition, ResultExt};
+use vim::VimModeSetting;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent},
+    location_list::{LocationEntry, LocationList},
     notifications::NotifyResultExt,
     register_deserializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
@@ -41,6 +46,7 @@ use settings::Settings;
 use smol::Timer;
 
 use std::{
+    collections::BTreeSet,
     ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
@@ -52,6 +58,10 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 ];
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+const SCROLLBACK_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_PERSISTED_COMMANDS: usize = 100;
+/// Number of bytes of the terminal's output searched for locations.
+const LOCATION_LIST_MAX_BYTES: usize = 1024 * 1024;
 
 ///Event to transmit the scroll from the element to the view
 #[derive(Clone, Debug, PartialEq)]
@@ -92,6 +102,7 @@ pub struct TerminalView {
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
     show_title: bool,
+    pending_scrollback_save: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -121,7 +132,7 @@ impl TerminalView {
         let terminal = workspace
             .project()
             .update(cx, |project, cx| {
-                project.create_terminal(working_directory, None, window, cx)
+                project.create_terminal(working_directory, None, None, window, cx)
             })
             .notify_err(workspace, cx);
 
@@ -168,6 +179,7 @@ impl TerminalView {
             can_navigate_to_selected_word: false,
             workspace_id,
             show_title: TerminalSettings::get_global(cx).toolbar.title,
+            pending_scrollback_save: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
@@ -195,9 +207,23 @@ impl TerminalView {
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
+        let sharing_entries = self.sharing_menu_entries(cx);
+        let can_toggle_vi_mode = self.can_toggle_vi_mode(cx);
         let context_menu = ContextMenu::build(cx, |menu, _| {
-            menu.action("Clear", Box::new(Clear))
-                .action("Close", Box::new(CloseActiveItem { save_intent: None }))
+            let mut menu = menu
+                .action("Clear", Box::new(Clear))
+                .action("Copy Last Command Output", Box::new(CopyLastCommandOutput));
+            if can_toggle_vi_mode {
+                menu = menu.action("Toggle Vi Mode", Box::new(ToggleViMode));
+            }
+            if !sharing_entries.is_empty() {
+                menu = menu.separator();
+                for (label, handler) in sharing_entries {
+                    menu = menu.entry(label, None, handler);
+                }
+                menu = menu.separator();
+            }
+            menu.action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
         cx.focus_view(&context_menu);
@@ -215,6 +241,74 @@ impl TerminalView {
         self.context_menu = Some((context_menu, position, subscription));
     }
 
+    /// The context menu entries to share the terminal with the collaborators of the project,
+    /// and to allow them to type into it, when hosting a shared project.
+    fn sharing_menu_entries(
+        &self,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<(SharedString, Box<dyn Fn(&mut WindowContext)>)> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Vec::new();
+        };
+        let workspace = workspace.read(cx);
+        let project = workspace.project().clone();
+        let user_store = workspace.app_state().user_store.clone();
+        if !project.read(cx).is_shared() || self.terminal.read(cx).task().is_some() {
+            return Vec::new();
+        }
+
+        let terminal = self.terminal.clone();
+        let Some(writers) = project.read(cx).terminal_writers(&terminal).cloned() else {
+            return vec![(
+                "Share Terminal".into(),
+                Box::new(move |cx| {
+                    project
+                        .update(cx, |project, cx| project.share_terminal(&terminal, cx))
+                        .log_err();
+                }),
+            )];
+        };
+
+        let mut entries: Vec<(SharedString, Box<dyn Fn(&mut WindowContext)>)> = vec![(
+            "Stop Sharing Terminal".into(),
+            Box::new({
+                let project = project.clone();
+                let terminal_id = terminal.entity_id().as_u64();
+                move |cx| {
+                    project.update(cx, |project, cx| project.unshare_terminal(terminal_id, cx))
+                }
+            }),
+        )];
+        let user_ids = project
+            .read(cx)
+            .collaborators()
+            .values()
+            .map(|collaborator| collaborator.user_id)
+            .collect::<BTreeSet<_>>();
+        for user_id in user_ids {
+            let Some(user) = user_store.read(cx).get_cached_user(user_id) else {
+                continue;
+            };
+            let allowed = writers.contains(&user_id);
+            let label = if allowed {
+                format!("Stop {} From Typing", user.github_login)
+            } else {
+                format!("Allow {} to Type", user.github_login)
+            };
+            let project = project.clone();
+            let terminal = terminal.clone();
+            entries.push((
+                label.into(),
+                Box::new(move |cx| {
+                    project.update(cx, |project, cx| {
+                        project.set_terminal_writer(&terminal, user_id, !allowed, cx)
+                    })
+                }),
+            ));
+        }
+        entries
+    }
+
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         let settings = TerminalSettings::get_global(cx);
         self.show_title = settings.toolbar.title;
@@ -245,6 +339,115 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.copy_last_command_output());
+        cx.notify();
+    }
+
+    /// Makes the `path:row:column` locations printed in the terminal, such as the errors of a
+    /// build task, the active location list of the workspace.
+    fn populate_location_list(&mut self, _: &PopulateLocationList, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let terminal = self.terminal.read(cx);
+        let title = terminal.title(false);
+        let cwd = terminal.get_cwd();
+        let text = terminal.scrollback_text(LOCATION_LIST_MAX_BYTES);
+
+        let mut locations = Vec::new();
+        for (path, point) in printed_locations(&text) {
+            let abs_path = match &cwd {
+                Some(cwd) => cwd.join(path),
+                None => path,
+            };
+            if let Some((worktree, path)) = project.read(cx).find_local_worktree(&abs_path, cx) {
+                let project_path = ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: path.into(),
+                };
+                locations.push((project_path, point));
+            }
+        }
+        let open_buffers = project.update(cx, |project, cx| {
+            locations
+                .iter()
+                .map(|(project_path, _)| project.open_buffer(project_path.clone(), cx))
+                .collect::<Vec<_>>()
+        });
+
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let buffers = futures::future::join_all(open_buffers).await;
+            workspace.update(&mut cx, |workspace, cx| {
+                let entries = buffers
+                    .into_iter()
+                    .zip(locations)
+                    .filter_map(|(buffer, (_, point))| {
+                        let buffer = buffer.log_err()?;
+                        let anchor = {
+                            let buffer = buffer.read(cx);
+                            buffer.anchor_before(buffer.clip_point(point, Bias::Left))
+                        };
+                        Some(LocationEntry::new(buffer, anchor..anchor, cx))
+                    })
+                    .collect();
+                let list = LocationList::new(title, entries);
+                workspace
+                    .location_lists()
+                    .update(cx, |lists, cx| lists.push(list, cx));
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Vi mode is bound by the vim keymap, so it is only available with vim mode on.
+    fn can_toggle_vi_mode(&self, cx: &AppContext) -> bool {
+        VimModeSetting::get_global(cx).0 || self.terminal.read(cx).vi_mode_enabled()
+    }
+
+    fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
+        if !self.can_toggle_vi_mode(cx) {
+            return;
+        }
+        self.terminal.update(cx, |term, _| term.toggle_vi_mode());
+        cx.notify();
+    }
+
+    fn vi_motion(&mut self, motion: ViMotion, cx: &mut ViewContext<Self>) {
+        self.terminal.update(cx, |term, _| term.vi_motion(motion));
+        cx.notify();
+    }
+
+    fn toggle_vi_selection(&mut self, kind: ViSelection, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.toggle_vi_selection(kind));
+        cx.notify();
+    }
+
+    fn vi_yank(&mut self, _: &ViYank, cx: &mut ViewContext<Self>) {
+        self.terminal.update(cx, |term, _| term.vi_yank());
+        cx.notify();
+    }
+
     fn clear(&mut self, _: &Clear, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.clear());
         cx.notify();
@@ -332,6 +535,9 @@ impl TerminalView {
     }
 
     fn send_text(&mut self, text: &SendText, cx: &mut ViewContext<Self>) {
+        if self.terminal.read(cx).vi_mode_enabled() {
+            return;
+        }
         self.clear_bel(cx);
         self.terminal.update(cx, |term, _| {
             term.input(text.0.to_string());
@@ -339,6 +545,9 @@ impl TerminalView {
     }
 
     fn send_keystroke(&mut self, text: &SendKeystroke, cx: &mut ViewContext<Self>) {
+        if self.terminal.read(cx).vi_mode_enabled() {
+            return;
+        }
         if let Some(keystroke) = Keystroke::parse(&text.0).log_err() {
             self.clear_bel(cx);
             self.terminal.update(cx, |term, cx| {
@@ -352,6 +561,10 @@ impl TerminalView {
         dispatch_context.add("Terminal");
 
         let mode = self.terminal.read(cx).last_content.mode;
+        // Vi mode reuses the motions of vim mode, which are bound in this context.
+        if self.terminal.read(cx).vi_mode_enabled() {
+            dispatch_context.add("VimControl");
+        }
         dispatch_context.set(
             "screen",
             if mode.contains(TermMode::ALT_SCREEN) {
@@ -422,6 +635,58 @@ impl TerminalView {
         dispatch_context
     }
 
+    /// Saves the scrollback and command history a few seconds after new output, at most once
+    /// per interval, so that they survive an unexpected restart.
+    fn schedule_scrollback_save(&mut self, cx: &mut ViewContext<Self>) {
+        if self.pending_scrollback_save.is_some()
+            || self.terminal.read(cx).task().is_some()
+            || self.terminal.read(cx).is_remote()
+            || !TerminalSettings::get_global(cx).persist_scrollback.enabled
+        {
+            return;
+        }
+
+        self.pending_scrollback_save = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SCROLLBACK_SAVE_INTERVAL)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_scrollback_save = None;
+                let max_bytes = TerminalSettings::get_global(cx)
+                    .persist_scrollback
+                    .max_bytes;
+                let terminal = this.terminal.read(cx);
+                let scrollback = terminal.scrollback_text(max_bytes);
+                let mut commands = terminal
+                    .command_history()
+                    .commands()
+                    .rev()
+                    .take(MAX_PERSISTED_COMMANDS)
+                    .map(|command| SerializedShellCommand {
+                        command: command.command.clone(),
+                        exit_code: command.exit_code,
+                        cwd: command.cwd.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                commands.reverse();
+                let Some(commands) = serde_json::to_string(&commands).log_err() else {
+                    return;
+                };
+                let item_id = cx.entity_id().as_u64();
+                let workspace_id = this.workspace_id;
+                cx.background_executor()
+                    .spawn(async move {
+                        TERMINAL_DB
+                            .save_scrollback(item_id, workspace_id, scrollback, commands)
+                            .await
+                            .log_err();
+                    })
+                    .detach();
+            })
+            .ok();
+        }));
+    }
+
     fn set_terminal(&mut self, terminal: Model<Terminal>, cx: &mut ViewContext<'_, TerminalView>) {
         self._terminal_subscriptions =
             subscribe_for_terminal_events(&terminal, self.workspace.clone(), cx);
@@ -442,6 +707,7 @@ fn subscribe_for_terminal_events(
                 cx.emit(Event::Wakeup);
                 cx.emit(ItemEvent::UpdateTab);
                 cx.emit(SearchEvent::MatchesInvalidated);
+                this.schedule_scrollback_save(cx);
             }
 
             Event::Bell => {
@@ -454,7 +720,7 @@ fn subscribe_for_terminal_events(
             Event::TitleChanged => {
                 cx.emit(ItemEvent::UpdateTab);
                 let terminal = this.terminal().read(cx);
-                if terminal.task().is_none() {
+                if terminal.task().is_none() && !terminal.is_remote() {
                     if let Some(cwd) = terminal.get_cwd() {
                         let item_id = cx.entity_id();
                         let workspace_id = this.workspace_id;
@@ -703,6 +969,11 @@ impl TerminalView {
         self.clear_bel(cx);
         self.pause_cursor_blinking(cx);
 
+        // Keys without a vi mode binding must not reach the shell.
+        if self.terminal.read(cx).vi_mode_enabled() {
+            return;
+        }
+
         self.terminal.update(cx, |term, cx| {
             term.try_keystroke(
                 &event.keystroke,
@@ -741,6 +1012,15 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::copy))
             .on_action(cx.listener(TerminalView::paste))
             .on_action(cx.listener(TerminalView::clear))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::populate_location_list))
+            .on_action(cx.listener(TerminalView::toggle_vi_mode))
+            .on_action(cx.listener(TerminalView::vi_yank))
+            .when(self.terminal.read(cx).vi_mode_enabled(), |div| {
+                vi_mode::on_vim_actions(div, cx)
+            })
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -886,9 +1166,40 @@ impl Item for TerminalView {
                 })
                 .filter(|cwd| !cwd.as_os_str().is_empty());
 
+            let restore_scrollback =
+                cx.update(|cx| TerminalSettings::get_global(cx).persist_scrollback.enabled)?;
+            let restored_scrollback = if restore_scrollback {
+                TERMINAL_DB
+                    .get_scrollback(item_id, workspace_id)
+                    .log_err()
+                    .flatten()
+                    .and_then(|(scrollback, commands)| {
+                        let text = scrollback.filter(|scrollback| !scrollback.is_empty())?;
+                        let commands = commands
+                            .and_then(|commands| {
+                                serde_json::from_str::<Vec<SerializedShellCommand>>(&commands)
+                                    .log_err()
+                            })
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|command| {
+                                ShellCommand::restored(
+                                    command.command,
+                                    command.exit_code,
+                                    command.cwd,
+                                )
+                            })
+                            .collect();
+                        Some(RestoredScrollback { text, commands })
+                    })
+            } else {
+                None
+            };
+
             let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(cwd, None, window, cx)
+                project.create_terminal(cwd, None, restored_scrollback, window, cx)
             })??;
+
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
             })
@@ -922,6 +1233,7 @@ impl SearchableItem for TerminalView {
             word: false,
             regex: true,
             replacement: false,
+            structural: false,
         }
     }
 
@@ -1081,6 +1393,29 @@ fn get_path_from_wt(wt: &LocalWorktree) -> Option<PathBuf> {
         .map(|_| wt.abs_path().to_path_buf())
 }
 
+/// Returns the `path:row` and `path:row:column` locations found in the output of a terminal, in
+/// the order they were printed and without duplicates.
+fn printed_locations(text: &str) -> Vec<(PathBuf, language::Point)> {
+    let mut seen = HashSet::default();
+    text.split_whitespace()
+        .filter_map(|word| {
+            let word = word.trim_matches(|c| matches!(c, '(' | ')' | '[' | ']' | '"' | '\'' | ','));
+            let position =
+                PathLikeWithPosition::parse_str(word, |path| anyhow::Ok(PathBuf::from(path)))
+                    .ok()?;
+            // Skip the likes of timestamps, which parse as a file name and a row.
+            let path = &position.path_like;
+            if path.extension().is_none() && path.parent() == Some(Path::new("")) {
+                return None;
+            }
+            let row = position.row?.checked_sub(1)?;
+            let column = position.column.unwrap_or(1).saturating_sub(1);
+            let location = (position.path_like, language::Point::new(row, column));
+            seen.insert(location.clone()).then_some(location)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1089,6 +1424,22 @@ mod tests {
     use std::path::Path;
     use workspace::AppState;
 
+    #[test]
+    fn test_printed_locations() {
+        let text = "error[E0425]: cannot find value `x` in this scope\n \
+             --> src/main.rs:3:5\n\
+             warning: unused variable (src/lib.rs:10)\n\
+             src/main.rs:3:5: repeated\n\
+             not a location: 12:30, README.md";
+        assert_eq!(
+            printed_locations(text),
+            [
+                (PathBuf::from("src/main.rs"), language::Point::new(2, 4)),
+                (PathBuf::from("src/lib.rs"), language::Point::new(9, 0)),
+            ]
+        );
+    }
+
     // Working directory calculation tests
 
     // No Worktrees in project -> home_dir()
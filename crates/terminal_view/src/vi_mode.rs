@@ -0,0 +1,91 @@
+//! The vi mode of the terminal is driven by the motions of vim mode, bound by its keymap, so
+//! that moving over the scrollback works like moving in an editor.
+
+use gpui::{Action, Div, InteractiveElement as _, ViewContext, WindowContext};
+use terminal::{ViMotion, ViSelection};
+
+use crate::TerminalView;
+
+/// Handles the vim actions that make sense in the scrollback. Others, like operators, aren't
+/// bound in the terminal.
+pub(crate) fn on_vim_actions(div: Div, cx: &ViewContext<TerminalView>) -> Div {
+    div.on_action(motion(cx, |_: &vim::Left| ViMotion::Left))
+        .on_action(motion(cx, |_: &vim::Backspace| ViMotion::Left))
+        .on_action(motion(cx, |_: &vim::Right| ViMotion::Right))
+        .on_action(motion(cx, |_: &vim::Space| ViMotion::Right))
+        .on_action(motion(cx, |_: &vim::Down| ViMotion::Down))
+        .on_action(motion(cx, |_: &vim::Up| ViMotion::Up))
+        .on_action(motion(cx, |action: &vim::NextWordStart| {
+            if action.ignore_punctuation {
+                ViMotion::NextBigWordStart
+            } else {
+                ViMotion::NextWordStart
+            }
+        }))
+        .on_action(motion(cx, |action: &vim::NextWordEnd| {
+            if action.ignore_punctuation {
+                ViMotion::NextBigWordEnd
+            } else {
+                ViMotion::NextWordEnd
+            }
+        }))
+        .on_action(motion(cx, |action: &vim::PreviousWordStart| {
+            if action.ignore_punctuation {
+                ViMotion::PreviousBigWordStart
+            } else {
+                ViMotion::PreviousWordStart
+            }
+        }))
+        .on_action(motion(cx, |action: &vim::PreviousWordEnd| {
+            if action.ignore_punctuation {
+                ViMotion::PreviousBigWordEnd
+            } else {
+                ViMotion::PreviousWordEnd
+            }
+        }))
+        .on_action(motion(cx, |_: &vim::StartOfLine| ViMotion::StartOfLine))
+        .on_action(motion(cx, |_: &vim::FirstNonWhitespace| {
+            ViMotion::FirstNonWhitespace
+        }))
+        .on_action(motion(cx, |_: &vim::StartOfLineDownward| {
+            ViMotion::FirstNonWhitespace
+        }))
+        .on_action(motion(cx, |_: &vim::EndOfLine| ViMotion::EndOfLine))
+        .on_action(motion(cx, |_: &vim::WindowTop| ViMotion::WindowTop))
+        .on_action(motion(cx, |_: &vim::WindowMiddle| ViMotion::WindowMiddle))
+        .on_action(motion(cx, |_: &vim::WindowBottom| ViMotion::WindowBottom))
+        .on_action(motion(cx, |_: &vim::StartOfDocument| {
+            ViMotion::StartOfDocument
+        }))
+        .on_action(motion(cx, |_: &vim::EndOfDocument| ViMotion::EndOfDocument))
+        .on_action(motion(cx, |_: &vim::Matching| ViMotion::Matching))
+        .on_action(motion(cx, |_: &vim::PageUp| ViMotion::PageUp))
+        .on_action(motion(cx, |_: &vim::PageDown| ViMotion::PageDown))
+        .on_action(motion(cx, |_: &vim::ScrollUp| ViMotion::HalfPageUp))
+        .on_action(motion(cx, |_: &vim::ScrollDown| ViMotion::HalfPageDown))
+        .on_action(cx.listener(|this, _: &vim::NextLineStart, cx| {
+            this.vi_motion(ViMotion::Down, cx);
+            this.vi_motion(ViMotion::FirstNonWhitespace, cx);
+        }))
+        .on_action(selection(cx, |_: &vim::ToggleVisual| {
+            ViSelection::Character
+        }))
+        .on_action(selection(cx, |_: &vim::ToggleVisualLine| ViSelection::Line))
+        .on_action(selection(cx, |_: &vim::ToggleVisualBlock| {
+            ViSelection::Block
+        }))
+}
+
+fn motion<A: Action>(
+    cx: &ViewContext<TerminalView>,
+    to_motion: impl Fn(&A) -> ViMotion + 'static,
+) -> impl Fn(&A, &mut WindowContext) + 'static {
+    cx.listener(move |this, action, cx| this.vi_motion(to_motion(action), cx))
+}
+
+fn selection<A: Action>(
+    cx: &ViewContext<TerminalView>,
+    to_selection: impl Fn(&A) -> ViSelection + 'static,
+) -> impl Fn(&A, &mut WindowContext) + 'static {
+    cx.listener(move |this, action, cx| this.toggle_vi_selection(to_selection(action), cx))
+}
@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, fmt};
+
+/// The character encoding of a file's contents, and whether they start with a byte order mark.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::utf8()
+    }
+}
+
+impl fmt::Debug for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display_name())
+    }
+}
+
+/// The encodings offered when choosing one explicitly, in addition to UTF-8.
+const COMMON_ENCODINGS: &[&encoding_rs::Encoding] = &[
+    encoding_rs::UTF_16LE,
+    encoding_rs::UTF_16BE,
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_15,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::WINDOWS_1253,
+    encoding_rs::WINDOWS_1254,
+    encoding_rs::WINDOWS_1255,
+    encoding_rs::WINDOWS_1256,
+    encoding_rs::WINDOWS_1257,
+    encoding_rs::WINDOWS_1258,
+    encoding_rs::WINDOWS_874,
+    encoding_rs::KOI8_R,
+    encoding_rs::KOI8_U,
+    encoding_rs::IBM866,
+    encoding_rs::MACINTOSH,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::ISO_2022_JP,
+    encoding_rs::EUC_KR,
+    encoding_rs::GBK,
+    encoding_rs::GB18030,
+    encoding_rs::BIG5,
+];
+
+impl Encoding {
+    pub fn utf8() -> Self {
+        Self {
+            encoding: encoding_rs::UTF_8,
+            bom: false,
+        }
+    }
+
+    /// Returns the encoding with the given label, as used by `.editorconfig`'s
+    /// `charset` property or by the WHATWG Encoding Standard.
+    pub fn for_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        if label.eq_ignore_ascii_case("utf-8-bom") {
+            return Some(Self::utf8().with_bom(true));
+        }
+        let encoding = encoding_rs::Encoding::for_label_no_replacement(label.as_bytes())?;
+        Some(Self {
+            encoding,
+            bom: false,
+        })
+    }
+
+    /// All of the encodings a file can be reopened or saved with.
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::utf8(), Self::utf8().with_bom(true)]
+            .into_iter()
+            .chain(COMMON_ENCODINGS.iter().map(|&encoding| Self {
+                encoding,
+                bom: false,
+            }))
+    }
+
+    /// The canonical name of the encoding, e.g. `UTF-8` or `windows-1252`.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// The name of the encoding, mentioning the byte order mark if there is one.
+    pub fn display_name(&self) -> Cow<'static, str> {
+        if self.bom {
+            format!("{} with BOM", self.name()).into()
+        } else {
+            self.name().into()
+        }
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Sets whether a byte order mark is written when saving. Only the Unicode
+    /// encodings can have one.
+    pub fn with_bom(mut self, bom: bool) -> Self {
+        self.bom = bom && self.is_unicode();
+        self
+    }
+
+    fn is_unicode(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8 || self.is_utf16()
+    }
+
+    fn is_utf16(&self) -> bool {
+        self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE
+    }
+
+    /// Guesses the encoding of a file's contents. A byte order mark always wins,
+    /// then the `preferred` encoding is used if the contents are valid in it.
+    /// Otherwise UTF-16 without a BOM and UTF-8 are tried. Contents that are neither
+    /// and contain zero bytes are considered binary, and rejected. Shift-JIS is only
+    /// picked for contents that read as Japanese text, falling back to Windows-1252,
+    /// which can decode any byte sequence.
+    pub fn detect(bytes: &[u8], preferred: Option<Self>) -> Result<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Ok(Self {
+                encoding,
+                bom: true,
+            });
+        }
+
+        let is_binary = bytes.contains(&0);
+        if let Some(preferred) = preferred {
+            if (!is_binary || preferred.is_utf16()) && preferred.decodes_without_errors(bytes) {
+                return Ok(preferred.with_bom(false));
+            }
+        }
+
+        if let Some(encoding) = detect_utf16(bytes) {
+            return Ok(Self {
+                encoding,
+                bom: false,
+            });
+        }
+
+        if std::str::from_utf8(bytes).is_ok() {
+            return Ok(Self::utf8());
+        }
+
+        if is_binary {
+            return Err(anyhow!("the file appears to be binary"));
+        }
+
+        if let Some(text) =
+            encoding_rs::SHIFT_JIS.decode_without_bom_handling_and_without_replacement(bytes)
+        {
+            if reads_as_japanese(&text) {
+                return Ok(Self {
+                    encoding: encoding_rs::SHIFT_JIS,
+                    bom: false,
+                });
+            }
+        }
+
+        log::warn!("couldn't detect the encoding of a file, falling back to windows-1252");
+        Ok(Self {
+            encoding: encoding_rs::WINDOWS_1252,
+            bom: false,
+        })
+    }
+
+    fn decodes_without_errors(&self, bytes: &[u8]) -> bool {
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .is_some()
+    }
+
+    /// Decodes the given bytes, skipping the byte order mark if the encoding has one.
+    /// Malformed sequences are replaced with U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = if self.bom {
+            match encoding_rs::Encoding::for_bom(bytes) {
+                Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+                _ => bytes,
+            }
+        } else {
+            bytes
+        };
+        let (text, _) = self.encoding.decode_without_bom_handling(bytes);
+        text.into_owned()
+    }
+
+    /// Encodes the given text, failing if it contains characters that can't be
+    /// represented in this encoding.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let code_units = if self.encoding == encoding_rs::UTF_16LE {
+            Some(
+                text.encode_utf16()
+                    .flat_map(u16::to_le_bytes)
+                    .collect::<Vec<_>>(),
+            )
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            Some(
+                text.encode_utf16()
+                    .flat_map(u16::to_be_bytes)
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+        let bytes = match code_units {
+            Some(code_units) => Cow::Owned(code_units),
+            None => {
+                let (bytes, _, has_unmappable_characters) = self.encoding.encode(text);
+                if has_unmappable_characters {
+                    return Err(anyhow!(
+                        "the text contains characters that can't be represented in {}",
+                        self.name()
+                    ));
+                }
+                bytes
+            }
+        };
+
+        if self.bom {
+            let mut with_bom = self.bom_bytes().to_vec();
+            with_bom.extend_from_slice(&bytes);
+            Ok(with_bom.into())
+        } else {
+            Ok(bytes)
+        }
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == encoding_rs::UTF_16LE {
+            &[0xFF, 0xFE]
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            &[0xFE, 0xFF]
+        } else {
+            &[0xEF, 0xBB, 0xBF]
+        }
+    }
+}
+
+/// Whether text decoded as Shift-JIS is likely to be Japanese, rather than text in a
+/// single-byte encoding whose accented letters happen to form valid Shift-JIS sequences.
+/// Those decode to kanji, whereas Japanese text is mostly made of kana.
+fn reads_as_japanese(text: &str) -> bool {
+    let (mut kana, mut non_ascii) = (0, 0);
+    for ch in text.chars().filter(|ch| !ch.is_ascii()) {
+        non_ascii += 1;
+        kana += matches!(ch, '\u{3040}'..='\u{30ff}') as usize;
+    }
+    kana > 0 && kana * 4 >= non_ascii
+}
+
+/// Detects UTF-16 text without a byte order mark by looking for the zero bytes
+/// that ASCII characters are encoded with.
+fn detect_utf16(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    if bytes.len() < 2 || bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    let sample = &bytes[..bytes.len().min(1024)];
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_zeros += (pair[0] == 0) as usize;
+        odd_zeros += (pair[1] == 0) as usize;
+    }
+
+    let pairs = sample.len() / 2;
+    let encoding = if odd_zeros * 2 > pairs && even_zeros == 0 {
+        encoding_rs::UTF_16LE
+    } else if even_zeros * 2 > pairs && odd_zeros == 0 {
+        encoding_rs::UTF_16BE
+    } else {
+        return None;
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .is_some()
+        .then_some(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        let detect = |bytes: &[u8], preferred| Encoding::detect(bytes, preferred).unwrap();
+        assert_eq!(detect(b"plain text", None), Encoding::utf8());
+        assert_eq!(detect("caf\u{e9}".as_bytes(), None), Encoding::utf8());
+        assert_eq!(
+            detect(b"\xEF\xBB\xBFtext", None),
+            Encoding::utf8().with_bom(true)
+        );
+
+        let windows_1252 = Encoding::for_label("latin1").unwrap();
+        assert_eq!(windows_1252.name(), "windows-1252");
+        assert_eq!(detect(b"caf\xe9 au lait", None), windows_1252);
+        // Valid as Shift-JIS, where it decodes to a kanji rather than to Japanese text.
+        assert_eq!(detect(b"caf\xe9s", None), windows_1252);
+
+        let shift_jis = Encoding::for_label("shift_jis").unwrap();
+        assert_eq!(
+            detect(b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd", None),
+            shift_jis
+        );
+        assert_eq!(
+            detect(b"\x82\xb1\x82\xf1", Some(windows_1252)),
+            windows_1252
+        );
+
+        let utf16le = Encoding::for_label("utf-16le").unwrap();
+        assert_eq!(detect(b"h\0i\0", None), utf16le);
+        assert_eq!(detect(b"\xFF\xFEh\0i\0", None), utf16le.with_bom(true));
+
+        // Binary contents are rejected, even when they're valid in the preferred encoding.
+        assert!(Encoding::detect(b"\x7fELF\x02\x01\x01\0\0\xff", None).is_err());
+        assert!(Encoding::detect(b"\x7fELF\x02\x01\x01\0\0\xff", Some(windows_1252)).is_err());
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let windows_1252 = Encoding::for_label("windows-1252").unwrap();
+        assert_eq!(windows_1252.decode(b"caf\xe9"), "caf\u{e9}");
+        assert_eq!(
+            windows_1252.encode("caf\u{e9}").unwrap().as_ref(),
+            b"caf\xe9"
+        );
+        assert!(windows_1252.encode("\u{3053}").is_err());
+
+        let utf8_bom = Encoding::for_label("utf-8-bom").unwrap();
+        assert_eq!(utf8_bom.decode(b"\xEF\xBB\xBFhi"), "hi");
+        assert_eq!(utf8_bom.encode("hi").unwrap().as_ref(), b"\xEF\xBB\xBFhi");
+
+        let utf16be = Encoding::for_label("utf-16be").unwrap().with_bom(true);
+        assert_eq!(utf16be.decode(b"\xFE\xFF\0h\0i"), "hi");
+        assert_eq!(utf16be.encode("hi").unwrap().as_ref(), b"\xFE\xFF\0h\0i");
+
+        assert!(!windows_1252.with_bom(true).has_bom());
+    }
+}
@@ -524,6 +524,36 @@ fn test_undo_redo() {
     assert_eq!(buffer.text(), "1234");
 }
 
+#[test]
+fn test_restore_undo_history() {
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "1234".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+    buffer.edit([(1..1, "abc")]);
+    buffer.edit([(0..1, "x")]);
+    buffer.edit([(7..7, "yz")]);
+    buffer.undo();
+    assert_eq!(buffer.text(), "xabc234");
+
+    let operations = buffer.operations().values().cloned().collect::<Vec<_>>();
+    let (undo_stack, redo_stack) = buffer.undo_history();
+    assert_eq!((undo_stack.len(), redo_stack.len()), (2, 1));
+
+    let mut restored = Buffer::new(0, BufferId::new(2).unwrap(), "1234".into());
+    restored.apply_ops(operations).unwrap();
+    restored.restore_undo_history(undo_stack, redo_stack);
+    assert_eq!(restored.text(), "xabc234");
+
+    restored.redo();
+    assert_eq!(restored.text(), "xabc234yz");
+    restored.undo();
+    restored.undo();
+    assert_eq!(restored.text(), "1abc234");
+    restored.undo();
+    assert_eq!(restored.text(), "1234");
+    restored.edit([(0..0, "0")]);
+    assert_eq!(restored.text(), "01234");
+}
+
 #[test]
 fn test_history() {
     let mut now = Instant::now();
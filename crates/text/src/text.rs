@@ -1,4 +1,5 @@
 mod anchor;
+mod encoding;
 pub mod locator;
 #[cfg(any(test, feature = "test-support"))]
 pub mod network;
@@ -14,6 +15,7 @@ pub use anchor::*;
 use anyhow::{anyhow, Context as _, Result};
 pub use clock::ReplicaId;
 use collections::{HashMap, HashSet};
+pub use encoding::Encoding;
 use locator::Locator;
 use operation_queue::OperationQueue;
 pub use patch::Patch;
@@ -1162,6 +1164,43 @@ impl Buffer {
         self.history.redo_stack.last()
     }
 
+    /// Returns the transactions on the undo and redo stacks, from the oldest to the newest.
+    pub fn undo_history(&self) -> (Vec<Transaction>, Vec<Transaction>) {
+        let transactions = |stack: &[HistoryEntry]| {
+            stack
+                .iter()
+                .map(|entry| entry.transaction.clone())
+                .collect::<Vec<_>>()
+        };
+        (
+            transactions(&self.history.undo_stack),
+            transactions(&self.history.redo_stack),
+        )
+    }
+
+    /// Replaces the undo and redo stacks with the given transactions, as returned by
+    /// [`Buffer::undo_history`]. Their operations must have been applied to this buffer.
+    pub fn restore_undo_history(
+        &mut self,
+        undo_stack: Vec<Transaction>,
+        redo_stack: Vec<Transaction>,
+    ) {
+        let now = Instant::now();
+        let entries = |transactions: Vec<Transaction>| {
+            transactions
+                .into_iter()
+                .map(|transaction| HistoryEntry {
+                    transaction,
+                    first_edit_at: now,
+                    last_edit_at: now,
+                    suppress_grouping: true,
+                })
+                .collect::<Vec<_>>()
+        };
+        self.history.undo_stack = entries(undo_stack);
+        self.history.redo_stack = entries(redo_stack);
+    }
+
     pub fn start_transaction(&mut self) -> Option<TransactionId> {
         self.start_transaction_at(Instant::now())
     }
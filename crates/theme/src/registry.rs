@@ -144,6 +144,10 @@ impl ThemeRegistry {
                     .collect::<Vec<_>>();
             }
 
+            // Themes can't define their accents yet, so we derive them from the player colors,
+            // which are picked to be distinct from one another.
+            let accents = player_colors.0.iter().map(|player| player.cursor).collect();
+
             Theme {
                 id: uuid::Uuid::new_v4().to_string(),
                 name: user_theme.name.into(),
@@ -157,7 +161,7 @@ impl ThemeRegistry {
                     status: status_colors,
                     player: player_colors,
                     syntax: Arc::new(syntax_colors),
-                    accents: Vec::new(),
+                    accents,
                 },
             }
         }));
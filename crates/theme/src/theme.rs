@@ -129,6 +129,12 @@ impl Theme {
         &self.styles.player
     }
 
+    /// Returns the accent colors for the theme, used by elements cycling through a series of colors.
+    #[inline(always)]
+    pub fn accents(&self) -> &[Hsla] {
+        &self.styles.accents
+    }
+
     /// Returns the [`ThemeColors`] for the theme.
     #[inline(always)]
     pub fn colors(&self) -> &ThemeColors {
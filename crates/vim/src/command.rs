@@ -2,7 +2,7 @@ use command_palette_hooks::CommandInterceptResult;
 use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
 use gpui::{impl_actions, Action, AppContext, ViewContext};
 use serde_derive::Deserialize;
-use workspace::{SaveIntent, Workspace};
+use workspace::{location_list, SaveIntent, Workspace};
 
 use crate::{
     motion::{EndOfDocument, Motion, StartOfDocument},
@@ -230,23 +230,29 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             .boxed_clone(),
         ),
 
-        // quickfix / loclist (merged together for now)
+        // quickfix, backed by the location lists of the workspace
         "cl" | "cli" | "clis" | "clist" => (
             "clist",
-            cx.build_action("diagnostics::Deploy", None).unwrap(),
+            cx.build_action("location_panel::ToggleFocus", None)
+                .unwrap(),
         ),
-        "cc" => ("cc", editor::actions::Hover.boxed_clone()),
-        "ll" => ("ll", editor::actions::Hover.boxed_clone()),
-        "cn" | "cne" | "cnex" | "cnext" => ("cnext", editor::actions::GoToDiagnostic.boxed_clone()),
-        "lne" | "lnex" | "lnext" => ("cnext", editor::actions::GoToDiagnostic.boxed_clone()),
-
-        "cpr" | "cpre" | "cprev" | "cprevi" | "cprevio" | "cpreviou" | "cprevious" => (
-            "cprevious",
-            editor::actions::GoToPrevDiagnostic.boxed_clone(),
+        "cope" | "copen" | "cw" | "cwi" | "cwin" | "cwind" | "cwindo" | "cwindow" => (
+            "copen",
+            cx.build_action("location_panel::ToggleFocus", None)
+                .unwrap(),
         ),
-        "cN" | "cNe" | "cNex" | "cNext" => {
-            ("cNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
+        "cc" => ("cc", location_list::GoTo { index: None }.boxed_clone()),
+        "cn" | "cne" | "cnex" | "cnext" => ("cnext", location_list::Next.boxed_clone()),
+        "cpr" | "cpre" | "cprev" | "cprevi" | "cprevio" | "cpreviou" | "cprevious" => {
+            ("cprevious", location_list::Prev.boxed_clone())
         }
+        "cN" | "cNe" | "cNex" | "cNext" => ("cNext", location_list::Prev.boxed_clone()),
+        "col" | "cold" | "colde" | "colder" => ("colder", location_list::Older.boxed_clone()),
+        "cnew" | "cnewe" | "cnewer" => ("cnewer", location_list::Newer.boxed_clone()),
+
+        // loclist, backed by the diagnostics for now
+        "ll" => ("ll", editor::actions::Hover.boxed_clone()),
+        "lne" | "lnex" | "lnext" => ("lnext", editor::actions::GoToDiagnostic.boxed_clone()),
         "lp" | "lpr" | "lpre" | "lprev" | "lprevi" | "lprevio" | "lpreviou" | "lprevious" => (
             "lprevious",
             editor::actions::GoToPrevDiagnostic.boxed_clone(),
@@ -338,6 +344,17 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
                     }
                     .boxed_clone(),
                 )
+            } else if let Some(number) = query
+                .strip_prefix("cc")
+                .and_then(|number| number.trim().parse::<usize>().ok())
+            {
+                (
+                    query,
+                    location_list::GoTo {
+                        index: Some(number.saturating_sub(1)),
+                    }
+                    .boxed_clone(),
+                )
             } else if let Ok(line) = query.parse::<u32>() {
                 (query, GoToLine { line }.boxed_clone())
             } else {
@@ -383,8 +400,11 @@ mod test {
     use std::path::Path;
 
     use crate::test::{NeovimBackedTestContext, VimTestContext};
-    use gpui::TestAppContext;
+    use gpui::{Action, TestAppContext};
     use indoc::indoc;
+    use workspace::location_list;
+
+    use super::command_interceptor;
 
     #[gpui::test]
     async fn test_command_basics(cx: &mut TestAppContext) {
@@ -519,4 +539,14 @@ mod test {
         cx.simulate_keystrokes([":", "q", "a", "enter"]);
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[gpui::test]
+    fn test_command_cc(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let action = |query| command_interceptor(query, cx).unwrap().action;
+            assert!(action(":cc").partial_eq(&location_list::GoTo { index: None }));
+            assert!(action(":cc 3").partial_eq(&location_list::GoTo { index: Some(2) }));
+            assert!(action(":cc3").partial_eq(&location_list::GoTo { index: Some(2) }));
+        });
+    }
 }
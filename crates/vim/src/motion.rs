@@ -98,30 +98,30 @@ pub enum Motion {
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct NextWordStart {
+pub struct NextWordStart {
     #[serde(default)]
-    ignore_punctuation: bool,
+    pub ignore_punctuation: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct NextWordEnd {
+pub struct NextWordEnd {
     #[serde(default)]
-    ignore_punctuation: bool,
+    pub ignore_punctuation: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct PreviousWordStart {
+pub struct PreviousWordStart {
     #[serde(default)]
-    ignore_punctuation: bool,
+    pub ignore_punctuation: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct PreviousWordEnd {
+pub struct PreviousWordEnd {
     #[serde(default)]
-    ignore_punctuation: bool,
+    pub ignore_punctuation: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -154,37 +154,37 @@ pub(crate) struct PreviousSubwordEnd {
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Up {
+pub struct Up {
     #[serde(default)]
-    pub(crate) display_lines: bool,
+    pub display_lines: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Down {
+pub struct Down {
     #[serde(default)]
-    pub(crate) display_lines: bool,
+    pub display_lines: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct FirstNonWhitespace {
+pub struct FirstNonWhitespace {
     #[serde(default)]
-    display_lines: bool,
+    pub display_lines: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct EndOfLine {
+pub struct EndOfLine {
     #[serde(default)]
-    display_lines: bool,
+    pub display_lines: bool,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct StartOfLine {
     #[serde(default)]
-    pub(crate) display_lines: bool,
+    pub display_lines: bool,
 }
 
 impl_actions!(
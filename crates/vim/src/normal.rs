@@ -4,7 +4,7 @@ mod delete;
 mod increment;
 mod paste;
 pub(crate) mod repeat;
-mod scroll;
+pub(crate) mod scroll;
 pub(crate) mod search;
 pub mod substitute;
 mod yank;
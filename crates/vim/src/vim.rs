@@ -29,7 +29,14 @@ use gpui::{
 use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
+// Motions that views with a vi mode of their own, like the terminal, handle.
+pub use motion::{
+    Backspace, Down, EndOfDocument, EndOfLine, FirstNonWhitespace, Left, Matching, NextLineStart,
+    NextWordEnd, NextWordStart, PreviousWordEnd, PreviousWordStart, Right, Space, StartOfDocument,
+    StartOfLine, StartOfLineDownward, Up, WindowBottom, WindowMiddle, WindowTop,
+};
 use normal::normal_replace;
+pub use normal::scroll::{PageDown, PageUp, ScrollDown, ScrollUp};
 use replace::multi_replace;
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -38,6 +45,7 @@ use settings::{update_settings_file, Settings, SettingsStore};
 use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
 use std::{ops::Range, sync::Arc};
 use visual::{visual_block_motion, visual_replace};
+pub use visual::{ToggleVisual, ToggleVisualBlock, ToggleVisualLine};
 use workspace::{self, Workspace};
 
 use crate::state::ReplayableAction;
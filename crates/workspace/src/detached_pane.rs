@@ -0,0 +1,911 @@
+//! Panes moved out of the center of a workspace into OS windows of their own.
+//!
+//! A detached pane still belongs to the workspace that opened it: its items come from the same
+//! project, workspace actions and modals work in its window, and the workspace serializes it
+//! along with the center panes. Views are bound to the window they were created in, so
+//! detaching a pane clones its items into the new window, the clones sharing the state of the
+//! original items, instead of moving the views themselves. Items that can't be cloned are
+//! reopened from their project path, and the few that have neither, like terminals, stay in
+//! the workspace window. Dock panels are detached separately, see [`crate::detached_panel`].
+
+use crate::{
+    item::ItemHandle, pane, CloseWindow, ModalLayer, NewFile, Pane, ReattachPane, Save, SaveAs,
+    SaveIntent, SaveWithoutFormat, Toast, Workspace, WorkspaceId,
+};
+use anyhow::Result;
+use collections::{hash_map, HashSet};
+use gpui::{
+    div, Action as _, AnyWindowHandle, Bounds, Div, EntityId, GlobalPixels,
+    InteractiveElement as _, IntoElement, KeyContext, Model, ParentElement as _, Render,
+    Styled as _, Subscription, View, ViewContext, VisualContext as _, WeakView, WindowContext,
+    WindowHandle, WindowOptions,
+};
+use project::{Project, ProjectPath};
+use settings::Settings;
+use std::sync::{atomic::AtomicUsize, Arc};
+use theme::{ActiveTheme, ThemeSettings};
+use util::ResultExt;
+use uuid::Uuid;
+
+/// The root view of the window of a detached pane.
+pub struct DetachedPane {
+    workspace: WeakView<Workspace>,
+    workspace_window: AnyWindowHandle,
+    pane: View<Pane>,
+    modal_layer: View<ModalLayer>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// The workspace's side of a detached pane.
+pub(crate) struct DetachedPaneWindow {
+    pub(crate) window: WindowHandle<DetachedPane>,
+    pub(crate) pane: View<Pane>,
+    pub(crate) modal_layer: View<ModalLayer>,
+    /// The bounds of the window, relative to the display containing it.
+    pub(crate) bounds: Option<(Bounds<GlobalPixels>, Uuid)>,
+}
+
+impl DetachedPane {
+    fn new(
+        workspace: &View<Workspace>,
+        workspace_window: AnyWindowHandle,
+        project: Model<Project>,
+        pane_history_timestamp: Arc<AtomicUsize>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let weak_workspace = workspace.downgrade();
+        let pane = cx.new_view(|cx| {
+            let mut pane = Pane::new(
+                weak_workspace.clone(),
+                project,
+                pane_history_timestamp,
+                None,
+                NewFile.boxed_clone(),
+                cx,
+            );
+            pane.set_can_split(false, cx);
+            pane
+        });
+        let modal_layer = cx.new_view(|_| ModalLayer::new());
+
+        // Closing the window moves its items back into the workspace window.
+        cx.on_window_should_close({
+            let workspace = weak_workspace.clone();
+            let pane = pane.clone();
+            move |cx| {
+                workspace_window
+                    .update(cx, |_, cx| {
+                        workspace.update(cx, |workspace, cx| workspace.reattach_pane(&pane, cx))
+                    })
+                    .ok();
+                false
+            }
+        });
+
+        let subscriptions = vec![
+            cx.observe(&pane, |_, _, cx| cx.notify()),
+            cx.observe_release(workspace, |_, _, cx| cx.remove_window()),
+            cx.observe_window_bounds(|this, cx| {
+                let Some(bounds) = display_relative_bounds(cx) else {
+                    return;
+                };
+                let window = cx.window_handle();
+                this.update_workspace(cx, |workspace, cx| {
+                    if let Some(detached_pane) = workspace
+                        .detached_panes
+                        .iter_mut()
+                        .find(|detached_pane| AnyWindowHandle::from(detached_pane.window) == window)
+                    {
+                        detached_pane.bounds = Some(bounds);
+                        workspace.serialize_workspace(cx).detach();
+                    }
+                })
+                .ok();
+            }),
+        ];
+
+        Self {
+            workspace: weak_workspace,
+            workspace_window,
+            pane,
+            modal_layer,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn pane(&self) -> &View<Pane> {
+        &self.pane
+    }
+
+    /// Updates the workspace from its own window, where the items it opens and the prompts it
+    /// shows belong.
+    fn update_workspace<R>(
+        &self,
+        cx: &mut WindowContext,
+        update: impl FnOnce(&mut Workspace, &mut ViewContext<Workspace>) -> R,
+    ) -> Result<R> {
+        let workspace = self.workspace.clone();
+        self.workspace_window
+            .update(cx, |_, cx| workspace.update(cx, update))?
+    }
+
+    fn save_active_item(&mut self, save_intent: SaveIntent, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let pane = self.pane.read(cx);
+        let item_ix = pane.active_item_index();
+        let Some(item) = pane.active_item() else {
+            return;
+        };
+        let pane = self.pane.downgrade();
+
+        cx.spawn(|_, mut cx| async move {
+            Pane::save_item(project, &pane, item_ix, item.as_ref(), save_intent, &mut cx)
+                .await
+                .map(|_| ())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn reattach(&mut self, _: &ReattachPane, cx: &mut ViewContext<Self>) {
+        let pane = self.pane.clone();
+        self.update_workspace(cx, |workspace, cx| workspace.reattach_pane(&pane, cx))
+            .log_err();
+    }
+}
+
+impl Render for DetachedPane {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        detached_window_root(&self.workspace, cx)
+            .on_action(cx.listener(|this, action: &Save, cx| {
+                this.save_active_item(action.save_intent.unwrap_or(SaveIntent::Save), cx)
+            }))
+            .on_action(cx.listener(|this, _: &SaveWithoutFormat, cx| {
+                this.save_active_item(SaveIntent::SaveWithoutFormat, cx)
+            }))
+            .on_action(
+                cx.listener(|this, _: &SaveAs, cx| this.save_active_item(SaveIntent::SaveAs, cx)),
+            )
+            .on_action(cx.listener(Self::reattach))
+            .on_action(cx.listener(|this, _: &CloseWindow, cx| this.reattach(&ReattachPane, cx)))
+            .child(self.pane.clone())
+            .child(self.modal_layer.clone())
+    }
+}
+
+/// The root element of the window of a detached pane or panel, where workspace actions run, so
+/// that the modals they open show up in that window.
+pub(crate) fn detached_window_root<V: 'static>(
+    workspace: &WeakView<Workspace>,
+    cx: &mut ViewContext<V>,
+) -> Div {
+    let mut context = KeyContext::default();
+    context.add("Workspace");
+
+    let (ui_font, ui_font_size) = {
+        let theme_settings = ThemeSettings::get_global(cx);
+        (
+            theme_settings.ui_font.family.clone(),
+            theme_settings.ui_font_size,
+        )
+    };
+    let theme = cx.theme().clone();
+    let colors = theme.colors();
+    cx.set_rem_size(ui_font_size);
+
+    let mut root = div();
+    if let Some(workspace) = workspace.upgrade() {
+        root = workspace.update(cx, |workspace, cx| {
+            workspace.add_workspace_actions_listeners(root, cx)
+        });
+    }
+
+    root.key_context(context)
+        .relative()
+        .size_full()
+        .flex()
+        .flex_col()
+        .font(ui_font)
+        .text_color(colors.text)
+        .bg(colors.background)
+}
+
+/// The bounds of the window of the given context, relative to the display containing it, like
+/// the stored bounds of workspace windows.
+pub(crate) fn display_relative_bounds(cx: &WindowContext) -> Option<(Bounds<GlobalPixels>, Uuid)> {
+    let display = cx.display()?;
+    let display_uuid = display.uuid().log_err()?;
+    let mut bounds = cx.window_bounds();
+    let display_bounds = display.bounds();
+    bounds.origin.x -= display_bounds.origin.x;
+    bounds.origin.y -= display_bounds.origin.y;
+    Some((bounds, display_uuid))
+}
+
+impl Workspace {
+    /// The panes moved into windows of their own, in the order they were detached.
+    pub fn detached_panes(&self) -> impl Iterator<Item = &View<Pane>> {
+        self.detached_panes
+            .iter()
+            .map(|detached_pane| &detached_pane.pane)
+    }
+
+    /// The modal layer of the window of the given context, which is either the workspace window
+    /// or the window of one of its detached panes or panels.
+    pub(crate) fn modal_layer_for_window(&self, cx: &WindowContext) -> &View<ModalLayer> {
+        let window = cx.window_handle();
+        self.detached_panes
+            .iter()
+            .find(|detached_pane| AnyWindowHandle::from(detached_pane.window) == window)
+            .map(|detached_pane| &detached_pane.modal_layer)
+            .or_else(|| {
+                self.detached_panels
+                    .iter()
+                    .find(|detached_panel| AnyWindowHandle::from(detached_panel.window) == window)
+                    .map(|detached_panel| &detached_panel.modal_layer)
+            })
+            .unwrap_or(&self.modal_layer)
+    }
+
+    /// The pane that items opened from the window of the given context go to by default.
+    pub(crate) fn detached_pane_for_window(&self, cx: &WindowContext) -> Option<&View<Pane>> {
+        let window = cx.window_handle();
+        self.detached_panes
+            .iter()
+            .find(|detached_pane| AnyWindowHandle::from(detached_pane.window) == window)
+            .map(|detached_pane| &detached_pane.pane)
+    }
+
+    /// Moves the items of a center pane into a new window. The items are cloned there, so
+    /// unsaved changes survive, and removed from the center pane. Items that can't be cloned
+    /// are reopened from their project path, and the others stay in the center pane.
+    pub fn detach_pane(
+        &mut self,
+        pane: View<Pane>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<WindowHandle<DetachedPane>> {
+        if !self.panes.contains(&pane) {
+            return None;
+        }
+
+        let items = PaneItems::new(&pane, cx);
+        if items.items.is_empty() {
+            return None;
+        }
+
+        let window = self.open_detached_window(None, None, cx).log_err()?;
+        let detached_pane = window.read(cx).ok()?.pane.clone();
+        let workspace_id = self.database_id();
+        let cloned_item_ids = window
+            .update(cx, |_, cx| {
+                items.clone_into(&detached_pane, workspace_id, true, cx)
+            })
+            .log_err()?;
+        pane.update(cx, |pane, cx| remove_items(pane, &cloned_item_ids, cx));
+
+        let (items_to_reopen, items_left_behind) = items.uncloned(&cloned_item_ids, cx);
+        if items_left_behind > 0 {
+            self.show_toast(
+                Toast::new(
+                    DETACHED_PANE_TOAST_ID,
+                    format!(
+                        "{items_left_behind} tab(s) can't be moved to another window, \
+                        and stayed in this one"
+                    ),
+                ),
+                cx,
+            );
+        }
+
+        let active_item_id = items.active_item_id;
+        cx.spawn(|this, mut cx| async move {
+            for (item_id, project_path, pinned) in items_to_reopen {
+                let focus_item = Some(item_id) == active_item_id;
+                // Build the item in the new window, which its views are bound to.
+                let open_item = window.update(&mut cx, |_, cx| {
+                    this.update(cx, |workspace, cx| {
+                        workspace.open_path(
+                            project_path,
+                            Some(detached_pane.downgrade()),
+                            focus_item,
+                            cx,
+                        )
+                    })
+                })??;
+                let Some(item) = open_item.await.log_err() else {
+                    continue;
+                };
+
+                if pinned {
+                    detached_pane.update(&mut cx, |detached_pane, cx| {
+                        if let Some(ix) = detached_pane.index_for_item(item.as_ref()) {
+                            detached_pane.pin_tab_at(ix, cx);
+                        }
+                    })?;
+                }
+                pane.update(&mut cx, |pane, cx| {
+                    remove_items(pane, &HashSet::from_iter([item_id]), cx)
+                })?;
+            }
+
+            window.update(&mut cx, |_, cx| cx.activate_window())?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        Some(window)
+    }
+
+    /// Moves the items of a detached pane back into the last active center pane, and closes its
+    /// window. Like when detaching it, items are cloned or reopened from their project path.
+    /// Items that can be neither are closed, prompting to save them if needed.
+    pub fn reattach_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        let Some(detached_pane) = self
+            .detached_panes
+            .iter()
+            .find(|detached_pane| &detached_pane.pane == pane)
+        else {
+            return;
+        };
+        let window = detached_pane.window;
+        if pane.read(cx).items_len() == 0 {
+            self.remove_detached_pane(pane, cx);
+            return;
+        }
+
+        let destination = self
+            .last_active_center_pane
+            .as_ref()
+            .and_then(|pane| pane.upgrade())
+            .unwrap_or_else(|| self.center.first_pane());
+        let items = PaneItems::new(pane, cx);
+        let mut moved_item_ids = items.clone_into(&destination, self.database_id(), false, cx);
+        let (items_to_reopen, _) = items.uncloned(&moved_item_ids, cx);
+        let open_items = items_to_reopen
+            .into_iter()
+            .map(|(item_id, project_path, _)| {
+                moved_item_ids.insert(item_id);
+                self.open_path(project_path, Some(destination.downgrade()), false, cx)
+            })
+            .collect::<Vec<_>>();
+
+        let pane = pane.clone();
+        cx.spawn(|_, mut cx| async move {
+            for open_item in open_items {
+                open_item.await.log_err();
+            }
+
+            // Removing the last item of the pane closes its window.
+            let close_items = window.update(&mut cx, |_, cx| {
+                pane.update(cx, |pane, cx| {
+                    remove_items(pane, &moved_item_ids, cx);
+                    pane.close_items(cx, SaveIntent::Close, |_| true)
+                })
+            })?;
+            close_items.await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens an empty detached pane. Its bounds are relative to the given display.
+    pub(crate) fn open_detached_window(
+        &mut self,
+        bounds: Option<Bounds<GlobalPixels>>,
+        display: Option<Uuid>,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<WindowHandle<DetachedPane>> {
+        let options = self.detached_window_options(bounds, display, cx);
+        let workspace = cx.view().clone();
+        let workspace_window = cx.window_handle();
+        let project = self.project.clone();
+        let pane_history_timestamp = self.pane_history_timestamp.clone();
+        let window = cx.open_window(options, move |cx| {
+            cx.new_view(|cx| {
+                DetachedPane::new(
+                    &workspace,
+                    workspace_window,
+                    project,
+                    pane_history_timestamp,
+                    cx,
+                )
+            })
+        });
+
+        let detached = window.read(cx)?;
+        let pane = detached.pane.clone();
+        let modal_layer = detached.modal_layer.clone();
+        cx.subscribe(&pane, Self::handle_detached_pane_event)
+            .detach();
+        self.detached_panes.push(DetachedPaneWindow {
+            window,
+            pane,
+            modal_layer,
+            bounds: bounds.zip(display),
+        });
+        Ok(window)
+    }
+
+    /// The options of the window of a detached pane or panel, whose bounds are relative to the
+    /// given display.
+    pub(crate) fn detached_window_options(
+        &self,
+        bounds: Option<Bounds<GlobalPixels>>,
+        display: Option<Uuid>,
+        cx: &mut ViewContext<Self>,
+    ) -> WindowOptions {
+        let screen_bounds = display.and_then(|display| {
+            cx.displays()
+                .into_iter()
+                .find(|screen| screen.uuid().ok() == Some(display))
+                .map(|screen| screen.bounds())
+        });
+        let mut options = (self.app_state.build_window_options)(display, cx);
+        options.bounds = bounds.map(|mut bounds| {
+            if let Some(screen_bounds) = screen_bounds {
+                bounds.origin.x += screen_bounds.origin.x;
+                bounds.origin.y += screen_bounds.origin.y;
+            }
+            bounds
+        });
+        options
+    }
+
+    fn handle_detached_pane_event(
+        &mut self,
+        pane: View<Pane>,
+        event: &pane::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            pane::Event::AddItem { item } => item.added_to_pane(self, pane, cx),
+            pane::Event::Remove => self.remove_detached_pane(&pane, cx),
+            pane::Event::ChangeItemTitle => self.update_window_edited(cx),
+            pane::Event::RemoveItem { item_id } => {
+                self.update_window_edited(cx);
+                if let hash_map::Entry::Occupied(entry) = self.panes_by_item.entry(*item_id) {
+                    if entry.get().entity_id() == pane.entity_id() {
+                        entry.remove();
+                    }
+                }
+            }
+            // Detached panes can't be split or zoomed, and never become the active pane, which
+            // stays in the workspace window.
+            pane::Event::Split(_)
+            | pane::Event::ActivateItem { .. }
+            | pane::Event::Focus
+            | pane::Event::ZoomIn
+            | pane::Event::ZoomOut => {}
+        }
+
+        self.serialize_workspace(cx).detach();
+    }
+
+    fn remove_detached_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self
+            .detached_panes
+            .iter()
+            .position(|detached_pane| &detached_pane.pane == pane)
+        else {
+            return;
+        };
+        let detached_pane = self.detached_panes.remove(ix);
+        for item in pane.read(cx).items() {
+            self.panes_by_item.remove(&item.item_id());
+        }
+        // The window may be the one being updated, when it is asked to close.
+        let window = detached_pane.window;
+        cx.defer(move |_, cx| {
+            window.update(cx, |_, cx| cx.remove_window()).ok();
+        });
+        cx.notify();
+    }
+}
+
+const DETACHED_PANE_TOAST_ID: usize = 0x5e7a_c4ed;
+
+/// The items of a pane being moved into another window.
+struct PaneItems {
+    /// The items of the pane, and whether their tab is pinned.
+    items: Vec<(Box<dyn ItemHandle>, bool)>,
+    active_item_id: Option<EntityId>,
+}
+
+impl PaneItems {
+    fn new(pane: &View<Pane>, cx: &WindowContext) -> Self {
+        let pane = pane.read(cx);
+        Self {
+            items: pane
+                .items()
+                .enumerate()
+                .map(|(ix, item)| (item.boxed_clone(), pane.is_tab_pinned(ix)))
+                .collect(),
+            active_item_id: pane.active_item().map(|item| item.item_id()),
+        }
+    }
+
+    /// Clones the items into `destination`, a pane of the window of the given context, as
+    /// views are bound to the window they were created in. Returns the ids of the items that
+    /// were cloned, since items can refuse to be.
+    fn clone_into(
+        &self,
+        destination: &View<Pane>,
+        workspace_id: WorkspaceId,
+        focus: bool,
+        cx: &mut WindowContext,
+    ) -> HashSet<EntityId> {
+        let mut cloned_item_ids = HashSet::default();
+        let mut active_clone_id = None;
+        for (item, pinned) in &self.items {
+            let Some(clone) = item.clone_on_split(workspace_id, cx) else {
+                continue;
+            };
+            cloned_item_ids.insert(item.item_id());
+            let clone_id = clone.item_id();
+            if self.active_item_id == Some(item.item_id()) {
+                active_clone_id = Some(clone_id);
+            }
+            destination.update(cx, |pane, cx| {
+                let ix = pane.items_len();
+                pane.add_item(clone, false, false, Some(ix), cx);
+                if *pinned {
+                    if let Some(ix) = pane.items().position(|item| item.item_id() == clone_id) {
+                        pane.pin_tab_at(ix, cx);
+                    }
+                }
+            });
+        }
+
+        if let Some(active_clone_id) = active_clone_id {
+            destination.update(cx, |pane, cx| {
+                if let Some(ix) = pane
+                    .items()
+                    .position(|item| item.item_id() == active_clone_id)
+                {
+                    pane.activate_item(ix, false, focus, cx);
+                }
+            });
+        }
+        cloned_item_ids
+    }
+
+    /// The items that weren't cloned and can be reopened from their project path, with whether
+    /// their tab is pinned, and the number of items that can't.
+    fn uncloned(
+        &self,
+        cloned_item_ids: &HashSet<EntityId>,
+        cx: &WindowContext,
+    ) -> (Vec<(EntityId, ProjectPath, bool)>, usize) {
+        let mut items_to_reopen = Vec::new();
+        let mut items_left_behind = 0;
+        for (item, pinned) in &self.items {
+            if cloned_item_ids.contains(&item.item_id()) {
+                continue;
+            }
+            match item.project_path(cx) {
+                Some(project_path) => items_to_reopen.push((item.item_id(), project_path, *pinned)),
+                None => items_left_behind += 1,
+            }
+        }
+        (items_to_reopen, items_left_behind)
+    }
+}
+
+fn remove_items(pane: &mut Pane, item_ids: &HashSet<EntityId>, cx: &mut ViewContext<Pane>) {
+    while let Some(ix) = pane
+        .items()
+        .position(|item| item_ids.contains(&item.item_id()))
+    {
+        pane.remove_item(ix, false, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{item::Item, register_project_item, ProjectItem};
+    use gpui::{
+        AppContext, Context as _, EventEmitter, FocusHandle, FocusableView, Task, TestAppContext,
+        VisualTestContext,
+    };
+    use project::{FakeFs, ProjectEntryId};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use theme::LoadThemes;
+
+    struct TestFile {
+        entry_id: Option<ProjectEntryId>,
+        project_path: ProjectPath,
+    }
+
+    impl project::Item for TestFile {
+        fn try_open(
+            project: &Model<Project>,
+            path: &ProjectPath,
+            cx: &mut AppContext,
+        ) -> Option<Task<gpui::Result<Model<Self>>>> {
+            let entry_id = project
+                .read(cx)
+                .entry_for_path(path, cx)
+                .map(|entry| entry.id);
+            let project_path = path.clone();
+            Some(cx.spawn(|mut cx| async move {
+                cx.new_model(|_| TestFile {
+                    entry_id,
+                    project_path,
+                })
+            }))
+        }
+
+        fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+            self.entry_id
+        }
+
+        fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+            Some(self.project_path.clone())
+        }
+    }
+
+    struct TestFileView {
+        file: Model<TestFile>,
+        focus_handle: FocusHandle,
+    }
+
+    impl Item for TestFileView {
+        type Event = ();
+
+        fn for_each_project_item(
+            &self,
+            cx: &AppContext,
+            f: &mut dyn FnMut(EntityId, &dyn project::Item),
+        ) {
+            f(self.file.entity_id(), self.file.read(cx))
+        }
+
+        fn is_singleton(&self, _: &AppContext) -> bool {
+            true
+        }
+    }
+
+    impl EventEmitter<()> for TestFileView {}
+
+    impl FocusableView for TestFileView {
+        fn focus_handle(&self, _: &gpui::AppContext) -> FocusHandle {
+            self.focus_handle.clone()
+        }
+    }
+
+    impl Render for TestFileView {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            gpui::Empty
+        }
+    }
+
+    impl ProjectItem for TestFileView {
+        type Item = TestFile;
+
+        fn for_project_item(
+            _: Model<Project>,
+            file: Model<Self::Item>,
+            cx: &mut ViewContext<Self>,
+        ) -> Self {
+            Self {
+                file,
+                focus_handle: cx.focus_handle(),
+            }
+        }
+    }
+
+    /// An item without a project path.
+    struct TestScratchView {
+        name: &'static str,
+        cloneable: bool,
+        focus_handle: FocusHandle,
+    }
+
+    impl TestScratchView {
+        fn new(name: &'static str, cloneable: bool, cx: &mut ViewContext<Self>) -> Self {
+            Self {
+                name,
+                cloneable,
+                focus_handle: cx.focus_handle(),
+            }
+        }
+    }
+
+    impl Item for TestScratchView {
+        type Event = ();
+
+        fn clone_on_split(&self, _: WorkspaceId, cx: &mut ViewContext<Self>) -> Option<View<Self>> {
+            self.cloneable
+                .then(|| cx.new_view(|cx| Self::new(self.name, self.cloneable, cx)))
+        }
+    }
+
+    impl EventEmitter<()> for TestScratchView {}
+
+    impl FocusableView for TestScratchView {
+        fn focus_handle(&self, _: &gpui::AppContext) -> FocusHandle {
+            self.focus_handle.clone()
+        }
+    }
+
+    impl Render for TestScratchView {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            gpui::Empty
+        }
+    }
+
+    #[gpui::test]
+    async fn test_detach_and_reattach_pane(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "", "b.txt": "" }))
+            .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        for path in ["a.txt", "b.txt"] {
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.open_path((worktree_id, path), None, true, cx)
+                })
+                .await
+                .unwrap();
+        }
+        let center_pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+        assert_pane_paths(&center_pane, &["a.txt", "b.txt"], cx);
+
+        let window = workspace
+            .update(cx, |workspace, cx| {
+                workspace.detach_pane(center_pane.clone(), cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        // The items moved into the window of the detached pane, which belongs to the workspace.
+        assert_eq!(cx.windows().len(), 2);
+        let detached_pane = workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.items(cx).count(), 2);
+            workspace.detached_panes().next().unwrap().clone()
+        });
+        assert_eq!(
+            window
+                .update(cx, |detached, _| detached.pane().clone())
+                .unwrap(),
+            detached_pane
+        );
+        assert_pane_paths(&detached_pane, &["a.txt", "b.txt"], cx);
+        assert_pane_paths(&center_pane, &[], cx);
+
+        // Items opened from the window of the detached pane go to it.
+        let opened_item = window
+            .update(cx, |_, cx| {
+                workspace.update(cx, |workspace, cx| {
+                    workspace.open_path((worktree_id, "a.txt"), None, true, cx)
+                })
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let ix = detached_pane.update(cx, |pane, _| pane.index_for_item(&*opened_item));
+        assert_eq!(ix, Some(0));
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.reattach_pane(&detached_pane, cx)
+        });
+        cx.run_until_parked();
+
+        assert_eq!(cx.windows().len(), 1);
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(workspace.detached_panes().count(), 0)
+        });
+        assert_pane_paths(&center_pane, &["a.txt", "b.txt"], cx);
+    }
+
+    #[gpui::test]
+    async fn test_detach_and_reattach_items_without_path(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "" })).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let center_pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+        for (name, cloneable) in [("notes", true), ("log", false)] {
+            let item = cx.new_view(|cx| TestScratchView::new(name, cloneable, cx));
+            workspace.update(cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(item), cx)
+            });
+        }
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, cx)
+            })
+            .await
+            .unwrap();
+        center_pane.update(cx, |pane, cx| pane.pin_tab_at(0, cx));
+        assert_pane_items(&center_pane, &["notes", "log", "a.txt"], cx);
+
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.detach_pane(center_pane.clone(), cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        // The item that can be neither cloned nor reopened stays behind, and the user is told.
+        let detached_pane = workspace.update(cx, |workspace, _| {
+            assert!(workspace
+                .notifications
+                .iter()
+                .any(|(_, id, _)| *id == DETACHED_PANE_TOAST_ID));
+            workspace.detached_panes().next().unwrap().clone()
+        });
+        assert_pane_items(&detached_pane, &["notes", "a.txt"], cx);
+        assert!(detached_pane.read_with(cx, |pane, _| pane.is_tab_pinned(0)));
+        assert_pane_items(&center_pane, &["log"], cx);
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.reattach_pane(&detached_pane, cx)
+        });
+        cx.run_until_parked();
+
+        assert_eq!(cx.windows().len(), 1);
+        assert_pane_items(&center_pane, &["notes", "a.txt", "log"], cx);
+    }
+
+    #[track_caller]
+    fn assert_pane_items(pane: &View<Pane>, expected_items: &[&str], cx: &mut VisualTestContext) {
+        pane.update(cx, |pane, cx| {
+            let items = pane
+                .items()
+                .map(|item| match item.downcast::<TestScratchView>() {
+                    Some(scratch) => scratch.read(cx).name.to_string(),
+                    None => item
+                        .project_path(cx)
+                        .unwrap()
+                        .path
+                        .to_string_lossy()
+                        .into_owned(),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(items, expected_items);
+        });
+    }
+
+    #[track_caller]
+    fn assert_pane_paths(pane: &View<Pane>, expected_paths: &[&str], cx: &mut VisualTestContext) {
+        pane.update(cx, |pane, cx| {
+            let paths = pane
+                .items()
+                .map(|item| {
+                    item.project_path(cx)
+                        .unwrap()
+                        .path
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(paths, expected_paths);
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(LoadThemes::JustBase, cx);
+            crate::init_settings(cx);
+            Project::init_settings(cx);
+            register_project_item::<TestFileView>(cx);
+        });
+    }
+}
@@ -0,0 +1,477 @@
+//! Dock panels moved into OS windows of their own.
+//!
+//! Like a detached pane, a detached panel belongs to the workspace that opened it: workspace
+//! actions and modals work in its window, and the workspace serializes it and reopens it when
+//! restored. A panel's views are bound to the window they were created in, and panels aren't
+//! items that can be cloned, so detaching a panel drops it from its dock and loads it anew in
+//! the new window, with the loader registered through [`register_detachable_panel`], and
+//! reattaching it does the same the other way around. Only the state a panel restores when it
+//! is loaded carries over, such as the layout and working directories of a terminal panel,
+//! whose shells are started again.
+
+use crate::{
+    detached_pane::{detached_window_root, display_relative_bounds},
+    dock::{Panel, PanelEvent, PanelHandle},
+    CloseWindow, ModalLayer, ReattachPanel, Workspace,
+};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use futures::{future::LocalBoxFuture, Future, FutureExt as _};
+use gpui::{
+    div, AnyView, AnyWindowHandle, AppContext, AsyncWindowContext, Bounds, Global, GlobalPixels,
+    InteractiveElement as _, IntoElement, ParentElement as _, Render, Styled as _, Subscription,
+    View, ViewContext, VisualContext as _, WeakView, WindowContext, WindowHandle,
+};
+use std::sync::Arc;
+use util::ResultExt;
+use uuid::Uuid;
+
+type LoadPanel = Arc<
+    dyn Fn(
+        WeakView<Workspace>,
+        AsyncWindowContext,
+    ) -> LocalBoxFuture<'static, Result<Arc<dyn PanelHandle>>>,
+>;
+
+struct PanelLoader {
+    load: LoadPanel,
+    add_to_dock: fn(&mut Workspace, AnyView, &mut WindowContext),
+    observe_close: fn(&AnyView, &mut ViewContext<DetachedPanel>) -> Option<Subscription>,
+}
+
+#[derive(Default)]
+struct DetachablePanels(HashMap<&'static str, PanelLoader>);
+
+impl Global for DetachablePanels {}
+
+/// Lets the panels of the given type be detached into windows of their own, where they are
+/// loaded with `load`, like when the workspace is opened.
+pub fn register_detachable_panel<T, F, Fut>(cx: &mut AppContext, load: F)
+where
+    T: Panel,
+    F: Fn(WeakView<Workspace>, AsyncWindowContext) -> Fut + 'static,
+    Fut: Future<Output = Result<View<T>>> + 'static,
+{
+    let loader = PanelLoader {
+        load: Arc::new(move |workspace, cx| {
+            let panel = load(workspace, cx);
+            async move { Ok(Arc::new(panel.await?) as Arc<dyn PanelHandle>) }.boxed_local()
+        }),
+        add_to_dock: |workspace, panel, cx| {
+            if let Ok(panel) = panel.downcast::<T>() {
+                workspace.add_panel(panel, cx);
+            }
+        },
+        observe_close: |panel, cx| {
+            let panel = panel.clone().downcast::<T>().ok()?;
+            Some(cx.subscribe(&panel, |this, _, event, cx| {
+                // Closing the panel closes its dock when it is docked, so it goes back there.
+                if let PanelEvent::Close = event {
+                    this.reattach(&ReattachPanel, cx);
+                }
+            }))
+        },
+    };
+    cx.default_global::<DetachablePanels>()
+        .0
+        .insert(T::persistent_name(), loader);
+}
+
+pub(crate) fn is_panel_detachable(name: &str, cx: &AppContext) -> bool {
+    panel_loader(name, cx).is_some()
+}
+
+fn panel_loader<'a>(name: &str, cx: &'a AppContext) -> Option<(&'static str, &'a PanelLoader)> {
+    let (name, loader) = cx.try_global::<DetachablePanels>()?.0.get_key_value(name)?;
+    Some((*name, loader))
+}
+
+/// The root view of the window of a detached panel.
+pub struct DetachedPanel {
+    workspace: WeakView<Workspace>,
+    workspace_window: AnyWindowHandle,
+    name: &'static str,
+    panel: Option<Arc<dyn PanelHandle>>,
+    modal_layer: View<ModalLayer>,
+    _panel_subscription: Option<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// The workspace's side of a detached panel.
+pub(crate) struct DetachedPanelWindow {
+    pub(crate) name: &'static str,
+    pub(crate) window: WindowHandle<DetachedPanel>,
+    pub(crate) modal_layer: View<ModalLayer>,
+    /// The bounds of the window, relative to the display containing it.
+    pub(crate) bounds: Option<(Bounds<GlobalPixels>, Uuid)>,
+}
+
+impl DetachedPanel {
+    fn new(
+        workspace: &View<Workspace>,
+        workspace_window: AnyWindowHandle,
+        name: &'static str,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let weak_workspace = workspace.downgrade();
+        let modal_layer = cx.new_view(|_| ModalLayer::new());
+
+        // Closing the window puts the panel back in its dock.
+        cx.on_window_should_close({
+            let workspace = weak_workspace.clone();
+            move |cx| {
+                workspace_window
+                    .update(cx, |_, cx| {
+                        workspace.update(cx, |workspace, cx| workspace.reattach_panel(name, cx))
+                    })
+                    .ok();
+                false
+            }
+        });
+
+        let subscriptions = vec![
+            cx.observe_release(workspace, |_, _, cx| cx.remove_window()),
+            cx.observe_window_bounds(|this, cx| {
+                let Some(bounds) = display_relative_bounds(cx) else {
+                    return;
+                };
+                let window = cx.window_handle();
+                this.update_workspace(cx, |workspace, cx| {
+                    if let Some(detached_panel) =
+                        workspace.detached_panels.iter_mut().find(|detached_panel| {
+                            AnyWindowHandle::from(detached_panel.window) == window
+                        })
+                    {
+                        detached_panel.bounds = Some(bounds);
+                        workspace.serialize_workspace(cx).detach();
+                    }
+                })
+                .ok();
+            }),
+        ];
+
+        Self {
+            workspace: weak_workspace,
+            workspace_window,
+            name,
+            panel: None,
+            modal_layer,
+            _panel_subscription: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// The persistent name of the panel, which identifies it across docks and windows.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The panel, once it is loaded in this window.
+    pub fn panel(&self) -> Option<&Arc<dyn PanelHandle>> {
+        self.panel.as_ref()
+    }
+
+    fn set_panel(&mut self, panel: Arc<dyn PanelHandle>, cx: &mut ViewContext<Self>) {
+        let observe_close = panel_loader(self.name, cx).map(|(_, loader)| loader.observe_close);
+        self._panel_subscription =
+            observe_close.and_then(|observe_close| observe_close(&panel.to_any(), cx));
+        panel.set_active(true, cx);
+        panel.focus_handle(cx).focus(cx);
+        self.panel = Some(panel);
+        cx.notify();
+    }
+
+    /// Updates the workspace from its own window, where the prompts it shows belong.
+    fn update_workspace<R>(
+        &self,
+        cx: &mut WindowContext,
+        update: impl FnOnce(&mut Workspace, &mut ViewContext<Workspace>) -> R,
+    ) -> Result<R> {
+        let workspace = self.workspace.clone();
+        self.workspace_window
+            .update(cx, |_, cx| workspace.update(cx, update))?
+    }
+
+    fn reattach(&mut self, _: &ReattachPanel, cx: &mut ViewContext<Self>) {
+        let name = self.name;
+        self.update_workspace(cx, |workspace, cx| workspace.reattach_panel(name, cx))
+            .log_err();
+    }
+}
+
+impl Render for DetachedPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        detached_window_root(&self.workspace, cx)
+            .on_action(cx.listener(Self::reattach))
+            .on_action(cx.listener(|this, _: &CloseWindow, cx| this.reattach(&ReattachPanel, cx)))
+            .children(
+                self.panel
+                    .as_ref()
+                    .map(|panel| div().flex_1().overflow_hidden().child(panel.to_any())),
+            )
+            .child(self.modal_layer.clone())
+    }
+}
+
+impl Workspace {
+    /// The persistent names of the panels moved into windows of their own, in the order they
+    /// were detached.
+    pub fn detached_panels(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.detached_panels
+            .iter()
+            .map(|detached_panel| detached_panel.name)
+    }
+
+    /// The persistent name of the panel of the focused dock, or else of the first open dock.
+    pub(crate) fn dock_panel_to_detach(&self, cx: &WindowContext) -> Option<&'static str> {
+        let docks = [&self.left_dock, &self.bottom_dock, &self.right_dock];
+        let panel = docks
+            .iter()
+            .find(|dock| dock.focus_handle(cx).contains_focused(cx))
+            .or_else(|| docks.iter().find(|dock| dock.read(cx).is_open()))
+            .and_then(|dock| dock.read(cx).visible_panel().cloned())?;
+        Some(panel.persistent_name())
+    }
+
+    /// Moves the panel with the given persistent name out of its dock into a new window, or
+    /// brings its window forward if it is already detached. Does nothing for panels that
+    /// weren't registered with [`register_detachable_panel`].
+    pub fn detach_panel(
+        &mut self,
+        name: &str,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<WindowHandle<DetachedPanel>> {
+        self.open_detached_panel(name, None, None, cx).log_err()
+    }
+
+    /// Puts a detached panel back in its dock, and closes its window.
+    pub fn reattach_panel(&mut self, name: &str, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self
+            .detached_panels
+            .iter()
+            .position(|detached_panel| detached_panel.name == name)
+        else {
+            return;
+        };
+        let detached_panel = self.detached_panels.remove(ix);
+        // The window may be the one being updated, when it is asked to close.
+        let window = detached_panel.window;
+        cx.defer(move |_, cx| {
+            window.update(cx, |_, cx| cx.remove_window()).ok();
+        });
+        self.serialize_workspace(cx).detach();
+        cx.notify();
+
+        let Some((load, add_to_dock)) = panel_loader(detached_panel.name, cx)
+            .map(|(_, loader)| (loader.load.clone(), loader.add_to_dock))
+        else {
+            return;
+        };
+        let name = detached_panel.name;
+        let load_panel = load(self.weak_self.clone(), cx.to_async());
+        cx.spawn(|this, mut cx| async move {
+            let panel = load_panel.await?;
+            this.update(&mut cx, |workspace, cx| {
+                add_to_dock(workspace, panel.to_any(), cx);
+                for dock in [
+                    &workspace.left_dock,
+                    &workspace.bottom_dock,
+                    &workspace.right_dock,
+                ] {
+                    if let Some(panel_ix) = dock.read(cx).panel_index_for_persistent_name(name, cx)
+                    {
+                        dock.update(cx, |dock, cx| {
+                            dock.activate_panel(panel_ix, cx);
+                            dock.set_open(true, cx);
+                        });
+                    }
+                }
+                panel.focus_handle(cx).focus(cx);
+                workspace.serialize_workspace(cx).detach();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Brings the window of a detached panel forward and focuses the panel in it.
+    pub(crate) fn focus_detached_panel(&self, name: &str, cx: &mut WindowContext) -> bool {
+        let Some(detached_panel) = self
+            .detached_panels
+            .iter()
+            .find(|detached_panel| detached_panel.name == name)
+        else {
+            return false;
+        };
+        detached_panel
+            .window
+            .update(cx, |detached_panel, cx| {
+                cx.activate_window();
+                if let Some(panel) = &detached_panel.panel {
+                    panel.focus_handle(cx).focus(cx);
+                }
+            })
+            .is_ok()
+    }
+
+    /// Opens the window of a detached panel, whose bounds are relative to the given display, and
+    /// loads the panel in it, removing it from its dock.
+    pub(crate) fn open_detached_panel(
+        &mut self,
+        name: &str,
+        bounds: Option<Bounds<GlobalPixels>>,
+        display: Option<Uuid>,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<WindowHandle<DetachedPanel>> {
+        let (name, load) = panel_loader(name, cx)
+            .map(|(name, loader)| (name, loader.load.clone()))
+            .ok_or_else(|| anyhow!("the {name} panel can't be detached"))?;
+        if let Some(detached_panel) = self
+            .detached_panels
+            .iter()
+            .find(|detached_panel| detached_panel.name == name)
+        {
+            let window = detached_panel.window;
+            window.update(cx, |_, cx| cx.activate_window())?;
+            return Ok(window);
+        }
+
+        for dock in [&self.left_dock, &self.bottom_dock, &self.right_dock] {
+            dock.update(cx, |dock, cx| {
+                dock.remove_panel_with_persistent_name(name, cx)
+            });
+        }
+
+        let options = self.detached_window_options(bounds, display, cx);
+        let workspace = cx.view().clone();
+        let workspace_window = cx.window_handle();
+        let window = cx.open_window(options, move |cx| {
+            cx.new_view(|cx| DetachedPanel::new(&workspace, workspace_window, name, cx))
+        });
+        let modal_layer = window.read(cx)?.modal_layer.clone();
+        self.detached_panels.push(DetachedPanelWindow {
+            name,
+            window,
+            modal_layer,
+            bounds: bounds.zip(display),
+        });
+        self.serialize_workspace(cx).detach();
+        cx.notify();
+
+        // Load the panel in its new window, which its views are bound to.
+        let window_cx = window.update(cx, |_, cx| cx.to_async())?;
+        let load_panel = load(self.weak_self.clone(), window_cx);
+        cx.spawn(|this, mut cx| async move {
+            match load_panel.await {
+                Ok(panel) => window.update(&mut cx, |detached_panel, cx| {
+                    detached_panel.set_panel(panel, cx)
+                }),
+                Err(error) => {
+                    this.update(&mut cx, |workspace, cx| workspace.reattach_panel(name, cx))?;
+                    Err(error)
+                }
+            }
+        })
+        .detach_and_log_err(cx);
+
+        Ok(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dock::{test::TestPanel, DockPosition};
+    use gpui::{Task, TestAppContext};
+    use project::{FakeFs, Project};
+    use settings::SettingsStore;
+    use theme::LoadThemes;
+
+    #[gpui::test]
+    async fn test_detach_and_reattach_panel(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TestPanel::new(DockPosition::Right, cx));
+            workspace.add_panel(panel, cx);
+        });
+
+        let window = workspace
+            .update(cx, |workspace, cx| workspace.detach_panel("TestPanel", cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        // The panel left its dock, and was loaded again in its own window.
+        assert_eq!(cx.windows().len(), 2);
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(
+                workspace.detached_panels().collect::<Vec<_>>(),
+                ["TestPanel"]
+            );
+            assert!(workspace.panel::<TestPanel>(cx).is_none());
+            assert_eq!(workspace.right_dock().read(cx).panels_len(), 0);
+        });
+        window
+            .update(cx, |detached_panel, cx| {
+                let panel = detached_panel.panel().unwrap();
+                assert_eq!(panel.persistent_name(), "TestPanel");
+                assert!(
+                    panel
+                        .to_any()
+                        .downcast::<TestPanel>()
+                        .unwrap()
+                        .read(cx)
+                        .active
+                );
+            })
+            .unwrap();
+
+        // Panels added to the workspace while detached stay out of their dock.
+        workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TestPanel::new(DockPosition::Right, cx));
+            workspace.add_panel(panel, cx);
+            assert!(workspace.panel::<TestPanel>(cx).is_none());
+        });
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.reattach_panel("TestPanel", cx)
+        });
+        cx.run_until_parked();
+
+        assert_eq!(cx.windows().len(), 1);
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.detached_panels().count(), 0);
+            assert!(workspace.panel::<TestPanel>(cx).is_some());
+            assert!(workspace.right_dock().read(cx).is_open());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_detach_unregistered_panel(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let window = workspace.update(cx, |workspace, cx| workspace.detach_panel("OtherPanel", cx));
+        assert!(window.is_none());
+        assert_eq!(cx.windows().len(), 1);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(LoadThemes::JustBase, cx);
+            crate::init_settings(cx);
+            Project::init_settings(cx);
+            register_detachable_panel(cx, |workspace: WeakView<Workspace>, mut cx| {
+                let panel = workspace.update(&mut cx, |_, cx| {
+                    cx.new_view(|cx| TestPanel::new(DockPosition::Right, cx))
+                });
+                Task::ready(panel)
+            });
+        });
+    }
+}
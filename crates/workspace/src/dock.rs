@@ -1,6 +1,7 @@
+use crate::detached_panel::is_panel_detachable;
 use crate::persistence::model::DockData;
-use crate::DraggedDock;
 use crate::{status_bar::StatusItemView, Workspace};
+use crate::{DetachPanel, DraggedDock};
 use gpui::{
     deferred, div, px, Action, AnchorCorner, AnyView, AppContext, Axis, ClickEvent, Entity,
     EntityId, EventEmitter, FocusHandle, FocusableView, IntoElement, KeyContext, MouseButton,
@@ -449,17 +450,31 @@ impl Dock {
             .iter()
             .position(|entry| entry.panel.panel_id() == Entity::entity_id(panel))
         {
-            if panel_ix == self.active_panel_index {
-                self.active_panel_index = 0;
-                self.set_open(false, cx);
-            } else if panel_ix < self.active_panel_index {
-                self.active_panel_index -= 1;
-            }
-            self.panel_entries.remove(panel_ix);
-            cx.notify();
+            self.remove_panel_at(panel_ix, cx);
         }
     }
 
+    pub(crate) fn remove_panel_with_persistent_name(
+        &mut self,
+        name: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(panel_ix) = self.panel_index_for_persistent_name(name, cx) {
+            self.remove_panel_at(panel_ix, cx);
+        }
+    }
+
+    fn remove_panel_at(&mut self, panel_ix: usize, cx: &mut ViewContext<Self>) {
+        if panel_ix == self.active_panel_index {
+            self.active_panel_index = 0;
+            self.set_open(false, cx);
+        } else if panel_ix < self.active_panel_index {
+            self.active_panel_index -= 1;
+        }
+        self.panel_entries.remove(panel_ix);
+        cx.notify();
+    }
+
     pub fn panels_len(&self) -> usize {
         self.panel_entries.len()
     }
@@ -703,6 +718,15 @@ impl Render for PanelButtons {
                                         )
                                     }
                                 }
+                                if is_panel_detachable(name, cx) {
+                                    menu = menu.separator().action(
+                                        "Move to New Window",
+                                        DetachPanel {
+                                            name: Some(name.to_string()),
+                                        }
+                                        .boxed_clone(),
+                                    );
+                                }
                                 menu
                             })
                         })
@@ -25,6 +25,7 @@ use smallvec::SmallVec;
 use std::{
     any::{Any, TypeId},
     cell::RefCell,
+    num::NonZeroUsize,
     ops::Range,
     path::PathBuf,
     rc::Rc,
@@ -40,6 +41,8 @@ pub const LEADER_UPDATE_THROTTLE: Duration = Duration::from_millis(200);
 pub struct ItemSettings {
     pub git_status: bool,
     pub close_position: ClosePosition,
+    pub sort_by: TabSortBy,
+    pub max_tabs: Option<NonZeroUsize>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -50,6 +53,19 @@ pub enum ClosePosition {
     Right,
 }
 
+/// The order of the unpinned tabs of a pane.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TabSortBy {
+    /// Tabs stay where they were opened or dragged to.
+    #[default]
+    Manual,
+    /// Tabs are ordered by when they were last used, the most recent one first.
+    Recency,
+    /// Tabs are ordered by their title.
+    Name,
+}
+
 impl ClosePosition {
     pub fn right(&self) -> bool {
         match self {
@@ -64,11 +80,20 @@ pub struct ItemSettingsContent {
     /// Whether to show the Git file status on a tab item.
     ///
     /// Default: true
-    git_status: Option<bool>,
+    pub git_status: Option<bool>,
     /// Position of the close button in a tab.
     ///
     /// Default: right
-    close_position: Option<ClosePosition>,
+    pub close_position: Option<ClosePosition>,
+    /// How to order the unpinned tabs of a pane, re-sorted whenever a tab is opened.
+    ///
+    /// Default: manual
+    pub sort_by: Option<TabSortBy>,
+    /// The maximum number of tabs in a pane. When exceeded, the least recently used
+    /// tabs that are neither pinned nor dirty are closed. No limit when unset.
+    ///
+    /// Default: null
+    pub max_tabs: Option<NonZeroUsize>,
 }
 
 impl Settings for ItemSettings {
@@ -85,6 +110,44 @@ impl Settings for ItemSettings {
     }
 }
 
+#[derive(Deserialize)]
+pub struct PreviewTabsSettings {
+    pub enabled: bool,
+    pub enable_preview_from_file_finder: bool,
+    pub enable_preview_from_code_navigation: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PreviewTabsSettingsContent {
+    /// Whether to open files in a preview tab, which is replaced by the next file opened
+    /// that way, until it is edited, double-clicked or kept open.
+    ///
+    /// Default: true
+    enabled: Option<bool>,
+    /// Whether files opened from the file finder open in the preview tab.
+    ///
+    /// Default: true
+    enable_preview_from_file_finder: Option<bool>,
+    /// Whether files opened by navigating to a definition or reference open in the preview tab.
+    ///
+    /// Default: true
+    enable_preview_from_code_navigation: Option<bool>,
+}
+
+impl Settings for PreviewTabsSettings {
+    const KEY: Option<&'static str> = Some("preview_tabs");
+
+    type FileContent = PreviewTabsSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
 pub enum ItemEvent {
     CloseItem,
@@ -223,6 +286,16 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
             "deserialize() must be implemented if serialized_item_kind() returns Some(_)"
         )
     }
+    /// Persists the item's state, including its unsaved changes, so that they're restored
+    /// when the workspace is deserialized. Returns `None` if the unsaved changes can't be
+    /// restored, in which case the user is asked to save them before quitting.
+    fn persist_unsaved_changes(
+        &mut self,
+        _workspace_id: WorkspaceId,
+        _cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        None
+    }
     fn show_toolbar(&self) -> bool {
         true
     }
@@ -296,6 +369,11 @@ pub trait ItemHandle: 'static + Send {
     fn breadcrumb_location(&self, cx: &AppContext) -> ToolbarItemLocation;
     fn breadcrumbs(&self, theme: &Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>>;
     fn serialized_item_kind(&self) -> Option<&'static str>;
+    fn persist_unsaved_changes(
+        &self,
+        workspace_id: WorkspaceId,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Result<()>>>;
     fn show_toolbar(&self, cx: &AppContext) -> bool;
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>>;
 }
@@ -530,6 +608,8 @@ impl<T: Item> ItemHandle for View<T> {
                         }
 
                         ItemEvent::Edit => {
+                            pane.update(cx, |pane, cx| pane.handle_item_edit(item.item_id(), cx));
+
                             let autosave = WorkspaceSettings::get_global(cx).autosave;
                             if let AutosaveSetting::AfterDelay { milliseconds } = autosave {
                                 let delay = Duration::from_millis(milliseconds);
@@ -656,6 +736,16 @@ impl<T: Item> ItemHandle for View<T> {
         T::serialized_item_kind()
     }
 
+    fn persist_unsaved_changes(
+        &self,
+        workspace_id: WorkspaceId,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Result<()>>> {
+        self.update(cx, |item, cx| {
+            item.persist_unsaved_changes(workspace_id, cx)
+        })
+    }
+
     fn show_toolbar(&self, cx: &AppContext) -> bool {
         self.read(cx).show_toolbar()
     }
@@ -0,0 +1,368 @@
+//! Named layouts: the arrangement of the center panes, the state of the docks and optionally the
+//! items open in the panes, saved under a name to be switched to later.
+//!
+//! Layouts are saved either for a workspace, or for the user, in which case they can be applied
+//! in any workspace and never include items.
+
+use crate::{
+    dock::{Dock, DockPosition},
+    item::ItemHandle,
+    persistence::{
+        model::{SerializedLayout, SerializedLayoutDock, SerializedLayoutMember},
+        SerializedAxis, DB,
+    },
+    Member, Pane, PaneAxis, PaneGroup, Workspace, WorkspaceId,
+};
+use anyhow::{anyhow, Context as _, Result};
+use gpui::{px, AnyWeakView, AppContext, Task, View, ViewContext, WindowContext};
+use project::ProjectPath;
+use std::path::{Path, PathBuf};
+use util::ResultExt;
+
+/// A layout that can be applied to a workspace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedLayout {
+    pub name: String,
+    /// Whether the layout belongs to the user rather than to the workspace.
+    pub global: bool,
+}
+
+impl Workspace {
+    /// Lists the layouts of this workspace, followed by the ones of the user that it doesn't
+    /// shadow, sorted by name.
+    pub fn layouts(&self, cx: &AppContext) -> Result<Vec<SavedLayout>> {
+        Ok(DB
+            .layouts(self.layouts_workspace_id(cx))?
+            .into_iter()
+            .map(|(name, global, _)| SavedLayout { name, global })
+            .collect())
+    }
+
+    /// Saves the current arrangement of the workspace under the given name. Items are only saved
+    /// in the layouts of a workspace.
+    pub fn save_layout(
+        &mut self,
+        name: String,
+        global: bool,
+        include_items: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let workspace_id = if global {
+            None
+        } else if let Some(workspace_id) = self.layouts_workspace_id(cx) {
+            Some(workspace_id)
+        } else {
+            return Task::ready(Err(anyhow!("layouts can only be saved for local projects")));
+        };
+
+        let layout = SerializedLayout {
+            center: self.serialize_layout_member(&self.center.root, include_items && !global, cx),
+            left_dock: serialize_layout_dock(&self.left_dock, cx),
+            right_dock: serialize_layout_dock(&self.right_dock, cx),
+            bottom_dock: serialize_layout_dock(&self.bottom_dock, cx),
+        };
+        // The workspace has to be stored for its layouts to refer to it.
+        let serialize = self.serialize_workspace(cx);
+        cx.background_executor().spawn(async move {
+            serialize.await;
+            DB.save_layout(workspace_id, name, layout).await
+        })
+    }
+
+    pub fn delete_layout(&mut self, name: String, global: bool, cx: &mut ViewContext<Self>) {
+        let workspace_id = if global {
+            None
+        } else {
+            self.layouts_workspace_id(cx)
+        };
+        cx.background_executor()
+            .spawn(DB.delete_layout(workspace_id, name))
+            .detach_and_log_err(cx);
+    }
+
+    /// Rearranges the workspace as described by the layout with the given name. Items open in the
+    /// workspace are kept, either in the pane the layout puts them in or in its active pane.
+    pub fn apply_layout(&mut self, name: &str, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let layout = DB
+            .layouts(self.layouts_workspace_id(cx))
+            .and_then(|layouts| {
+                layouts
+                    .into_iter()
+                    .find(|(layout_name, _, _)| layout_name == name)
+                    .map(|(_, _, layout)| layout)
+                    .with_context(|| format!("no layout named {name:?}"))
+            });
+        let layout = match layout {
+            Ok(layout) => layout,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let project = self.project.clone();
+        let old_panes = self.center.panes().into_iter().cloned().collect::<Vec<_>>();
+        let mut old_items = Vec::new();
+        for pane in &old_panes {
+            pane.update(cx, |pane, cx| {
+                old_items.extend(pane.items().map(|item| {
+                    let abs_path = item
+                        .project_path(cx)
+                        .and_then(|path| project.read(cx).absolute_path(&path, cx));
+                    (abs_path, item.boxed_clone())
+                }));
+                for ix in (0..pane.items_len()).rev() {
+                    pane.remove_item(ix, false, cx);
+                }
+            });
+        }
+
+        let mut layout_panes = Vec::new();
+        let root =
+            self.deserialize_layout_member(layout.center, &mut old_items, &mut layout_panes, cx);
+        let active_pane = layout_panes
+            .iter()
+            .find(|layout_pane| layout_pane.active)
+            .or(layout_panes.first())
+            .map(|layout_pane| layout_pane.pane.clone())
+            .expect("a layout has at least one pane");
+        // Items the layout doesn't know about stay open in its active pane.
+        active_pane.update(cx, |pane, cx| {
+            for (_, item) in old_items {
+                pane.add_item(item, false, false, None, cx);
+            }
+        });
+
+        self.remove_panes(self.center.root.clone(), cx);
+        for pane in &old_panes {
+            self.unfollow(pane, cx);
+            self.last_leaders_by_pane.remove(&pane.downgrade());
+        }
+        self.center = PaneGroup::with_root(root);
+        self.active_pane = active_pane.clone();
+        self.last_active_center_pane = Some(active_pane.downgrade());
+
+        let mut zoomed_panel = None;
+        for (dock, layout_dock) in [
+            (self.left_dock.clone(), layout.left_dock),
+            (self.right_dock.clone(), layout.right_dock),
+            (self.bottom_dock.clone(), layout.bottom_dock),
+        ] {
+            zoomed_panel = apply_layout_dock(&dock, &layout_dock, cx).or(zoomed_panel);
+        }
+        // Focusing the center hides zoomed docks, so only one of them or the active pane can be
+        // zoomed.
+        self.zoomed = None;
+        self.zoomed_position = None;
+        if let Some((panel, position)) = zoomed_panel {
+            self.zoomed = Some(panel);
+            self.zoomed_position = Some(position);
+        } else {
+            let zoomed = layout_panes
+                .iter()
+                .any(|layout_pane| layout_pane.zoomed && layout_pane.pane == active_pane);
+            active_pane.update(cx, |pane, cx| {
+                pane.set_zoomed(zoomed, cx);
+                pane.focus(cx);
+            });
+            if zoomed {
+                self.zoomed = Some(active_pane.downgrade().into());
+            }
+        }
+
+        let mut open_tasks = Vec::new();
+        for layout_pane in &layout_panes {
+            for path in &layout_pane.paths_to_open {
+                open_tasks.push(self.open_path(
+                    path.clone(),
+                    Some(layout_pane.pane.downgrade()),
+                    false,
+                    cx,
+                ));
+            }
+        }
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            for task in open_tasks {
+                task.await.log_err();
+            }
+            this.update(&mut cx, |this, cx| {
+                let project = this.project.clone();
+                for layout_pane in layout_panes {
+                    let Some(active_item) = layout_pane.active_item else {
+                        continue;
+                    };
+                    layout_pane.pane.update(cx, |pane, cx| {
+                        let ix = pane.items().position(|item| {
+                            item.project_path(cx)
+                                .and_then(|path| project.read(cx).absolute_path(&path, cx))
+                                .as_deref()
+                                == Some(active_item.as_path())
+                        });
+                        if let Some(ix) = ix {
+                            pane.activate_item(ix, false, false, cx);
+                        }
+                    });
+                }
+                this.serialize_workspace(cx).detach();
+            })
+        })
+    }
+
+    /// The workspace that layouts of their own are saved for. Only local projects are stored.
+    fn layouts_workspace_id(&self, cx: &AppContext) -> Option<WorkspaceId> {
+        self.location(cx)
+            .filter(|location| !location.paths().is_empty())
+            .map(|_| self.database_id)
+    }
+
+    fn serialize_layout_member(
+        &self,
+        member: &Member,
+        include_items: bool,
+        cx: &WindowContext,
+    ) -> SerializedLayoutMember {
+        match member {
+            Member::Axis(PaneAxis {
+                axis,
+                members,
+                flexes,
+                bounding_boxes: _,
+            }) => SerializedLayoutMember::Group {
+                axis: SerializedAxis(*axis),
+                flexes: flexes.lock().clone(),
+                children: members
+                    .iter()
+                    .map(|member| self.serialize_layout_member(member, include_items, cx))
+                    .collect(),
+            },
+            Member::Pane(pane_handle) => {
+                let pane = pane_handle.read(cx);
+                let project = self.project.read(cx);
+                let abs_path =
+                    |item: &Box<dyn ItemHandle>| project.absolute_path(&item.project_path(cx)?, cx);
+                let (items, active_item) = if include_items {
+                    (
+                        pane.items().filter_map(&abs_path).collect(),
+                        pane.active_item().as_ref().and_then(&abs_path),
+                    )
+                } else {
+                    (Vec::new(), None)
+                };
+                SerializedLayoutMember::Pane {
+                    items,
+                    active_item,
+                    active: pane_handle == &self.active_pane,
+                    zoomed: pane.is_zoomed(),
+                }
+            }
+        }
+    }
+
+    fn deserialize_layout_member(
+        &mut self,
+        member: SerializedLayoutMember,
+        old_items: &mut Vec<(Option<PathBuf>, Box<dyn ItemHandle>)>,
+        layout_panes: &mut Vec<LayoutPane>,
+        cx: &mut ViewContext<Self>,
+    ) -> Member {
+        match member {
+            SerializedLayoutMember::Group {
+                axis,
+                flexes,
+                children,
+            } => {
+                let members = children
+                    .into_iter()
+                    .map(|child| self.deserialize_layout_member(child, old_items, layout_panes, cx))
+                    .collect::<Vec<_>>();
+                let flexes = Some(flexes).filter(|flexes| flexes.len() == members.len());
+                Member::Axis(PaneAxis::load(axis.0, members, flexes))
+            }
+            SerializedLayoutMember::Pane {
+                items,
+                active_item,
+                active,
+                zoomed,
+            } => {
+                let pane = self.add_pane(cx);
+                let mut paths_to_open = Vec::new();
+                for abs_path in items {
+                    let old_item_ix = old_items
+                        .iter()
+                        .position(|(path, _)| path.as_ref() == Some(&abs_path));
+                    if let Some(ix) = old_item_ix {
+                        let (_, item) = old_items.remove(ix);
+                        pane.update(cx, |pane, cx| pane.add_item(item, false, false, None, cx));
+                    } else if let Some(project_path) = self.layout_project_path(&abs_path, cx) {
+                        paths_to_open.push(project_path);
+                    }
+                }
+                layout_panes.push(LayoutPane {
+                    pane: pane.clone(),
+                    paths_to_open,
+                    active_item,
+                    active,
+                    zoomed,
+                });
+                Member::Pane(pane)
+            }
+        }
+    }
+
+    /// Items outside of the worktrees of the project are not reopened.
+    fn layout_project_path(&self, abs_path: &Path, cx: &AppContext) -> Option<ProjectPath> {
+        let (worktree, path) = self.project.read(cx).find_local_worktree(abs_path, cx)?;
+        Some(ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.into(),
+        })
+    }
+}
+
+struct LayoutPane {
+    pane: View<Pane>,
+    paths_to_open: Vec<ProjectPath>,
+    active_item: Option<PathBuf>,
+    active: bool,
+    zoomed: bool,
+}
+
+fn serialize_layout_dock(dock: &View<Dock>, cx: &WindowContext) -> SerializedLayoutDock {
+    let dock = dock.read(cx);
+    SerializedLayoutDock {
+        visible: dock.is_open(),
+        active_panel: dock
+            .active_panel()
+            .map(|panel| panel.persistent_name().to_string()),
+        zoom: dock
+            .visible_panel()
+            .map_or(false, |panel| panel.is_zoomed(cx)),
+        size: dock.active_panel().map(|panel| panel.size(cx).into()),
+    }
+}
+
+/// Returns the panel of the dock that the layout zooms in, focused.
+fn apply_layout_dock(
+    dock: &View<Dock>,
+    layout_dock: &SerializedLayoutDock,
+    cx: &mut WindowContext,
+) -> Option<(AnyWeakView, DockPosition)> {
+    dock.update(cx, |dock, cx| {
+        let panel_ix = layout_dock
+            .active_panel
+            .as_ref()
+            .and_then(|name| dock.panel_index_for_persistent_name(name, cx));
+        if let Some(panel_ix) = panel_ix {
+            dock.activate_panel(panel_ix, cx);
+            if let Some(size) = layout_dock.size {
+                dock.resize_active_panel(Some(px(size)), cx);
+            }
+        }
+        dock.zoom_out(cx);
+        dock.set_open(layout_dock.visible, cx);
+
+        let panel = dock.visible_panel().filter(|_| layout_dock.zoom)?.clone();
+        panel.set_zoomed(true, cx);
+        panel.focus_handle(cx).focus(cx);
+        Some((panel.to_any().downgrade(), dock.position()))
+    })
+}
@@ -0,0 +1,413 @@
+//! Lists of locations produced by searches, diagnostics, tasks or language servers, which can be
+//! stepped through with [`Next`] and [`Prev`], or jumped to with [`GoTo`], whatever produced
+//! them.
+//!
+//! A workspace keeps a stack of the lists most recently produced, the newest one being active
+//! until an older one is activated with [`Older`]. Locations are anchored in their buffers, so
+//! they follow the edits made after the list was produced, without keeping the buffers open.
+
+use anyhow::{anyhow, Result};
+use gpui::{
+    actions, impl_actions, AppContext, EventEmitter, Model, ModelContext, SharedString, Task,
+    WeakModel,
+};
+use language::{Anchor, Bias, Buffer, Point, ToPoint};
+use project::{Project, ProjectPath, WorktreeId};
+use serde::Deserialize;
+use std::{ops::Range, path::Path, sync::Arc};
+
+actions!(location_list, [Next, Prev, Older, Newer]);
+
+/// Jumps to an entry of the active list.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct GoTo {
+    /// The index of the entry, or none for the entry that was jumped to last.
+    #[serde(default)]
+    pub index: Option<usize>,
+}
+
+impl_actions!(location_list, [GoTo]);
+
+/// Number of lists kept in the stack of a workspace.
+const LOCATION_LIST_LIMIT: usize = 10;
+
+/// A location of a list, described by the line it starts on or by a message.
+///
+/// Entries don't keep their buffer open. Jumping to an entry whose buffer was closed reopens
+/// it by path, at the position the entry had when the buffer was last read.
+#[derive(Clone, Debug)]
+pub struct LocationEntry {
+    buffer: WeakModel<Buffer>,
+    project_path: Option<ProjectPath>,
+    range: Range<Anchor>,
+    /// The position of the range in the last buffer it was read from.
+    position: Point,
+    /// The line the range started on in the last buffer it was read from.
+    line: SharedString,
+    message: Option<SharedString>,
+}
+
+impl LocationEntry {
+    /// Returns an entry for the given range, described by the line it starts on.
+    pub fn new(buffer: Model<Buffer>, range: Range<Anchor>, cx: &AppContext) -> Self {
+        let snapshot = buffer.read(cx);
+        let project_path = project_path(snapshot);
+        let position = range.start.to_point(snapshot);
+        let line = line_at(snapshot, position.row);
+        Self {
+            buffer: buffer.downgrade(),
+            project_path,
+            range,
+            position,
+            line,
+            message: None,
+        }
+    }
+
+    /// Describes the entry with a message rather than with its line, like for diagnostics.
+    pub fn with_message(mut self, message: impl Into<SharedString>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// The message of the entry, or the line it's on, as currently edited if its buffer is
+    /// open.
+    pub fn label(&self, cx: &AppContext) -> SharedString {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+        match self.buffer.upgrade() {
+            Some(buffer) => {
+                let buffer = buffer.read(cx);
+                line_at(buffer, self.range.start.to_point(buffer).row)
+            }
+            None => self.line.clone(),
+        }
+    }
+
+    /// The start of the entry, following the edits of its buffer while it's open.
+    pub fn position(&self, cx: &AppContext) -> Point {
+        match self.buffer.upgrade() {
+            Some(buffer) => self.range.start.to_point(buffer.read(cx)),
+            None => self.position,
+        }
+    }
+
+    /// The path of the entry's file, relative to its worktree.
+    pub fn path(&self, cx: &AppContext) -> Option<Arc<Path>> {
+        match self.buffer.upgrade() {
+            Some(buffer) => buffer.read(cx).file().map(|file| file.path().clone()),
+            None => self.project_path.as_ref().map(|path| path.path.clone()),
+        }
+    }
+
+    /// Returns the buffer of the entry and the start of the entry in it, reopening the buffer
+    /// if it was closed since the entry was produced.
+    pub fn open(
+        &self,
+        project: &Model<Project>,
+        cx: &mut AppContext,
+    ) -> Task<Result<(Model<Buffer>, Point)>> {
+        if let Some(buffer) = self.buffer.upgrade() {
+            let position = self.range.start.to_point(buffer.read(cx));
+            return Task::ready(Ok((buffer, position)));
+        }
+        let Some(project_path) = self.project_path.clone() else {
+            return Task::ready(Err(anyhow!("the buffer of the location was closed")));
+        };
+
+        let position = self.position;
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let position =
+                buffer.read_with(&cx, |buffer, _| buffer.clip_point(position, Bias::Left))?;
+            Ok((buffer, position))
+        })
+    }
+
+    /// Attaches the entry to a buffer it was reopened in.
+    fn reattach(&mut self, buffer: &Model<Buffer>, cx: &AppContext) {
+        let snapshot = buffer.read(cx);
+        let position = snapshot.clip_point(self.position, Bias::Left);
+        self.buffer = buffer.downgrade();
+        self.range = snapshot.anchor_after(position)..snapshot.anchor_after(position);
+    }
+}
+
+fn project_path(buffer: &Buffer) -> Option<ProjectPath> {
+    let file = buffer.file()?;
+    Some(ProjectPath {
+        worktree_id: WorktreeId::from_usize(file.worktree_id()),
+        path: file.path().clone(),
+    })
+}
+
+/// The trimmed text of a line of a buffer.
+fn line_at(buffer: &Buffer, row: u32) -> SharedString {
+    buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+        .collect::<String>()
+        .trim()
+        .to_string()
+        .into()
+}
+
+pub struct LocationList {
+    pub title: SharedString,
+    pub entries: Vec<LocationEntry>,
+    /// The entry that was jumped to last.
+    pub selected: Option<usize>,
+}
+
+impl LocationList {
+    pub fn new(title: impl Into<SharedString>, entries: Vec<LocationEntry>) -> Self {
+        Self {
+            title: title.into(),
+            entries,
+            selected: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocationListsEvent {
+    /// A list was pushed on the stack.
+    ListAdded,
+    /// An entry of the active list was selected, to be jumped to.
+    EntrySelected(usize),
+}
+
+/// The stack of the location lists of a workspace.
+#[derive(Default)]
+pub struct LocationLists {
+    lists: Vec<LocationList>,
+    active_index: usize,
+}
+
+impl EventEmitter<LocationListsEvent> for LocationLists {}
+
+impl LocationLists {
+    /// Pushes a list on the stack and activates it. As when producing a list after going back to
+    /// an older one, the lists newer than the active one are dropped.
+    pub fn push(&mut self, list: LocationList, cx: &mut ModelContext<Self>) {
+        self.lists.truncate(self.active_index + 1);
+        self.lists.push(list);
+        let excess = self.lists.len().saturating_sub(LOCATION_LIST_LIMIT);
+        self.lists.drain(..excess);
+        self.active_index = self.lists.len() - 1;
+        cx.emit(LocationListsEvent::ListAdded);
+        cx.notify();
+    }
+
+    pub fn lists(&self) -> &[LocationList] {
+        &self.lists
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active_index
+    }
+
+    pub fn active_list(&self) -> Option<&LocationList> {
+        self.lists.get(self.active_index)
+    }
+
+    /// Selects an entry of the active list, returning it to be jumped to.
+    pub fn select(&mut self, ix: usize, cx: &mut ModelContext<Self>) -> Option<LocationEntry> {
+        let list = self.lists.get_mut(self.active_index)?;
+        let entry = list.entries.get(ix)?.clone();
+        list.selected = Some(ix);
+        cx.emit(LocationListsEvent::EntrySelected(ix));
+        cx.notify();
+        Some(entry)
+    }
+
+    /// Selects the entry that was jumped to last, or the first entry if none is selected yet.
+    pub fn select_current(&mut self, cx: &mut ModelContext<Self>) -> Option<LocationEntry> {
+        let selected = self.active_list()?.selected;
+        self.select(selected.unwrap_or(0), cx)
+    }
+
+    /// Selects the entry after the selected one, or the first entry if none is selected yet.
+    pub fn select_next(&mut self, cx: &mut ModelContext<Self>) -> Option<LocationEntry> {
+        let selected = self.active_list()?.selected;
+        self.select(selected.map_or(0, |ix| ix + 1), cx)
+    }
+
+    /// Selects the entry before the selected one, or the last entry if none is selected yet.
+    pub fn select_prev(&mut self, cx: &mut ModelContext<Self>) -> Option<LocationEntry> {
+        let list = self.active_list()?;
+        let ix = match list.selected {
+            Some(ix) => ix.checked_sub(1)?,
+            None => list.entries.len().checked_sub(1)?,
+        };
+        self.select(ix, cx)
+    }
+
+    /// Attaches the entries whose buffer was closed since they were produced to the buffer it
+    /// was reopened as, so that they follow its edits again.
+    pub fn reattach_buffer(&mut self, buffer: &Model<Buffer>, cx: &AppContext) {
+        let Some(project_path) = project_path(buffer.read(cx)) else {
+            return;
+        };
+        for entry in self.lists.iter_mut().flat_map(|list| &mut list.entries) {
+            if entry.buffer.upgrade().is_none()
+                && entry.project_path.as_ref() == Some(&project_path)
+            {
+                entry.reattach(buffer, cx);
+            }
+        }
+    }
+
+    /// Activates the list produced before the active one, returning whether there was one.
+    pub fn activate_older(&mut self, cx: &mut ModelContext<Self>) -> bool {
+        if self.active_index == 0 {
+            return false;
+        }
+        self.active_index -= 1;
+        cx.notify();
+        true
+    }
+
+    /// Activates the list produced after the active one, returning whether there was one.
+    pub fn activate_newer(&mut self, cx: &mut ModelContext<Self>) -> bool {
+        if self.active_index + 1 >= self.lists.len() {
+            return false;
+        }
+        self.active_index += 1;
+        cx.notify();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context, TestAppContext};
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    fn test_location_lists(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| {
+            let id = language::BufferId::new(cx.entity_id().as_u64()).unwrap();
+            Buffer::new(0, id, "one\n  two\nthree\n")
+        });
+        let list = |title: &str, cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                let entries = (0..3)
+                    .map(|row| {
+                        let snapshot = buffer.read(cx).snapshot();
+                        let anchor = snapshot.anchor_after(Point::new(row, 0));
+                        LocationEntry::new(buffer.clone(), anchor..anchor, cx)
+                    })
+                    .collect();
+                LocationList::new(title.to_string(), entries)
+            })
+        };
+        let lists = cx.new_model(|_| LocationLists::default());
+
+        let first = list("first", cx);
+        lists.update(cx, |lists, cx| {
+            lists.push(first, cx);
+            assert_eq!(
+                lists.active_list().unwrap().entries[1].label(cx).as_ref(),
+                "two"
+            );
+
+            let label =
+                |entry: Option<LocationEntry>, cx: &AppContext| entry.map(|entry| entry.label(cx));
+            assert_eq!(label(lists.select_prev(cx), cx), Some("three".into()));
+            assert_eq!(label(lists.select_prev(cx), cx), Some("two".into()));
+            assert_eq!(label(lists.select_next(cx), cx), Some("three".into()));
+            assert_eq!(label(lists.select_next(cx), cx), None);
+            assert_eq!(lists.active_list().unwrap().selected, Some(2));
+        });
+
+        // Entries follow the edits of their buffer, and so do their labels.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "zero\n"), (6..9, "2")], None, cx)
+        });
+        lists.read_with(cx, |lists, cx| {
+            let entries = &lists.active_list().unwrap().entries;
+            assert_eq!(entries[0].position(cx), Point::new(1, 0));
+            assert_eq!(entries[1].label(cx).as_ref(), "2");
+        });
+
+        // Producing a list after going back to an older one drops the newer ones.
+        let second = list("second", cx);
+        let third = list("third", cx);
+        lists.update(cx, |lists, cx| {
+            lists.push(second, cx);
+            assert!(lists.activate_older(cx));
+            assert!(!lists.activate_older(cx));
+            assert_eq!(lists.active_list().unwrap().title.as_ref(), "first");
+            lists.push(third, cx);
+            let titles = lists
+                .lists()
+                .iter()
+                .map(|list| list.title.as_ref())
+                .collect::<Vec<_>>();
+            assert_eq!(titles, ["first", "third"]);
+            assert!(!lists.activate_newer(cx));
+        });
+
+        for _ in 0..LOCATION_LIST_LIMIT {
+            let list = list("more", cx);
+            lists.update(cx, |lists, cx| lists.push(list, cx));
+        }
+        lists.read_with(cx, |lists, _| {
+            assert_eq!(lists.lists().len(), LOCATION_LIST_LIMIT);
+            assert_eq!(lists.active_index(), LOCATION_LIST_LIMIT - 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_reopen_closed_buffer(cx: &mut TestAppContext) {
+        crate::tests::init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "one\ntwo\nthree\n" }))
+            .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_buffer((worktree_id, "a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let entry = cx.update(|cx| {
+            let anchor = buffer.read(cx).anchor_after(Point::new(2, 0));
+            LocationEntry::new(buffer.clone(), anchor..anchor, cx)
+        });
+        let lists = cx.new_model(|_| LocationLists::default());
+        lists.update(cx, |lists, cx| {
+            lists.push(LocationList::new("list", vec![entry]), cx)
+        });
+
+        // Lists don't keep the buffers of their entries open.
+        drop(buffer);
+        cx.run_until_parked();
+        let entry = lists.read_with(cx, |lists, _| {
+            lists.active_list().unwrap().entries[0].clone()
+        });
+        assert!(entry.buffer.upgrade().is_none());
+        cx.read(|cx| {
+            assert_eq!(entry.label(cx).as_ref(), "three");
+            assert_eq!(entry.path(cx).as_deref(), Some(Path::new("a.txt")));
+        });
+
+        // Jumping to the entry reopens its buffer, whose edits the entry follows again.
+        let (buffer, position) = cx.update(|cx| entry.open(&project, cx)).await.unwrap();
+        assert_eq!(position, Point::new(2, 0));
+        lists.update(cx, |lists, cx| lists.reattach_buffer(&buffer, cx));
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        lists.read_with(cx, |lists, cx| {
+            let entry = &lists.active_list().unwrap().entries[0];
+            assert_eq!(entry.position(cx), Point::new(3, 0));
+        });
+    }
+}
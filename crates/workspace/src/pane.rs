@@ -1,5 +1,8 @@
 use crate::{
-    item::{ClosePosition, Item, ItemHandle, ItemSettings, WeakItemHandle},
+    item::{
+        ClosePosition, Item, ItemHandle, ItemSettings, PreviewTabsSettings, TabSortBy,
+        WeakItemHandle,
+    },
     toolbar::Toolbar,
     workspace_settings::{AutosaveSetting, WorkspaceSettings},
     NewCenterTerminal, NewFile, NewSearch, OpenVisible, SplitDirection, ToggleZoom, Workspace,
@@ -109,11 +112,13 @@ actions!(
         GoBack,
         DeploySearch,
         GoForward,
+        KeepPreviewItem,
         ReopenClosedItem,
         SplitLeft,
         SplitUp,
         SplitRight,
         SplitDown,
+        TogglePinTab,
     ]
 );
 
@@ -167,6 +172,10 @@ pub struct Pane {
     focus_handle: FocusHandle,
     items: Vec<Box<dyn ItemHandle>>,
     activation_history: Vec<EntityId>,
+    /// The item shown in the preview tab, replaced by the next item opened for preview.
+    preview_item_id: Option<EntityId>,
+    /// The number of pinned items, which always come first in the pane.
+    pinned_tab_count: usize,
     zoomed: bool,
     was_focused: bool,
     active_item_index: usize,
@@ -206,6 +215,8 @@ struct NavHistoryState {
     paths_by_item: HashMap<EntityId, (ProjectPath, Option<PathBuf>)>,
     pane: WeakView<Pane>,
     next_timestamp: Arc<AtomicUsize>,
+    /// Items that were in the preview tab when they were removed from the pane.
+    removed_preview_items: HashSet<EntityId>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -263,6 +274,8 @@ impl Pane {
             focus_handle,
             items: Vec::new(),
             activation_history: Vec::new(),
+            preview_item_id: None,
+            pinned_tab_count: 0,
             was_focused: false,
             zoomed: false,
             active_item_index: 0,
@@ -275,6 +288,7 @@ impl Pane {
                 paths_by_item: Default::default(),
                 pane: handle.clone(),
                 next_timestamp,
+                removed_preview_items: Default::default(),
             }))),
             toolbar: cx.new_view(|_| Toolbar::new()),
             new_item_menu: None,
@@ -422,6 +436,11 @@ impl Pane {
         self.active_item_index
     }
 
+    /// The edge of the pane a dragged item is currently hovering over, if any.
+    pub fn drag_split_direction(&self) -> Option<SplitDirection> {
+        self.drag_split_direction
+    }
+
     pub fn set_can_split(&mut self, can_split: bool, cx: &mut ViewContext<Self>) {
         self.can_split = can_split;
         cx.notify();
@@ -507,13 +526,69 @@ impl Pane {
         self.toolbar.update(cx, |_, cx| cx.notify());
     }
 
+    pub fn preview_item_id(&self) -> Option<EntityId> {
+        self.preview_item_id
+    }
+
+    pub fn preview_item(&self) -> Option<Box<dyn ItemHandle>> {
+        self.preview_item_id
+            .and_then(|id| self.items.iter().find(|item| item.item_id() == id))
+            .cloned()
+    }
+
+    fn preview_item_idx(&self) -> Option<usize> {
+        let preview_item_id = self.preview_item_id?;
+        self.items
+            .iter()
+            .position(|item| item.item_id() == preview_item_id)
+    }
+
+    /// Shows the given item in the preview tab, or keeps the previewed item open when `None`.
+    pub fn set_preview_item_id(&mut self, item_id: Option<EntityId>, cx: &mut ViewContext<Self>) {
+        if self.preview_item_id == item_id {
+            return;
+        }
+        if item_id.is_some() && !PreviewTabsSettings::get_global(cx).enabled {
+            return;
+        }
+
+        self.preview_item_id = item_id;
+        cx.emit(Event::ChangeItemTitle);
+        cx.notify();
+    }
+
+    /// Keeps an item open once edited, instead of replacing it with the next item previewed.
+    pub fn handle_item_edit(&mut self, item_id: EntityId, cx: &mut ViewContext<Self>) {
+        if self.preview_item_id == Some(item_id) {
+            self.set_preview_item_id(None, cx);
+        }
+    }
+
+    fn keep_preview_item(&mut self, _: &KeepPreviewItem, cx: &mut ViewContext<Self>) {
+        self.set_preview_item_id(None, cx);
+    }
+
+    /// Closes the item in the preview tab, to replace it with another one, and returns its index.
+    pub fn close_current_preview_item(&mut self, cx: &mut ViewContext<Self>) -> Option<usize> {
+        let item_idx = self.preview_item_idx()?;
+        if self.items[item_idx].is_dirty(cx) {
+            self.set_preview_item_id(None, cx);
+            return None;
+        }
+
+        self.remove_item(item_idx, false, cx);
+        Some(item_idx)
+    }
+
     pub(crate) fn open_item(
         &mut self,
         project_entry_id: Option<ProjectEntryId>,
         focus_item: bool,
+        allow_preview: bool,
         cx: &mut ViewContext<Self>,
         build_item: impl FnOnce(&mut ViewContext<Pane>) -> Box<dyn ItemHandle>,
     ) -> Box<dyn ItemHandle> {
+        let allow_preview = allow_preview && PreviewTabsSettings::get_global(cx).enabled;
         let mut existing_item = None;
         if let Some(project_entry_id) = project_entry_id {
             for (index, item) in self.items.iter().enumerate() {
@@ -528,11 +603,31 @@ impl Pane {
         }
 
         if let Some((index, existing_item)) = existing_item {
+            // Opening the previewed item for keeps moves it out of the preview tab, but
+            // navigating back or forward to it leaves it there.
+            let navigating = matches!(
+                self.nav_history.mode(),
+                NavigationMode::GoingBack | NavigationMode::GoingForward
+            );
+            if !allow_preview
+                && !navigating
+                && self.preview_item_id == Some(existing_item.item_id())
+            {
+                self.set_preview_item_id(None, cx);
+            }
             self.activate_item(index, focus_item, focus_item, cx);
             existing_item
         } else {
+            let destination_index = if allow_preview {
+                self.close_current_preview_item(cx)
+            } else {
+                None
+            };
             let new_item = build_item(cx);
-            self.add_item(new_item.clone(), true, focus_item, None, cx);
+            self.add_item(new_item.clone(), true, focus_item, destination_index, cx);
+            if allow_preview {
+                self.set_preview_item_id(Some(new_item.item_id()), cx);
+            }
             new_item
         }
     }
@@ -592,6 +687,18 @@ impl Pane {
             }
         });
 
+        // Pinned items are only moved among the pinned ones, other items always come after them.
+        match existing_item_index {
+            Some(existing_item_index) if self.is_tab_pinned(existing_item_index) => {
+                insertion_index = if destination_index.is_some() {
+                    insertion_index.min(self.pinned_tab_count - 1)
+                } else {
+                    existing_item_index
+                };
+            }
+            _ => insertion_index = insertion_index.max(self.pinned_tab_count),
+        }
+
         if let Some(existing_item_index) = existing_item_index {
             // If the item already exists, move it to the desired destination and activate it
 
@@ -633,6 +740,152 @@ impl Pane {
         }
 
         cx.emit(Event::AddItem { item });
+        self.sort_items(cx);
+        self.close_items_over_max_tabs(cx);
+    }
+
+    pub fn pinned_count(&self) -> usize {
+        self.pinned_tab_count
+    }
+
+    pub fn is_tab_pinned(&self, ix: usize) -> bool {
+        ix < self.pinned_tab_count
+    }
+
+    fn pinned_item_ids(&self) -> Vec<EntityId> {
+        self.items[..self.pinned_tab_count]
+            .iter()
+            .map(|item| item.item_id())
+            .collect()
+    }
+
+    fn toggle_pin_tab(&mut self, _: &TogglePinTab, cx: &mut ViewContext<Self>) {
+        if self.items.is_empty() {
+            return;
+        }
+        let ix = self.active_item_index;
+        if self.is_tab_pinned(ix) {
+            self.unpin_tab_at(ix, cx);
+        } else {
+            self.pin_tab_at(ix, cx);
+        }
+    }
+
+    /// Pins the item at the given index, moving it after the items pinned so far.
+    pub fn pin_tab_at(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if ix >= self.items.len() || self.is_tab_pinned(ix) {
+            return;
+        }
+        // Pinned items are kept open, so they can't be in the preview tab.
+        if self.preview_item_id == Some(self.items[ix].item_id()) {
+            self.set_preview_item_id(None, cx);
+        }
+
+        self.move_item_within_pane(ix, self.pinned_tab_count);
+        self.pinned_tab_count += 1;
+        cx.emit(Event::ChangeItemTitle);
+        cx.notify();
+    }
+
+    /// Unpins the item at the given index, moving it before the other unpinned items.
+    pub fn unpin_tab_at(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if !self.is_tab_pinned(ix) {
+            return;
+        }
+
+        self.pinned_tab_count -= 1;
+        self.move_item_within_pane(ix, self.pinned_tab_count);
+        self.sort_items(cx);
+        cx.emit(Event::ChangeItemTitle);
+        cx.notify();
+    }
+
+    /// Moves an item to another index of the pane, keeping the same item active.
+    fn move_item_within_pane(&mut self, from_ix: usize, to_ix: usize) {
+        let active_item_id = self.active_item().map(|item| item.item_id());
+        let item = self.items.remove(from_ix);
+        self.items.insert(to_ix, item);
+        self.restore_active_item_index(active_item_id);
+    }
+
+    fn restore_active_item_index(&mut self, active_item_id: Option<EntityId>) {
+        if let Some(ix) = active_item_id.and_then(|active_item_id| {
+            self.items
+                .iter()
+                .position(|item| item.item_id() == active_item_id)
+        }) {
+            self.active_item_index = ix;
+        }
+    }
+
+    /// Orders the unpinned items as configured by the `tabs.sort_by` setting.
+    fn sort_items(&mut self, cx: &mut ViewContext<Self>) {
+        let sort_by = ItemSettings::get_global(cx).sort_by;
+        if sort_by == TabSortBy::Manual || self.items.len() <= self.pinned_tab_count + 1 {
+            return;
+        }
+
+        let active_item_id = self.active_item().map(|item| item.item_id());
+        let unpinned_items = &mut self.items[self.pinned_tab_count..];
+        match sort_by {
+            TabSortBy::Manual => {}
+            TabSortBy::Recency => {
+                // Items never activated come last.
+                let activation_history = &self.activation_history;
+                unpinned_items.sort_by_key(|item| {
+                    cmp::Reverse(
+                        activation_history
+                            .iter()
+                            .position(|&item_id| item_id == item.item_id()),
+                    )
+                });
+            }
+            TabSortBy::Name => {
+                // Items without a title, such as terminals, come last.
+                let cx: &AppContext = cx;
+                unpinned_items.sort_by_cached_key(|item| {
+                    let title = item
+                        .tab_description(0, cx)
+                        .map(|title| title.to_lowercase());
+                    (title.is_none(), title)
+                });
+            }
+        }
+        self.restore_active_item_index(active_item_id);
+        self.tab_bar_scroll_handle
+            .scroll_to_item(self.active_item_index);
+        cx.notify();
+    }
+
+    /// Closes the least recently used items past the `tabs.max_tabs` setting, sparing the
+    /// pinned, dirty and active ones.
+    fn close_items_over_max_tabs(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(max_tabs) = ItemSettings::get_global(cx).max_tabs else {
+            return;
+        };
+        let excess_count = self.items.len().saturating_sub(max_tabs.get());
+        if excess_count == 0 {
+            return;
+        }
+
+        let active_item_id = self.active_item().map(|item| item.item_id());
+        let mut item_ids_to_close = self.items[self.pinned_tab_count..]
+            .iter()
+            .filter(|item| Some(item.item_id()) != active_item_id && !item.is_dirty(cx))
+            .map(|item| item.item_id())
+            .collect::<Vec<_>>();
+        // Items never activated are the least recently used ones.
+        item_ids_to_close.sort_by_key(|item_id| {
+            self.activation_history
+                .iter()
+                .position(|activated_item_id| activated_item_id == item_id)
+        });
+
+        for item_id in item_ids_to_close.into_iter().take(excess_count) {
+            if let Some(ix) = self.items.iter().position(|item| item.item_id() == item_id) {
+                self.remove_item(ix, false, cx);
+            }
+        }
     }
 
     pub fn items_len(&self) -> usize {
@@ -792,10 +1045,11 @@ impl Pane {
         }
 
         let active_item_id = self.items[self.active_item_index].item_id();
+        let pinned_item_ids = self.pinned_item_ids();
         Some(self.close_items(
             cx,
             action.save_intent.unwrap_or(SaveIntent::Close),
-            move |item_id| item_id != active_item_id,
+            move |item_id| item_id != active_item_id && !pinned_item_ids.contains(&item_id),
         ))
     }
 
@@ -806,6 +1060,7 @@ impl Pane {
     ) -> Option<Task<Result<()>>> {
         let item_ids: Vec<_> = self
             .items()
+            .skip(self.pinned_tab_count)
             .filter(|item| !item.is_dirty(cx))
             .map(|item| item.item_id())
             .collect();
@@ -831,10 +1086,12 @@ impl Pane {
         item_id: EntityId,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
+        let pinned_item_ids = self.pinned_item_ids();
         let item_ids: Vec<_> = self
             .items()
             .take_while(|item| item.item_id() != item_id)
             .map(|item| item.item_id())
+            .filter(|item_id| !pinned_item_ids.contains(item_id))
             .collect();
         self.close_items(cx, SaveIntent::Close, move |item_id| {
             item_ids.contains(&item_id)
@@ -858,11 +1115,13 @@ impl Pane {
         item_id: EntityId,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
+        let pinned_item_ids = self.pinned_item_ids();
         let item_ids: Vec<_> = self
             .items()
             .rev()
             .take_while(|item| item.item_id() != item_id)
             .map(|item| item.item_id())
+            .filter(|item_id| !pinned_item_ids.contains(item_id))
             .collect();
         self.close_items(cx, SaveIntent::Close, move |item_id| {
             item_ids.contains(&item_id)
@@ -878,11 +1137,12 @@ impl Pane {
             return None;
         }
 
-        Some(
-            self.close_items(cx, action.save_intent.unwrap_or(SaveIntent::Close), |_| {
-                true
-            }),
-        )
+        let pinned_item_ids = self.pinned_item_ids();
+        Some(self.close_items(
+            cx,
+            action.save_intent.unwrap_or(SaveIntent::Close),
+            move |item_id| !pinned_item_ids.contains(&item_id),
+        ))
     }
 
     pub(super) fn file_names_for_prompt(
@@ -1061,6 +1321,9 @@ impl Pane {
         }
 
         let item = self.items.remove(item_index);
+        if item_index < self.pinned_tab_count {
+            self.pinned_tab_count -= 1;
+        }
 
         cx.emit(Event::RemoveItem {
             item_id: item.item_id(),
@@ -1079,6 +1342,15 @@ impl Pane {
         item.deactivated(cx);
         self.nav_history.set_mode(NavigationMode::Normal);
 
+        if self.preview_item_id == Some(item.item_id()) {
+            self.preview_item_id = None;
+            self.nav_history
+                .0
+                .lock()
+                .removed_preview_items
+                .insert(item.item_id());
+        }
+
         if let Some(path) = item.project_path(cx) {
             let abs_path = self
                 .nav_history
@@ -1306,8 +1578,10 @@ impl Pane {
         cx: &mut ViewContext<'_, Pane>,
     ) -> impl IntoElement {
         let is_active = ix == self.active_item_index;
+        let is_pinned = self.is_tab_pinned(ix);
 
-        let label = item.tab_content(Some(detail), is_active, cx);
+        // Pinned tabs are kept compact, without the details telling apart items with the same title.
+        let label = item.tab_content((!is_pinned).then_some(detail), is_active, cx);
         let close_side = &ItemSettings::get_global(cx).close_position;
 
         let indicator = maybe!({
@@ -1321,6 +1595,7 @@ impl Pane {
         });
 
         let item_id = item.item_id();
+        let is_preview = self.preview_item_id == Some(item_id);
         let is_first_item = ix == 0;
         let is_last_item = ix == self.items.len() - 1;
         let position_relative_to_active_item = ix.cmp(&self.active_item_index);
@@ -1338,9 +1613,13 @@ impl Pane {
                 ClosePosition::Right => ui::TabCloseSide::End,
             })
             .selected(is_active)
-            .on_click(
-                cx.listener(move |pane: &mut Self, _, cx| pane.activate_item(ix, true, true, cx)),
-            )
+            .on_click(cx.listener(move |pane: &mut Self, event: &ClickEvent, cx| {
+                // Double clicking a tab keeps it open, as for any other item opened for keeps.
+                if event.up.click_count > 1 && pane.preview_item_id == Some(item_id) {
+                    pane.set_preview_item_id(None, cx);
+                }
+                pane.activate_item(ix, true, true, cx)
+            }))
             // TODO: This should be a click listener with the middle mouse button instead of a mouse down listener.
             .on_mouse_down(
                 MouseButton::Middle,
@@ -1384,7 +1663,21 @@ impl Pane {
                 tab.tooltip(move |cx| Tooltip::text(text.clone(), cx))
             })
             .start_slot::<Indicator>(indicator)
-            .end_slot(
+            .end_slot(if is_pinned {
+                IconButton::new("unpin tab", IconName::Pin)
+                    .shape(IconButtonShape::Square)
+                    .icon_color(Color::Muted)
+                    .size(ButtonSize::None)
+                    .icon_size(IconSize::XSmall)
+                    .on_click(cx.listener(move |pane, _, cx| {
+                        if let Some(ix) =
+                            pane.items.iter().position(|item| item.item_id() == item_id)
+                        {
+                            pane.unpin_tab_at(ix, cx);
+                        }
+                    }))
+                    .tooltip(|cx| Tooltip::text("Unpin Tab", cx))
+            } else {
                 IconButton::new("close tab", IconName::Close)
                     .shape(IconButtonShape::Square)
                     .icon_color(Color::Muted)
@@ -1393,9 +1686,9 @@ impl Pane {
                     .on_click(cx.listener(move |pane, _, cx| {
                         pane.close_item_by_id(item_id, SaveIntent::Close, cx)
                             .detach_and_log_err(cx);
-                    })),
-            )
-            .child(label);
+                    }))
+            })
+            .child(div().when(is_preview, |this| this.italic()).child(label));
 
         let single_entry_to_resolve = {
             let item_entries = self.items[ix].project_entry_ids(cx);
@@ -1411,7 +1704,35 @@ impl Pane {
             let pane = pane.clone();
             ContextMenu::build(cx, move |mut menu, cx| {
                 if let Some(pane) = pane.upgrade() {
+                    if is_preview {
+                        menu = menu
+                            .entry(
+                                "Keep Open",
+                                Some(Box::new(KeepPreviewItem)),
+                                cx.handler_for(&pane, move |pane, cx| {
+                                    pane.set_preview_item_id(None, cx);
+                                }),
+                            )
+                            .separator();
+                    }
                     menu = menu
+                        .entry(
+                            if is_pinned { "Unpin Tab" } else { "Pin Tab" },
+                            Some(Box::new(TogglePinTab)),
+                            cx.handler_for(&pane, move |pane, cx| {
+                                let Some(ix) =
+                                    pane.items.iter().position(|item| item.item_id() == item_id)
+                                else {
+                                    return;
+                                };
+                                if pane.is_tab_pinned(ix) {
+                                    pane.unpin_tab_at(ix, cx);
+                                } else {
+                                    pane.pin_tab_at(ix, cx);
+                                }
+                            }),
+                        )
+                        .separator()
                         .entry(
                             "Close",
                             Some(Box::new(CloseActiveItem { save_intent: None })),
@@ -1789,6 +2110,8 @@ impl Render for Pane {
             .on_action(cx.listener(|pane, _: &GoBack, cx| pane.navigate_backward(cx)))
             .on_action(cx.listener(|pane, _: &GoForward, cx| pane.navigate_forward(cx)))
             .on_action(cx.listener(Pane::toggle_zoom))
+            .on_action(cx.listener(Pane::keep_preview_item))
+            .on_action(cx.listener(Pane::toggle_pin_tab))
             .on_action(cx.listener(|pane: &mut Pane, action: &ActivateItem, cx| {
                 pane.activate_item(action.0, true, true, cx);
             }))
@@ -2088,6 +2411,7 @@ impl NavHistory {
     pub fn remove_item(&mut self, item_id: EntityId) {
         let mut state = self.0.lock();
         state.paths_by_item.remove(&item_id);
+        state.removed_preview_items.remove(&item_id);
         state
             .backward_stack
             .retain(|entry| entry.item.id() != item_id);
@@ -2102,6 +2426,12 @@ impl NavHistory {
     pub fn path_for_item(&self, item_id: EntityId) -> Option<(ProjectPath, Option<PathBuf>)> {
         self.0.lock().paths_by_item.get(&item_id).cloned()
     }
+
+    /// Whether the item was in the preview tab when it was removed from the pane, so that
+    /// navigating back to it reopens it there.
+    pub fn removed_as_preview(&self, item_id: EntityId) -> bool {
+        self.0.lock().removed_preview_items.contains(&item_id)
+    }
 }
 
 impl NavHistoryState {
@@ -2134,6 +2464,7 @@ mod tests {
     use gpui::{TestAppContext, VisualTestContext};
     use project::FakeFs;
     use settings::SettingsStore;
+    use std::num::NonZeroUsize;
     use theme::LoadThemes;
 
     #[gpui::test]
@@ -2580,6 +2911,216 @@ mod tests {
         assert_item_labels(&pane, [], cx);
     }
 
+    #[gpui::test]
+    async fn test_preview_items(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, None, cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+        let open_preview = |label: &'static str, pane: &View<Pane>, cx: &mut VisualTestContext| {
+            pane.update(cx, |pane, cx| {
+                pane.open_item(None, true, true, cx, |cx| {
+                    Box::new(cx.new_view(|cx| TestItem::new(cx).with_label(label)))
+                })
+            })
+        };
+
+        add_labeled_item(&pane, "A", false, cx);
+        let b = open_preview("B", &pane, cx);
+        assert_item_labels(&pane, ["A", "B*"], cx);
+        pane.update(cx, |pane, _| {
+            assert_eq!(pane.preview_item_id(), Some(b.item_id()))
+        });
+
+        // Previewing another item replaces the previewed one, in the same tab.
+        pane.update(cx, |pane, cx| pane.activate_item(0, false, false, cx));
+        let c = open_preview("C", &pane, cx);
+        assert_item_labels(&pane, ["A", "C*"], cx);
+        pane.update(cx, |pane, _| {
+            assert_eq!(pane.preview_item_id(), Some(c.item_id()))
+        });
+
+        // Kept items are not replaced.
+        pane.update(cx, |pane, cx| pane.keep_preview_item(&KeepPreviewItem, cx));
+        pane.update(cx, |pane, _| assert_eq!(pane.preview_item_id(), None));
+        open_preview("D", &pane, cx);
+        assert_item_labels(&pane, ["A", "C", "D*"], cx);
+
+        // Neither are edited ones.
+        let d = pane.update(cx, |pane, _| pane.preview_item().unwrap());
+        pane.update(cx, |pane, cx| pane.handle_item_edit(d.item_id(), cx));
+        pane.update(cx, |pane, _| assert_eq!(pane.preview_item_id(), None));
+        let e = open_preview("E", &pane, cx);
+        assert_item_labels(&pane, ["A", "C", "D", "E*"], cx);
+
+        // Closing the previewed item leaves no preview item behind, and remembers it was
+        // previewed, unlike kept items.
+        pane.update(cx, |pane, cx| {
+            pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["A", "C", "D*"], cx);
+        pane.update(cx, |pane, cx| {
+            assert_eq!(pane.preview_item_id(), None);
+            assert!(pane.nav_history().removed_as_preview(e.item_id()));
+            pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["A", "C*"], cx);
+        pane.update(cx, |pane, _| {
+            assert!(!pane.nav_history().removed_as_preview(d.item_id()))
+        });
+
+        // Navigating to the previewed item leaves it in the preview tab.
+        let f = pane.update(cx, |pane, cx| {
+            pane.open_item(Some(ProjectEntryId::from_proto(1)), true, true, cx, |cx| {
+                Box::new(cx.new_view(|cx| {
+                    TestItem::new(cx)
+                        .with_label("F")
+                        .with_singleton(true)
+                        .with_project_items(&[TestProjectItem::new(1, "F.txt", cx)])
+                }))
+            })
+        });
+        assert_item_labels(&pane, ["A", "C", "F*"], cx);
+        pane.update(cx, |pane, cx| {
+            pane.activate_item(0, false, false, cx);
+            pane.nav_history_mut().set_mode(NavigationMode::GoingBack);
+            pane.open_item(
+                Some(ProjectEntryId::from_proto(1)),
+                true,
+                false,
+                cx,
+                |_| unreachable!(),
+            );
+            pane.nav_history_mut().set_mode(NavigationMode::Normal);
+            assert_eq!(pane.preview_item_id(), Some(f.item_id()));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_pinned_tabs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, None, cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+        set_labeled_items(&pane, ["A", "B", "C", "D*"], cx);
+
+        // Pinned items move after the items pinned so far.
+        pane.update(cx, |pane, cx| {
+            pane.pin_tab_at(2, cx);
+            pane.pin_tab_at(3, cx);
+        });
+        assert_item_labels(&pane, ["C", "D*", "A", "B"], cx);
+        pane.update(cx, |pane, _| assert_eq!(pane.pinned_count(), 2));
+
+        // New items are added after the pinned ones.
+        pane.update(cx, |pane, cx| pane.activate_item(0, false, false, cx));
+        add_labeled_item(&pane, "E", false, cx);
+        assert_item_labels(&pane, ["C", "D", "E*", "A", "B"], cx);
+
+        // Bulk closes leave the pinned items open.
+        pane.update(cx, |pane, cx| {
+            pane.close_inactive_items(&CloseInactiveItems { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["C", "D", "E*"], cx);
+
+        pane.update(cx, |pane, cx| {
+            pane.close_all_items(&CloseAllItems { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["C*", "D"], cx);
+
+        // Unpinned items move before the other unpinned items.
+        pane.update(cx, |pane, cx| pane.activate_item(1, false, false, cx));
+        add_labeled_item(&pane, "F", false, cx);
+        pane.update(cx, |pane, cx| pane.unpin_tab_at(0, cx));
+        assert_item_labels(&pane, ["D", "C", "F*"], cx);
+        pane.update(cx, |pane, _| assert_eq!(pane.pinned_count(), 1));
+
+        // Closing a pinned item individually unpins it.
+        pane.update(cx, |pane, cx| pane.activate_item(0, false, false, cx));
+        pane.update(cx, |pane, cx| {
+            pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        pane.update(cx, |pane, _| assert_eq!(pane.pinned_count(), 0));
+    }
+
+    #[gpui::test]
+    async fn test_tab_sorting_and_max_tabs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, None, cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+        let add_titled_item = |title: &'static str, cx: &mut VisualTestContext| {
+            pane.update(cx, |pane, cx| {
+                let item = cx.new_view(|cx| {
+                    let mut item = TestItem::new(cx).with_label(title);
+                    item.tab_descriptions = Some(vec![title]);
+                    item
+                });
+                pane.add_item(Box::new(item), false, false, None, cx);
+            });
+        };
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ItemSettings>(cx, |settings| {
+                settings.sort_by = Some(TabSortBy::Name);
+            });
+        });
+        add_titled_item("C", cx);
+        add_titled_item("A", cx);
+        add_titled_item("B", cx);
+        assert_item_labels(&pane, ["A", "B*", "C"], cx);
+
+        // Pinned items are not sorted with the others.
+        pane.update(cx, |pane, cx| pane.pin_tab_at(2, cx));
+        add_titled_item("D", cx);
+        assert_item_labels(&pane, ["C", "A", "B", "D*"], cx);
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ItemSettings>(cx, |settings| {
+                settings.sort_by = Some(TabSortBy::Recency);
+            });
+        });
+        pane.update(cx, |pane, cx| pane.activate_item(1, false, false, cx));
+        add_labeled_item(&pane, "E", true, cx);
+        assert_item_labels(&pane, ["C", "E*^", "A", "D", "B"], cx);
+
+        // The least recently used clean and unpinned items are closed past the maximum.
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<ItemSettings>(cx, |settings| {
+                settings.sort_by = Some(TabSortBy::Manual);
+                settings.max_tabs = NonZeroUsize::new(4);
+            });
+        });
+        add_labeled_item(&pane, "F", false, cx);
+        assert_item_labels(&pane, ["C", "E^", "F*", "A"], cx);
+        add_labeled_item(&pane, "G", false, cx);
+        assert_item_labels(&pane, ["C", "E^", "F", "G*"], cx);
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
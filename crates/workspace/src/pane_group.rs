@@ -1,4 +1,7 @@
-use crate::{pane_group::element::pane_axis, AppState, FollowerState, Pane, Workspace};
+use crate::{
+    pane_group::element::{pane_axis, OnResize},
+    AppState, FollowerState, Pane, Workspace,
+};
 use anyhow::{anyhow, Result};
 use call::{ActiveCall, ParticipantLocation};
 use collections::HashMap;
@@ -9,8 +12,9 @@ use gpui::{
 use parking_lot::Mutex;
 use project::Project;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 use ui::prelude::*;
+use util::ResultExt;
 
 pub const HANDLE_HITBOX_SIZE: f32 = 4.0;
 const HORIZONTAL_MIN_SIZE: f32 = 80.;
@@ -24,6 +28,18 @@ pub struct PaneGroup {
     pub(crate) root: Member,
 }
 
+/// The arrangement of the panes of a group, for pane groups that live outside of the center of
+/// a workspace, like in panels, and serialize themselves.
+#[derive(Clone)]
+pub enum PaneLayout {
+    Axis {
+        axis: Axis,
+        members: Vec<PaneLayout>,
+        flexes: Option<Vec<f32>>,
+    },
+    Pane(View<Pane>),
+}
+
 impl PaneGroup {
     pub(crate) fn with_root(root: Member) -> Self {
         Self { root }
@@ -91,6 +107,15 @@ impl PaneGroup {
         };
     }
 
+    /// Builds a group from a layout. Returns `None` if the layout has no panes.
+    pub fn from_layout(layout: PaneLayout) -> Option<Self> {
+        Some(Self::with_root(Member::from_layout(layout)?))
+    }
+
+    pub fn layout(&self) -> PaneLayout {
+        self.root.layout()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         &self,
@@ -102,6 +127,12 @@ impl PaneGroup {
         app_state: &Arc<AppState>,
         cx: &mut ViewContext<Workspace>,
     ) -> impl IntoElement {
+        let workspace = cx.view().downgrade();
+        let on_resize: OnResize = Rc::new(move |cx| {
+            workspace
+                .update(cx, |workspace, cx| workspace.schedule_serialize(cx))
+                .log_err();
+        });
         self.root.render(
             project,
             0,
@@ -110,17 +141,43 @@ impl PaneGroup {
             active_pane,
             zoomed,
             app_state,
+            &on_resize,
             cx,
         )
     }
 
-    pub(crate) fn panes(&self) -> Vec<&View<Pane>> {
+    /// Renders the panes of a group that isn't the center of a workspace, so without any of the
+    /// state of collaborators. Resizing its splits calls `on_resize`, for the owner of the group
+    /// to serialize the new flexes.
+    pub fn render_standalone(
+        &self,
+        project: &Model<Project>,
+        active_pane: &View<Pane>,
+        zoomed: Option<&AnyWeakView>,
+        app_state: &Arc<AppState>,
+        on_resize: impl Fn(&mut WindowContext) + 'static,
+        cx: &mut ViewContext<Workspace>,
+    ) -> impl IntoElement {
+        self.root.render(
+            project,
+            0,
+            &HashMap::default(),
+            None,
+            active_pane,
+            zoomed,
+            app_state,
+            &(Rc::new(on_resize) as OnResize),
+            cx,
+        )
+    }
+
+    pub fn panes(&self) -> Vec<&View<Pane>> {
         let mut panes = Vec::new();
         self.root.collect_panes(&mut panes);
         panes
     }
 
-    pub(crate) fn first_pane(&self) -> View<Pane> {
+    pub fn first_pane(&self) -> View<Pane> {
         self.root.first_pane()
     }
 }
@@ -132,6 +189,39 @@ pub(crate) enum Member {
 }
 
 impl Member {
+    fn from_layout(layout: PaneLayout) -> Option<Self> {
+        match layout {
+            PaneLayout::Axis {
+                axis,
+                members,
+                flexes,
+            } => {
+                let mut members = members
+                    .into_iter()
+                    .filter_map(Self::from_layout)
+                    .collect::<Vec<_>>();
+                if members.len() > 1 {
+                    let flexes = flexes.filter(|flexes| flexes.len() == members.len());
+                    Some(Member::Axis(PaneAxis::load(axis, members, flexes)))
+                } else {
+                    members.pop()
+                }
+            }
+            PaneLayout::Pane(pane) => Some(Member::Pane(pane)),
+        }
+    }
+
+    fn layout(&self) -> PaneLayout {
+        match self {
+            Member::Axis(axis) => PaneLayout::Axis {
+                axis: axis.axis,
+                members: axis.members.iter().map(Member::layout).collect(),
+                flexes: Some(axis.flexes.lock().clone()),
+            },
+            Member::Pane(pane) => PaneLayout::Pane(pane.clone()),
+        }
+    }
+
     fn new_axis(old_pane: View<Pane>, new_pane: View<Pane>, direction: SplitDirection) -> Self {
         use Axis::*;
         use SplitDirection::*;
@@ -173,6 +263,7 @@ impl Member {
         active_pane: &View<Pane>,
         zoomed: Option<&AnyWeakView>,
         app_state: &Arc<AppState>,
+        on_resize: &OnResize,
         cx: &mut ViewContext<Workspace>,
     ) -> impl IntoElement {
         match self {
@@ -297,6 +388,7 @@ impl Member {
                     active_pane,
                     zoomed,
                     app_state,
+                    on_resize,
                     cx,
                 )
                 .into_any(),
@@ -488,6 +580,7 @@ impl PaneAxis {
         active_pane: &View<Pane>,
         zoomed: Option<&AnyWeakView>,
         app_state: &Arc<AppState>,
+        on_resize: &OnResize,
         cx: &mut ViewContext<Workspace>,
     ) -> gpui::AnyElement {
         debug_assert!(self.members.len() == self.flexes.lock().len());
@@ -498,7 +591,7 @@ impl PaneAxis {
             basis,
             self.flexes.clone(),
             self.bounding_boxes.clone(),
-            cx.view().downgrade(),
+            on_resize.clone(),
         )
         .children(self.members.iter().enumerate().map(|(ix, member)| {
             if member.contains(active_pane) {
@@ -513,6 +606,7 @@ impl PaneAxis {
                     active_pane,
                     zoomed,
                     app_state,
+                    on_resize,
                     cx,
                 )
                 .into_any_element()
@@ -598,17 +692,13 @@ mod element {
 
     use gpui::{
         px, relative, Along, AnyElement, Axis, Bounds, Element, IntoElement, MouseDownEvent,
-        MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Size, Style, WeakView,
-        WindowContext,
+        MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Size, Style, WindowContext,
     };
     use gpui::{CursorStyle, Hitbox};
     use parking_lot::Mutex;
     use settings::Settings;
     use smallvec::SmallVec;
     use ui::prelude::*;
-    use util::ResultExt;
-
-    use crate::Workspace;
 
     use crate::WorkspaceSettings;
 
@@ -616,12 +706,15 @@ mod element {
 
     const DIVIDER_SIZE: f32 = 1.0;
 
+    /// Called when the flexes of an axis change, for the owner of its group to serialize them.
+    pub(crate) type OnResize = Rc<dyn Fn(&mut WindowContext)>;
+
     pub(super) fn pane_axis(
         axis: Axis,
         basis: usize,
         flexes: Arc<Mutex<Vec<f32>>>,
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnResize,
     ) -> PaneAxisElement {
         PaneAxisElement {
             axis,
@@ -630,7 +723,7 @@ mod element {
             bounding_boxes,
             children: SmallVec::new(),
             active_pane_ix: None,
-            workspace,
+            on_resize,
         }
     }
 
@@ -641,7 +734,7 @@ mod element {
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
         children: SmallVec<[AnyElement; 2]>,
         active_pane_ix: Option<usize>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnResize,
     }
 
     pub struct PaneAxisLayout {
@@ -674,7 +767,7 @@ mod element {
             axis: Axis,
             child_start: Point<Pixels>,
             container_size: Size<Pixels>,
-            workspace: WeakView<Workspace>,
+            on_resize: &OnResize,
             cx: &mut WindowContext,
         ) {
             let min_size = match axis {
@@ -747,10 +840,9 @@ mod element {
 
                 proposed_current_pixel_change -= current_pixel_change;
             }
-
-            workspace
-                .update(cx, |this, cx| this.schedule_serialize(cx))
-                .log_err();
+            // The owner of the group reads the flexes to serialize them.
+            drop(flexes);
+            on_resize(cx);
             cx.stop_propagation();
             cx.refresh();
         }
@@ -920,17 +1012,17 @@ mod element {
                     cx.on_mouse_event({
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let handle_hitbox = handle.hitbox.clone();
                         move |e: &MouseDownEvent, phase, cx| {
                             if phase.bubble() && handle_hitbox.is_hovered(cx) {
                                 dragged_handle.replace(Some(ix));
                                 if e.click_count >= 2 {
-                                    let mut borrow = flexes.lock();
-                                    *borrow = vec![1.; borrow.len()];
-                                    workspace
-                                        .update(cx, |this, cx| this.schedule_serialize(cx))
-                                        .log_err();
+                                    {
+                                        let mut borrow = flexes.lock();
+                                        *borrow = vec![1.; borrow.len()];
+                                    }
+                                    on_resize(cx);
 
                                     cx.refresh();
                                 }
@@ -939,7 +1031,7 @@ mod element {
                         }
                     });
                     cx.on_mouse_event({
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
                         let child_bounds = child.bounds;
@@ -955,7 +1047,7 @@ mod element {
                                         axis,
                                         child_bounds.origin,
                                         bounds.size,
-                                        workspace.clone(),
+                                        &on_resize,
                                         cx,
                                     )
                                 }
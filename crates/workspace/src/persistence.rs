@@ -5,6 +5,7 @@ use std::path::Path;
 use anyhow::{anyhow, bail, Context, Result};
 use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::sql};
 use gpui::{point, size, Axis, Bounds};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
@@ -17,8 +18,8 @@ use uuid::Uuid;
 use crate::WorkspaceId;
 
 use model::{
-    GroupId, PaneId, SerializedItem, SerializedPane, SerializedPaneGroup, SerializedWorkspace,
-    WorkspaceLocation,
+    GroupId, PaneId, SerializedDetachedPane, SerializedDetachedPanel, SerializedItem,
+    SerializedLayout, SerializedPane, SerializedPaneGroup, SerializedWorkspace, WorkspaceLocation,
 };
 
 use self::model::DockStructure;
@@ -58,6 +59,26 @@ impl sqlez::bindable::Column for SerializedAxis {
     }
 }
 
+impl Serialize for SerializedAxis {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            gpui::Axis::Horizontal => "Horizontal",
+            gpui::Axis::Vertical => "Vertical",
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerializedAxis {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "Horizontal" => Ok(Self(Axis::Horizontal)),
+            "Vertical" => Ok(Self(Axis::Vertical)),
+            axis => Err(serde::de::Error::custom(format!("invalid axis {axis:?}"))),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct SerializedWindowsBounds(pub(crate) Bounds<gpui::GlobalPixels>);
 
@@ -154,6 +175,7 @@ define_connection! {
     //     pane_id: usize, // Primary key for panes
     //     workspace_id: usize, // References workspaces table
     //     active: bool,
+    //     pinned_count: usize, // Number of pinned items, which come first in the pane
     // )
     //
     // center_panes(
@@ -162,6 +184,37 @@ define_connection! {
     //     position: Option<usize>, // None indicates this is the root
     // )
     //
+    // detached_panes(
+    //     pane_id: usize, // Primary key for detached_panes
+    //     workspace_id: usize, // References workspaces table
+    //     position: usize, // Order in which the windows of the panes were opened
+    //     window_state: String, // WindowBounds Discriminant
+    //     window_x: Option<f32>, // WindowBounds::Fixed RectF x
+    //     window_y: Option<f32>, // WindowBounds::Fixed RectF y
+    //     window_width: Option<f32>, // WindowBounds::Fixed RectF width
+    //     window_height: Option<f32>, // WindowBounds::Fixed RectF height
+    //     display: Option<Uuid>, // Display id
+    // )
+    //
+    // detached_panels(
+    //     workspace_id: usize, // References workspaces table
+    //     position: usize, // Order in which the windows of the panels were opened
+    //     panel_name: String, // The persistent name of the panel
+    //     window_state: String, // WindowBounds Discriminant
+    //     window_x: Option<f32>, // WindowBounds::Fixed RectF x
+    //     window_y: Option<f32>, // WindowBounds::Fixed RectF y
+    //     window_width: Option<f32>, // WindowBounds::Fixed RectF width
+    //     window_height: Option<f32>, // WindowBounds::Fixed RectF height
+    //     display: Option<Uuid>, // Display id
+    // )
+    //
+    // layouts(
+    //     layout_id: usize, // Primary key for layouts
+    //     workspace_id: Option<usize>, // References workspaces table. None for the layouts of the user
+    //     name: String, // Unique per workspace
+    //     layout: String, // A JSON encoded SerializedLayout
+    // )
+    //
     // CREATE TABLE items(
     //     item_id: usize, // This is the item's view id, so this is not unique
     //     workspace_id: usize, // References workspaces table
@@ -169,6 +222,7 @@ define_connection! {
     //     kind: String, // Indicates which view this connects to. This is the key in the item_deserializers global
     //     position: usize, // Position of the item in the parent pane. This is equivalent to panes' position column
     //     active: bool, // Indicates if this item is the active one in the pane
+    //     preview: bool // Indicates if this item is a preview item
     // )
     pub static ref DB: WorkspaceDb<()> =
     &[sql!(
@@ -280,6 +334,63 @@ define_connection! {
     sql!(
         ALTER TABLE workspaces ADD COLUMN fullscreen INTEGER; //bool
     ),
+    // Add preview field to items
+    sql!(
+        ALTER TABLE items ADD COLUMN preview INTEGER; //bool
+    ),
+    // Add pinned_count field to panes
+    sql!(
+        ALTER TABLE panes ADD COLUMN pinned_count INTEGER DEFAULT 0;
+    ),
+    // Add panes detached into windows of their own
+    sql!(
+        CREATE TABLE detached_panes(
+            pane_id INTEGER PRIMARY KEY,
+            workspace_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            window_state TEXT,
+            window_x REAL,
+            window_y REAL,
+            window_width REAL,
+            window_height REAL,
+            display BLOB,
+            FOREIGN KEY(pane_id) REFERENCES panes(pane_id)
+            ON DELETE CASCADE,
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
+    // Add named layouts, for a workspace or for all of them
+    sql!(
+        CREATE TABLE layouts(
+            layout_id INTEGER PRIMARY KEY,
+            workspace_id INTEGER,
+            name TEXT NOT NULL,
+            layout TEXT NOT NULL,
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
+    // Add dock panels detached into windows of their own
+    sql!(
+        CREATE TABLE detached_panels(
+            workspace_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            panel_name TEXT NOT NULL,
+            window_state TEXT,
+            window_x REAL,
+            window_y REAL,
+            window_width REAL,
+            window_height REAL,
+            display BLOB,
+            PRIMARY KEY(workspace_id, position),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
@@ -342,6 +453,16 @@ impl WorkspaceDb {
             fullscreen: fullscreen.unwrap_or(false),
             display,
             docks,
+            detached_panes: self
+                .get_detached_panes(workspace_id)
+                .context("Getting detached panes")
+                .log_err()
+                .unwrap_or_default(),
+            detached_panels: self
+                .get_detached_panels(workspace_id)
+                .context("Getting detached panels")
+                .log_err()
+                .unwrap_or_default(),
         })
     }
 
@@ -353,7 +474,10 @@ impl WorkspaceDb {
                 // Clear out panes and pane_groups
                 conn.exec_bound(sql!(
                     DELETE FROM pane_groups WHERE workspace_id = ?1;
-                    DELETE FROM panes WHERE workspace_id = ?1;))?(workspace.id)
+                    DELETE FROM panes WHERE workspace_id = ?1;
+                    DELETE FROM detached_panels WHERE workspace_id = ?1;))?(
+                    workspace.id
+                )
                 .expect("Clearing old panes");
 
                 conn.exec_bound(sql!(
@@ -398,6 +522,16 @@ impl WorkspaceDb {
                 Self::save_pane_group(conn, workspace.id, &workspace.center_group, None)
                     .context("save pane group in save workspace")?;
 
+                // Save the panes in windows of their own
+                for (position, detached_pane) in workspace.detached_panes.iter().enumerate() {
+                    Self::save_detached_pane(conn, workspace.id, detached_pane, position)
+                        .context("save detached pane in save workspace")?;
+                }
+                for (position, detached_panel) in workspace.detached_panels.iter().enumerate() {
+                    Self::save_detached_panel(conn, workspace.id, detached_panel, position)
+                        .context("save detached panel in save workspace")?;
+                }
+
                 Ok(())
             })
             .log_err();
@@ -474,6 +608,7 @@ impl WorkspaceDb {
                 SerializedPaneGroup::Pane(SerializedPane {
                     active: true,
                     children: vec![],
+                    pinned_count: 0,
                 })
             }))
     }
@@ -489,15 +624,17 @@ impl WorkspaceDb {
             Option<SerializedAxis>,
             Option<PaneId>,
             Option<bool>,
+            Option<usize>,
             Option<String>,
         );
         self.select_bound::<GroupKey, GroupOrPane>(sql!(
-            SELECT group_id, axis, pane_id, active, flexes
+            SELECT group_id, axis, pane_id, active, pinned_count, flexes
                 FROM (SELECT
                         group_id,
                         axis,
                         NULL as pane_id,
                         NULL as active,
+                        NULL as pinned_count,
                         position,
                         parent_group_id,
                         workspace_id,
@@ -509,6 +646,7 @@ impl WorkspaceDb {
                         NULL,
                         center_panes.pane_id,
                         panes.active as active,
+                        panes.pinned_count as pinned_count,
                         position,
                         parent_group_id,
                         panes.workspace_id as workspace_id,
@@ -519,7 +657,7 @@ impl WorkspaceDb {
                 ORDER BY position
         ))?((group_id, workspace_id))?
         .into_iter()
-        .map(|(group_id, axis, pane_id, active, flexes)| {
+        .map(|(group_id, axis, pane_id, active, pinned_count, flexes)| {
             if let Some((group_id, axis)) = group_id.zip(axis) {
                 let flexes = flexes
                     .map(|flexes: String| serde_json::from_str::<Vec<f32>>(&flexes))
@@ -534,6 +672,7 @@ impl WorkspaceDb {
                 Ok(SerializedPaneGroup::Pane(SerializedPane::new(
                     self.get_items(pane_id)?,
                     active,
+                    pinned_count.unwrap_or(0),
                 )))
             } else {
                 bail!("Pane Group Child was neither a pane group or a pane");
@@ -604,12 +743,7 @@ impl WorkspaceDb {
         pane: &SerializedPane,
         parent: Option<(GroupId, usize)>,
     ) -> Result<PaneId> {
-        let pane_id = conn.select_row_bound::<_, i64>(sql!(
-            INSERT INTO panes(workspace_id, active)
-            VALUES (?, ?)
-            RETURNING pane_id
-        ))?((workspace_id, pane.active))?
-        .ok_or_else(|| anyhow!("Could not retrieve inserted pane_id"))?;
+        let pane_id = Self::insert_pane(conn, workspace_id, pane)?;
 
         let (parent_id, order) = unzip_option(parent);
         conn.exec_bound(sql!(
@@ -617,14 +751,158 @@ impl WorkspaceDb {
             VALUES (?, ?, ?)
         ))?((pane_id, parent_id, order))?;
 
+        Ok(pane_id)
+    }
+
+    fn insert_pane(
+        conn: &Connection,
+        workspace_id: WorkspaceId,
+        pane: &SerializedPane,
+    ) -> Result<PaneId> {
+        let pane_id = conn.select_row_bound::<_, i64>(sql!(
+            INSERT INTO panes(workspace_id, active, pinned_count)
+            VALUES (?, ?, ?)
+            RETURNING pane_id
+        ))?((workspace_id, pane.active, pane.pinned_count))?
+        .ok_or_else(|| anyhow!("Could not retrieve inserted pane_id"))?;
+
         Self::save_items(conn, workspace_id, pane_id, &pane.children).context("Saving items")?;
 
         Ok(pane_id)
     }
 
+    fn get_detached_panes(&self, workspace_id: WorkspaceId) -> Result<Vec<SerializedDetachedPane>> {
+        type DetachedPaneRow = (
+            PaneId,
+            bool,
+            usize,
+            Option<SerializedWindowsBounds>,
+            Option<Uuid>,
+        );
+        self.select_bound::<WorkspaceId, DetachedPaneRow>(sql!(
+            SELECT
+                detached_panes.pane_id,
+                panes.active,
+                panes.pinned_count,
+                window_state,
+                window_x,
+                window_y,
+                window_width,
+                window_height,
+                display
+            FROM detached_panes
+            JOIN panes ON detached_panes.pane_id = panes.pane_id
+            WHERE detached_panes.workspace_id = ?
+            ORDER BY position
+        ))?(workspace_id)?
+        .into_iter()
+        .map(|(pane_id, active, pinned_count, bounds, display)| {
+            Ok(SerializedDetachedPane {
+                pane: SerializedPane::new(self.get_items(pane_id)?, active, pinned_count),
+                bounds: bounds.map(|bounds| bounds.0),
+                display,
+            })
+        })
+        // Filter out the windows of panes which don't have any items
+        .filter(|detached_pane| match detached_pane {
+            Ok(detached_pane) => !detached_pane.pane.children.is_empty(),
+            _ => true,
+        })
+        .collect()
+    }
+
+    fn save_detached_pane(
+        conn: &Connection,
+        workspace_id: WorkspaceId,
+        detached_pane: &SerializedDetachedPane,
+        position: usize,
+    ) -> Result<()> {
+        let pane_id = Self::insert_pane(conn, workspace_id, &detached_pane.pane)?;
+
+        conn.exec_bound(sql!(
+            INSERT INTO detached_panes(
+                pane_id,
+                workspace_id,
+                position,
+                window_state,
+                window_x,
+                window_y,
+                window_width,
+                window_height,
+                display
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ))?((
+            pane_id,
+            workspace_id,
+            position,
+            detached_pane.bounds.map(SerializedWindowsBounds),
+            detached_pane.display,
+        ))?;
+
+        Ok(())
+    }
+
+    fn get_detached_panels(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<SerializedDetachedPanel>> {
+        type DetachedPanelRow = (String, Option<SerializedWindowsBounds>, Option<Uuid>);
+        Ok(self.select_bound::<WorkspaceId, DetachedPanelRow>(sql!(
+            SELECT
+                panel_name,
+                window_state,
+                window_x,
+                window_y,
+                window_width,
+                window_height,
+                display
+            FROM detached_panels
+            WHERE workspace_id = ?
+            ORDER BY position
+        ))?(workspace_id)?
+        .into_iter()
+        .map(|(name, bounds, display)| SerializedDetachedPanel {
+            name,
+            bounds: bounds.map(|bounds| bounds.0),
+            display,
+        })
+        .collect())
+    }
+
+    fn save_detached_panel(
+        conn: &Connection,
+        workspace_id: WorkspaceId,
+        detached_panel: &SerializedDetachedPanel,
+        position: usize,
+    ) -> Result<()> {
+        conn.exec_bound(sql!(
+            INSERT INTO detached_panels(
+                workspace_id,
+                position,
+                panel_name,
+                window_state,
+                window_x,
+                window_y,
+                window_width,
+                window_height,
+                display
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ))?((
+            workspace_id,
+            position,
+            detached_panel.name.as_str(),
+            detached_panel.bounds.map(SerializedWindowsBounds),
+            detached_panel.display,
+        ))?;
+
+        Ok(())
+    }
+
     fn get_items(&self, pane_id: PaneId) -> Result<Vec<SerializedItem>> {
         self.select_bound(sql!(
-            SELECT kind, item_id, active FROM items
+            SELECT kind, item_id, active, preview FROM items
             WHERE pane_id = ?
                 ORDER BY position
         ))?(pane_id)
@@ -637,7 +915,7 @@ impl WorkspaceDb {
         items: &[SerializedItem],
     ) -> Result<()> {
         let mut insert = conn.exec_bound(sql!(
-            INSERT INTO items(workspace_id, pane_id, position, kind, item_id, active) VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO items(workspace_id, pane_id, position, kind, item_id, active, preview) VALUES (?, ?, ?, ?, ?, ?, ?)
         )).context("Preparing insertion")?;
         for (position, item) in items.iter().enumerate() {
             insert((workspace_id, pane_id, position, item))?;
@@ -667,6 +945,51 @@ impl WorkspaceDb {
         }
     }
 
+    /// Returns the layouts of the given workspace along with the ones of the user, which are
+    /// shadowed by layouts of the workspace with the same name.
+    pub(crate) fn layouts(
+        &self,
+        workspace_id: Option<WorkspaceId>,
+    ) -> Result<Vec<(String, bool, SerializedLayout)>> {
+        let mut layouts = self
+            .select_bound::<Option<WorkspaceId>, (String, bool, SerializedLayout)>(sql!(
+                SELECT name, workspace_id IS NULL, layout
+                FROM layouts
+                WHERE workspace_id IS NULL OR workspace_id = ?
+                ORDER BY name, workspace_id IS NULL
+            ))?(workspace_id)?;
+        layouts.dedup_by(|layout, previous| layout.0 == previous.0);
+        Ok(layouts)
+    }
+
+    /// Saves a layout under the given name, replacing any layout with the same name. Layouts
+    /// without a workspace belong to the user.
+    pub(crate) async fn save_layout(
+        &self,
+        workspace_id: Option<WorkspaceId>,
+        name: String,
+        layout: SerializedLayout,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_layout", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM layouts WHERE workspace_id IS ? AND name = ?
+                ))?((workspace_id, name.as_str()))?;
+                conn.exec_bound(sql!(
+                    INSERT INTO layouts(workspace_id, name, layout) VALUES (?, ?, ?)
+                ))?((workspace_id, name.as_str(), &layout))
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) async fn delete_layout(workspace_id: Option<WorkspaceId>, name: String) -> Result<()> {
+            DELETE FROM layouts
+            WHERE workspace_id IS ? AND name = ?
+        }
+    }
+
     query! {
         pub(crate) async fn set_fullscreen(workspace_id: WorkspaceId, fullscreen: bool) -> Result<()> {
             UPDATE workspaces
@@ -760,6 +1083,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         let workspace_2 = SerializedWorkspace {
@@ -770,6 +1095,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         db.save_workspace(workspace_1.clone()).await;
@@ -837,26 +1164,29 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 5, false),
-                                SerializedItem::new("Terminal", 6, true),
+                                SerializedItem::new("Terminal", 5, false, false),
+                                SerializedItem::new("Terminal", 6, true, false),
                             ],
                             false,
+                            0,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 7, true),
-                                SerializedItem::new("Terminal", 8, false),
+                                SerializedItem::new("Terminal", 7, true, false),
+                                SerializedItem::new("Terminal", 8, false, false),
                             ],
                             false,
+                            0,
                         )),
                     ],
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 9, false),
-                        SerializedItem::new("Terminal", 10, true),
+                        SerializedItem::new("Terminal", 9, false, false),
+                        SerializedItem::new("Terminal", 10, true, false),
                     ],
                     false,
+                    0,
                 )),
             ],
         );
@@ -869,6 +1199,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         db.save_workspace(workspace.clone()).await;
@@ -898,6 +1230,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         let mut workspace_2 = SerializedWorkspace {
@@ -908,6 +1242,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         db.save_workspace(workspace_1.clone()).await;
@@ -945,6 +1281,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         };
 
         db.save_workspace(workspace_3.clone()).await;
@@ -965,7 +1303,11 @@ mod tests {
     }
 
     use crate::persistence::model::SerializedWorkspace;
-    use crate::persistence::model::{SerializedItem, SerializedPane, SerializedPaneGroup};
+    use crate::persistence::model::{
+        SerializedDetachedPane, SerializedDetachedPanel, SerializedItem, SerializedPane,
+        SerializedPaneGroup,
+    };
+    use std::path::PathBuf;
 
     fn default_workspace<P: AsRef<Path>>(
         workspace_id: &[P],
@@ -979,6 +1321,8 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             fullscreen: false,
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
         }
     }
 
@@ -1001,26 +1345,29 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 1, false),
-                                SerializedItem::new("Terminal", 2, true),
+                                SerializedItem::new("Terminal", 1, false, false),
+                                SerializedItem::new("Terminal", 2, true, false),
                             ],
                             false,
+                            1,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 4, false),
-                                SerializedItem::new("Terminal", 3, true),
+                                SerializedItem::new("Terminal", 4, false, false),
+                                SerializedItem::new("Terminal", 3, true, false),
                             ],
                             true,
+                            0,
                         )),
                     ],
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 5, true),
-                        SerializedItem::new("Terminal", 6, false),
+                        SerializedItem::new("Terminal", 5, true, false),
+                        SerializedItem::new("Terminal", 6, false, true),
                     ],
                     false,
+                    0,
                 )),
             ],
         );
@@ -1034,6 +1381,178 @@ mod tests {
         assert_eq!(workspace.center_group, new_workspace.center_group);
     }
 
+    #[gpui::test]
+    async fn test_detached_panes() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_detached_panes").await);
+
+        let center_pane = SerializedPaneGroup::Pane(SerializedPane::new(
+            vec![SerializedItem::new("Terminal", 1, true, false)],
+            true,
+            0,
+        ));
+        let mut workspace = default_workspace(&["/tmp"], &center_pane);
+        workspace.detached_panes = vec![
+            SerializedDetachedPane {
+                pane: SerializedPane::new(
+                    vec![
+                        SerializedItem::new("Terminal", 2, false, false),
+                        SerializedItem::new("Terminal", 3, true, false),
+                    ],
+                    false,
+                    1,
+                ),
+                bounds: Some(Bounds {
+                    origin: point(10.0.into(), 20.0.into()),
+                    size: size(800.0.into(), 600.0.into()),
+                }),
+                display: Some(Uuid::from_u128(1)),
+            },
+            SerializedDetachedPane {
+                pane: SerializedPane::new(
+                    vec![SerializedItem::new("Terminal", 4, true, true)],
+                    false,
+                    0,
+                ),
+                bounds: None,
+                display: None,
+            },
+        ];
+
+        db.save_workspace(workspace.clone()).await;
+        let new_workspace = db.workspace_for_roots(&["/tmp"]).unwrap();
+        assert_eq!(workspace.center_group, new_workspace.center_group);
+        assert_eq!(workspace.detached_panes, new_workspace.detached_panes);
+
+        // Reattaching the panes drops their windows.
+        workspace.detached_panes.clear();
+        db.save_workspace(workspace.clone()).await;
+        let new_workspace = db.workspace_for_roots(&["/tmp"]).unwrap();
+        assert_eq!(new_workspace.detached_panes, Vec::new());
+    }
+
+    #[gpui::test]
+    async fn test_detached_panels() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_detached_panels").await);
+
+        let mut workspace = default_workspace(&["/tmp"], &Default::default());
+        workspace.detached_panels = vec![
+            SerializedDetachedPanel {
+                name: "TerminalPanel".into(),
+                bounds: Some(Bounds {
+                    origin: point(10.0.into(), 20.0.into()),
+                    size: size(800.0.into(), 600.0.into()),
+                }),
+                display: Some(Uuid::from_u128(1)),
+            },
+            SerializedDetachedPanel {
+                name: "ProjectPanel".into(),
+                bounds: None,
+                display: None,
+            },
+        ];
+
+        db.save_workspace(workspace.clone()).await;
+        let new_workspace = db.workspace_for_roots(&["/tmp"]).unwrap();
+        assert_eq!(workspace.detached_panels, new_workspace.detached_panels);
+
+        // Reattaching the panels drops their windows.
+        workspace.detached_panels.clear();
+        db.save_workspace(workspace.clone()).await;
+        let new_workspace = db.workspace_for_roots(&["/tmp"]).unwrap();
+        assert_eq!(new_workspace.detached_panels, Vec::new());
+    }
+
+    #[gpui::test]
+    async fn test_layouts() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_layouts").await);
+
+        let workspace = default_workspace(&["/tmp"], &Default::default());
+        db.save_workspace(workspace.clone()).await;
+
+        let layout = |items: Vec<&str>| SerializedLayout {
+            center: model::SerializedLayoutMember::Group {
+                axis: SerializedAxis(Axis::Horizontal),
+                flexes: vec![1., 2.],
+                children: vec![
+                    model::SerializedLayoutMember::Pane {
+                        items: items.into_iter().map(PathBuf::from).collect(),
+                        active_item: None,
+                        active: true,
+                        zoomed: false,
+                    },
+                    model::SerializedLayoutMember::Pane {
+                        items: Vec::new(),
+                        active_item: None,
+                        active: false,
+                        zoomed: true,
+                    },
+                ],
+            },
+            left_dock: model::SerializedLayoutDock {
+                visible: true,
+                active_panel: Some("ProjectPanel".into()),
+                zoom: false,
+                size: Some(240.),
+            },
+            right_dock: Default::default(),
+            bottom_dock: Default::default(),
+        };
+        let review = layout(vec!["/tmp/a.rs", "/tmp/b.rs"]);
+        let global_review = layout(Vec::new());
+        let focus = layout(Vec::new());
+
+        db.save_layout(Some(workspace.id), "review".into(), review.clone())
+            .await
+            .unwrap();
+        db.save_layout(None, "review".into(), global_review.clone())
+            .await
+            .unwrap();
+        db.save_layout(None, "focus".into(), focus.clone())
+            .await
+            .unwrap();
+
+        // The layouts of the workspace shadow the ones of the user.
+        assert_eq!(
+            db.layouts(Some(workspace.id)).unwrap(),
+            vec![
+                ("focus".to_string(), true, focus.clone()),
+                ("review".to_string(), false, review.clone()),
+            ]
+        );
+        assert_eq!(
+            db.layouts(None).unwrap(),
+            vec![
+                ("focus".to_string(), true, focus.clone()),
+                ("review".to_string(), true, global_review.clone()),
+            ]
+        );
+
+        // Saving a layout again replaces it.
+        let new_review = layout(vec!["/tmp/c.rs"]);
+        db.save_layout(Some(workspace.id), "review".into(), new_review.clone())
+            .await
+            .unwrap();
+        db.delete_layout(None, "focus".into()).await.unwrap();
+        assert_eq!(
+            db.layouts(Some(workspace.id)).unwrap(),
+            vec![("review".to_string(), false, new_review)]
+        );
+
+        db.delete_layout(Some(workspace.id), "review".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.layouts(Some(workspace.id)).unwrap(),
+            vec![("review".to_string(), true, global_review)]
+        );
+    }
+
     #[gpui::test]
     async fn test_cleanup_panes() {
         env_logger::try_init().ok();
@@ -1048,26 +1567,29 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 1, false),
-                                SerializedItem::new("Terminal", 2, true),
+                                SerializedItem::new("Terminal", 1, false, false),
+                                SerializedItem::new("Terminal", 2, true, false),
                             ],
                             false,
+                            0,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 4, false),
-                                SerializedItem::new("Terminal", 3, true),
+                                SerializedItem::new("Terminal", 4, false, false),
+                                SerializedItem::new("Terminal", 3, true, false),
                             ],
                             true,
+                            0,
                         )),
                     ],
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 5, false),
-                        SerializedItem::new("Terminal", 6, true),
+                        SerializedItem::new("Terminal", 5, false, false),
+                        SerializedItem::new("Terminal", 6, true, false),
                     ],
                     false,
+                    0,
                 )),
             ],
         );
@@ -1083,17 +1605,19 @@ mod tests {
             vec![
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 1, false),
-                        SerializedItem::new("Terminal", 2, true),
+                        SerializedItem::new("Terminal", 1, false, false),
+                        SerializedItem::new("Terminal", 2, true, false),
                     ],
                     false,
+                    0,
                 )),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 4, true),
-                        SerializedItem::new("Terminal", 3, false),
+                        SerializedItem::new("Terminal", 4, true, false),
+                        SerializedItem::new("Terminal", 3, false, false),
                     ],
                     true,
+                    0,
                 )),
             ],
         );
@@ -8,6 +8,7 @@ use db::sqlez::{
 };
 use gpui::{AsyncWindowContext, Bounds, GlobalPixels, Model, Task, View, WeakView};
 use project::Project;
+use serde::{Deserialize, Serialize};
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
@@ -73,6 +74,24 @@ pub(crate) struct SerializedWorkspace {
     pub(crate) fullscreen: bool,
     pub(crate) display: Option<Uuid>,
     pub(crate) docks: DockStructure,
+    pub(crate) detached_panes: Vec<SerializedDetachedPane>,
+    pub(crate) detached_panels: Vec<SerializedDetachedPanel>,
+}
+
+/// A pane that was moved out of the center of the workspace into a window of its own.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct SerializedDetachedPane {
+    pub(crate) pane: SerializedPane,
+    pub(crate) bounds: Option<Bounds<GlobalPixels>>,
+    pub(crate) display: Option<Uuid>,
+}
+
+/// A dock panel that was moved into a window of its own, by its persistent name.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct SerializedDetachedPanel {
+    pub(crate) name: String,
+    pub(crate) bounds: Option<Bounds<GlobalPixels>>,
+    pub(crate) display: Option<Uuid>,
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -137,6 +156,59 @@ impl Bind for DockData {
     }
 }
 
+/// A named arrangement of the center panes and the docks of a workspace, which can be switched
+/// to. Items are referred to by their absolute paths, as the layouts outlive the items.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub(crate) struct SerializedLayout {
+    pub(crate) center: SerializedLayoutMember,
+    pub(crate) left_dock: SerializedLayoutDock,
+    pub(crate) right_dock: SerializedLayoutDock,
+    pub(crate) bottom_dock: SerializedLayoutDock,
+}
+
+impl StaticColumnCount for SerializedLayout {}
+impl Bind for &SerializedLayout {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        serde_json::to_string(self)
+            .context("Serializing layout")?
+            .bind(statement, start_index)
+    }
+}
+
+impl Column for SerializedLayout {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (layout, next_index) = String::column(statement, start_index)?;
+        Ok((
+            serde_json::from_str(&layout).context("Deserializing layout")?,
+            next_index,
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum SerializedLayoutMember {
+    Group {
+        axis: SerializedAxis,
+        flexes: Vec<f32>,
+        children: Vec<SerializedLayoutMember>,
+    },
+    Pane {
+        items: Vec<PathBuf>,
+        active_item: Option<PathBuf>,
+        active: bool,
+        zoomed: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SerializedLayoutDock {
+    pub(crate) visible: bool,
+    pub(crate) active_panel: Option<String>,
+    pub(crate) zoom: bool,
+    pub(crate) size: Option<f32>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum SerializedPaneGroup {
     Group {
@@ -153,6 +225,7 @@ impl Default for SerializedPaneGroup {
         Self::Pane(SerializedPane {
             children: vec![SerializedItem::default()],
             active: false,
+            pinned_count: 0,
         })
     }
 }
@@ -229,11 +302,16 @@ impl SerializedPaneGroup {
 pub struct SerializedPane {
     pub(crate) active: bool,
     pub(crate) children: Vec<SerializedItem>,
+    pub(crate) pinned_count: usize,
 }
 
 impl SerializedPane {
-    pub fn new(children: Vec<SerializedItem>, active: bool) -> Self {
-        SerializedPane { children, active }
+    pub fn new(children: Vec<SerializedItem>, active: bool, pinned_count: usize) -> Self {
+        SerializedPane {
+            children,
+            active,
+            pinned_count,
+        }
     }
 
     pub async fn deserialize_to(
@@ -245,8 +323,7 @@ impl SerializedPane {
         cx: &mut AsyncWindowContext,
     ) -> Result<Vec<Option<Box<dyn ItemHandle>>>> {
         let mut item_tasks = Vec::new();
-        let mut active_item_index = None;
-        for (index, item) in self.children.iter().enumerate() {
+        for item in &self.children {
             let project = project.clone();
             item_tasks.push(pane.update(cx, |_, cx| {
                 if let Some(deserializer) = cx.global::<ItemDeserializers>().get(&item.kind) {
@@ -258,26 +335,44 @@ impl SerializedPane {
                     )))
                 }
             })?);
-            if item.active {
-                active_item_index = Some(index);
-            }
         }
 
         let mut items = Vec::new();
-        for item_handle in futures::future::join_all(item_tasks).await {
+        let mut active_item = None;
+        for (index, (item, item_handle)) in self
+            .children
+            .iter()
+            .zip(futures::future::join_all(item_tasks).await)
+            .enumerate()
+        {
             let item_handle = item_handle.log_err();
             items.push(item_handle.clone());
 
             if let Some(item_handle) = item_handle {
                 pane.update(cx, |pane, cx| {
                     pane.add_item(item_handle.clone(), true, true, None, cx);
+                    // Pin items as they are added, so that they are neither sorted nor closed
+                    // to respect the maximum number of tabs.
+                    if index < self.pinned_count {
+                        if let Some(ix) = pane.index_for_item(item_handle.as_ref()) {
+                            pane.pin_tab_at(ix, cx);
+                        }
+                    }
+                    if item.preview {
+                        pane.set_preview_item_id(Some(item_handle.item_id()), cx);
+                    }
                 })?;
+                if item.active {
+                    active_item = Some(item_handle);
+                }
             }
         }
 
-        if let Some(active_item_index) = active_item_index {
+        if let Some(active_item) = active_item {
             pane.update(cx, |pane, cx| {
-                pane.activate_item(active_item_index, false, false, cx);
+                if let Some(ix) = pane.index_for_item(active_item.as_ref()) {
+                    pane.activate_item(ix, false, false, cx);
+                }
             })?;
         }
 
@@ -294,14 +389,16 @@ pub struct SerializedItem {
     pub kind: Arc<str>,
     pub item_id: ItemId,
     pub active: bool,
+    pub preview: bool,
 }
 
 impl SerializedItem {
-    pub fn new(kind: impl AsRef<str>, item_id: ItemId, active: bool) -> Self {
+    pub fn new(kind: impl AsRef<str>, item_id: ItemId, active: bool, preview: bool) -> Self {
         Self {
             kind: Arc::from(kind.as_ref()),
             item_id,
             active,
+            preview,
         }
     }
 }
@@ -313,20 +410,22 @@ impl Default for SerializedItem {
             kind: Arc::from("Terminal"),
             item_id: 100000,
             active: false,
+            preview: false,
         }
     }
 }
 
 impl StaticColumnCount for SerializedItem {
     fn column_count() -> usize {
-        3
+        4
     }
 }
 impl Bind for &SerializedItem {
     fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
         let next_index = statement.bind(&self.kind, start_index)?;
         let next_index = statement.bind(&self.item_id, next_index)?;
-        statement.bind(&self.active, next_index)
+        let next_index = statement.bind(&self.active, next_index)?;
+        statement.bind(&self.preview, next_index)
     }
 }
 
@@ -335,11 +434,14 @@ impl Column for SerializedItem {
         let (kind, next_index) = Arc::<str>::column(statement, start_index)?;
         let (item_id, next_index) = ItemId::column(statement, next_index)?;
         let (active, next_index) = bool::column(statement, next_index)?;
+        // Items saved before preview tabs existed have no preview column value.
+        let (preview, next_index) = Option::<bool>::column(statement, next_index)?;
         Ok((
             SerializedItem {
                 kind,
                 item_id,
                 active,
+                preview: preview.unwrap_or(false),
             },
             next_index,
         ))
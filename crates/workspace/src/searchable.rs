@@ -30,6 +30,8 @@ pub struct SearchOptions {
     pub regex: bool,
     /// Specifies whether the item supports search & replace.
     pub replacement: bool,
+    /// Specifies whether the item can be searched for code patterns in its syntax trees.
+    pub structural: bool,
 }
 
 pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
@@ -41,6 +43,7 @@ pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
             word: true,
             regex: true,
             replacement: true,
+            structural: true,
         }
     }
 
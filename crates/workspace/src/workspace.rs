@@ -1,5 +1,9 @@
+mod detached_pane;
+mod detached_panel;
 pub mod dock;
 pub mod item;
+mod layouts;
+pub mod location_list;
 mod modal_layer;
 pub mod notifications;
 pub mod pane;
@@ -19,6 +23,10 @@ use client::{
 };
 use collections::{hash_map, HashMap, HashSet};
 use derive_more::{Deref, DerefMut};
+pub use detached_pane::DetachedPane;
+use detached_pane::DetachedPaneWindow;
+use detached_panel::DetachedPanelWindow;
+pub use detached_panel::{register_detachable_panel, DetachedPanel};
 use dock::{Dock, DockPosition, Panel, PanelButtons, PanelHandle};
 use futures::{
     channel::{mpsc, oneshot},
@@ -32,13 +40,20 @@ use gpui::{
     LayoutId, ManagedView, Model, ModelContext, PathPromptOptions, Point, PromptLevel, Render,
     Size, Subscription, Task, View, WeakView, WindowHandle, WindowOptions,
 };
-use item::{FollowableItem, FollowableItemHandle, Item, ItemHandle, ItemSettings, ProjectItem};
+use item::{
+    FollowableItem, FollowableItemHandle, Item, ItemHandle, ItemSettings, PreviewTabsSettings,
+    ProjectItem,
+};
 use itertools::Itertools;
 use language::{LanguageRegistry, Rope};
+pub use layouts::SavedLayout;
 use lazy_static::lazy_static;
+use location_list::LocationLists;
 pub use modal_layer::*;
 use node_runtime::NodeRuntime;
-use notifications::{simple_message_notification::MessageNotification, NotificationHandle};
+use notifications::{
+    simple_message_notification::MessageNotification, NotificationHandle, NotifyTaskExt,
+};
 pub use pane::*;
 pub use pane_group::*;
 use persistence::{model::SerializedWorkspace, SerializedWindowsBounds, DB};
@@ -84,7 +99,10 @@ use uuid::Uuid;
 pub use workspace_settings::{AutosaveSetting, WorkspaceSettings};
 
 use crate::persistence::{
-    model::{DockData, DockStructure, SerializedItem, SerializedPane, SerializedPaneGroup},
+    model::{
+        DockData, DockStructure, SerializedDetachedPane, SerializedDetachedPanel, SerializedItem,
+        SerializedPane, SerializedPaneGroup,
+    },
     SerializedAxis,
 };
 
@@ -128,6 +146,9 @@ actions!(
         ToggleRightDock,
         ToggleBottomDock,
         CloseAllDocks,
+        DetachPane,
+        ReattachPane,
+        ReattachPanel,
     ]
 );
 
@@ -175,17 +196,53 @@ pub struct CloseInactiveTabsAndPanes {
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SendKeystrokes(pub String);
 
+/// Moves a dock panel into a window of its own. Without a name, the panel of the focused dock
+/// is detached.
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+pub struct DetachPanel {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct ApplyLayout {
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveLayout {
+    pub name: String,
+    /// Saves the layout for every workspace, without its items.
+    #[serde(default)]
+    pub global: bool,
+    #[serde(default)]
+    pub include_items: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteLayout {
+    pub name: String,
+    #[serde(default)]
+    pub global: bool,
+}
+
 impl_actions!(
     workspace,
     [
         ActivatePane,
         ActivatePaneInDirection,
+        ApplyLayout,
         CloseAllItemsAndPanes,
         CloseInactiveTabsAndPanes,
+        DeleteLayout,
+        DetachPanel,
         NewFileInDirection,
         OpenTerminal,
         Save,
         SaveAll,
+        SaveLayout,
         SwapPaneInDirection,
         SendKeystrokes,
     ]
@@ -260,6 +317,7 @@ impl Column for WorkspaceId {
 pub fn init_settings(cx: &mut AppContext) {
     WorkspaceSettings::register(cx);
     ItemSettings::register(cx);
+    PreviewTabsSettings::register(cx);
 }
 
 pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
@@ -546,6 +604,8 @@ pub struct Workspace {
     right_dock: View<Dock>,
     panes: Vec<View<Pane>>,
     panes_by_item: HashMap<EntityId, WeakView<Pane>>,
+    detached_panes: Vec<DetachedPaneWindow>,
+    detached_panels: Vec<DetachedPanelWindow>,
     active_pane: View<Pane>,
     last_active_center_pane: Option<WeakView<Pane>>,
     last_active_view_id: Option<proto::ViewId>,
@@ -554,6 +614,7 @@ pub struct Workspace {
     titlebar_item: Option<AnyView>,
     notifications: Vec<(TypeId, usize, Box<dyn NotificationHandle>)>,
     project: Model<Project>,
+    location_lists: Model<LocationLists>,
     follower_states: HashMap<View<Pane>, FollowerState>,
     last_leaders_by_pane: HashMap<WeakView<Pane>, PeerId>,
     window_edited: bool,
@@ -813,6 +874,8 @@ impl Workspace {
             center: PaneGroup::new(center_pane.clone()),
             panes: vec![center_pane.clone()],
             panes_by_item: Default::default(),
+            detached_panes: Vec::new(),
+            detached_panels: Vec::new(),
             active_pane: center_pane.clone(),
             last_active_center_pane: Some(center_pane.downgrade()),
             last_active_view_id: None,
@@ -824,6 +887,7 @@ impl Workspace {
             bottom_dock,
             right_dock,
             project: project.clone(),
+            location_lists: cx.new_model(|_| LocationLists::default()),
             follower_states: Default::default(),
             last_leaders_by_pane: Default::default(),
             dispatching_keystrokes: Default::default(),
@@ -997,6 +1061,14 @@ impl Workspace {
     }
 
     pub fn add_panel<T: Panel>(&mut self, panel: View<T>, cx: &mut WindowContext) {
+        // A detached panel was loaded again in its own window.
+        if self
+            .detached_panels()
+            .any(|name| name == T::persistent_name())
+        {
+            return;
+        }
+
         let dock = match panel.position(cx) {
             DockPosition::Left => &self.left_dock,
             DockPosition::Bottom => &self.bottom_dock,
@@ -1024,6 +1096,11 @@ impl Workspace {
         &self.project
     }
 
+    /// The stack of location lists produced in this workspace, which any producer can push to.
+    pub fn location_lists(&self) -> &Model<LocationLists> {
+        &self.location_lists
+    }
+
     pub fn recent_navigation_history(
         &self,
         limit: Option<usize>,
@@ -1116,10 +1193,14 @@ impl Workspace {
                     // If the item is no longer present in this pane, then retrieve its
                     // project path in order to reopen it.
                     else {
-                        break pane
-                            .nav_history()
+                        let nav_history = pane.nav_history();
+                        let is_preview = matches!(
+                            mode,
+                            NavigationMode::GoingBack | NavigationMode::GoingForward
+                        ) && nav_history.removed_as_preview(entry.item.id());
+                        break nav_history
                             .path_for_item(entry.item.id())
-                            .map(|(project_path, _)| (project_path, entry));
+                            .map(|(project_path, _)| (project_path, entry, is_preview));
                     }
                 }
             })
@@ -1127,7 +1208,7 @@ impl Workspace {
             None
         };
 
-        if let Some((project_path, entry)) = to_load {
+        if let Some((project_path, entry, is_preview)) = to_load {
             // If the item was no longer present, then load it again from its previous path.
             let task = self.load_path(project_path, cx);
             cx.spawn(|workspace, mut cx| async move {
@@ -1140,7 +1221,8 @@ impl Workspace {
                     })?;
 
                     pane.update(&mut cx, |pane, cx| {
-                        let item = pane.open_item(project_entry_id, true, cx, build_item);
+                        let item =
+                            pane.open_item(project_entry_id, true, is_preview, cx, build_item);
                         navigated |= Some(item.item_id()) != prev_active_item_id;
                         pane.nav_history_mut().set_mode(NavigationMode::Normal);
                         if let Some(data) = entry.data {
@@ -1323,16 +1405,63 @@ impl Workspace {
                 }
             }
 
+            let persisted_items = if quitting {
+                this.update(&mut cx, |this, cx| this.persist_unsaved_changes(cx))?
+                    .await
+            } else {
+                HashSet::default()
+            };
+
             this.update(&mut cx, |this, cx| {
-                this.save_all_internal(SaveIntent::Close, cx)
+                this.save_all_internal(SaveIntent::Close, persisted_items, cx)
             })?
             .await
         })
     }
 
+    /// Persists the unsaved changes of the items that support restoring them, returning
+    /// the ids of those items, which don't need to be saved before quitting.
+    fn persist_unsaved_changes(&mut self, cx: &mut ViewContext<Self>) -> Task<HashSet<EntityId>> {
+        // Items are only restored along with workspaces that have a location.
+        if !WorkspaceSettings::get_global(cx).restore_unsaved_buffers || self.location(cx).is_none()
+        {
+            return Task::ready(HashSet::default());
+        }
+
+        let workspace_id = self.database_id;
+        let items = self
+            .panes
+            .iter()
+            .flat_map(|pane| pane.read(cx).items().cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let tasks = items
+            .into_iter()
+            .filter_map(|item| {
+                let task = item.persist_unsaved_changes(workspace_id, cx)?;
+                Some((item.item_id(), task))
+            })
+            .collect::<Vec<_>>();
+        let serialize_workspace = self.serialize_workspace(cx);
+
+        cx.spawn(|_, _| async move {
+            let mut persisted_items = HashSet::default();
+            for (item_id, task) in tasks {
+                if task.await.log_err().is_some() {
+                    persisted_items.insert(item_id);
+                }
+            }
+            serialize_workspace.await;
+            persisted_items
+        })
+    }
+
     fn save_all(&mut self, action: &SaveAll, cx: &mut ViewContext<Self>) {
-        self.save_all_internal(action.save_intent.unwrap_or(SaveIntent::SaveAll), cx)
-            .detach_and_log_err(cx);
+        self.save_all_internal(
+            action.save_intent.unwrap_or(SaveIntent::SaveAll),
+            HashSet::default(),
+            cx,
+        )
+        .detach_and_log_err(cx);
     }
 
     fn send_keystrokes(&mut self, action: &SendKeystrokes, cx: &mut ViewContext<Self>) {
@@ -1378,6 +1507,7 @@ impl Workspace {
     fn save_all_internal(
         &mut self,
         mut save_intent: SaveIntent,
+        skipped_items: HashSet<EntityId>,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<bool>> {
         if self.project.read(cx).is_disconnected() {
@@ -1386,9 +1516,10 @@ impl Workspace {
         let dirty_items = self
             .panes
             .iter()
+            .chain(self.detached_panes())
             .flat_map(|pane| {
                 pane.read(cx).items().filter_map(|item| {
-                    if item.is_dirty(cx) {
+                    if item.is_dirty(cx) && !skipped_items.contains(&item.item_id()) {
                         Some((pane.downgrade(), item.boxed_clone()))
                     } else {
                         None
@@ -1654,7 +1785,10 @@ impl Workspace {
         &'a self,
         cx: &'a AppContext,
     ) -> impl 'a + Iterator<Item = &Box<dyn ItemHandle>> {
-        self.panes.iter().flat_map(|pane| pane.read(cx).items())
+        self.panes
+            .iter()
+            .chain(self.detached_panes())
+            .flat_map(|pane| pane.read(cx).items())
     }
 
     pub fn item_of_type<T: Item>(&self, cx: &AppContext) -> Option<View<T>> {
@@ -1875,6 +2009,7 @@ impl Workspace {
                 return panel;
             }
         }
+        self.focus_detached_panel(T::persistent_name(), cx);
         None
     }
 
@@ -1983,6 +2118,16 @@ impl Workspace {
         pane: View<Pane>,
         item: Box<dyn ItemHandle>,
         cx: &mut WindowContext,
+    ) {
+        self.add_item_at(pane, item, None, cx);
+    }
+
+    fn add_item_at(
+        &mut self,
+        pane: View<Pane>,
+        item: Box<dyn ItemHandle>,
+        destination_index: Option<usize>,
+        cx: &mut WindowContext,
     ) {
         if let Some(text) = item.telemetry_event_text(cx) {
             self.client()
@@ -1990,7 +2135,9 @@ impl Workspace {
                 .report_app_event(format!("{}: open", text));
         }
 
-        pane.update(cx, |pane, cx| pane.add_item(item, true, true, None, cx));
+        pane.update(cx, |pane, cx| {
+            pane.add_item(item, true, true, destination_index, cx)
+        });
     }
 
     pub fn split_item(
@@ -2063,8 +2210,25 @@ impl Workspace {
         pane: Option<WeakView<Pane>>,
         focus_item: bool,
         cx: &mut WindowContext,
+    ) -> Task<Result<Box<dyn ItemHandle>, anyhow::Error>> {
+        self.open_path_preview(path, pane, focus_item, false, cx)
+    }
+
+    /// Opens the path in the preview tab of the pane when `allow_preview` is set, replacing the
+    /// item previewed there so far.
+    pub fn open_path_preview(
+        &mut self,
+        path: impl Into<ProjectPath>,
+        pane: Option<WeakView<Pane>>,
+        focus_item: bool,
+        allow_preview: bool,
+        cx: &mut WindowContext,
     ) -> Task<Result<Box<dyn ItemHandle>, anyhow::Error>> {
         let pane = pane.unwrap_or_else(|| {
+            // Items opened from the window of a detached pane go to that pane.
+            if let Some(detached_pane) = self.detached_pane_for_window(cx) {
+                return detached_pane.downgrade();
+            }
             self.last_active_center_pane.clone().unwrap_or_else(|| {
                 self.panes
                     .first()
@@ -2077,7 +2241,7 @@ impl Workspace {
         cx.spawn(move |mut cx| async move {
             let (project_entry_id, build_item) = task.await?;
             pane.update(&mut cx, |pane, cx| {
-                pane.open_item(project_entry_id, focus_item, cx, build_item)
+                pane.open_item(project_entry_id, focus_item, allow_preview, cx, build_item)
             })
         })
     }
@@ -2107,7 +2271,7 @@ impl Workspace {
                 let pane = pane.upgrade()?;
                 let new_pane = this.split_pane(pane, SplitDirection::Right, cx);
                 new_pane.update(cx, |new_pane, cx| {
-                    Some(new_pane.open_item(project_entry_id, true, cx, build_item))
+                    Some(new_pane.open_item(project_entry_id, true, false, cx, build_item))
                 })
             })
             .map(|option| option.ok_or_else(|| anyhow!("pane was dropped")))?
@@ -2137,22 +2301,53 @@ impl Workspace {
         project_item: Model<T::Item>,
         cx: &mut ViewContext<Self>,
     ) -> View<T>
+    where
+        T: ProjectItem,
+    {
+        self.open_project_item_preview(pane, project_item, false, cx)
+    }
+
+    /// Opens the project item in the preview tab of the pane when `allow_preview` is set,
+    /// replacing the item previewed there so far.
+    pub fn open_project_item_preview<T>(
+        &mut self,
+        pane: View<Pane>,
+        project_item: Model<T::Item>,
+        allow_preview: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> View<T>
     where
         T: ProjectItem,
     {
         use project::Item as _;
 
+        let allow_preview = allow_preview && PreviewTabsSettings::get_global(cx).enabled;
         let entry_id = project_item.read(cx).entry_id(cx);
         if let Some(item) = entry_id
             .and_then(|entry_id| pane.read(cx).item_for_entry(entry_id, cx))
             .and_then(|item| item.downcast())
         {
+            pane.update(cx, |pane, cx| {
+                if !allow_preview && pane.preview_item_id() == Some(item.item_id()) {
+                    pane.set_preview_item_id(None, cx);
+                }
+            });
             self.activate_item(&item, cx);
             return item;
         }
 
+        let destination_index = if allow_preview {
+            pane.update(cx, |pane, cx| pane.close_current_preview_item(cx))
+        } else {
+            None
+        };
         let item = cx.new_view(|cx| T::for_project_item(self.project().clone(), project_item, cx));
-        self.add_item(pane, Box::new(item.clone()), cx);
+        self.add_item_at(pane.clone(), Box::new(item.clone()), destination_index, cx);
+        if allow_preview {
+            pane.update(cx, |pane, cx| {
+                pane.set_preview_item_id(Some(item.item_id()), cx)
+            });
+        }
         item
     }
 
@@ -2539,6 +2734,10 @@ impl Workspace {
     }
 
     fn remove_pane(&mut self, pane: View<Pane>, cx: &mut ViewContext<Self>) {
+        // Panes replaced by a layout are already gone from the center.
+        if !self.panes.contains(&pane) {
+            return;
+        }
         if self.center.remove(&pane).unwrap() {
             self.force_remove_pane(&pane, cx);
             self.unfollow(&pane, cx);
@@ -3387,9 +3586,15 @@ impl Workspace {
 
     fn serialize_workspace(&self, cx: &mut WindowContext) -> Task<()> {
         fn serialize_pane_handle(pane_handle: &View<Pane>, cx: &WindowContext) -> SerializedPane {
-            let (items, active) = {
+            let (items, active, pinned_count) = {
                 let pane = pane_handle.read(cx);
                 let active_item_id = pane.active_item().map(|item| item.item_id());
+                // Items that can't be serialized don't count towards the pinned ones.
+                let pinned_count = pane
+                    .items()
+                    .take(pane.pinned_count())
+                    .filter(|item_handle| item_handle.serialized_item_kind().is_some())
+                    .count();
                 (
                     pane.items()
                         .filter_map(|item_handle| {
@@ -3397,14 +3602,16 @@ impl Workspace {
                                 kind: Arc::from(item_handle.serialized_item_kind()?),
                                 item_id: item_handle.item_id().as_u64(),
                                 active: Some(item_handle.item_id()) == active_item_id,
+                                preview: pane.preview_item_id() == Some(item_handle.item_id()),
                             })
                         })
                         .collect::<Vec<_>>(),
                     pane.has_focus(cx),
+                    pinned_count,
                 )
             };
 
-            SerializedPane::new(items, active)
+            SerializedPane::new(items, active, pinned_count)
         }
 
         fn build_serialized_pane_group(
@@ -3488,6 +3695,24 @@ impl Workspace {
             if !location.paths().is_empty() {
                 let center_group = build_serialized_pane_group(&self.center.root, cx);
                 let docks = build_serialized_docks(self, cx);
+                let detached_panes = self
+                    .detached_panes
+                    .iter()
+                    .map(|detached_pane| SerializedDetachedPane {
+                        pane: serialize_pane_handle(&detached_pane.pane, cx),
+                        bounds: detached_pane.bounds.map(|(bounds, _)| bounds),
+                        display: detached_pane.bounds.map(|(_, display)| display),
+                    })
+                    .collect();
+                let detached_panels = self
+                    .detached_panels
+                    .iter()
+                    .map(|detached_panel| SerializedDetachedPanel {
+                        name: detached_panel.name.to_string(),
+                        bounds: detached_panel.bounds.map(|(bounds, _)| bounds),
+                        display: detached_panel.bounds.map(|(_, display)| display),
+                    })
+                    .collect();
                 let serialized_workspace = SerializedWorkspace {
                     id: self.database_id,
                     location,
@@ -3496,6 +3721,8 @@ impl Workspace {
                     display: Default::default(),
                     docks,
                     fullscreen: cx.is_fullscreen(),
+                    detached_panes,
+                    detached_panels,
                 };
                 return cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace));
             }
@@ -3610,6 +3837,41 @@ impl Workspace {
                 cx.notify();
             })?;
 
+            // Reopen the windows of the panes that were detached
+            for detached in serialized_workspace.detached_panes {
+                let window = workspace.update(&mut cx, |workspace, cx| {
+                    workspace.open_detached_window(detached.bounds, detached.display, cx)
+                })??;
+                let (pane, mut window_cx) = window.update(&mut cx, |detached_pane, cx| {
+                    (detached_pane.pane().clone(), cx.to_async())
+                })?;
+                detached
+                    .pane
+                    .deserialize_to(
+                        &project,
+                        &pane.downgrade(),
+                        serialized_workspace.id,
+                        workspace.clone(),
+                        &mut window_cx,
+                    )
+                    .await
+                    .log_err();
+                workspace.update(&mut cx, |workspace, cx| {
+                    if pane.read(cx).items_len() == 0 {
+                        workspace.reattach_pane(&pane, cx);
+                    }
+                })?;
+            }
+
+            // Reopen the windows of the dock panels that were detached
+            workspace.update(&mut cx, |workspace, cx| {
+                for detached in &serialized_workspace.detached_panels {
+                    workspace
+                        .open_detached_panel(&detached.name, detached.bounds, detached.display, cx)
+                        .log_err();
+                }
+            })?;
+
             // Serialize ourself to make sure our timestamps and any pane / item changes are replicated
             workspace.update(&mut cx, |workspace, cx| {
                 workspace.serialize_workspace(cx).detach()
@@ -3681,6 +3943,32 @@ impl Workspace {
             .on_action(cx.listener(Workspace::open))
             .on_action(cx.listener(Workspace::close_window))
             .on_action(cx.listener(Workspace::activate_pane_at_index))
+            .on_action(cx.listener(|workspace, _: &DetachPane, cx| {
+                let pane = workspace.active_pane().clone();
+                workspace.detach_pane(pane, cx);
+            }))
+            .on_action(cx.listener(|workspace, action: &DetachPanel, cx| {
+                let name = match &action.name {
+                    Some(name) => Some(name.as_str()),
+                    None => workspace.dock_panel_to_detach(cx),
+                };
+                if let Some(name) = name {
+                    workspace.detach_panel(name, cx);
+                }
+            }))
+            .on_action(cx.listener(|workspace, action: &ApplyLayout, cx| {
+                workspace
+                    .apply_layout(&action.name, cx)
+                    .detach_and_notify_err(cx);
+            }))
+            .on_action(cx.listener(|workspace, action: &SaveLayout, cx| {
+                workspace
+                    .save_layout(action.name.clone(), action.global, action.include_items, cx)
+                    .detach_and_notify_err(cx);
+            }))
+            .on_action(cx.listener(|workspace, action: &DeleteLayout, cx| {
+                workspace.delete_layout(action.name.clone(), action.global, cx);
+            }))
             .on_action(
                 cx.listener(|workspace: &mut Workspace, _: &ReopenClosedItem, cx| {
                     workspace.reopen_closed_item(cx).detach();
@@ -3740,18 +4028,22 @@ impl Workspace {
     }
 
     pub fn has_active_modal(&self, cx: &WindowContext<'_>) -> bool {
-        self.modal_layer.read(cx).has_active_modal()
+        self.modal_layer_for_window(cx).read(cx).has_active_modal()
     }
 
-    pub fn active_modal<V: ManagedView + 'static>(&mut self, cx: &AppContext) -> Option<View<V>> {
-        self.modal_layer.read(cx).active_modal()
+    pub fn active_modal<V: ManagedView + 'static>(
+        &mut self,
+        cx: &WindowContext,
+    ) -> Option<View<V>> {
+        self.modal_layer_for_window(cx).read(cx).active_modal()
     }
 
     pub fn toggle_modal<V: ModalView, B>(&mut self, cx: &mut WindowContext, build: B)
     where
         B: FnOnce(&mut ViewContext<V>) -> V,
     {
-        self.modal_layer
+        self.modal_layer_for_window(cx)
+            .clone()
             .update(cx, |modal_layer, cx| modal_layer.toggle_modal(cx, build))
     }
 }
@@ -5879,6 +6171,95 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_layouts(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/layouts", json!({})).await;
+
+        let project = Project::test(fs, ["/layouts".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let item_1 = cx.new_view(|cx| TestItem::new(cx));
+        let item_2 = cx.new_view(|cx| TestItem::new(cx));
+        let pane_1 = workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TestPanel::new(DockPosition::Left, cx));
+            workspace.add_panel(panel, cx);
+            workspace.add_item_to_active_pane(Box::new(item_1.clone()), cx);
+            workspace.add_item_to_active_pane(Box::new(item_2.clone()), cx);
+            workspace.active_pane().clone()
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.save_layout("focus".into(), false, true, cx)
+            })
+            .await
+            .unwrap();
+
+        // Split the items and open the left dock.
+        workspace.update(cx, |workspace, cx| {
+            let pane_2 = workspace.split_pane(pane_1.clone(), SplitDirection::Right, cx);
+            workspace.move_item(pane_1.clone(), pane_2, item_2.item_id(), 0, cx);
+            workspace.toggle_dock(DockPosition::Left, cx);
+            workspace
+                .left_dock()
+                .update(cx, |dock, cx| dock.resize_active_panel(Some(px(400.)), cx));
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.save_layout("review".into(), false, true, cx)
+            })
+            .await
+            .unwrap();
+
+        workspace.update(cx, |workspace, cx| {
+            let layouts = workspace.layouts(cx).unwrap();
+            assert_eq!(
+                layouts
+                    .iter()
+                    .map(|layout| layout.name.as_str())
+                    .collect::<Vec<_>>(),
+                ["focus", "review"]
+            );
+        });
+
+        // Switching layouts keeps the open items.
+        workspace
+            .update(cx, |workspace, cx| workspace.apply_layout("focus", cx))
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.panes().len(), 1);
+            assert_eq!(workspace.active_pane().read(cx).items_len(), 2);
+            assert!(!workspace.left_dock().read(cx).is_open());
+        });
+
+        workspace
+            .update(cx, |workspace, cx| workspace.apply_layout("review", cx))
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.panes().len(), 2);
+            assert_eq!(workspace.center.panes().len(), 2);
+            assert_eq!(
+                workspace
+                    .panes()
+                    .iter()
+                    .map(|pane| pane.read(cx).items_len())
+                    .sum::<usize>(),
+                2
+            );
+            let left_dock = workspace.left_dock().read(cx);
+            assert!(left_dock.is_open());
+            assert_eq!(left_dock.active_panel_size(cx), Some(px(400.)));
+        });
+
+        let result = workspace
+            .update(cx, |workspace, cx| workspace.apply_layout("debug", cx))
+            .await;
+        assert!(result.is_err());
+    }
+
     mod register_project_item_tests {
         use ui::Context as _;
 
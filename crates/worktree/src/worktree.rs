@@ -32,11 +32,11 @@ use ignore::IgnoreStack;
 use itertools::Itertools;
 use language::{
     proto::{
-        deserialize_fingerprint, deserialize_version, serialize_fingerprint, serialize_line_ending,
-        serialize_version,
+        deserialize_fingerprint, deserialize_version, serialize_encoding, serialize_fingerprint,
+        serialize_line_ending, serialize_version,
     },
-    Buffer, Capability, DiagnosticEntry, File as _, LineEnding, PointUtf16, Rope, RopeFingerprint,
-    Unclipped,
+    Buffer, Capability, DiagnosticEntry, Encoding, File as _, LineEnding, PointUtf16, Rope,
+    RopeFingerprint, Unclipped,
 };
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use parking_lot::Mutex;
@@ -760,7 +760,7 @@ impl LocalWorktree {
     ) -> Task<Result<Model<Buffer>>> {
         let path = Arc::from(path);
         cx.spawn(move |this, mut cx| async move {
-            let (file, contents, diff_base) = this
+            let (file, contents, encoding, diff_base) = this
                 .update(&mut cx, |t, cx| t.as_local().unwrap().load(&path, cx))?
                 .await?;
             let text_buffer = cx
@@ -774,6 +774,7 @@ impl LocalWorktree {
                     Some(Arc::new(file)),
                     Capability::ReadWrite,
                 )
+                .with_encoding(encoding)
             })
         })
     }
@@ -1068,15 +1069,27 @@ impl LocalWorktree {
         &self,
         path: &Path,
         cx: &mut ModelContext<Worktree>,
-    ) -> Task<Result<(File, String, Option<String>)>> {
+    ) -> Task<Result<(File, String, Encoding, Option<String>)>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
+        let preferred_encoding = cx
+            .global::<SettingsStore>()
+            .editorconfig_properties(cx.entity_id().as_u64() as usize, &path)
+            .and_then(|properties| Encoding::for_label(properties.charset.as_deref()?));
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let bytes = fs.load_bytes(&abs_path).await?;
+            let (text, encoding) = cx
+                .background_executor()
+                .spawn(async move {
+                    let encoding = Encoding::detect(&bytes, preferred_encoding)?;
+                    anyhow::Ok((encoding.decode(&bytes), encoding))
+                })
+                .await
+                .with_context(|| format!("failed to load {abs_path:?}"))?;
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
             if let Some(repo) = snapshot.repository_for_path(&path) {
@@ -1113,6 +1126,7 @@ impl LocalWorktree {
                         is_private: entry.is_private,
                     },
                     text,
+                    encoding,
                     diff_base,
                 )),
                 None => {
@@ -1137,6 +1151,7 @@ impl LocalWorktree {
                             is_private,
                         },
                         text,
+                        encoding,
                         diff_base,
                     ))
                 }
@@ -1164,7 +1179,8 @@ impl LocalWorktree {
         let text = buffer.as_rope().clone();
         let fingerprint = text.fingerprint();
         let version = buffer.version();
-        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), cx);
+        let encoding = buffer.encoding();
+        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), encoding, cx);
         let fs = Arc::clone(&self.fs);
         let abs_path = self.absolutize(&path);
         let is_private = self.snapshot.is_path_private(&path);
@@ -1226,6 +1242,7 @@ impl LocalWorktree {
                     version: serialize_version(&version),
                     mtime: mtime.map(|time| time.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
+                    encoding: Some(serialize_encoding(encoding)),
                 })?;
             }
 
@@ -1304,14 +1321,16 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Option<Entry>>> {
         let path: Arc<Path> = path.into();
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
-        let write = cx
-            .background_executor()
-            .spawn(async move { fs.save(&abs_path?, &text, line_ending).await });
+        let write = cx.background_executor().spawn(async move {
+            fs.save_with_encoding(&abs_path?, &text, line_ending, encoding)
+                .await
+        });
 
         cx.spawn(|this, mut cx| async move {
             write.await?;
@@ -3005,12 +3024,12 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load_bytes(&self, cx: &AppContext) -> Task<Result<Vec<u8>>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
         cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+            .spawn(async move { fs.load_bytes(&abs_path?).await })
     }
 
     fn buffer_reloaded(
@@ -3019,6 +3038,7 @@ impl language::LocalFile for File {
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: Option<SystemTime>,
         cx: &mut AppContext,
     ) {
@@ -3033,6 +3053,7 @@ impl language::LocalFile for File {
                     mtime: mtime.map(|time| time.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
                     line_ending: serialize_line_ending(line_ending) as i32,
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .log_err();
         }
@@ -3102,6 +3123,8 @@ pub struct Entry {
     pub path: Arc<Path>,
     pub inode: u64,
     pub mtime: Option<SystemTime>,
+    /// The length of the file in bytes, as of the last scan.
+    pub size: u64,
     pub is_symlink: bool,
 
     /// Whether this entry is ignored by Git.
@@ -3172,6 +3195,7 @@ impl Entry {
             path,
             inode: metadata.inode,
             mtime: Some(metadata.mtime),
+            size: metadata.len,
             is_symlink: metadata.is_symlink,
             is_ignored: false,
             is_external: false,
@@ -4745,6 +4769,7 @@ impl<'a> From<&'a Entry> for proto::Entry {
             path: entry.path.to_string_lossy().into(),
             inode: entry.inode,
             mtime: entry.mtime.map(|time| time.into()),
+            size: entry.size,
             is_symlink: entry.is_symlink,
             is_ignored: entry.is_ignored,
             is_external: entry.is_external,
@@ -4771,6 +4796,7 @@ impl<'a> TryFrom<(&'a CharBag, proto::Entry)> for Entry {
             path,
             inode: entry.inode,
             mtime: entry.mtime.map(|time| time.into()),
+            size: entry.size,
             is_symlink: entry.is_symlink,
             is_ignored: entry.is_ignored,
             is_external: entry.is_external,
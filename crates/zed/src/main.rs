@@ -247,14 +247,18 @@ fn main() {
         project_panel::init(Assets, cx);
         tasks_ui::init(cx);
         channel::init(&client, user_store.clone(), cx);
+        semantic_index::init(client.http_client(), cx);
         search::init(cx);
         vim::init(cx);
         terminal_view::init(cx);
 
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);
+        layout_selector::init(cx);
+        location_panel::init(cx);
         call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         notifications::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         collab_ui::init(&app_state, cx);